@@ -0,0 +1,232 @@
+//! Per-frame cost under heavy synthetic input
+
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use xdl::{
+    backend::{SyntheticBackend, SyntheticEvent},
+    vi::{Button, ButtonBatch, InputBundle, KeyEntry, KeyRepeatConfig},
+    Backend, Input, Key, KeyState, RawKey,
+};
+
+const DT: Duration = Duration::from_nanos(16_666_667);
+
+/// Events of a mashing frame
+fn events(backend: &mut SyntheticBackend) -> Vec<SyntheticEvent> {
+    let mut events = Vec::new();
+    backend.generate_frame(&mut events);
+    events
+}
+
+/// 50 single-key actions
+fn action_map() -> Vec<Button> {
+    Key::ALL
+        .iter()
+        .take(50)
+        .map(|&key| {
+            let bundle = InputBundle::from_keys([key]);
+            Button::new(bundle, KeyRepeatConfig::repeat_frames(20, 6))
+        })
+        .collect()
+}
+
+/// 120 actions of two keys each, with or without modifiers
+fn large_action_map() -> Vec<Button> {
+    (0..120)
+        .map(|i| {
+            let key = |j: usize| Key::ALL[(i + j) % Key::ALL.len()];
+            let mut bundle = InputBundle::from_keys([key(0), key(60)]);
+            bundle.keys[1].ctrl = i % 3 == 0;
+            Button::new(bundle, KeyRepeatConfig::repeat_frames(20, 6))
+        })
+        .collect()
+}
+
+/// Hides the key bitsets, so that every bundle is evaluated
+struct Naive<'a>(&'a Input);
+
+impl<'a> KeyState for Naive<'a> {
+    fn is_key_down(&self, key: Key) -> bool {
+        self.0.is_key_down(key)
+    }
+
+    fn was_key_down(&self, key: Key) -> bool {
+        self.0.was_key_down(key)
+    }
+
+    fn is_raw_key_down(&self, key: RawKey) -> bool {
+        self.0.is_raw_key_down(key)
+    }
+
+    fn was_raw_key_down(&self, key: RawKey) -> bool {
+        self.0.was_raw_key_down(key)
+    }
+}
+
+fn frame(c: &mut Criterion) {
+    let mut backend = SyntheticBackend::new(0)
+        .with_toggles_per_frame(64)
+        .with_mouse_jitter(4);
+    let events = self::events(&mut backend);
+
+    c.bench_function("frame/on_event", |b| {
+        let mut input = Input::new();
+        b.iter(|| {
+            for ev in &events {
+                black_box(backend.on_event(&mut input, black_box(ev)));
+            }
+        })
+    });
+
+    c.bench_function("frame/on_end_frame", |b| {
+        let mut input = Input::new();
+        for ev in &events {
+            backend.on_event(&mut input, ev);
+        }
+        b.iter(|| backend.on_end_frame(black_box(&mut input)))
+    });
+
+    c.bench_function("frame/button_update_50", |b| {
+        let mut input = Input::new();
+        backend.on_frame(&mut input, &events);
+        let mut buttons = self::action_map();
+        b.iter(|| {
+            for button in &mut buttons {
+                button.update(&input, DT);
+            }
+        })
+    });
+
+    c.bench_function("frame/pressed_keys", |b| {
+        let mut input = Input::new();
+        input.on_end_frame();
+        for ev in &events {
+            backend.on_event(&mut input, ev);
+        }
+        b.iter(|| {
+            Key::ALL
+                .iter()
+                .filter(|&&key| input.kbd.is_key_pressed(key))
+                .count()
+        })
+    });
+}
+
+/// 200 actions of one entry each, with or without modifiers
+fn entry_map() -> Vec<Button> {
+    (0..200)
+        .map(|i| {
+            let mut entry = KeyEntry::from(Key::ALL[i % Key::ALL.len()]);
+            entry.ctrl = i % 2 == 0;
+            entry.shift = i % 3 == 0;
+            let bundle = InputBundle::from_keys([entry]);
+            Button::new(bundle, KeyRepeatConfig::repeat_frames(20, 6))
+        })
+        .collect()
+}
+
+/// Mashing: most bundles change every frame, so they're evaluated
+fn mashing_frames(c: &mut Criterion) {
+    let mut backend = SyntheticBackend::new(0).with_toggles_per_frame(64);
+    let frames: Vec<_> = (0..64).map(|_| self::events(&mut backend)).collect();
+
+    c.bench_function("frame/button_update_200_entries", |b| {
+        let mut input = Input::new();
+        let mut buttons = self::entry_map();
+        b.iter(|| {
+            for events in &frames {
+                backend.on_frame(&mut input, events);
+                for button in &mut buttons {
+                    button.update(&input, DT);
+                }
+                backend.on_end_frame(&mut input);
+            }
+        })
+    });
+
+    c.bench_function("frame/button_update_200_entries_naive", |b| {
+        let mut input = Input::new();
+        let mut buttons = self::entry_map();
+        b.iter(|| {
+            for events in &frames {
+                backend.on_frame(&mut input, events);
+                for button in &mut buttons {
+                    button.update(&Naive(&input), DT);
+                }
+                backend.on_end_frame(&mut input);
+            }
+        })
+    });
+}
+
+fn batch(c: &mut Criterion) {
+    let mut backend = SyntheticBackend::new(0).with_toggles_per_frame(8);
+    let frames: Vec<_> = (0..64).map(|_| self::events(&mut backend)).collect();
+
+    c.bench_function("frame/button_update_200_standalone", |b| {
+        let mut input = Input::new();
+        let mut buttons = self::entry_map();
+        b.iter(|| {
+            for events in &frames {
+                backend.on_frame(&mut input, events);
+                for button in &mut buttons {
+                    button.update(&input, DT);
+                }
+                backend.on_end_frame(&mut input);
+            }
+        })
+    });
+
+    c.bench_function("frame/button_update_200_batch", |b| {
+        let mut input = Input::new();
+        let mut batch = ButtonBatch::new();
+        for (i, button) in self::entry_map().into_iter().enumerate() {
+            batch.register(i, button);
+        }
+        b.iter(|| {
+            for events in &frames {
+                backend.on_frame(&mut input, events);
+                batch.update_all(&input, DT);
+                backend.on_end_frame(&mut input);
+            }
+        })
+    });
+}
+
+/// Typical play: a few keys change per frame
+fn quiet_frames(c: &mut Criterion) {
+    let mut backend = SyntheticBackend::new(0).with_toggles_per_frame(2);
+    let frames: Vec<_> = (0..64).map(|_| self::events(&mut backend)).collect();
+
+    c.bench_function("frame/button_update_120_quiet", |b| {
+        let mut input = Input::new();
+        let mut buttons = self::large_action_map();
+        b.iter(|| {
+            for events in &frames {
+                backend.on_frame(&mut input, events);
+                for button in &mut buttons {
+                    button.update(&input, DT);
+                }
+                backend.on_end_frame(&mut input);
+            }
+        })
+    });
+
+    c.bench_function("frame/button_update_120_quiet_naive", |b| {
+        let mut input = Input::new();
+        let mut buttons = self::large_action_map();
+        b.iter(|| {
+            for events in &frames {
+                backend.on_frame(&mut input, events);
+                for button in &mut buttons {
+                    button.update(&Naive(&input), DT);
+                }
+                backend.on_end_frame(&mut input);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, frame, quiet_frames, mashing_frames, batch);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+//! Key translation throughput: `HashMap` (the previous implementation) vs `KeyTranslation`
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use xdl::{backend::KeyTranslation, Key};
+
+/// SDL-like keycodes: scattered values with a high bit for non-character keys
+fn table() -> Vec<(u32, Key)> {
+    Key::ALL
+        .iter()
+        .map(|&key| {
+            let code = key as u32;
+            let code = if code < 0x30 {
+                code | 0x4000_0000
+            } else {
+                code
+            };
+            (code, key)
+        })
+        .collect()
+}
+
+fn translation(c: &mut Criterion) {
+    let table = self::table();
+    let codes: Vec<u32> = table.iter().map(|(code, _)| *code).collect();
+
+    let map: HashMap<u32, Key> = table.iter().cloned().collect();
+    let sorted: KeyTranslation<u32> = KeyTranslation::from_table(&table);
+
+    c.bench_function("translate/hash_map", |b| {
+        b.iter(|| {
+            for code in &codes {
+                black_box(map.get(black_box(code)).cloned());
+            }
+        })
+    });
+
+    c.bench_function("translate/sorted", |b| {
+        b.iter(|| {
+            for code in &codes {
+                black_box(sorted.translate(black_box(code)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, translation);
+criterion_main!(benches);
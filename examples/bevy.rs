@@ -0,0 +1,97 @@
+//! Moves a player with the arrow keys in a headless Bevy app, fed with scripted key events
+//!
+//! Run with `cargo run --example bevy --features bevy`.
+
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        keyboard::{Key as LogicalKey, KeyCode, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use xdl::{
+    backend::{ActionMapResource, Actions, XdlAppExt, XdlPlugin},
+    vi::{presets, AxisDirButton, KeyRepeatConfig},
+    Input,
+};
+
+/// The game's virtual input
+struct Controls {
+    dir: AxisDirButton,
+}
+
+impl Actions for Controls {
+    fn update(&mut self, input: &Input, dt: Duration) {
+        self.dir.update(input, dt);
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+struct Position(IVec2);
+
+fn movement(controls: Res<ActionMapResource<Controls>>, mut pos: ResMut<Position>) {
+    if let Some(dir) = controls.dir.dir8_down() {
+        let [x, y] = dir.signs_i32();
+        pos.0 += IVec2::new(x, y);
+        println!("{:?} -> {:?}", dir, pos.0);
+    }
+}
+
+fn key(key_code: KeyCode, logical_key: LogicalKey, state: ButtonState) -> KeyboardInput {
+    KeyboardInput {
+        key_code,
+        logical_key,
+        state,
+        window: Entity::PLACEHOLDER,
+    }
+}
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, XdlPlugin))
+        .add_actions(Controls {
+            dir: presets::arrows(KeyRepeatConfig::NoRepeat),
+        })
+        .init_resource::<Position>()
+        .add_systems(Update, movement);
+    app.finish();
+    app.cleanup();
+
+    // hold right, then down-right, then release both
+    let script = [
+        vec![key(
+            KeyCode::ArrowRight,
+            LogicalKey::ArrowRight,
+            ButtonState::Pressed,
+        )],
+        vec![],
+        vec![key(
+            KeyCode::ArrowDown,
+            LogicalKey::ArrowDown,
+            ButtonState::Pressed,
+        )],
+        vec![],
+        vec![
+            key(
+                KeyCode::ArrowRight,
+                LogicalKey::ArrowRight,
+                ButtonState::Released,
+            ),
+            key(
+                KeyCode::ArrowDown,
+                LogicalKey::ArrowDown,
+                ButtonState::Released,
+            ),
+        ],
+    ];
+
+    for events in script {
+        for ev in events {
+            app.world.send_event(ev);
+        }
+        app.update();
+    }
+}
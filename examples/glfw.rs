@@ -0,0 +1,39 @@
+//! Opens a window and prints pressed keys
+//!
+//! Run with `cargo run --example glfw --features glfw`.
+
+use glfw::Context;
+
+use xdl::{backend::GlfwBackend, Backend, Input, Key};
+
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    let (mut window, events) = glfw
+        .create_window(640, 480, "xdl", glfw::WindowMode::Windowed)
+        .unwrap();
+
+    window.set_key_polling(true);
+    window.set_focus_polling(true);
+    window.make_current();
+
+    let mut backend = GlfwBackend::new();
+    let mut input = Input::new();
+
+    while !window.should_close() {
+        glfw.poll_events();
+        for (_, ev) in glfw::flush_messages(&events) {
+            backend.on_event(&mut input, &ev);
+        }
+
+        for key in Key::ALL.iter().filter(|k| input.kbd.is_key_pressed(**k)) {
+            println!("pressed: {}", key);
+        }
+
+        if input.kbd.is_key_pressed(Key::Escape) {
+            window.set_should_close(true);
+        }
+
+        window.swap_buffers();
+        backend.on_end_frame(&mut input);
+    }
+}
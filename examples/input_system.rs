@@ -0,0 +1,73 @@
+//! Updates a whole control scheme with one call to `InputSystem::tick`
+//!
+//! Run with `cargo run --example input_system`.
+
+use std::time::Duration;
+
+use xdl::{
+    backend::ManualBackend,
+    vi::{AxisDirButton, Button, InputBundle, InputSystem, KeyRepeatConfig},
+    Input, Key,
+};
+
+fn bundle(key: Key) -> InputBundle {
+    InputBundle::from_keys([key])
+}
+
+fn main() {
+    let repeat = KeyRepeatConfig::repeat(Duration::from_millis(48), Duration::from_millis(32));
+
+    // instead of keeping the objects in separate fields and updating each of them every frame:
+    //
+    //     select.update(&input, dt);
+    //     cancel.update(&input, dt);
+    //     dir.update(&input, dt);
+    //
+    // they are registered once and updated together
+    let mut system = InputSystem::default();
+    let select = system.add(Button::single(Key::Enter, repeat));
+    let cancel = system.add(Button::single(Key::Escape, KeyRepeatConfig::NoRepeat));
+    let dir = system.add(AxisDirButton::new(
+        repeat,
+        [bundle(Key::Right), bundle(Key::Left)],
+        [bundle(Key::Down), bundle(Key::Up)],
+    ));
+
+    // a fake player: holds Enter, then walks down-right and cancels
+    let mut backend = ManualBackend::new();
+    let mut input = Input::new();
+    for frame in 0..12 {
+        match frame {
+            1 => backend.key_down(Key::Enter),
+            6 => {
+                backend.key_up(Key::Enter);
+                backend.key_down(Key::Right);
+            }
+            7 => backend.key_down(Key::Down),
+            10 => {
+                backend.key_up(Key::Right);
+                backend.key_up(Key::Down);
+                backend.key_down(Key::Escape);
+            }
+            _ => {}
+        }
+        backend.update(&mut input);
+        input.tick(Duration::from_millis(16));
+
+        system.tick(&input);
+
+        println!(
+            "frame {:2}: select: {:5}, cancel: {:5}, dir: {:?}",
+            frame,
+            system[select].is_pressed(),
+            system[cancel].is_pressed(),
+            system[dir].dir8_pressed(),
+        );
+
+        backend.end_frame(&mut input);
+    }
+
+    // no more cancel button
+    system.remove(cancel);
+    println!("{} objects left", system.len());
+}
@@ -0,0 +1,46 @@
+//! Moves a square with vi keys
+//!
+//! Run with `cargo run --example macroquad --features macroquad`.
+
+use macroquad::prelude::*;
+
+use xdl::{
+    backend::MiniquadBackend,
+    vi::{AxisDirButton, InputBundle, KeyRepeatConfig},
+    Backend, Input, Key,
+};
+
+fn bundle(key: Key) -> InputBundle {
+    InputBundle::from_keys([key])
+}
+
+#[macroquad::main("xdl")]
+async fn main() {
+    let mut backend = MiniquadBackend::new();
+    let mut input = Input::new();
+
+    let mut dir = AxisDirButton::new(
+        KeyRepeatConfig::repeat_frames(8, 4),
+        [bundle(Key::L), bundle(Key::H)],
+        [bundle(Key::J), bundle(Key::K)],
+    );
+
+    let mut pos = (10i32, 10i32);
+
+    loop {
+        backend.pump(&mut input);
+        dir.update(&input, std::time::Duration::from_secs_f32(get_frame_time()));
+
+        if let Some(d) = dir.dir8_pressed() {
+            let [x, y] = d.signs_i32();
+            pos.0 += x;
+            pos.1 += y;
+        }
+
+        clear_background(BLACK);
+        draw_rectangle(pos.0 as f32 * 16.0, pos.1 as f32 * 16.0, 16.0, 16.0, WHITE);
+
+        backend.on_end_frame(&mut input);
+        next_frame().await
+    }
+}
@@ -0,0 +1,80 @@
+//! Binds a (fake) MIDI foot pedal to a virtual button
+//!
+//! Run with `cargo run --example pedal`.
+
+use std::{any::Any, time::Duration};
+
+use xdl::{
+    vi::{Button, InputBundle, KeyRepeatConfig},
+    CustomInput, Device, Input,
+};
+
+/// Sustain pedal. A real one would be fed from a MIDI callback
+#[derive(Debug, Clone, Default)]
+struct Pedal {
+    down: bool,
+    was_down: bool,
+}
+
+impl Device for Pedal {
+    fn name(&self) -> &str {
+        "pedal"
+    }
+
+    fn channels(&self) -> Vec<String> {
+        vec!["sustain".to_string()]
+    }
+
+    fn is_down(&self, channel: &str) -> bool {
+        channel == "sustain" && self.down
+    }
+
+    fn was_down(&self, channel: &str) -> bool {
+        channel == "sustain" && self.was_down
+    }
+
+    fn on_end_frame(&mut self) {
+        self.was_down = self.down;
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn clone_box(&self) -> Box<dyn Device> {
+        Box::new(self.clone())
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn main() {
+    let mut input = Input::new();
+    input.register_device(Box::new(Pedal::default()));
+
+    let mut sustain = Button::new(
+        InputBundle {
+            custom: std::iter::once(CustomInput::new("pedal", "sustain")).collect(),
+            ..Default::default()
+        },
+        KeyRepeatConfig::no_repeat(),
+    );
+
+    // the pedal is down on frames 2 and 3
+    for frame in 0..6 {
+        input.device_mut::<Pedal>("pedal").unwrap().down = frame == 2 || frame == 3;
+
+        sustain.update(&input, Duration::from_millis(16));
+        println!(
+            "frame {}: down: {}, pressed: {}, released: {}",
+            frame,
+            sustain.is_down(),
+            sustain.is_pressed(),
+            sustain.is_released()
+        );
+
+        input.on_end_frame();
+    }
+}
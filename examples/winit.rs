@@ -0,0 +1,41 @@
+//! Opens a window and prints pressed keys
+//!
+//! Run with `cargo run --example winit --features winit`.
+
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::EventLoop,
+    window::WindowBuilder,
+};
+
+use xdl::{backend::WinitBackend, Backend, Input, Key};
+
+fn main() {
+    let event_loop = EventLoop::new().unwrap();
+    let _window = WindowBuilder::new()
+        .with_title("xdl")
+        .build(&event_loop)
+        .unwrap();
+
+    let mut backend = WinitBackend::new();
+    let mut input = Input::new();
+
+    event_loop
+        .run(move |ev, elwt| match ev {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => elwt.exit(),
+            Event::WindowEvent { event, .. } => {
+                backend.on_event(&mut input, &event);
+            }
+            Event::AboutToWait => {
+                for key in Key::ALL.iter().filter(|k| input.kbd.is_key_pressed(**k)) {
+                    println!("pressed: {}", key);
+                }
+                backend.on_end_frame(&mut input);
+            }
+            _ => {}
+        })
+        .unwrap();
+}
@@ -2,12 +2,22 @@
 Primitive axis types
 */
 
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Pos | Neg | Neutral
+///
+/// On `serde`, it's serialized by variant name and deserialized case-insensitively with aliases
+/// (`"positive"`, `"+"`, `"negative"`, `"-"`, `"zero"`, `"0"`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "igri", derive(igri::Inspect))]
 pub enum Sign {
     /// Right | Down
@@ -19,7 +29,7 @@ pub enum Sign {
 }
 
 impl Sign {
-    pub fn to_i8(&self) -> i32 {
+    pub fn to_i8(&self) -> i8 {
         match self {
             Self::Pos => 1,
             Self::Neg => -1,
@@ -51,6 +61,14 @@ impl Sign {
         }
     }
 
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Self::Pos => 1.0,
+            Self::Neg => -1.0,
+            Self::Neutral => 0.0,
+        }
+    }
+
     pub fn to_isize(&self) -> isize {
         match self {
             Self::Pos => 1,
@@ -60,13 +78,7 @@ impl Sign {
     }
 
     pub fn from_i32(x: i32) -> Self {
-        if x > 0 {
-            Self::Pos
-        } else if x < 0 {
-            Self::Neg
-        } else {
-            Self::Neutral
-        }
+        Self::from(x.cmp(&0))
     }
 
     pub fn inv(&self) -> Self {
@@ -78,9 +90,261 @@ impl Sign {
     }
 }
 
+impl From<Sign> for i8 {
+    fn from(sign: Sign) -> Self {
+        sign.to_i8()
+    }
+}
+
+impl From<Sign> for i32 {
+    fn from(sign: Sign) -> Self {
+        sign.to_i32()
+    }
+}
+
+impl From<Sign> for f32 {
+    fn from(sign: Sign) -> Self {
+        sign.to_f32()
+    }
+}
+
+/// Greater | Less | Equal
+impl From<Ordering> for Sign {
+    fn from(ord: Ordering) -> Self {
+        match ord {
+            Ordering::Greater => Self::Pos,
+            Ordering::Less => Self::Neg,
+            Ordering::Equal => Self::Neutral,
+        }
+    }
+}
+
+/// Lowercase without separators (`"North-East"` -> `"northeast"`)
+///
+/// A single character is kept as is, so that `"-"` is not stripped.
+fn normalize_name(name: &str) -> String {
+    if name.chars().count() == 1 {
+        return name.to_lowercase();
+    }
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+impl Sign {
+    /// Parses variant names and aliases case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match normalize_name(name).as_str() {
+            "pos" | "positive" | "+" => Sign::Pos,
+            "neg" | "negative" | "-" => Sign::Neg,
+            "neutral" | "zero" | "0" => Sign::Neutral,
+            _ => return None,
+        })
+    }
+}
+
+impl Dir4 {
+    /// Parses short names, long names and aliases case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match normalize_name(name).as_str() {
+            "n" | "north" | "up" => Dir4::N,
+            "e" | "east" | "right" => Dir4::E,
+            "s" | "south" | "down" => Dir4::S,
+            "w" | "west" | "left" => Dir4::W,
+            _ => return None,
+        })
+    }
+}
+
+impl Dir8 {
+    /// Parses short names, long names and aliases case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match normalize_name(name).as_str() {
+            "n" | "north" | "up" => Dir8::N,
+            "ne" | "northeast" | "upright" => Dir8::NE,
+            "e" | "east" | "right" => Dir8::E,
+            "se" | "southeast" | "downright" => Dir8::SE,
+            "s" | "south" | "down" => Dir8::S,
+            "sw" | "southwest" | "downleft" => Dir8::SW,
+            "w" | "west" | "left" => Dir8::W,
+            "nw" | "northwest" | "upleft" => Dir8::NW,
+            _ => return None,
+        })
+    }
+}
+
+/// Error on parsing a direction from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirError {
+    /// The token that could not be parsed
+    pub token: String,
+}
+
+impl fmt::Display for ParseDirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown direction: `{}`", self.token)
+    }
+}
+
+impl std::error::Error for ParseDirError {}
+
+/// Short name (`N`, `E`, ..)
+impl fmt::Display for Dir4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Short name (`N`, `NE`, ..)
+impl fmt::Display for Dir8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Accepts short names, long names and aliases case-insensitively. See [`Dir4::from_name`].
+impl FromStr for Dir4 {
+    type Err = ParseDirError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| ParseDirError {
+            token: s.to_string(),
+        })
+    }
+}
+
+/// Accepts short names, long names (`"north-east"`, `"northeast"`) and aliases
+/// case-insensitively. See [`Dir8::from_name`].
+impl FromStr for Dir8 {
+    type Err = ParseDirError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| ParseDirError {
+            token: s.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+mod name_serde {
+    //! Case-insensitive deserialization with aliases
+
+    use super::*;
+    use serde::de::{self, Deserializer, EnumAccess, VariantAccess, Visitor};
+    use std::marker::PhantomData;
+
+    trait FromName: Sized {
+        const NAME: &'static str;
+        const VARIANTS: &'static [&'static str];
+        fn from_name(name: &str) -> Option<Self>;
+    }
+
+    impl FromName for Sign {
+        const NAME: &'static str = "Sign";
+        const VARIANTS: &'static [&'static str] = &["Pos", "Neg", "Neutral"];
+        fn from_name(name: &str) -> Option<Self> {
+            Sign::from_name(name)
+        }
+    }
+
+    impl FromName for Dir4 {
+        const NAME: &'static str = "Dir4";
+        const VARIANTS: &'static [&'static str] = &["N", "E", "S", "W"];
+        fn from_name(name: &str) -> Option<Self> {
+            Dir4::from_name(name)
+        }
+    }
+
+    impl FromName for Dir8 {
+        const NAME: &'static str = "Dir8";
+        const VARIANTS: &'static [&'static str] = &["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+        fn from_name(name: &str) -> Option<Self> {
+            Dir8::from_name(name)
+        }
+    }
+
+    struct NameVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: FromName> Visitor<'de> for NameVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} variant name or alias", T::NAME)
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<T, E> {
+            T::from_name(s).ok_or_else(|| E::unknown_variant(s, T::VARIANTS))
+        }
+
+        fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<T, A::Error> {
+            let (name, variant) = data.variant::<String>()?;
+            variant.unit_variant()?;
+            self.visit_str(&name)
+        }
+    }
+
+    macro_rules! impl_deserialize {
+        ($($ty:ty),*) => {
+            $(
+                impl<'de> Deserialize<'de> for $ty {
+                    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                        deserializer.deserialize_enum(
+                            <$ty as FromName>::NAME,
+                            <$ty as FromName>::VARIANTS,
+                            NameVisitor(PhantomData),
+                        )
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_deserialize!(Sign, Dir4, Dir8);
+}
+
+/// Same as [`Sign::inv`]
+impl Neg for Sign {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.inv()
+    }
+}
+
+/// Sign multiplication. `Neutral` absorbs.
+impl Mul<Sign> for Sign {
+    type Output = Self;
+    fn mul(self, rhs: Sign) -> Self {
+        Self::from_i32(self.to_i32() * rhs.to_i32())
+    }
+}
+
+/// Scales an integer by the sign
+///
+/// ```
+/// use xdl::Dir8;
+///
+/// let mut pos = [10, 10];
+/// let speed = 3;
+///
+/// let [sx, sy] = Dir8::NE.signs();
+/// pos[0] += sx * speed;
+/// pos[1] += sy * speed;
+///
+/// assert_eq!(pos, [13, 7]);
+/// ```
+impl Mul<i32> for Sign {
+    type Output = i32;
+    fn mul(self, rhs: i32) -> i32 {
+        self.to_i32() * rhs
+    }
+}
+
 /// N | E | S | W
+///
+/// On `serde`, it's serialized by short name and deserialized case-insensitively with aliases
+/// (`"north"`, `"up"`, ..). Following the Y-down coordinate system, `"up"` means N and `"down"`
+/// means S.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "igri", derive(igri::Inspect))]
 pub enum Dir4 {
     N,
@@ -89,7 +353,21 @@ pub enum Dir4 {
     W,
 }
 
+/// Signs
+///
+/// X axis goes right and Y axis goes down (N is `[Neutral, Neg]`), same as [`Dir8`].
 impl Dir4 {
+    /// Inverse of [`Dir4::signs`]. Returns `None` for neutral or diagonal signs.
+    pub fn from_signs(signs: [Sign; 2]) -> Option<Self> {
+        Some(match [signs[0].to_i8(), signs[1].to_i8()] {
+            [0, -1] => Dir4::N,
+            [1, 0] => Dir4::E,
+            [0, 1] => Dir4::S,
+            [-1, 0] => Dir4::W,
+            _ => return None,
+        })
+    }
+
     pub fn x_sign(&self) -> Sign {
         use Dir4::*;
         use Sign::*;
@@ -129,7 +407,97 @@ impl Dir4 {
     }
 }
 
+/// Angles
+///
+/// X axis goes right and Y axis goes down, so angles are measured from E and increase
+/// clockwise on screen: E = 0, S = π/2, W = π, N = 3π/2.
 impl Dir4 {
+    pub fn unit_vec(&self) -> [f32; 2] {
+        [self.x_sign().to_f32(), self.y_sign().to_f32()]
+    }
+
+    /// Angle in `[0, 2π)` radians
+    pub fn to_radians(&self) -> f32 {
+        use std::f32::consts::PI;
+        match self {
+            Dir4::E => 0.0,
+            Dir4::S => PI / 2.0,
+            Dir4::W => PI,
+            Dir4::N => PI * 3.0 / 2.0,
+        }
+    }
+
+    /// Angle in `[0, 360)` degrees
+    pub fn to_degrees(&self) -> f32 {
+        self.to_radians().to_degrees()
+    }
+
+    /// Snaps an angle to the nearest of the four sectors. See [`Dir4::from_degrees`].
+    pub fn from_radians(rad: f32) -> Self {
+        Self::from_degrees(rad.to_degrees())
+    }
+
+    /// Snaps an angle to the nearest of the four sectors
+    ///
+    /// Angles exactly between two directions (45°, 135°, ..) resolve to the clockwise one. NaN
+    /// resolves to E.
+    pub fn from_degrees(deg: f32) -> Self {
+        match (deg / 90.0 + 0.5).floor().rem_euclid(4.0) as u8 {
+            1 => Dir4::S,
+            2 => Dir4::W,
+            3 => Dir4::N,
+            _ => Dir4::E,
+        }
+    }
+
+    /// Snaps a vector to the nearest of the four 90° sectors
+    ///
+    /// Returns `None` if the vector is shorter than `min_len`, zero-length or NaN.
+    pub fn from_vec2(v: [f32; 2], min_len: f32) -> Option<Self> {
+        let len = (v[0] * v[0] + v[1] * v[1]).sqrt();
+        if len.is_nan() || len <= 0.0 || len < min_len {
+            return None;
+        }
+        Some(Self::from_radians(v[1].atan2(v[0])))
+    }
+}
+
+/// Iteration
+impl Dir4 {
+    pub const CLOCKWISE: &'static [Dir4; 4] = &[Dir4::N, Dir4::E, Dir4::S, Dir4::W];
+
+    /// All of the directions (clockwise from N)
+    pub const ALL: &'static [Dir4; 4] = Self::CLOCKWISE;
+
+    pub const COUNTER_CLOCKWISE: &'static [Dir4; 4] = &[Dir4::N, Dir4::W, Dir4::S, Dir4::E];
+
+    /// N, E, S, W
+    pub fn clockwise() -> &'static [Dir4; 4] {
+        Self::CLOCKWISE
+    }
+
+    /// N, W, S, E
+    pub fn counter_clockwise() -> &'static [Dir4; 4] {
+        Self::COUNTER_CLOCKWISE
+    }
+
+    /// Clockwise from N
+    pub fn iter() -> impl Iterator<Item = Dir4> {
+        Self::CLOCKWISE.iter().cloned()
+    }
+}
+
+impl Dir4 {
+    /// The two directions 90° away ([counter-clockwise, clockwise])
+    pub fn perpendiculars(&self) -> [Dir4; 2] {
+        match self {
+            Dir4::N => [Dir4::W, Dir4::E],
+            Dir4::E => [Dir4::N, Dir4::S],
+            Dir4::S => [Dir4::E, Dir4::W],
+            Dir4::W => [Dir4::S, Dir4::N],
+        }
+    }
+
     pub fn inv(&self) -> Dir4 {
         match self {
             Dir4::N => Dir4::S,
@@ -140,9 +508,46 @@ impl Dir4 {
     }
 }
 
-/// N | NE | E | SE | S | SW | W | NW
+impl Dir4 {
+    pub fn to_dir8(&self) -> Dir8 {
+        match self {
+            Dir4::N => Dir8::N,
+            Dir4::E => Dir8::E,
+            Dir4::S => Dir8::S,
+            Dir4::W => Dir8::W,
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Self {
+        dir.to_dir8()
+    }
+}
+
+/// Clockwise | CounterClockwise
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RotationPreference {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Same as [`Dir4::inv`]
+impl Neg for Dir4 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.inv()
+    }
+}
+
+/// N | NE | E | SE | S | SW | W | NW
+///
+/// On `serde`, it's serialized by short name and deserialized case-insensitively with aliases
+/// (`"north-east"`, `"northeast"`, `"up-right"`, ..). Following the Y-down coordinate system,
+/// `"up"` means N and `"down"` means S.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "igri", derive(igri::Inspect))]
 pub enum Dir8 {
     N = 0,
@@ -155,7 +560,11 @@ pub enum Dir8 {
     NW = 7,
 }
 
+/// Signs
+///
+/// X axis goes right and Y axis goes down (N is `[Neutral, Neg]`), same as [`Dir4`].
 impl Dir8 {
+    /// Inverse of [`Dir8::signs`]. Returns `None` for neutral signs.
     pub fn from_signs(signs: [Sign; 2]) -> Option<Self> {
         let x = signs[0].to_i8();
         let y = signs[1].to_i8();
@@ -218,74 +627,786 @@ impl Dir8 {
     }
 }
 
+/// [`Dir4`] conversions
+impl Dir8 {
+    /// Cardinal directions are kept. Diagonals are rotated by 45° in the preferred direction.
+    pub fn to_dir4(&self, preference: RotationPreference) -> Dir4 {
+        if let Some(dir) = self.try_to_dir4() {
+            return dir;
+        }
+
+        let dir = match preference {
+            RotationPreference::Clockwise => self.r45(),
+            RotationPreference::CounterClockwise => self.l45(),
+        };
+        dir.try_to_dir4().unwrap()
+    }
+
+    /// Returns `None` for diagonals
+    pub fn try_to_dir4(&self) -> Option<Dir4> {
+        Some(match self {
+            Dir8::N => Dir4::N,
+            Dir8::E => Dir4::E,
+            Dir8::S => Dir4::S,
+            Dir8::W => Dir4::W,
+            _ => return None,
+        })
+    }
+
+    /// [x, y] components of a diagonal. Returns `None` for cardinal directions.
+    pub fn split_diagonal(&self) -> Option<(Dir4, Dir4)> {
+        Some(match self {
+            Dir8::NE => (Dir4::E, Dir4::N),
+            Dir8::SE => (Dir4::E, Dir4::S),
+            Dir8::SW => (Dir4::W, Dir4::S),
+            Dir8::NW => (Dir4::W, Dir4::N),
+            _ => return None,
+        })
+    }
+}
+
+/// Angles
+///
+/// X axis goes right and Y axis goes down, so angles are measured from E and increase
+/// clockwise on screen: E = 0, SE = π/4, S = π/2, .., NE = 7π/4.
+impl Dir8 {
+    /// Direction vector of length 1 (diagonals are normalized)
+    pub fn unit_vec(&self) -> [f32; 2] {
+        let [x, y] = self.signs_f32();
+        if x != 0.0 && y != 0.0 {
+            let s = std::f32::consts::FRAC_1_SQRT_2;
+            [x * s, y * s]
+        } else {
+            [x, y]
+        }
+    }
+
+    /// Angle in `[0, 2π)` radians
+    pub fn to_radians(&self) -> f32 {
+        // E is the origin
+        let ix = (*self as i32 - Dir8::E as i32).rem_euclid(8);
+        ix as f32 * std::f32::consts::FRAC_PI_4
+    }
+
+    /// Angle in `[0, 360)` degrees
+    pub fn to_degrees(&self) -> f32 {
+        self.to_radians().to_degrees()
+    }
+
+    /// Snaps an angle to the nearest of the eight 45° sectors. See [`Dir8::from_degrees`].
+    pub fn from_radians(rad: f32) -> Self {
+        Self::from_degrees(rad.to_degrees())
+    }
+
+    /// Snaps an angle to the nearest of the eight 45° sectors
+    ///
+    /// Angles exactly between two directions (22.5°, 67.5°, ..) resolve to the clockwise one.
+    /// NaN resolves to E.
+    pub fn from_degrees(deg: f32) -> Self {
+        let ix = (deg / 45.0 + 0.5).floor().rem_euclid(8.0) as i8;
+        Dir8::E.rotate(ix)
+    }
+
+    /// Snaps a vector to the nearest of the eight 45° sectors
+    ///
+    /// Returns `None` if the vector is shorter than `min_len`, zero-length or NaN. Agrees with
+    /// [`Dir8::from_signs`] for axis-aligned and diagonal inputs.
+    pub fn from_vec2(v: [f32; 2], min_len: f32) -> Option<Self> {
+        let len = (v[0] * v[0] + v[1] * v[1]).sqrt();
+        if len.is_nan() || len <= 0.0 || len < min_len {
+            return None;
+        }
+        Some(Self::from_radians(v[1].atan2(v[0])))
+    }
+}
+
 impl Dir8 {
     pub const CLOCKWISE: &'static [Dir8; 8] = {
         use Dir8::*;
         &[N, NE, E, SE, S, SW, W, NW]
     };
 
-    pub fn inv(&self) -> Self {
-        match self {
-            Dir8::N => Dir8::S,
-            Dir8::NE => Dir8::SW,
-            Dir8::E => Dir8::W,
-            Dir8::SE => Dir8::NW,
-            Dir8::S => Dir8::N,
-            Dir8::SW => Dir8::NE,
-            Dir8::W => Dir8::E,
-            Dir8::NW => Dir8::SE,
+    /// All of the directions (clockwise from N)
+    pub const ALL: &'static [Dir8; 8] = Self::CLOCKWISE;
+
+    pub const COUNTER_CLOCKWISE: &'static [Dir8; 8] = {
+        use Dir8::*;
+        &[N, NW, W, SW, S, SE, E, NE]
+    };
+
+    pub const CARDINALS: &'static [Dir8; 4] = &[Dir8::N, Dir8::E, Dir8::S, Dir8::W];
+
+    pub const DIAGONALS: &'static [Dir8; 4] = &[Dir8::NE, Dir8::SE, Dir8::SW, Dir8::NW];
+
+    /// N, NE, E, ..
+    pub fn clockwise() -> &'static [Dir8; 8] {
+        Self::CLOCKWISE
+    }
+
+    /// N, NW, W, ..
+    pub fn counter_clockwise() -> &'static [Dir8; 8] {
+        Self::COUNTER_CLOCKWISE
+    }
+
+    /// N, E, S, W
+    pub fn cardinals() -> &'static [Dir8; 4] {
+        Self::CARDINALS
+    }
+
+    /// NE, SE, SW, NW
+    pub fn diagonals() -> &'static [Dir8; 4] {
+        Self::DIAGONALS
+    }
+
+    /// Clockwise from N
+    pub fn iter() -> impl Iterator<Item = Dir8> {
+        Self::CLOCKWISE.iter().cloned()
+    }
+
+    /// Eight directions clockwise, beginning at `start`
+    ///
+    /// ```
+    /// use xdl::Dir8;
+    ///
+    /// let facing = Dir8::E;
+    /// // scan neighbors clockwise from the facing direction
+    /// let scan = Dir8::clockwise_from(facing).collect::<Vec<_>>();
+    /// assert_eq!(scan[0], Dir8::E);
+    /// assert_eq!(scan[1], Dir8::SE);
+    /// assert_eq!(scan[7], Dir8::NE);
+    /// ```
+    pub fn clockwise_from(start: Dir8) -> impl Iterator<Item = Dir8> {
+        (0..8).map(move |i| start.rotate(i))
+    }
+
+    /// Rotates clockwise by 45 degrees × `steps`. Negative steps rotate counter-clockwise.
+    pub fn rotate(&self, steps: i8) -> Self {
+        let ix = (*self as i32 + steps as i32).rem_euclid(8);
+        Self::CLOCKWISE[ix as usize]
+    }
+
+    /// N, E, S or W
+    pub fn is_cardinal(&self) -> bool {
+        !self.is_diagonal()
+    }
+
+    /// NE, SE, SW or NW
+    pub fn is_diagonal(&self) -> bool {
+        self.x_sign() != Sign::Neutral && self.y_sign() != Sign::Neutral
+    }
+
+    /// The two neighbors 45° away ([counter-clockwise, clockwise])
+    pub fn adjacent(&self) -> [Dir8; 2] {
+        [self.l45(), self.r45()]
+    }
+
+    /// If `other` is 45° away
+    pub fn is_adjacent_to(&self, other: Dir8) -> bool {
+        self.steps_to(other).abs() == 1
+    }
+
+    /// Index in [`Dir8::CLOCKWISE`] (N = 0, NE = 1, ..)
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Inverse of [`Dir8::index`]. The index wraps around by 8.
+    pub fn from_index(ix: u8) -> Self {
+        Self::CLOCKWISE[(ix % 8) as usize]
+    }
+
+    /// Signed shortest rotation in 45° steps to `other`. Positive is clockwise.
+    ///
+    /// Opposite directions are always `4` (clockwise). `self.rotate(self.steps_to(other))` is
+    /// `other`.
+    pub fn steps_to(&self, other: Dir8) -> i8 {
+        let diff = (other.index() as i8 - self.index() as i8).rem_euclid(8);
+        if diff > 4 {
+            diff - 8
+        } else {
+            diff
         }
     }
 
+    pub fn inv(&self) -> Self {
+        self.rotate(4)
+    }
+
     pub fn r45(&self) -> Self {
-        match self {
-            Dir8::N => Dir8::NE,
-            Dir8::NE => Dir8::E,
-            Dir8::E => Dir8::SE,
-            Dir8::SE => Dir8::S,
-            Dir8::S => Dir8::SW,
-            Dir8::SW => Dir8::W,
-            Dir8::W => Dir8::NW,
-            Dir8::NW => Dir8::N,
-        }
+        self.rotate(1)
     }
 
     pub fn l45(&self) -> Self {
-        match self {
-            Dir8::N => Dir8::NW,
-            Dir8::NE => Dir8::W,
-            Dir8::E => Dir8::NE,
-            Dir8::SE => Dir8::E,
-            Dir8::S => Dir8::SE,
-            Dir8::SW => Dir8::SW,
-            Dir8::W => Dir8::SW,
-            Dir8::NW => Dir8::W,
-        }
+        self.rotate(-1)
     }
 
     pub fn r90(&self) -> Self {
-        match self {
-            Dir8::N => Dir8::E,
-            Dir8::NE => Dir8::SE,
-            Dir8::E => Dir8::S,
-            Dir8::SE => Dir8::SW,
-            Dir8::S => Dir8::W,
-            Dir8::SW => Dir8::NW,
-            Dir8::W => Dir8::N,
-            Dir8::NW => Dir8::NE,
-        }
+        self.rotate(2)
     }
 
     pub fn l90(&self) -> Self {
-        match self {
-            Dir8::N => Dir8::W,
-            Dir8::NE => Dir8::NE,
-            Dir8::E => Dir8::N,
-            Dir8::SE => Dir8::NE,
-            Dir8::S => Dir8::E,
-            Dir8::SW => Dir8::SE,
-            Dir8::W => Dir8::S,
-            Dir8::NW => Dir8::SW,
+        self.rotate(-2)
+    }
+}
+
+/// Same as [`Dir8::inv`]
+impl Neg for Dir8 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.inv()
+    }
+}
+
+/// Rotates clockwise by 45° × `steps`
+///
+/// ```
+/// use xdl::Dir8;
+///
+/// assert_eq!(Dir8::N + 2, Dir8::E);
+/// assert_eq!(Dir8::N - 1, Dir8::NW);
+/// assert_eq!(-Dir8::NE, Dir8::SW);
+/// ```
+impl Add<i8> for Dir8 {
+    type Output = Self;
+    fn add(self, steps: i8) -> Self {
+        self.rotate(steps)
+    }
+}
+
+/// Rotates counter-clockwise by 45° × `steps`
+impl Sub<i8> for Dir8 {
+    type Output = Self;
+    fn sub(self, steps: i8) -> Self {
+        self.rotate(steps.wrapping_neg())
+    }
+}
+
+/// Radial dead zone for analog two-axis values
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeadZone {
+    /// Length under which the value is zero
+    pub inner: f32,
+    /// Length over which the value is of length one
+    pub outer: f32,
+}
+
+impl Default for DeadZone {
+    fn default() -> Self {
+        Self {
+            inner: 0.0,
+            outer: 1.0,
+        }
+    }
+}
+
+/// Analog two-axis value such as a stick position
+///
+/// X axis goes right and Y axis goes down.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Axis2d {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Axis2d {
+    pub const ZERO: Axis2d = Axis2d { x: 0.0, y: 0.0 };
+
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    /// Scaled to length one. Zero-length value stays zero.
+    pub fn normalized(&self) -> Self {
+        let len = self.length();
+        if len > 0.0 {
+            Self::new(self.x / len, self.y / len)
+        } else {
+            Self::ZERO
+        }
+    }
+
+    /// Scaled down to length one if it's longer
+    pub fn clamped_to_unit(&self) -> Self {
+        if self.length() > 1.0 {
+            self.normalized()
+        } else {
+            *self
+        }
+    }
+
+    /// Zero inside the inner radius, rescaled to `0.0..=1.0` between the inner and outer radius
+    pub fn dead_zoned(&self, dz: DeadZone) -> Self {
+        let len = self.length();
+        if len.is_nan() || len <= dz.inner {
+            return Self::ZERO;
+        }
+
+        let scaled = if dz.outer > dz.inner {
+            ((len - dz.inner) / (dz.outer - dz.inner)).min(1.0)
+        } else {
+            1.0
+        };
+
+        Self::new(self.x / len * scaled, self.y / len * scaled)
+    }
+
+    /// See [`Dir8::from_vec2`]
+    pub fn to_dir8(&self, min_len: f32) -> Option<Dir8> {
+        Dir8::from_vec2([self.x, self.y], min_len)
+    }
+
+    /// See [`Dir4::from_vec2`]
+    pub fn to_dir4(&self, min_len: f32) -> Option<Dir4> {
+        Dir4::from_vec2([self.x, self.y], min_len)
+    }
+}
+
+impl From<[f32; 2]> for Axis2d {
+    fn from(v: [f32; 2]) -> Self {
+        Self::new(v[0], v[1])
+    }
+}
+
+impl From<Axis2d> for [f32; 2] {
+    fn from(v: Axis2d) -> Self {
+        [v.x, v.y]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::xorshift64;
+
+    #[test]
+    fn rotation_wraps_around() {
+        for dir in Dir8::iter() {
+            for n in -16..16 {
+                assert_eq!(dir.rotate(n), dir.rotate(n + 8), "{:?} by {}", dir, n);
+            }
+            let mut d = dir;
+            for _ in 0..8 {
+                d = d.r45();
+            }
+            assert_eq!(d, dir);
+        }
+    }
+
+    #[test]
+    fn rotation_tables() {
+        for dir in Dir8::iter() {
+            assert_eq!(dir.r45().l45(), dir);
+            assert_eq!(dir.l45().r45(), dir);
+            assert_eq!(dir.r90(), dir.r45().r45());
+            assert_eq!(dir.l90(), dir.l45().l45());
+            assert_eq!(dir.inv(), dir.r90().r90());
+            assert_eq!(dir.inv(), dir.rotate(4));
+        }
+
+        assert_eq!(Dir8::NE.l45(), Dir8::N);
+        assert_eq!(Dir8::SW.l45(), Dir8::S);
+        assert_eq!(Dir8::NE.l90(), Dir8::NW);
+        assert_eq!(Dir8::SE.l90(), Dir8::NE);
+    }
+
+    #[test]
+    fn sign_conversions() {
+        use std::cmp::Ordering;
+
+        let cases = [
+            (Sign::Pos, 1, Ordering::Greater),
+            (Sign::Neg, -1, Ordering::Less),
+            (Sign::Neutral, 0, Ordering::Equal),
+        ];
+        for (sign, n, ord) in cases {
+            assert_eq!(sign.to_i8(), n as i8);
+            assert_eq!(sign.to_i32(), n);
+            assert_eq!(sign.to_i64(), n as i64);
+            assert_eq!(sign.to_isize(), n as isize);
+            assert_eq!(sign.to_f32(), n as f32);
+            assert_eq!(sign.to_f64(), n as f64);
+            assert_eq!(i8::from(sign), n as i8);
+            assert_eq!(i32::from(sign), n);
+            assert_eq!(f32::from(sign), n as f32);
+
+            assert_eq!(Sign::from_i32(n), sign);
+            assert_eq!(Sign::from_i32(n * 5), sign);
+            assert_eq!(Sign::from(ord), sign);
+        }
+    }
+
+    #[test]
+    fn dir4_conversions() {
+        for dir in Dir4::iter() {
+            assert_eq!(Dir4::from_signs(dir.signs()), Some(dir));
+            assert_eq!(Dir4::from_vec2(dir.unit_vec(), 0.5), Some(dir));
+            assert_eq!(Dir4::from_radians(dir.to_radians()), dir);
+            assert_eq!(Dir4::from_degrees(dir.to_degrees()), dir);
+
+            let dir8 = Dir8::from(dir);
+            assert_eq!(dir8, dir.to_dir8());
+            assert_eq!(dir8.try_to_dir4(), Some(dir));
+            assert_eq!(dir8.to_dir4(RotationPreference::Clockwise), dir);
+        }
+    }
+
+    #[test]
+    fn dir8_conversions() {
+        for dir in Dir8::iter() {
+            assert_eq!(Dir8::from_signs(dir.signs()), Some(dir));
+            assert_eq!(Dir8::from_vec2(dir.unit_vec(), 0.5), Some(dir));
+            assert_eq!(Dir8::from_vec2(dir.signs_f32(), 0.5), Some(dir));
+            assert_eq!(Dir8::from_radians(dir.to_radians()), dir);
+            assert_eq!(Dir8::from_degrees(dir.to_degrees()), dir);
+            assert_eq!(Dir8::from_index(dir.index()), dir);
+            assert_eq!(Axis2d::from(dir.unit_vec()).to_dir8(0.5), Some(dir));
         }
+
+        assert_eq!(Dir8::from_signs([Sign::Neutral; 2]), None);
+        assert_eq!(Dir8::NE.try_to_dir4(), None);
+        assert_eq!(Dir8::NE.to_dir4(RotationPreference::Clockwise), Dir4::E);
+        assert_eq!(
+            Dir8::NE.to_dir4(RotationPreference::CounterClockwise),
+            Dir4::N
+        );
+    }
+
+    /// Angular distance in degrees
+    fn angle_diff(a: f32, b: f32) -> f32 {
+        let d = (a - b).rem_euclid(360.0);
+        d.min(360.0 - d)
+    }
+
+    #[test]
+    fn angle_sweep_across_sectors() {
+        // 0.1° steps over two turns both ways, skipping the boundaries
+        for i in -7200..7200 {
+            let deg = i as f32 * 0.1;
+
+            let d8 = Dir8::from_degrees(deg);
+            let diff = self::angle_diff(deg, d8.to_degrees());
+            if (diff - 22.5).abs() > 1e-3 {
+                assert!(diff < 22.5, "{}° -> {:?}", deg, d8);
+            }
+
+            let d4 = Dir4::from_degrees(deg);
+            let diff = self::angle_diff(deg, d4.to_degrees());
+            if (diff - 45.0).abs() > 1e-3 {
+                assert!(diff < 45.0, "{}° -> {:?}", deg, d4);
+            }
+        }
+
+        // exactly between two directions, the clockwise one wins
+        for dir in Dir8::iter() {
+            let deg = dir.to_degrees() + 22.5;
+            assert_eq!(Dir8::from_degrees(deg), dir.r45(), "{}°", deg);
+            assert_eq!(Dir8::from_degrees(deg - 360.0), dir.r45(), "{}°", deg);
+        }
+        for dir in Dir4::iter() {
+            let deg = dir.to_degrees() + 45.0;
+            let cw = dir.to_dir8().r90().try_to_dir4().unwrap();
+            assert_eq!(Dir4::from_degrees(deg), cw, "{}°", deg);
+        }
+    }
+
+    /// xorshift64, mapped to `-1.0..1.0`
+    fn next_f32(seed: &mut u64) -> f32 {
+        (xorshift64(seed) >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+        a[0] * b[0] + a[1] * b[1]
+    }
+
+    #[test]
+    fn from_vec2_maximizes_the_dot_product() {
+        let mut seed = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..10_000 {
+            let scale = 1.0 + self::next_f32(&mut seed).abs() * 100.0;
+            let v = [
+                self::next_f32(&mut seed) * scale,
+                self::next_f32(&mut seed) * scale,
+            ];
+            let len = Axis2d::from(v).length();
+
+            let d8 = match Dir8::from_vec2(v, 0.1) {
+                Some(d8) => d8,
+                None => {
+                    assert!(len < 0.1, "{:?}", v);
+                    continue;
+                }
+            };
+            let max = Dir8::iter()
+                .map(|d| self::dot(d.unit_vec(), v))
+                .fold(f32::MIN, f32::max);
+            assert!(
+                self::dot(d8.unit_vec(), v) >= max - 1e-4 * len,
+                "{:?} -> {:?}",
+                v,
+                d8
+            );
+
+            let d4 = Dir4::from_vec2(v, 0.1).unwrap();
+            let max = Dir4::iter()
+                .map(|d| self::dot(d.unit_vec(), v))
+                .fold(f32::MIN, f32::max);
+            assert!(
+                self::dot(d4.unit_vec(), v) >= max - 1e-4 * len,
+                "{:?} -> {:?}",
+                v,
+                d4
+            );
+        }
+
+        assert_eq!(Dir8::from_vec2([0.3, 0.0], 0.5), None);
+        assert_eq!(Dir8::from_vec2([0.0, 0.0], 0.0), None);
+        assert_eq!(Dir8::from_vec2([f32::NAN, 1.0], 0.0), None);
+        assert_eq!(Dir4::from_vec2([1.0, f32::NAN], 0.0), None);
+        for dir in Dir8::iter() {
+            let [x, y] = dir.signs_f32();
+            assert_eq!(Dir8::from_vec2([x * 3.0, y * 3.0], 1.0), Some(dir));
+        }
+    }
+
+    #[test]
+    fn diagonals_to_dir4() {
+        use RotationPreference::*;
+
+        // (diagonal, clockwise, counter-clockwise)
+        let cases = [
+            (Dir8::NE, Dir4::E, Dir4::N),
+            (Dir8::SE, Dir4::S, Dir4::E),
+            (Dir8::SW, Dir4::W, Dir4::S),
+            (Dir8::NW, Dir4::N, Dir4::W),
+        ];
+        for (dir, cw, ccw) in cases {
+            assert_eq!(dir.to_dir4(Clockwise), cw, "{:?}", dir);
+            assert_eq!(dir.to_dir4(CounterClockwise), ccw, "{:?}", dir);
+            assert_eq!(dir.try_to_dir4(), None);
+
+            // the two preferences give the two components
+            let (x, y) = dir.split_diagonal().unwrap();
+            assert!([(cw, ccw), (ccw, cw)].contains(&(x, y)), "{:?}", dir);
+            assert_eq!(Dir8::from_signs([x.x_sign(), y.y_sign()]), Some(dir));
+        }
+
+        for dir in Dir4::iter() {
+            for pref in [Clockwise, CounterClockwise] {
+                assert_eq!(dir.to_dir8().to_dir4(pref), dir);
+            }
+            assert_eq!(dir.to_dir8().split_diagonal(), None);
+        }
+    }
+
+    #[test]
+    fn sign_multiplication_table() {
+        use Sign::*;
+
+        let table = [
+            (Pos, Pos, Pos),
+            (Pos, Neg, Neg),
+            (Pos, Neutral, Neutral),
+            (Neg, Pos, Neg),
+            (Neg, Neg, Pos),
+            (Neg, Neutral, Neutral),
+            (Neutral, Pos, Neutral),
+            (Neutral, Neg, Neutral),
+            (Neutral, Neutral, Neutral),
+        ];
+        for (a, b, ab) in table {
+            assert_eq!(a * b, ab, "{:?} * {:?}", a, b);
+            assert_eq!(a * b, b * a);
+        }
+
+        for sign in [Pos, Neg, Neutral] {
+            assert_eq!(-sign, sign.inv());
+            assert_eq!(-(-sign), sign);
+            for n in [-3, 0, 1, 7] {
+                assert_eq!(sign * n, sign.to_i32() * n);
+            }
+        }
+        for dir in Dir4::iter() {
+            assert_eq!(-dir, dir.inv());
+        }
+        for dir in Dir8::iter() {
+            assert_eq!(-dir, dir.inv());
+            assert_eq!(dir + 2, dir.r90());
+            assert_eq!(dir - 2, dir.l90());
+        }
+    }
+
+    #[test]
+    fn steps_to_reaches_every_direction() {
+        for a in Dir8::iter() {
+            for b in Dir8::iter() {
+                let steps = a.steps_to(b);
+                assert!((-3..=4).contains(&steps), "{:?} to {:?}: {}", a, b, steps);
+                assert_eq!(a.rotate(steps), b, "{:?} to {:?}", a, b);
+                assert_eq!(a + steps, b);
+
+                // shortest, and the opposite way round unless it's 180°
+                let back = b.steps_to(a);
+                if steps == 4 {
+                    assert_eq!(back, 4);
+                } else {
+                    assert_eq!(back, -steps);
+                }
+            }
+            assert_eq!(a.steps_to(a), 0);
+            assert_eq!(a.steps_to(a.inv()), 4);
+        }
+
+        for ix in 0..=255 {
+            assert_eq!(Dir8::from_index(ix).index(), ix % 8);
+        }
+    }
+
+    #[test]
+    fn adjacency_of_every_pair() {
+        for a in Dir8::iter() {
+            let adjacent = a.adjacent();
+            assert_eq!(adjacent, [a.l45(), a.r45()]);
+            assert_ne!(a.is_cardinal(), a.is_diagonal());
+            for b in adjacent {
+                // cardinals and diagonals alternate
+                assert_ne!(a.is_cardinal(), b.is_cardinal());
+            }
+
+            for b in Dir8::iter() {
+                let dot = self::dot(a.unit_vec(), b.unit_vec());
+                let expected = (dot - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-5;
+                assert_eq!(a.is_adjacent_to(b), expected, "{:?} {:?}", a, b);
+                assert_eq!(a.is_adjacent_to(b), adjacent.contains(&b));
+                assert_eq!(a.is_adjacent_to(b), b.is_adjacent_to(a));
+            }
+        }
+        assert_eq!(Dir8::cardinals().len(), 4);
+        assert!(Dir8::cardinals().iter().all(Dir8::is_cardinal));
+        assert!(Dir8::diagonals().iter().all(Dir8::is_diagonal));
+
+        for a in Dir4::iter() {
+            let perps = a.perpendiculars();
+            for b in Dir4::iter() {
+                let is_perp = self::dot(a.unit_vec(), b.unit_vec()) == 0.0;
+                assert_eq!(perps.contains(&b), is_perp, "{:?} {:?}", a, b);
+            }
+            assert_ne!(perps[0], perps[1]);
+        }
+    }
+
+    #[test]
+    fn axis2d_conversions() {
+        let v = Axis2d::new(0.25, -0.5);
+        assert_eq!(Axis2d::from(<[f32; 2]>::from(v)), v);
+        assert_eq!(<[f32; 2]>::from(Axis2d::from([1.0, 2.0])), [1.0, 2.0]);
+        assert_eq!(Axis2d::new(0.0, -1.0).to_dir4(0.5), Some(Dir4::N));
+        assert_eq!(Axis2d::ZERO.to_dir8(0.0), None);
+    }
+
+    #[test]
+    fn axis2d_dead_zone_rescales() {
+        let dz = DeadZone {
+            inner: 0.2,
+            outer: 0.8,
+        };
+        let dirs = [[1.0, 0.0], [0.0, -1.0], [0.6, 0.8], [-0.8, 0.6]];
+
+        for dir in dirs {
+            let at = |len: f32| Axis2d::new(dir[0] * len, dir[1] * len).dead_zoned(dz);
+
+            // zero inside the inner radius
+            for len in [0.0, 0.1, 0.2] {
+                assert_eq!(at(len), Axis2d::ZERO, "{:?} * {}", dir, len);
+            }
+
+            // linear from zero to one between the radii, keeping the direction
+            for (len, expected) in [(0.35, 0.25), (0.5, 0.5), (0.65, 0.75), (0.8, 1.0)] {
+                let v = at(len);
+                assert!((v.length() - expected).abs() < 1e-5, "{:?} * {}", dir, len);
+                assert!((v.x - dir[0] * expected).abs() < 1e-5);
+                assert!((v.y - dir[1] * expected).abs() < 1e-5);
+            }
+
+            // clamped outside the outer radius
+            assert!((at(2.0).length() - 1.0).abs() < 1e-5);
+        }
+
+        assert_eq!(Axis2d::new(f32::NAN, 0.0).dead_zoned(dz), Axis2d::ZERO);
+        let v = Axis2d::new(0.3, 0.4);
+        assert_eq!(v.dead_zoned(DeadZone::default()), v);
+        assert_eq!(v.dead_zoned(dz).to_dir8(0.0), v.to_dir8(0.0));
+    }
+
+    #[test]
+    fn sign_names() {
+        assert_eq!(Sign::from_name("-"), Some(Sign::Neg));
+        assert_eq!(Sign::from_name("+"), Some(Sign::Pos));
+        assert_eq!(Sign::from_name("0"), Some(Sign::Neutral));
+        assert_eq!(Sign::from_name("Negative"), Some(Sign::Neg));
+        assert_eq!(Sign::from_name(""), None);
+        assert_eq!(Sign::from_name("--"), None);
+    }
+
+    #[test]
+    fn dir_names() {
+        // Y axis goes down, so "up" is N
+        assert_eq!(Dir4::from_name("up"), Some(Dir4::N));
+        assert_eq!(Dir4::from_name("Down"), Some(Dir4::S));
+        assert_eq!(Dir8::from_name("up"), Some(Dir8::N));
+        assert_eq!(Dir8::from_name("up-right"), Some(Dir8::NE));
+        assert_eq!(Dir8::from_name("DOWN_LEFT"), Some(Dir8::SW));
+        assert_eq!(Dir8::from_name("North East"), Some(Dir8::NE));
+        assert_eq!(Dir8::from_name("ne"), Some(Dir8::NE));
+        assert_eq!(Dir8::from_name("-"), None);
+
+        for dir in Dir8::iter() {
+            assert_eq!(dir.to_string().parse::<Dir8>(), Ok(dir));
+        }
+        for dir in Dir4::iter() {
+            assert_eq!(dir.to_string().parse::<Dir4>(), Ok(dir));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        for sign in [Sign::Pos, Sign::Neg, Sign::Neutral] {
+            let s = serde_json::to_string(&sign).unwrap();
+            assert_eq!(serde_json::from_str::<Sign>(&s).unwrap(), sign);
+        }
+        for dir in Dir4::iter() {
+            let s = serde_json::to_string(&dir).unwrap();
+            assert_eq!(s, format!("\"{}\"", dir));
+            assert_eq!(serde_json::from_str::<Dir4>(&s).unwrap(), dir);
+        }
+        for dir in Dir8::iter() {
+            let s = serde_json::to_string(&dir).unwrap();
+            assert_eq!(s, format!("\"{}\"", dir));
+            assert_eq!(serde_json::from_str::<Dir8>(&s).unwrap(), dir);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_aliases() {
+        let sign = |s: &str| serde_json::from_str::<Sign>(s).ok();
+        assert_eq!(sign(r#""-""#), Some(Sign::Neg));
+        assert_eq!(sign(r#""positive""#), Some(Sign::Pos));
+        assert_eq!(sign(r#""zero""#), Some(Sign::Neutral));
+
+        let dir8 = |s: &str| serde_json::from_str::<Dir8>(s).ok();
+        assert_eq!(dir8(r#""up""#), Some(Dir8::N));
+        assert_eq!(dir8(r#""north""#), Some(Dir8::N));
+        assert_eq!(dir8(r#""south-west""#), Some(Dir8::SW));
+        assert_eq!(dir8(r#""nE""#), Some(Dir8::NE));
+        assert_eq!(dir8(r#""sideways""#), None);
+
+        assert_eq!(serde_json::from_str::<Dir4>(r#""UP""#).ok(), Some(Dir4::N));
     }
 }
@@ -4,11 +4,97 @@
 [`Backend`]: ../Backend
 */
 
+mod composite;
+mod filter;
+mod latency;
+mod manual;
+mod outcome;
+mod replay;
+mod translation;
+mod verify;
+
+#[cfg(any(test, feature = "test-util"))]
+mod script;
+
+#[cfg(feature = "bench-util")]
+mod synthetic;
+
 #[cfg(feature = "sdl2")]
 mod sdl2_support;
 
+#[cfg(feature = "winit")]
+mod winit_support;
+
+#[cfg(feature = "miniquad")]
+mod miniquad_support;
+
+#[cfg(feature = "glfw")]
+mod glfw_support;
+
+#[cfg(feature = "web")]
+pub mod web_support;
+
+#[cfg(feature = "crossterm")]
+mod crossterm_support;
+
+#[cfg(feature = "bevy")]
+mod bevy_support;
+
 #[cfg(feature = "sdl2")]
 pub extern crate sdl2;
 
+#[cfg(feature = "winit")]
+pub extern crate winit;
+
+#[cfg(feature = "miniquad")]
+pub extern crate miniquad;
+
+#[cfg(feature = "glfw")]
+pub extern crate glfw;
+
+#[cfg(feature = "crossterm")]
+pub extern crate crossterm;
+
+#[cfg(feature = "bevy")]
+pub extern crate bevy;
+
+pub use self::{
+    composite::{AnyBackend, Capabilities, CompositeBackend},
+    filter::{EventFilter, FilteredBackend},
+    latency::{Latency, LatencyBackend},
+    manual::{ManualBackend, ManualEvent},
+    outcome::{DeviceKind, EventOutcome},
+    translation::KeyTranslation,
+};
+
+pub use self::replay::{
+    RecordedEvent, RecordedInput, Recording, RecordingBackend, ReplayBackend, ReplayError,
+};
+pub use self::verify::{DesyncReport, KeyDiff, ReplayVerifier};
+
+#[cfg(feature = "bench-util")]
+pub use self::synthetic::{SyntheticBackend, SyntheticEvent};
+
+#[cfg(any(test, feature = "test-util"))]
+pub use self::script::{FrameScript, ScriptBackend};
+
 #[cfg(feature = "sdl2")]
 pub use self::sdl2_support::SdlBackend;
+
+#[cfg(feature = "winit")]
+pub use self::winit_support::WinitBackend;
+
+#[cfg(feature = "miniquad")]
+pub use self::miniquad_support::{Event as MiniquadEvent, MiniquadBackend};
+
+#[cfg(feature = "glfw")]
+pub use self::glfw_support::GlfwBackend;
+
+#[cfg(feature = "web")]
+pub use self::web_support::WebBackend;
+
+#[cfg(feature = "crossterm")]
+pub use self::crossterm_support::{CrosstermBackend, ReleasePolicy};
+
+#[cfg(feature = "bevy")]
+pub use self::bevy_support::{ActionMapResource, Actions, BevyBackend, XdlAppExt, XdlPlugin};
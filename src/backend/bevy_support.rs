@@ -0,0 +1,498 @@
+//! Bevy backend and plugin
+//!
+//! Built against Bevy 0.13. Key codes are translated from Bevy's physical key codes, so bindings
+//! don't depend on the keyboard layout. [`Input::pointer`] is positioned in logical pixels, as
+//! reported by Bevy's `CursorMoved`.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+use bevy::{
+    app::{App, Plugin, PostUpdate, PreUpdate},
+    ecs::{
+        event::EventReader,
+        schedule::IntoSystemConfigs,
+        system::{Res, ResMut, Resource},
+    },
+    input::{
+        keyboard::{KeyCode, KeyboardInput},
+        mouse::MouseButtonInput,
+        ButtonState,
+    },
+    time::Time,
+    window::{CursorMoved, ReceivedCharacter, WindowFocused},
+};
+
+use crate::{
+    backend::{DeviceKind, EventOutcome, KeyTranslation},
+    input::{keyboard::Key, pointer::MouseButton, Input},
+    vi::{ActionMap, AxisButton, AxisDirButton, Button, ButtonBatch, InputSystem},
+    Backend,
+};
+
+pub type ExternalKey = KeyCode;
+pub type Event = KeyboardInput;
+
+/// Bevy backend, driven by [`XdlPlugin`]
+#[derive(Debug, Clone)]
+pub struct BevyBackend {
+    map: KeyTranslation<ExternalKey>,
+}
+
+impl Resource for BevyBackend {}
+
+impl Default for BevyBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BevyBackend {
+    pub fn new() -> Self {
+        Self {
+            map: KeyTranslation::from_table(KEY_TRANSLATION),
+        }
+    }
+
+    pub fn translate(&self, key: &ExternalKey) -> Option<Key> {
+        self.map.translate(key)
+    }
+
+    /// Merges the mappings over the built-in table
+    pub fn with_overrides(mut self, overrides: HashMap<ExternalKey, Key>) -> Self {
+        self.map = self.map.with_overrides(overrides);
+        self
+    }
+
+    pub fn translation(&self) -> &KeyTranslation<ExternalKey> {
+        &self.map
+    }
+
+    pub fn translation_mut(&mut self) -> &mut KeyTranslation<ExternalKey> {
+        &mut self.map
+    }
+}
+
+impl Backend for BevyBackend {
+    type Event = Event;
+    type Key = ExternalKey;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        if let Some(key) = self.translate(&ev.key_code) {
+            match ev.state {
+                ButtonState::Pressed => input.kbd.on_key_down(key),
+                ButtonState::Released => input.kbd.on_key_up(key),
+            }
+        }
+        EventOutcome::consumed(DeviceKind::Keyboard)
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
+
+fn mouse_button(button: bevy::input::mouse::MouseButton) -> Option<MouseButton> {
+    use bevy::input::mouse::MouseButton as M;
+
+    Some(match button {
+        M::Left => MouseButton::Left,
+        M::Right => MouseButton::Right,
+        M::Middle => MouseButton::Middle,
+        M::Back => MouseButton::X1,
+        M::Forward => MouseButton::X2,
+        M::Other(_) => return None,
+    })
+}
+
+impl Resource for Input {}
+
+/// Inserts [`Input`] and [`BevyBackend`] as resources and runs the frame lifecycle
+///
+/// In `PreUpdate`, Bevy's keyboard, mouse and text events are applied and [`Input::tick`] is
+/// called with Bevy's `dt`. Virtual input added with [`XdlAppExt::add_actions`] is updated right
+/// after that. In `PostUpdate`, the frame is ended.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XdlPlugin;
+
+impl Plugin for XdlPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KeyboardInput>()
+            .add_event::<MouseButtonInput>()
+            .add_event::<CursorMoved>()
+            .add_event::<ReceivedCharacter>()
+            .add_event::<WindowFocused>()
+            .init_resource::<BevyBackend>()
+            .insert_resource(Input::new())
+            .add_systems(PreUpdate, (self::read_mouse, self::begin_frame).chain())
+            .add_systems(PostUpdate, self::end_frame);
+    }
+}
+
+fn begin_frame(
+    mut backend: ResMut<BevyBackend>,
+    mut input: ResMut<Input>,
+    mut keys: EventReader<KeyboardInput>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut focus: EventReader<WindowFocused>,
+    time: Res<Time>,
+) {
+    let input = &mut *input;
+    for ev in keys.read() {
+        backend.on_event(input, ev);
+    }
+    for ev in chars.read() {
+        input.text.on_text(&ev.char);
+    }
+    // we won't receive key up events while the window is not focused
+    if focus.read().any(|ev| !ev.focused) {
+        input.kbd.release_all();
+    }
+    input.tick(time.delta());
+}
+
+fn read_mouse(
+    mut input: ResMut<Input>,
+    mut cursor: EventReader<CursorMoved>,
+    mut buttons: EventReader<MouseButtonInput>,
+) {
+    if let Some(ev) = cursor.read().last() {
+        input
+            .pointer
+            .on_move([ev.position.x as i32, ev.position.y as i32]);
+    }
+    for ev in buttons.read() {
+        if let Some(button) = self::mouse_button(ev.button) {
+            match ev.state {
+                ButtonState::Pressed => input.on_mouse_down(button),
+                ButtonState::Released => input.on_mouse_up(button),
+            }
+        }
+    }
+}
+
+fn end_frame(mut backend: ResMut<BevyBackend>, mut input: ResMut<Input>) {
+    backend.on_end_frame(&mut input);
+}
+
+/// Virtual input stored in an [`ActionMapResource`], updated by [`XdlPlugin`] each frame
+pub trait Actions: Send + Sync + 'static {
+    fn update(&mut self, input: &Input, dt: Duration);
+}
+
+macro_rules! impl_actions {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Actions for $ty {
+                fn update(&mut self, input: &Input, dt: Duration) {
+                    <$ty>::update(self, input, dt);
+                }
+            }
+        )*
+    };
+}
+
+impl_actions!(ActionMap, InputSystem, Button, AxisButton, AxisDirButton);
+
+impl<A: Eq + Hash + Send + Sync + 'static> Actions for ButtonBatch<A> {
+    fn update(&mut self, input: &Input, dt: Duration) {
+        self.update_all(input, dt);
+    }
+}
+
+/// Resource of virtual input, added with [`XdlAppExt::add_actions`]
+///
+/// `A` is typically the game's own struct of buttons.
+#[derive(Debug, Clone, Default)]
+pub struct ActionMapResource<A>(pub A);
+
+impl<A: Actions> Resource for ActionMapResource<A> {}
+
+impl<A> Deref for ActionMapResource<A> {
+    type Target = A;
+    fn deref(&self) -> &A {
+        &self.0
+    }
+}
+
+impl<A> DerefMut for ActionMapResource<A> {
+    fn deref_mut(&mut self) -> &mut A {
+        &mut self.0
+    }
+}
+
+fn update_actions<A: Actions>(input: Res<Input>, mut actions: ResMut<ActionMapResource<A>>) {
+    actions.0.update(&input, input.dt());
+}
+
+/// Registration of virtual input to [`XdlPlugin`]
+pub trait XdlAppExt {
+    /// Inserts [`ActionMapResource`] updated after [`Input`] on each frame
+    fn add_actions<A: Actions>(&mut self, actions: A) -> &mut Self;
+}
+
+impl XdlAppExt for App {
+    fn add_actions<A: Actions>(&mut self, actions: A) -> &mut Self {
+        self.insert_resource(ActionMapResource(actions))
+            .add_systems(
+                PreUpdate,
+                self::update_actions::<A>.after(self::begin_frame),
+            )
+    }
+}
+
+/// Same as the winit table; Bevy's key codes are winit's. Every key code is mapped to a unique
+/// [`Key`]. `NumpadEnter` is not mapped since [`Key`] has only one enter key.
+pub(crate) const KEY_TRANSLATION: &[(KeyCode, Key)] = &[
+    (KeyCode::KeyA, Key::A),
+    (KeyCode::KeyB, Key::B),
+    (KeyCode::KeyC, Key::C),
+    (KeyCode::KeyD, Key::D),
+    (KeyCode::KeyE, Key::E),
+    (KeyCode::KeyF, Key::F),
+    (KeyCode::KeyG, Key::G),
+    (KeyCode::KeyH, Key::H),
+    (KeyCode::KeyI, Key::I),
+    (KeyCode::KeyJ, Key::J),
+    (KeyCode::KeyK, Key::K),
+    (KeyCode::KeyL, Key::L),
+    (KeyCode::KeyM, Key::M),
+    (KeyCode::KeyN, Key::N),
+    (KeyCode::KeyO, Key::O),
+    (KeyCode::KeyP, Key::P),
+    (KeyCode::KeyQ, Key::Q),
+    (KeyCode::KeyR, Key::R),
+    (KeyCode::KeyS, Key::S),
+    (KeyCode::KeyT, Key::T),
+    (KeyCode::KeyU, Key::U),
+    (KeyCode::KeyV, Key::V),
+    (KeyCode::KeyW, Key::W),
+    (KeyCode::KeyX, Key::X),
+    (KeyCode::KeyY, Key::Y),
+    (KeyCode::KeyZ, Key::Z),
+    (KeyCode::Digit0, Key::D0),
+    (KeyCode::Digit1, Key::D1),
+    (KeyCode::Digit2, Key::D2),
+    (KeyCode::Digit3, Key::D3),
+    (KeyCode::Digit4, Key::D4),
+    (KeyCode::Digit5, Key::D5),
+    (KeyCode::Digit6, Key::D6),
+    (KeyCode::Digit7, Key::D7),
+    (KeyCode::Digit8, Key::D8),
+    (KeyCode::Digit9, Key::D9),
+    (KeyCode::Numpad0, Key::NumPad0),
+    (KeyCode::Numpad1, Key::NumPad1),
+    (KeyCode::Numpad2, Key::NumPad2),
+    (KeyCode::Numpad3, Key::NumPad3),
+    (KeyCode::Numpad4, Key::NumPad4),
+    (KeyCode::Numpad5, Key::NumPad5),
+    (KeyCode::Numpad6, Key::NumPad6),
+    (KeyCode::Numpad7, Key::NumPad7),
+    (KeyCode::Numpad8, Key::NumPad8),
+    (KeyCode::Numpad9, Key::NumPad9),
+    (KeyCode::NumpadClear, Key::OemClear),
+    (KeyCode::NumpadDecimal, Key::Decimal),
+    (KeyCode::NumpadDivide, Key::Slash),
+    (KeyCode::NumpadSubtract, Key::Minus),
+    (KeyCode::NumpadMultiply, Key::Star),
+    (KeyCode::NumpadAdd, Key::Plus),
+    (KeyCode::F1, Key::F1),
+    (KeyCode::F2, Key::F2),
+    (KeyCode::F3, Key::F3),
+    (KeyCode::F4, Key::F4),
+    (KeyCode::F5, Key::F5),
+    (KeyCode::F6, Key::F6),
+    (KeyCode::F7, Key::F7),
+    (KeyCode::F8, Key::F8),
+    (KeyCode::F9, Key::F9),
+    (KeyCode::F10, Key::F10),
+    (KeyCode::F11, Key::F11),
+    (KeyCode::F12, Key::F12),
+    (KeyCode::F13, Key::F13),
+    (KeyCode::F14, Key::F14),
+    (KeyCode::F15, Key::F15),
+    (KeyCode::F16, Key::F16),
+    (KeyCode::F17, Key::F17),
+    (KeyCode::F18, Key::F18),
+    (KeyCode::F19, Key::F19),
+    (KeyCode::F20, Key::F20),
+    (KeyCode::F21, Key::F21),
+    (KeyCode::F22, Key::F22),
+    (KeyCode::F23, Key::F23),
+    (KeyCode::F24, Key::F24),
+    (KeyCode::Space, Key::Space),
+    (KeyCode::ArrowUp, Key::Up),
+    (KeyCode::ArrowDown, Key::Down),
+    (KeyCode::ArrowLeft, Key::Left),
+    (KeyCode::ArrowRight, Key::Right),
+    (KeyCode::AltLeft, Key::LAlt),
+    (KeyCode::AltRight, Key::RAlt),
+    (KeyCode::ControlLeft, Key::LCtrl),
+    (KeyCode::ControlRight, Key::RCtrl),
+    (KeyCode::SuperLeft, Key::LMeta),
+    (KeyCode::SuperRight, Key::RMeta),
+    (KeyCode::ShiftLeft, Key::LShift),
+    (KeyCode::ShiftRight, Key::RShift),
+    (KeyCode::ContextMenu, Key::Apps),
+    (KeyCode::Slash, Key::OemQuestion),
+    (KeyCode::Backslash, Key::OemBackslash),
+    (KeyCode::BracketLeft, Key::OemOpenBrackets),
+    (KeyCode::BracketRight, Key::OemCloseBrackets),
+    (KeyCode::CapsLock, Key::CapsLock),
+    (KeyCode::Comma, Key::OemComma),
+    (KeyCode::Delete, Key::Delete),
+    (KeyCode::End, Key::End),
+    (KeyCode::Backspace, Key::Back),
+    (KeyCode::Enter, Key::Enter),
+    (KeyCode::Escape, Key::Escape),
+    (KeyCode::Home, Key::Home),
+    (KeyCode::Insert, Key::Insert),
+    (KeyCode::Minus, Key::OemMinus),
+    (KeyCode::NumLock, Key::NumLock),
+    (KeyCode::PageUp, Key::PageUp),
+    (KeyCode::PageDown, Key::PageDown),
+    (KeyCode::Pause, Key::Pause),
+    (KeyCode::Period, Key::OemPeriod),
+    (KeyCode::Equal, Key::OemPlus),
+    (KeyCode::PrintScreen, Key::PrintScreen),
+    (KeyCode::Quote, Key::OemQuotes),
+    (KeyCode::ScrollLock, Key::Scroll),
+    (KeyCode::Semicolon, Key::OemSemicolon),
+    (KeyCode::Sleep, Key::Sleep),
+    (KeyCode::Tab, Key::Tab),
+    (KeyCode::Backquote, Key::OemTilde),
+    (KeyCode::AudioVolumeUp, Key::VolumeUp),
+    (KeyCode::AudioVolumeDown, Key::VolumeDown),
+    (KeyCode::AudioVolumeMute, Key::VolumeMute),
+];
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::entity::Entity,
+        input::keyboard::Key as LogicalKey,
+        time::{Time, TimeUpdateStrategy},
+    };
+
+    use super::*;
+    use crate::vi::KeyRepeatConfig;
+
+    const DT: Duration = Duration::from_millis(10);
+
+    /// Headless app with a jump button on `Space`
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((bevy::time::TimePlugin, XdlPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(DT))
+            .add_actions(Button::single(Key::Space, KeyRepeatConfig::NoRepeat));
+        app
+    }
+
+    fn key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+        app.world.send_event(KeyboardInput {
+            key_code,
+            logical_key: LogicalKey::Unidentified(bevy::input::keyboard::NativeKey::Unidentified),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    fn jump(app: &App) -> &Button {
+        &app.world.resource::<ActionMapResource<Button>>().0
+    }
+
+    #[test]
+    fn plugin_runs_the_frame_lifecycle() {
+        let mut app = self::app();
+        app.update();
+        assert!(!jump(&app).is_down());
+
+        key(&mut app, KeyCode::Space, ButtonState::Pressed);
+        app.update();
+        assert!(jump(&app).is_pressed());
+        let input = app.world.resource::<Input>();
+        assert!(input.kbd.is_key_down(Key::Space));
+        // ended in `PostUpdate`
+        assert!(!input.kbd.is_key_pressed(Key::Space));
+        assert_eq!(input.frame(), 2);
+
+        app.update();
+        assert!(jump(&app).is_down() && !jump(&app).is_pressed());
+
+        key(&mut app, KeyCode::Space, ButtonState::Released);
+        app.update();
+        assert!(jump(&app).is_released());
+    }
+
+    #[test]
+    fn focus_loss_releases_keys() {
+        let mut app = self::app();
+        key(&mut app, KeyCode::Space, ButtonState::Pressed);
+        app.update();
+        assert!(jump(&app).is_pressed());
+
+        app.world.send_event(WindowFocused {
+            window: Entity::PLACEHOLDER,
+            focused: false,
+        });
+        app.update();
+        assert!(jump(&app).is_released());
+        assert!(!app.world.resource::<Input>().kbd.is_key_down(Key::Space));
+    }
+
+    #[test]
+    fn mouse_events_move_the_pointer() {
+        let mut app = self::app();
+        for x in [3.0, 12.5] {
+            app.world.send_event(CursorMoved {
+                window: Entity::PLACEHOLDER,
+                position: bevy::math::Vec2::new(x, 4.0),
+                delta: None,
+            });
+        }
+        app.world.send_event(MouseButtonInput {
+            button: bevy::input::mouse::MouseButton::Back,
+            state: ButtonState::Pressed,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+
+        let input = app.world.resource::<Input>();
+        assert_eq!(input.pointer.pos(), [12, 4]);
+        assert!(input.pointer.is_down(MouseButton::X1));
+    }
+
+    #[derive(Default)]
+    struct Typed(String);
+
+    impl Resource for Typed {}
+
+    fn read_text(input: Res<Input>, mut typed: ResMut<Typed>) {
+        typed.0.push_str(input.text.text());
+    }
+
+    #[test]
+    fn text_and_time_in_update() {
+        let mut app = self::app();
+        app.init_resource::<Typed>()
+            .add_systems(bevy::app::Update, self::read_text);
+        app.update();
+        for c in ["a", "b"] {
+            app.world.send_event(ReceivedCharacter {
+                window: Entity::PLACEHOLDER,
+                char: c.into(),
+            });
+            app.update();
+        }
+        assert_eq!(app.world.resource::<Typed>().0, "ab");
+
+        let input = app.world.resource::<Input>();
+        assert_eq!(input.dt(), app.world.resource::<Time>().delta());
+        assert!(input.elapsed() > Duration::ZERO);
+    }
+}
@@ -0,0 +1,285 @@
+//! Multiple backends writing to one [`Input`]
+
+use std::{
+    any::Any,
+    fmt,
+    ops::{BitAnd, BitOr, BitOrAssign},
+};
+
+use crate::{backend::EventOutcome, Backend, Input};
+
+/// Set of devices a [`Backend`] writes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+    pub const NONE: Self = Self(0);
+    pub const KEYBOARD: Self = Self(1 << 0);
+    pub const MOUSE: Self = Self(1 << 1);
+    pub const GAMEPAD: Self = Self(1 << 2);
+    pub const TEXT: Self = Self(1 << 3);
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Capabilities {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Capabilities {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+/// Type-erased [`Backend`] that can be stored as `Box<dyn AnyBackend>`
+///
+/// Implemented for every [`Backend`] with a `'static` event type.
+pub trait AnyBackend {
+    /// Handles the event if it's of the backend's event type
+    fn on_event_any(&mut self, input: &mut Input, ev: &dyn Any) -> Option<EventOutcome>;
+    fn on_end_frame_any(&mut self, input: &mut Input);
+    fn post_end_frame_any(&mut self, input: &mut Input);
+    fn capabilities_any(&self) -> Capabilities;
+}
+
+impl<B: Backend> AnyBackend for B
+where
+    B::Event: 'static,
+{
+    fn on_event_any(&mut self, input: &mut Input, ev: &dyn Any) -> Option<EventOutcome> {
+        ev.downcast_ref::<B::Event>()
+            .map(|ev| self.on_event(input, ev))
+    }
+
+    fn on_end_frame_any(&mut self, input: &mut Input) {
+        Backend::on_end_frame(self, input);
+    }
+
+    fn post_end_frame_any(&mut self, input: &mut Input) {
+        Backend::post_end_frame(self, input);
+    }
+
+    fn capabilities_any(&self) -> Capabilities {
+        Backend::capabilities(self)
+    }
+}
+
+/// Fans events out to the backends by the event type
+///
+/// Every backend with the matching event type receives the event. Device buffers are swapped once
+/// per frame.
+#[derive(Default)]
+pub struct CompositeBackend {
+    backends: Vec<Box<dyn AnyBackend>>,
+}
+
+impl fmt::Debug for CompositeBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompositeBackend")
+            .field("len", &self.backends.len())
+            .field("capabilities", &self.capabilities())
+            .finish()
+    }
+}
+
+impl CompositeBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<B: Backend + 'static>(&mut self, backend: B) -> &mut Self {
+        self.backends.push(Box::new(backend));
+        self
+    }
+
+    pub fn add_boxed(&mut self, backend: Box<dyn AnyBackend>) -> &mut Self {
+        self.backends.push(backend);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.backends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.backends.is_empty()
+    }
+
+    /// Union of the capabilities of the backends
+    pub fn capabilities(&self) -> Capabilities {
+        self.backends
+            .iter()
+            .fold(Capabilities::NONE, |acc, b| acc | b.capabilities_any())
+    }
+
+    /// Devices claimed by more than one backend
+    pub fn conflicts(&self) -> Capabilities {
+        let mut seen = Capabilities::NONE;
+        let mut conflicts = Capabilities::NONE;
+        for b in &self.backends {
+            let caps = b.capabilities_any();
+            conflicts |= seen & caps;
+            seen |= caps;
+        }
+        conflicts
+    }
+
+    /// Forwards the event to the backends handling the event type
+    pub fn on_event<E: Any>(&mut self, input: &mut Input, ev: &E) -> EventOutcome {
+        let mut outcome = EventOutcome::IGNORED;
+        for b in &mut self.backends {
+            if let Some(o) = b.on_event_any(input, ev as &dyn Any) {
+                outcome = outcome.or(o);
+            }
+        }
+        outcome
+    }
+
+    /// Swaps the device buffers once and then lets each backend finish the frame
+    pub fn on_end_frame(&mut self, input: &mut Input) {
+        input.on_end_frame();
+        for b in &mut self.backends {
+            b.post_end_frame_any(input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use super::*;
+    use crate::{
+        backend::{DeviceKind, ManualBackend, ManualEvent},
+        input::device::Device,
+        Key,
+    };
+
+    /// Gamepad buttons as a registered device
+    #[derive(Debug, Clone, Default)]
+    struct MockPad {
+        down: Vec<String>,
+        prev: Vec<String>,
+    }
+
+    impl Device for MockPad {
+        fn name(&self) -> &str {
+            "pad"
+        }
+
+        fn is_down(&self, channel: &str) -> bool {
+            self.down.iter().any(|c| c == channel)
+        }
+
+        fn was_down(&self, channel: &str) -> bool {
+            self.prev.iter().any(|c| c == channel)
+        }
+
+        fn on_end_frame(&mut self) {
+            self.prev = self.down.clone();
+        }
+
+        fn clear(&mut self) {
+            self.down.clear();
+            self.prev.clear();
+        }
+
+        fn clone_box(&self) -> Box<dyn Device> {
+            Box::new(self.clone())
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    /// Button down (`true`) or up
+    struct PadEvent(&'static str, bool);
+
+    struct PadBackend;
+
+    impl Backend for PadBackend {
+        type Event = PadEvent;
+        type Key = ();
+
+        fn on_event(&mut self, input: &mut Input, ev: &PadEvent) -> EventOutcome {
+            let pad = input.device_mut::<MockPad>("pad").unwrap();
+            pad.down.retain(|c| c != ev.0);
+            if ev.1 {
+                pad.down.push(ev.0.to_string());
+            }
+            EventOutcome::consumed(DeviceKind::Gamepad)
+        }
+
+        fn on_end_frame(&mut self, input: &mut Input) {
+            input.on_end_frame();
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities::GAMEPAD
+        }
+    }
+
+    #[test]
+    fn keyboard_and_gamepad_in_one_frame() {
+        let mut input = Input::new();
+        input.register_device(Box::new(MockPad::default()));
+
+        let mut backend = CompositeBackend::new();
+        backend.add(ManualBackend::new()).add(PadBackend);
+        assert_eq!(
+            backend.capabilities(),
+            Capabilities::KEYBOARD | Capabilities::GAMEPAD
+        );
+        assert!(backend.conflicts().is_empty());
+
+        let outcome = backend.on_event(&mut input, &ManualEvent::KeyDown(Key::Z));
+        assert_eq!(outcome, EventOutcome::consumed(DeviceKind::Keyboard));
+        let outcome = backend.on_event(&mut input, &PadEvent("a", true));
+        assert_eq!(outcome, EventOutcome::consumed(DeviceKind::Gamepad));
+        // no backend handles the event type
+        assert_eq!(backend.on_event(&mut input, &0u32), EventOutcome::IGNORED);
+
+        let pad = input.device("pad").unwrap();
+        assert!(input.kbd.is_key_pressed(Key::Z));
+        assert!(pad.is_down("a") && !pad.was_down("a"));
+
+        // buffers are swapped exactly once
+        let end_frames = input.end_frames;
+        backend.on_end_frame(&mut input);
+        assert_eq!(input.end_frames, end_frames + 1);
+
+        let pad = input.device("pad").unwrap();
+        assert!(input.kbd.is_key_down(Key::Z) && !input.kbd.is_key_pressed(Key::Z));
+        assert!(pad.is_down("a") && pad.was_down("a"));
+    }
+
+    #[test]
+    fn conflicts_of_two_keyboards() {
+        let mut backend = CompositeBackend::new();
+        backend.add(ManualBackend::new()).add(PadBackend);
+        backend.add(ManualBackend::new());
+        assert_eq!(backend.conflicts(), Capabilities::KEYBOARD);
+    }
+}
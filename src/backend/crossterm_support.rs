@@ -0,0 +1,304 @@
+//! crossterm backend
+//!
+//! Terminals don't deliver key up events, so keys are released by the backend according to a
+//! [`ReleasePolicy`]:
+//!
+//! * [`ReleasePolicy::EndOfFrame`]: every key event is a one-frame press, released on the next
+//!   frame. Terminal auto-repeat produces new presses (an event arriving on the release frame
+//!   keeps the key down instead), so `vi` buttons should use [`KeyRepeatConfig::NoRepeat`].
+//! * [`ReleasePolicy::Hold`]: keys are held until no event arrives for the duration. The key stays
+//!   down while terminal auto-repeat events keep arriving, so [`KeyRepeatConfig`] applies as with
+//!   other backends. The duration has to be longer than the terminal's initial repeat delay
+//!   (typically 250~600ms), or a held key would be released before auto-repeat starts.
+//!
+//! Modifiers reported with a key event are held along with the key. The side is not reported, so
+//! the left modifier key is pressed, which matches [`KeyEntry`] modifiers.
+//!
+//! [`KeyRepeatConfig`]: crate::vi::KeyRepeatConfig
+//! [`KeyRepeatConfig::NoRepeat`]: crate::vi::KeyRepeatConfig::NoRepeat
+//! [`KeyEntry`]: crate::vi::KeyEntry
+
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event as TermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::{
+    backend::{DeviceKind, EventOutcome},
+    input::{keyboard::Key, Input},
+    Backend,
+};
+
+pub type ExternalKey = KeyCode;
+pub type Event = TermEvent;
+
+/// How [`CrosstermBackend`] releases keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReleasePolicy {
+    /// Releases every key at the end of the frame
+    #[default]
+    EndOfFrame,
+    /// Releases a key when no event for it arrives for the duration
+    Hold(Duration),
+}
+
+/// [`crossterm`] backend
+#[derive(Debug, Clone, Default)]
+pub struct CrosstermBackend {
+    policy: ReleasePolicy,
+    /// Keys held by the backend and the time they were last seen
+    held: Vec<(Key, Instant)>,
+}
+
+impl CrosstermBackend {
+    pub fn new(policy: ReleasePolicy) -> Self {
+        Self {
+            policy,
+            held: Vec::new(),
+        }
+    }
+
+    pub fn policy(&self) -> ReleasePolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: ReleasePolicy) {
+        self.policy = policy;
+    }
+
+    pub fn translate(&self, key: &ExternalKey) -> Option<Key> {
+        self::translate(key)
+    }
+
+    fn hold(&mut self, input: &mut Input, key: Key, now: Instant) {
+        input.kbd.on_key_down(key);
+
+        match self.held.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, last)) => *last = now,
+            None => self.held.push((key, now)),
+        }
+    }
+
+    fn release(&mut self, input: &mut Input, key: Key) {
+        input.kbd.on_key_up(key);
+        self.held.retain(|(k, _)| *k != key);
+    }
+
+    fn on_key_event(&mut self, input: &mut Input, ev: &KeyEvent) {
+        let key = match self.translate(&ev.code) {
+            Some(key) => key,
+            None => return,
+        };
+
+        // only reported with the keyboard enhancement flags
+        if ev.kind == KeyEventKind::Release {
+            self.release(input, key);
+            return;
+        }
+
+        let now = Instant::now();
+        self.hold(input, key, now);
+
+        let mods = [
+            (KeyModifiers::CONTROL, Key::LCtrl),
+            (KeyModifiers::SHIFT, Key::LShift),
+            (KeyModifiers::ALT, Key::LAlt),
+            (KeyModifiers::SUPER, Key::LMeta),
+        ];
+
+        for (flag, key) in mods.iter() {
+            if ev.modifiers.contains(*flag) {
+                self.hold(input, *key, now);
+            }
+        }
+    }
+}
+
+impl Backend for CrosstermBackend {
+    type Event = Event;
+    type Key = ExternalKey;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        match ev {
+            TermEvent::Key(key_event) => {
+                self.on_key_event(input, key_event);
+                EventOutcome::consumed(DeviceKind::Keyboard)
+            }
+            TermEvent::FocusLost => {
+                input.kbd.release_all();
+                self.held.clear();
+                EventOutcome::IGNORED
+            }
+            _ => EventOutcome::IGNORED,
+        }
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+        self.post_end_frame(input);
+    }
+
+    fn post_end_frame(&mut self, input: &mut Input) {
+        // synthetic key up events (visible on the next frame)
+        let held = &mut self.held;
+        match self.policy {
+            ReleasePolicy::EndOfFrame => {
+                for (key, _) in held.drain(..) {
+                    input.kbd.on_key_up(key);
+                }
+            }
+            ReleasePolicy::Hold(duration) => {
+                let now = Instant::now();
+                held.retain(|(key, last)| {
+                    let is_alive = now.duration_since(*last) < duration;
+                    if !is_alive {
+                        input.kbd.on_key_up(*key);
+                    }
+                    is_alive
+                });
+            }
+        }
+    }
+}
+
+fn translate(code: &KeyCode) -> Option<Key> {
+    let key = match code {
+        KeyCode::Backspace => Key::Back,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        // Shift is reported as a modifier
+        KeyCode::Tab | KeyCode::BackTab => Key::Tab,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::Insert => Key::Insert,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::CapsLock => Key::CapsLock,
+        KeyCode::ScrollLock => Key::Scroll,
+        KeyCode::NumLock => Key::NumLock,
+        KeyCode::PrintScreen => Key::PrintScreen,
+        KeyCode::Pause => Key::Pause,
+        KeyCode::Menu => Key::Apps,
+        KeyCode::F(n @ 1..=24) => Key::try_from(Key::F1 as u32 + *n as u32 - 1).ok()?,
+        KeyCode::Char(c @ '0'..='9') => {
+            Key::try_from(Key::D0 as u32 + *c as u32 - '0' as u32).ok()?
+        }
+        KeyCode::Char(c) => Key::from_char(c.to_ascii_lowercase())?,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entries,
+        vi::{Button, InputBundle, KeyRepeatConfig},
+    };
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        TermEvent::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn key_translation() {
+        let backend = CrosstermBackend::default();
+        assert_eq!(backend.translate(&KeyCode::Char('a')), Some(Key::A));
+        // Shift is reported as a modifier
+        assert_eq!(backend.translate(&KeyCode::Char('A')), Some(Key::A));
+        assert_eq!(backend.translate(&KeyCode::BackTab), Some(Key::Tab));
+        assert_eq!(backend.translate(&KeyCode::Char('0')), Some(Key::D0));
+        assert_eq!(backend.translate(&KeyCode::Char('9')), Some(Key::D9));
+        assert_eq!(backend.translate(&KeyCode::F(1)), Some(Key::F1));
+        assert_eq!(backend.translate(&KeyCode::F(24)), Some(Key::F24));
+        assert_eq!(backend.translate(&KeyCode::F(25)), None);
+        assert_eq!(backend.translate(&KeyCode::Null), None);
+    }
+
+    #[test]
+    fn end_of_frame_releases_every_key() {
+        let mut backend = CrosstermBackend::new(ReleasePolicy::EndOfFrame);
+        let mut input = Input::new();
+
+        backend.on_event(&mut input, &key(KeyCode::Char('j'), KeyModifiers::NONE));
+        assert!(input.kbd.is_key_pressed(Key::J));
+        backend.on_end_frame(&mut input);
+        assert!(input.kbd.is_key_released(Key::J));
+        backend.on_end_frame(&mut input);
+
+        // auto-repeat is a new press
+        backend.on_event(&mut input, &key(KeyCode::Char('j'), KeyModifiers::NONE));
+        assert!(input.kbd.is_key_pressed(Key::J));
+    }
+
+    #[test]
+    fn hold_releases_after_the_duration() {
+        let duration = Duration::from_millis(50);
+        let mut backend = CrosstermBackend::new(ReleasePolicy::Hold(duration));
+        let mut input = Input::new();
+
+        backend.on_event(&mut input, &key(KeyCode::Char('j'), KeyModifiers::NONE));
+        backend.on_end_frame(&mut input);
+        assert!(input.kbd.is_key_down(Key::J));
+
+        // auto-repeat keeps the key down
+        backend.on_event(&mut input, &key(KeyCode::Char('j'), KeyModifiers::NONE));
+        assert!(input.kbd.is_key_down(Key::J));
+        assert!(!input.kbd.is_key_pressed(Key::J));
+
+        std::thread::sleep(duration);
+        backend.on_end_frame(&mut input);
+        assert!(!input.kbd.is_key_down(Key::J));
+    }
+
+    #[test]
+    fn release_event_and_focus_lost() {
+        let mut backend = CrosstermBackend::new(ReleasePolicy::Hold(Duration::from_secs(60)));
+        let mut input = Input::new();
+
+        backend.on_event(&mut input, &key(KeyCode::Up, KeyModifiers::NONE));
+        let release =
+            KeyEvent::new_with_kind(KeyCode::Up, KeyModifiers::NONE, KeyEventKind::Release);
+        backend.on_event(&mut input, &TermEvent::Key(release));
+        assert!(!input.kbd.is_key_down(Key::Up));
+
+        backend.on_event(&mut input, &key(KeyCode::Down, KeyModifiers::SHIFT));
+        backend.on_event(&mut input, &TermEvent::FocusLost);
+        assert!(!input.kbd.is_key_down(Key::Down));
+        assert!(!input.kbd.is_key_down(Key::LShift));
+        assert!(backend.held.is_empty());
+    }
+
+    #[test]
+    fn modifiers_match_key_entries() {
+        let mut backend = CrosstermBackend::new(ReleasePolicy::EndOfFrame);
+        let mut input = Input::new();
+        let bundle = InputBundle {
+            keys: entries!["Ctrl+S".parse().unwrap()],
+            ..Default::default()
+        };
+        let mut save = Button::new(bundle, KeyRepeatConfig::NoRepeat);
+
+        backend.on_event(&mut input, &key(KeyCode::Char('s'), KeyModifiers::NONE));
+        save.update(&input, Duration::from_millis(16));
+        assert!(!save.is_pressed());
+        for _ in 0..2 {
+            backend.on_end_frame(&mut input);
+            save.update(&input, Duration::from_millis(16));
+        }
+
+        backend.on_event(&mut input, &key(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert!(input.kbd.is_key_down(Key::LCtrl) && !input.kbd.is_key_down(Key::RCtrl));
+        save.update(&input, Duration::from_millis(16));
+        assert!(save.is_pressed());
+    }
+}
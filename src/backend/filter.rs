@@ -0,0 +1,114 @@
+//! Re-exported to super module
+
+use std::fmt;
+
+use crate::{
+    backend::{Capabilities, EventOutcome},
+    Backend, Input,
+};
+
+/// Returns `true` to pass the event to the inner backend
+pub type EventFilter<E> = Box<dyn FnMut(&E) -> bool>;
+
+/// Wraps a [`Backend`] with a pre-filter so that UI layers can suppress events from reaching
+/// [`Input`]
+pub struct FilteredBackend<B: Backend> {
+    inner: B,
+    filter: Option<EventFilter<B::Event>>,
+}
+
+impl<B: Backend> fmt::Debug for FilteredBackend<B>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FilteredBackend")
+            .field("inner", &self.inner)
+            .field("filter", &self.filter.is_some())
+            .finish()
+    }
+}
+
+impl<B: Backend> FilteredBackend<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            filter: None,
+        }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Events are passed to the inner backend only if the filter returns `true`
+    pub fn set_event_filter(&mut self, filter: impl FnMut(&B::Event) -> bool + 'static) {
+        self.filter = Some(Box::new(filter));
+    }
+
+    pub fn clear_event_filter(&mut self) {
+        self.filter = None;
+    }
+}
+
+impl<B: Backend> Backend for FilteredBackend<B> {
+    type Event = B::Event;
+    type Key = B::Key;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        if let Some(filter) = self.filter.as_mut() {
+            if !filter(ev) {
+                return EventOutcome::IGNORED;
+            }
+        }
+        self.inner.on_event(input, ev)
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        self.inner.on_end_frame(input);
+    }
+
+    fn post_end_frame(&mut self, input: &mut Input) {
+        self.inner.post_end_frame(input);
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{DeviceKind, ManualBackend, ManualEvent},
+        Key,
+    };
+
+    #[test]
+    fn filter_suppresses_key_press() {
+        let mut backend = FilteredBackend::new(ManualBackend::new());
+        let mut input = Input::new();
+        backend.set_event_filter(|ev| *ev != ManualEvent::KeyDown(Key::Escape));
+
+        let outcome = backend.on_event(&mut input, &ManualEvent::KeyDown(Key::Escape));
+        assert_eq!(outcome, EventOutcome::IGNORED);
+        assert!(!input.kbd.is_key_pressed(Key::Escape));
+
+        let outcome = backend.on_event(&mut input, &ManualEvent::KeyDown(Key::Enter));
+        assert_eq!(outcome, EventOutcome::consumed(DeviceKind::Keyboard));
+        assert!(input.kbd.is_key_pressed(Key::Enter));
+
+        backend.clear_event_filter();
+        backend.on_event(&mut input, &ManualEvent::KeyDown(Key::Escape));
+        assert!(input.kbd.is_key_pressed(Key::Escape));
+    }
+}
@@ -0,0 +1,334 @@
+//! GLFW backend
+
+use std::collections::HashMap;
+
+use glfw::{Action, Key as GlfwKey, Scancode, WindowEvent};
+
+use crate::{
+    backend::{DeviceKind, EventOutcome, KeyTranslation},
+    input::{keyboard::Key, pointer::MouseButton, Input},
+    Backend,
+};
+
+pub type ExternalKey = GlfwKey;
+pub type Event = WindowEvent;
+
+/// [`glfw`] backend
+///
+/// Enable key, mouse button, cursor position and focus polling on the window to receive the events. Keys that GLFW reports as
+/// `Key::Unknown` can be mapped by scancode with [`GlfwBackend::map_scancode`].
+#[derive(Debug, Clone)]
+pub struct GlfwBackend {
+    /// Keyed by `ExternalKey as i32`
+    map: KeyTranslation<i32>,
+    scancodes: HashMap<Scancode, Key>,
+}
+
+impl Default for GlfwBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlfwBackend {
+    pub fn new() -> Self {
+        Self {
+            map: KEY_TRANSLATION
+                .iter()
+                .map(|(from, to)| (*from as i32, *to))
+                .collect(),
+            scancodes: HashMap::new(),
+        }
+    }
+
+    pub fn translate(&self, key: &ExternalKey) -> Option<Key> {
+        self.map.translate(&(*key as i32))
+    }
+
+    /// Merges the mappings over the built-in table
+    pub fn with_overrides(mut self, overrides: HashMap<ExternalKey, Key>) -> Self {
+        for (from, to) in overrides {
+            self.map.add_mapping(from as i32, to);
+        }
+        self
+    }
+
+    pub fn translation(&self) -> &KeyTranslation<i32> {
+        &self.map
+    }
+
+    pub fn translation_mut(&mut self) -> &mut KeyTranslation<i32> {
+        &mut self.map
+    }
+
+    /// Maps a platform-specific scancode of a key unknown to GLFW
+    pub fn map_scancode(&mut self, scancode: Scancode, key: Key) {
+        self.scancodes.insert(scancode, key);
+    }
+
+    pub fn translate_scancode(&self, scancode: Scancode) -> Option<Key> {
+        self.scancodes.get(&scancode).cloned()
+    }
+}
+
+fn mouse_button(button: glfw::MouseButton) -> Option<MouseButton> {
+    use glfw::MouseButton as M;
+
+    Some(match button {
+        M::Button1 => MouseButton::Left,
+        M::Button2 => MouseButton::Right,
+        M::Button3 => MouseButton::Middle,
+        M::Button4 => MouseButton::X1,
+        M::Button5 => MouseButton::X2,
+        _ => return None,
+    })
+}
+
+impl Backend for GlfwBackend {
+    type Event = Event;
+    type Key = ExternalKey;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        match ev {
+            WindowEvent::Key(glfw_key, scancode, action, _mods) => {
+                let key = match glfw_key {
+                    GlfwKey::Unknown => self.translate_scancode(*scancode),
+                    _ => self.translate(glfw_key),
+                };
+
+                if let Some(key) = key {
+                    match action {
+                        Action::Press => input.kbd.on_key_down(key),
+                        Action::Release => input.kbd.on_key_up(key),
+                        // auto-repeat is handled by `vi` buttons
+                        Action::Repeat => {}
+                    }
+                }
+
+                EventOutcome::consumed(DeviceKind::Keyboard)
+            }
+            WindowEvent::CursorPos(x, y) => {
+                input.pointer.on_move([*x as i32, *y as i32]);
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            WindowEvent::MouseButton(button, action, _mods) => {
+                if let Some(button) = self::mouse_button(*button) {
+                    match action {
+                        Action::Press => input.on_mouse_down(button),
+                        Action::Release => input.on_mouse_up(button),
+                        Action::Repeat => {}
+                    }
+                }
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            WindowEvent::Scroll(..) => EventOutcome::consumed(DeviceKind::Mouse),
+            // we won't receive key up events while the window is not focused
+            WindowEvent::Focus(false) => {
+                input.kbd.release_all();
+                EventOutcome::IGNORED
+            }
+            _ => EventOutcome::IGNORED,
+        }
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
+
+/// Every key is mapped to a unique [`Key`]. `KpEnter` and `KpEqual` are not mapped.
+pub(crate) const KEY_TRANSLATION: &[(GlfwKey, Key)] = &[
+    (GlfwKey::A, Key::A),
+    (GlfwKey::B, Key::B),
+    (GlfwKey::C, Key::C),
+    (GlfwKey::D, Key::D),
+    (GlfwKey::E, Key::E),
+    (GlfwKey::F, Key::F),
+    (GlfwKey::G, Key::G),
+    (GlfwKey::H, Key::H),
+    (GlfwKey::I, Key::I),
+    (GlfwKey::J, Key::J),
+    (GlfwKey::K, Key::K),
+    (GlfwKey::L, Key::L),
+    (GlfwKey::M, Key::M),
+    (GlfwKey::N, Key::N),
+    (GlfwKey::O, Key::O),
+    (GlfwKey::P, Key::P),
+    (GlfwKey::Q, Key::Q),
+    (GlfwKey::R, Key::R),
+    (GlfwKey::S, Key::S),
+    (GlfwKey::T, Key::T),
+    (GlfwKey::U, Key::U),
+    (GlfwKey::V, Key::V),
+    (GlfwKey::W, Key::W),
+    (GlfwKey::X, Key::X),
+    (GlfwKey::Y, Key::Y),
+    (GlfwKey::Z, Key::Z),
+    (GlfwKey::Num0, Key::D0),
+    (GlfwKey::Num1, Key::D1),
+    (GlfwKey::Num2, Key::D2),
+    (GlfwKey::Num3, Key::D3),
+    (GlfwKey::Num4, Key::D4),
+    (GlfwKey::Num5, Key::D5),
+    (GlfwKey::Num6, Key::D6),
+    (GlfwKey::Num7, Key::D7),
+    (GlfwKey::Num8, Key::D8),
+    (GlfwKey::Num9, Key::D9),
+    (GlfwKey::Kp0, Key::NumPad0),
+    (GlfwKey::Kp1, Key::NumPad1),
+    (GlfwKey::Kp2, Key::NumPad2),
+    (GlfwKey::Kp3, Key::NumPad3),
+    (GlfwKey::Kp4, Key::NumPad4),
+    (GlfwKey::Kp5, Key::NumPad5),
+    (GlfwKey::Kp6, Key::NumPad6),
+    (GlfwKey::Kp7, Key::NumPad7),
+    (GlfwKey::Kp8, Key::NumPad8),
+    (GlfwKey::Kp9, Key::NumPad9),
+    (GlfwKey::KpDecimal, Key::Decimal),
+    (GlfwKey::KpDivide, Key::Slash),
+    (GlfwKey::KpSubtract, Key::Minus),
+    (GlfwKey::KpMultiply, Key::Star),
+    (GlfwKey::KpAdd, Key::Plus),
+    (GlfwKey::F1, Key::F1),
+    (GlfwKey::F2, Key::F2),
+    (GlfwKey::F3, Key::F3),
+    (GlfwKey::F4, Key::F4),
+    (GlfwKey::F5, Key::F5),
+    (GlfwKey::F6, Key::F6),
+    (GlfwKey::F7, Key::F7),
+    (GlfwKey::F8, Key::F8),
+    (GlfwKey::F9, Key::F9),
+    (GlfwKey::F10, Key::F10),
+    (GlfwKey::F11, Key::F11),
+    (GlfwKey::F12, Key::F12),
+    (GlfwKey::F13, Key::F13),
+    (GlfwKey::F14, Key::F14),
+    (GlfwKey::F15, Key::F15),
+    (GlfwKey::F16, Key::F16),
+    (GlfwKey::F17, Key::F17),
+    (GlfwKey::F18, Key::F18),
+    (GlfwKey::F19, Key::F19),
+    (GlfwKey::F20, Key::F20),
+    (GlfwKey::F21, Key::F21),
+    (GlfwKey::F22, Key::F22),
+    (GlfwKey::F23, Key::F23),
+    (GlfwKey::F24, Key::F24),
+    (GlfwKey::Space, Key::Space),
+    (GlfwKey::Up, Key::Up),
+    (GlfwKey::Down, Key::Down),
+    (GlfwKey::Left, Key::Left),
+    (GlfwKey::Right, Key::Right),
+    (GlfwKey::LeftAlt, Key::LAlt),
+    (GlfwKey::RightAlt, Key::RAlt),
+    (GlfwKey::LeftControl, Key::LCtrl),
+    (GlfwKey::RightControl, Key::RCtrl),
+    (GlfwKey::LeftSuper, Key::LMeta),
+    (GlfwKey::RightSuper, Key::RMeta),
+    (GlfwKey::LeftShift, Key::LShift),
+    (GlfwKey::RightShift, Key::RShift),
+    (GlfwKey::Menu, Key::Apps),
+    (GlfwKey::Slash, Key::OemQuestion),
+    (GlfwKey::Backslash, Key::OemBackslash),
+    (GlfwKey::LeftBracket, Key::OemOpenBrackets),
+    (GlfwKey::RightBracket, Key::OemCloseBrackets),
+    (GlfwKey::CapsLock, Key::CapsLock),
+    (GlfwKey::Comma, Key::OemComma),
+    (GlfwKey::Delete, Key::Delete),
+    (GlfwKey::End, Key::End),
+    (GlfwKey::Backspace, Key::Back),
+    (GlfwKey::Enter, Key::Enter),
+    (GlfwKey::Escape, Key::Escape),
+    (GlfwKey::Home, Key::Home),
+    (GlfwKey::Insert, Key::Insert),
+    (GlfwKey::Minus, Key::OemMinus),
+    (GlfwKey::NumLock, Key::NumLock),
+    (GlfwKey::PageUp, Key::PageUp),
+    (GlfwKey::PageDown, Key::PageDown),
+    (GlfwKey::Pause, Key::Pause),
+    (GlfwKey::Period, Key::OemPeriod),
+    (GlfwKey::Equal, Key::OemPlus),
+    (GlfwKey::PrintScreen, Key::PrintScreen),
+    (GlfwKey::Apostrophe, Key::OemQuotes),
+    (GlfwKey::ScrollLock, Key::Scroll),
+    (GlfwKey::Semicolon, Key::OemSemicolon),
+    (GlfwKey::Tab, Key::Tab),
+    (GlfwKey::GraveAccent, Key::OemTilde),
+];
+
+#[cfg(test)]
+mod tests {
+    use glfw::Modifiers;
+
+    use super::*;
+
+    fn key(key: GlfwKey, scancode: Scancode, action: Action) -> Event {
+        WindowEvent::Key(key, scancode, action, Modifiers::empty())
+    }
+
+    fn mouse(button: glfw::MouseButton, action: Action) -> Event {
+        WindowEvent::MouseButton(button, action, Modifiers::empty())
+    }
+
+    #[test]
+    fn key_translation_is_unique() {
+        let backend = GlfwBackend::new();
+        assert_eq!(backend.translation().len(), KEY_TRANSLATION.len());
+
+        let mut keys = Vec::new();
+        for (code, key) in KEY_TRANSLATION {
+            assert_eq!(backend.translate(code), Some(*key), "{:?}", code);
+            assert!(!keys.contains(key), "{:?} is mapped twice", key);
+            keys.push(*key);
+        }
+        assert_eq!(backend.translate(&GlfwKey::KpEnter), None);
+    }
+
+    #[test]
+    fn repeat_is_not_a_press() {
+        let mut backend = GlfwBackend::new();
+        let mut input = Input::new();
+        backend.on_event(&mut input, &key(GlfwKey::A, 0, Action::Press));
+        assert!(input.kbd.is_key_pressed(Key::A));
+        backend.on_end_frame(&mut input);
+
+        backend.on_event(&mut input, &key(GlfwKey::A, 0, Action::Repeat));
+        assert!(input.kbd.is_key_down(Key::A));
+        assert!(!input.kbd.is_key_pressed(Key::A));
+
+        backend.on_event(&mut input, &key(GlfwKey::A, 0, Action::Release));
+        assert!(input.kbd.is_key_released(Key::A));
+    }
+
+    #[test]
+    fn unknown_keys_are_mapped_by_scancode() {
+        let mut backend = GlfwBackend::new();
+        let mut input = Input::new();
+        backend.on_event(&mut input, &key(GlfwKey::Unknown, 42, Action::Press));
+        assert!(input.kbd.keys_down_bits().is_empty());
+
+        backend.map_scancode(42, Key::Kana);
+        backend.on_event(&mut input, &key(GlfwKey::Unknown, 42, Action::Press));
+        assert!(input.kbd.is_key_down(Key::Kana));
+
+        backend.on_event(&mut input, &WindowEvent::Focus(false));
+        assert!(!input.kbd.is_key_down(Key::Kana));
+    }
+
+    #[test]
+    fn mouse_events() {
+        let mut backend = GlfwBackend::new();
+        let mut input = Input::new();
+        let events = [
+            WindowEvent::CursorPos(12.5, 4.0),
+            mouse(glfw::MouseButton::Button4, Action::Press),
+            mouse(glfw::MouseButton::Button8, Action::Press),
+        ];
+        for ev in &events {
+            backend.on_event(&mut input, ev);
+        }
+        assert_eq!(input.pointer.pos(), [12, 4]);
+        assert!(input.pointer.is_pressed(MouseButton::X1));
+        assert!(!input.pointer.is_down(MouseButton::X2));
+    }
+}
@@ -0,0 +1,364 @@
+//! Artificial input latency for testing game feel
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    backend::{Capabilities, EventOutcome},
+    input::{
+        event::XdlEvent,
+        keyboard::{Key, RawKey},
+        Input,
+    },
+    utils, Backend,
+};
+
+/// Delay of [`LatencyBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Latency {
+    Frames(u32),
+    /// Measured with [`Input::elapsed`], so [`Input::tick`] has to be called
+    Time(Duration),
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self::Frames(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Due {
+    Frame(u64),
+    Time(Duration),
+}
+
+/// Wraps a [`Backend`] and delays the key events it produces
+///
+/// The inner backend writes to a private [`Input`]; its key events are queued and applied to the
+/// real [`Input`] at the start of a later frame. Delivery order is always kept, so a key up never
+/// arrives before its key down. Only key and mouse button events are forwarded; the cursor
+/// doesn't move.
+#[derive(Debug)]
+pub struct LatencyBackend<B> {
+    inner: B,
+    /// Written by the inner backend
+    shadow: Input,
+    queue: VecDeque<(Due, XdlEvent)>,
+    latency: Latency,
+    /// Extra delay up to this many frames (or this much time), chosen per event
+    jitter: Latency,
+    /// Probability of dropping a key press (and its release)
+    drop_rate: f32,
+    dropped_keys: Vec<Key>,
+    dropped_raw: Vec<RawKey>,
+    rng: u64,
+}
+
+impl<B: Backend> LatencyBackend<B> {
+    pub fn new(inner: B, latency: Latency) -> Self {
+        Self {
+            inner,
+            shadow: Input::new(),
+            queue: VecDeque::new(),
+            latency,
+            jitter: Latency::Frames(0),
+            drop_rate: 0.0,
+            dropped_keys: Vec::new(),
+            dropped_raw: Vec::new(),
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Adds random delay up to `jitter`, reproducible from the seed
+    pub fn with_jitter(mut self, jitter: Latency, seed: u64) -> Self {
+        self.jitter = jitter;
+        self.rng ^= seed;
+        self
+    }
+
+    /// Drops key presses with the probability in `0.0..=1.0`
+    pub fn with_drop_rate(mut self, rate: f32) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    pub fn set_latency(&mut self, latency: Latency) {
+        self.latency = latency;
+    }
+
+    /// Number of events waiting for delivery
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        utils::xorshift64(&mut self.rng)
+    }
+
+    fn roll(&mut self, rate: f32) -> bool {
+        let x = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        rate > 0.0 && x < rate
+    }
+
+    fn due(&mut self, input: &Input) -> Due {
+        let now_frame = input.frame();
+        let now_time = input.elapsed();
+
+        let due = match (self.latency, self.jitter) {
+            (Latency::Frames(n), Latency::Frames(j)) => {
+                let j = if j == 0 {
+                    0
+                } else {
+                    self.next_u64() % (j as u64 + 1)
+                };
+                Due::Frame(now_frame + n as u64 + j)
+            }
+            (Latency::Frames(n), Latency::Time(_)) => Due::Frame(now_frame + n as u64),
+            (Latency::Time(d), jitter) => {
+                let j = match jitter {
+                    Latency::Time(j) if j > Duration::ZERO => {
+                        j.mul_f64((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64)
+                    }
+                    _ => Duration::ZERO,
+                };
+                Due::Time(now_time + d + j)
+            }
+        };
+
+        // never overtake the previous event
+        match (self.queue.back(), due) {
+            (Some((Due::Frame(last), _)), Due::Frame(f)) if *last > f => Due::Frame(*last),
+            (Some((Due::Time(last), _)), Due::Time(t)) if *last > t => Due::Time(*last),
+            _ => due,
+        }
+    }
+
+    /// Queues the events the inner backend has logged since the mark
+    fn enqueue_since(&mut self, input: &Input, mark: usize) {
+        let events = self.shadow.events()[mark..].to_vec();
+        for ev in events {
+            let dropped = match ev {
+                XdlEvent::KeyDown(key, _) if self.roll(self.drop_rate) => {
+                    self.dropped_keys.push(key);
+                    true
+                }
+                XdlEvent::RawKeyDown(key, _) if self.roll(self.drop_rate) => {
+                    self.dropped_raw.push(key);
+                    true
+                }
+                XdlEvent::KeyUp(key, _) => self::take(&mut self.dropped_keys, key),
+                XdlEvent::RawKeyUp(key, _) => self::take(&mut self.dropped_raw, key),
+                _ => false,
+            };
+            if !dropped {
+                let due = self.due(input);
+                self.queue.push_back((due, ev));
+            }
+        }
+    }
+
+    fn deliver(&mut self, input: &mut Input) {
+        while let Some((due, ev)) = self.queue.front().cloned() {
+            let is_due = match due {
+                Due::Frame(f) => f <= input.frame(),
+                Due::Time(t) => t <= input.elapsed(),
+            };
+            if !is_due {
+                break;
+            }
+            self.queue.pop_front();
+
+            match ev {
+                XdlEvent::KeyDown(key, _) => input.kbd.on_key_down(key),
+                XdlEvent::KeyUp(key, _) => input.kbd.on_key_up(key),
+                XdlEvent::RawKeyDown(key, _) => input.kbd.on_raw_key_down(key),
+                XdlEvent::RawKeyUp(key, _) => input.kbd.on_raw_key_up(key),
+                XdlEvent::MouseDown(button, _) => input.on_mouse_down(button),
+                XdlEvent::MouseUp(button, _) => input.on_mouse_up(button),
+            }
+        }
+    }
+}
+
+/// Removes the item if found
+fn take<T: PartialEq>(items: &mut Vec<T>, item: T) -> bool {
+    match items.iter().position(|x| *x == item) {
+        Some(ix) => {
+            items.remove(ix);
+            true
+        }
+        None => false,
+    }
+}
+
+impl<B: Backend> Backend for LatencyBackend<B> {
+    type Event = B::Event;
+    type Key = B::Key;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        let mark = self.shadow.events().len();
+        let outcome = self.inner.on_event(&mut self.shadow, ev);
+        self.enqueue_since(input, mark);
+        // zero latency
+        self.deliver(input);
+        outcome
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+        self.post_end_frame(input);
+    }
+
+    /// Delivers the events due on the new frame
+    fn post_end_frame(&mut self, input: &mut Input) {
+        self.shadow.on_end_frame();
+        // synthetic events of the inner backend (e.g. releases of terminal backends)
+        self.inner.post_end_frame(&mut self.shadow);
+        self.enqueue_since(input, 0);
+        self.deliver(input);
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{ManualBackend, ManualEvent},
+        input::pointer::MouseButton,
+    };
+
+    /// Runs the frames, sending the key events of each frame. Returns the delivered events
+    fn run(
+        backend: &mut LatencyBackend<ManualBackend>,
+        frames: u64,
+        events: &[(u64, ManualEvent)],
+    ) -> Vec<(u64, XdlEvent)> {
+        let mut input = Input::new();
+        let mut delivered = Vec::new();
+        for frame in 0..frames {
+            for (_, ev) in events.iter().filter(|(f, _)| *f == frame) {
+                backend.on_event(&mut input, ev);
+            }
+            input.tick(Duration::from_millis(10));
+            delivered.extend(input.events().iter().map(|ev| (frame, *ev)));
+            backend.on_end_frame(&mut input);
+        }
+        delivered
+    }
+
+    fn keys(delivered: &[(u64, XdlEvent)]) -> Vec<(u64, bool, Key)> {
+        delivered
+            .iter()
+            .filter_map(|(frame, ev)| match *ev {
+                XdlEvent::KeyDown(key, _) => Some((*frame, true, key)),
+                XdlEvent::KeyUp(key, _) => Some((*frame, false, key)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn press_arrives_exactly_after_the_latency() {
+        let mut backend = LatencyBackend::new(ManualBackend::new(), Latency::Frames(3));
+        let events = [
+            (0, ManualEvent::KeyDown(Key::Space)),
+            (1, ManualEvent::KeyUp(Key::Space)),
+        ];
+        let delivered = self::run(&mut backend, 6, &events);
+        assert_eq!(
+            self::keys(&delivered),
+            [(3, true, Key::Space), (4, false, Key::Space)]
+        );
+        assert_eq!(backend.pending(), 0);
+    }
+
+    #[test]
+    fn mouse_buttons_are_delayed() {
+        let mut backend = LatencyBackend::new(ManualBackend::new(), Latency::Frames(2));
+        let events = [
+            (0, ManualEvent::MouseDown(MouseButton::Left)),
+            (1, ManualEvent::MouseUp(MouseButton::Left)),
+        ];
+        let delivered = self::run(&mut backend, 5, &events);
+        assert_eq!(
+            delivered,
+            [
+                (
+                    2,
+                    XdlEvent::MouseDown(MouseButton::Left, Duration::from_millis(20))
+                ),
+                (
+                    3,
+                    XdlEvent::MouseUp(MouseButton::Left, Duration::from_millis(30))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn time_latency() {
+        let latency = Latency::Time(Duration::from_millis(30));
+        let mut backend = LatencyBackend::new(ManualBackend::new(), latency);
+        let events = [(0, ManualEvent::KeyDown(Key::Space))];
+        let delivered = self::run(&mut backend, 6, &events);
+        // sent at 10ms, due at 40ms
+        assert_eq!(self::keys(&delivered), [(3, true, Key::Space)]);
+    }
+
+    #[test]
+    fn jitter_keeps_the_order() {
+        let mut events = Vec::new();
+        for i in 0..20 {
+            let key = [Key::A, Key::B, Key::C][i as usize % 3];
+            events.push((i * 2, ManualEvent::KeyDown(key)));
+            events.push((i * 2 + 1, ManualEvent::KeyUp(key)));
+        }
+
+        for seed in 0..8 {
+            let mut backend = LatencyBackend::new(ManualBackend::new(), Latency::Frames(1))
+                .with_jitter(Latency::Frames(4), seed);
+            let delivered = self::keys(&self::run(&mut backend, 60, &events));
+            assert_eq!(delivered.len(), events.len());
+
+            let sent = events.iter().map(|(_, ev)| match *ev {
+                ManualEvent::KeyDown(key) => (true, key),
+                ManualEvent::KeyUp(key) => (false, key),
+                _ => unreachable!(),
+            });
+            assert!(delivered
+                .iter()
+                .map(|(_, down, key)| (*down, *key))
+                .eq(sent));
+            assert!(delivered.windows(2).all(|w| w[0].0 <= w[1].0));
+        }
+    }
+
+    #[test]
+    fn dropped_presses_drop_their_releases() {
+        let mut backend =
+            LatencyBackend::new(ManualBackend::new(), Latency::Frames(0)).with_drop_rate(1.0);
+        let events = [
+            (0, ManualEvent::KeyDown(Key::Space)),
+            (2, ManualEvent::KeyUp(Key::Space)),
+        ];
+        assert!(self::run(&mut backend, 4, &events).is_empty());
+        assert_eq!(backend.pending(), 0);
+    }
+}
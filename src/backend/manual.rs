@@ -0,0 +1,222 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+use crate::{
+    backend::{DeviceKind, EventOutcome},
+    input::{
+        keyboard::{Key, RawKey},
+        pointer::MouseButton,
+        Input,
+    },
+    Backend,
+};
+
+/// Event pushed to [`ManualBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ManualEvent {
+    KeyDown(Key),
+    KeyUp(Key),
+    RawKeyDown(RawKey),
+    RawKeyUp(RawKey),
+    /// Cursor position in window pixels
+    MouseMove([i32; 2]),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    /// Committed text input character
+    Char(char),
+    /// Timestamps the following events. See [`Input::set_event_timestamp`]
+    Timestamp(Duration),
+}
+
+/// Backend driven by the caller, for headless tools and custom engines
+///
+/// Queue key events with [`ManualBackend::key_down`] and [`ManualBackend::key_up`], apply them
+/// with [`ManualBackend::update`] before reading [`Input`], then call
+/// [`ManualBackend::end_frame`].
+///
+/// ```
+/// use xdl::{backend::ManualBackend, Input, Key};
+///
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+///
+/// backend.key_down(Key::Space);
+/// backend.update(&mut input);
+/// assert!(input.kbd.is_key_pressed(Key::Space));
+/// backend.end_frame(&mut input);
+///
+/// backend.update(&mut input);
+/// assert!(input.kbd.is_key_down(Key::Space));
+/// assert!(!input.kbd.is_key_pressed(Key::Space));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualBackend {
+    queue: Vec<ManualEvent>,
+}
+
+impl ManualBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn key_down(&mut self, key: Key) {
+        self.queue.push(ManualEvent::KeyDown(key));
+    }
+
+    pub fn key_up(&mut self, key: Key) {
+        self.queue.push(ManualEvent::KeyUp(key));
+    }
+
+    pub fn raw_key_down(&mut self, key: RawKey) {
+        self.queue.push(ManualEvent::RawKeyDown(key));
+    }
+
+    pub fn raw_key_up(&mut self, key: RawKey) {
+        self.queue.push(ManualEvent::RawKeyUp(key));
+    }
+
+    pub fn mouse_move(&mut self, pos: [i32; 2]) {
+        self.queue.push(ManualEvent::MouseMove(pos));
+    }
+
+    pub fn mouse_down(&mut self, button: MouseButton) {
+        self.queue.push(ManualEvent::MouseDown(button));
+    }
+
+    pub fn mouse_up(&mut self, button: MouseButton) {
+        self.queue.push(ManualEvent::MouseUp(button));
+    }
+
+    /// Types the text, as committed by text input
+    pub fn text(&mut self, text: &str) {
+        self.queue.extend(text.chars().map(ManualEvent::Char));
+    }
+
+    /// Timestamps the events queued after this
+    pub fn timestamp(&mut self, time: Duration) {
+        self.queue.push(ManualEvent::Timestamp(time));
+    }
+
+    /// Applies the queued events
+    pub fn update(&mut self, input: &mut Input) {
+        for ev in self.queue.drain(..) {
+            self::apply(input, ev);
+        }
+    }
+
+    /// Swaps the device buffers
+    pub fn end_frame(&mut self, input: &mut Input) {
+        self.on_end_frame(input);
+    }
+}
+
+impl Backend for ManualBackend {
+    type Event = ManualEvent;
+    type Key = Key;
+
+    /// Applies the event immediately
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        self::apply(input, *ev);
+        match ev {
+            ManualEvent::MouseMove(_) | ManualEvent::MouseDown(_) | ManualEvent::MouseUp(_) => {
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            _ => EventOutcome::consumed(DeviceKind::Keyboard),
+        }
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
+
+fn apply(input: &mut Input, ev: ManualEvent) {
+    match ev {
+        ManualEvent::KeyDown(key) => input.kbd.on_key_down(key),
+        ManualEvent::KeyUp(key) => input.kbd.on_key_up(key),
+        ManualEvent::RawKeyDown(key) => input.kbd.on_raw_key_down(key),
+        ManualEvent::RawKeyUp(key) => input.kbd.on_raw_key_up(key),
+        ManualEvent::MouseMove(pos) => input.pointer.on_move(pos),
+        ManualEvent::MouseDown(button) => input.on_mouse_down(button),
+        ManualEvent::MouseUp(button) => input.on_mouse_up(button),
+        ManualEvent::Char(c) => input.text.on_text(c.encode_utf8(&mut [0; 4])),
+        ManualEvent::Timestamp(time) => input.set_event_timestamp(time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Runs with `--no-default-features` too
+
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        axis::Dir8,
+        vi::{presets::bundle, AxisDirButton, Button, KeyRepeatConfig},
+    };
+
+    const DT: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn button_repeats() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+        let mut button = Button::new(bundle(&[Key::Enter]), repeat);
+
+        let mut pressed = Vec::new();
+        for frame in 0..10 {
+            match frame {
+                0 => backend.key_down(Key::Enter),
+                8 => backend.key_up(Key::Enter),
+                _ => {}
+            }
+            backend.update(&mut input);
+            button.update(&input, DT);
+            if button.is_pressed() {
+                pressed.push(frame);
+            }
+            backend.end_frame(&mut input);
+        }
+
+        // the first press and the repeats
+        assert_eq!(pressed, [0, 4, 6]);
+        assert!(!button.is_down());
+    }
+
+    #[test]
+    fn axis_dir_button_mixes_keys() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut dir = AxisDirButton::new(
+            KeyRepeatConfig::NoRepeat,
+            [bundle(&[Key::Right]), bundle(&[Key::Left])],
+            [bundle(&[Key::Down]), bundle(&[Key::Up])],
+        );
+
+        let mut step = |backend: &mut ManualBackend, input: &mut Input| {
+            backend.update(input);
+            dir.update(input, DT);
+            backend.end_frame(input);
+            (dir.dir8_down(), dir.dir8_pressed())
+        };
+
+        backend.key_down(Key::Up);
+        assert_eq!(
+            step(&mut backend, &mut input),
+            (Some(Dir8::N), Some(Dir8::N))
+        );
+        backend.key_down(Key::Right);
+        assert_eq!(step(&mut backend, &mut input), (Some(Dir8::NE), None));
+        // the key pressed later wins over the opposite one
+        backend.key_down(Key::Left);
+        assert_eq!(step(&mut backend, &mut input), (Some(Dir8::NW), None));
+        backend.key_up(Key::Up);
+        backend.key_up(Key::Right);
+        backend.key_up(Key::Left);
+        assert_eq!(step(&mut backend, &mut input), (None, None));
+    }
+}
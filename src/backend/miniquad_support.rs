@@ -0,0 +1,403 @@
+//! miniquad/macroquad backend
+
+use std::collections::HashMap;
+
+use miniquad::{KeyCode, KeyMods};
+
+use crate::{
+    backend::{DeviceKind, EventOutcome, KeyTranslation},
+    input::{keyboard::Key, pointer::MouseButton, Input},
+    Backend,
+};
+
+pub type ExternalKey = KeyCode;
+
+/// Keyboard and mouse events forwarded from miniquad's `EventHandler` callbacks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    KeyDown {
+        keycode: KeyCode,
+        repeat: bool,
+    },
+    KeyUp {
+        keycode: KeyCode,
+    },
+    MouseMotion {
+        x: f32,
+        y: f32,
+    },
+    MouseButtonDown {
+        button: miniquad::MouseButton,
+        x: f32,
+        y: f32,
+    },
+    MouseButtonUp {
+        button: miniquad::MouseButton,
+        x: f32,
+        y: f32,
+    },
+}
+
+/// [`miniquad`] backend
+///
+/// Forward the `EventHandler` callbacks with the methods of the same names (e.g.
+/// [`MiniquadBackend::key_down_event`]), or call [`MiniquadBackend::pump`] each frame with
+/// `macroquad`.
+#[derive(Debug, Clone)]
+pub struct MiniquadBackend {
+    /// Keyed by `ExternalKey as u32`
+    map: KeyTranslation<u32>,
+}
+
+impl Default for MiniquadBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MiniquadBackend {
+    pub fn new() -> Self {
+        Self {
+            map: KEY_TRANSLATION
+                .iter()
+                .map(|(from, to)| (*from as u32, *to))
+                .collect(),
+        }
+    }
+
+    pub fn translate(&self, key: &ExternalKey) -> Option<Key> {
+        self.map.translate(&(*key as u32))
+    }
+
+    /// Merges the mappings over the built-in table
+    pub fn with_overrides(mut self, overrides: HashMap<ExternalKey, Key>) -> Self {
+        for (from, to) in overrides {
+            self.map.add_mapping(from as u32, to);
+        }
+        self
+    }
+
+    pub fn translation(&self) -> &KeyTranslation<u32> {
+        &self.map
+    }
+
+    pub fn translation_mut(&mut self) -> &mut KeyTranslation<u32> {
+        &mut self.map
+    }
+}
+
+/// `EventHandler` callbacks
+impl MiniquadBackend {
+    /// Auto-repeated key down events are ignored so that held keys are not seen as new presses
+    pub fn key_down_event(
+        &mut self,
+        input: &mut Input,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        repeat: bool,
+    ) {
+        self.on_event(input, &Event::KeyDown { keycode, repeat });
+    }
+
+    pub fn key_up_event(&mut self, input: &mut Input, keycode: KeyCode, _keymods: KeyMods) {
+        self.on_event(input, &Event::KeyUp { keycode });
+    }
+
+    pub fn mouse_motion_event(&mut self, input: &mut Input, x: f32, y: f32) {
+        self.on_event(input, &Event::MouseMotion { x, y });
+    }
+
+    pub fn mouse_button_down_event(
+        &mut self,
+        input: &mut Input,
+        button: miniquad::MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.on_event(input, &Event::MouseButtonDown { button, x, y });
+    }
+
+    pub fn mouse_button_up_event(
+        &mut self,
+        input: &mut Input,
+        button: miniquad::MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.on_event(input, &Event::MouseButtonUp { button, x, y });
+    }
+}
+
+fn mouse_button(button: miniquad::MouseButton) -> Option<MouseButton> {
+    use miniquad::MouseButton as M;
+
+    Some(match button {
+        M::Left => MouseButton::Left,
+        M::Right => MouseButton::Right,
+        M::Middle => MouseButton::Middle,
+        M::Unknown => return None,
+    })
+}
+
+fn pos(x: f32, y: f32) -> [i32; 2] {
+    [x as i32, y as i32]
+}
+
+#[cfg(feature = "macroquad")]
+impl MiniquadBackend {
+    /// Reads macroquad's key and mouse state. Call it once per frame before reading [`Input`]
+    pub fn pump(&self, input: &mut Input) {
+        for keycode in macroquad::input::get_keys_pressed() {
+            if let Some(key) = self.translate(&keycode) {
+                input.kbd.on_key_down(key);
+            }
+        }
+
+        for keycode in macroquad::input::get_keys_released() {
+            if let Some(key) = self.translate(&keycode) {
+                input.kbd.on_key_up(key);
+            }
+        }
+
+        let (x, y) = macroquad::input::mouse_position();
+        input.pointer.on_move(self::pos(x, y));
+        for &mq_button in &[
+            miniquad::MouseButton::Left,
+            miniquad::MouseButton::Right,
+            miniquad::MouseButton::Middle,
+        ] {
+            let button = match self::mouse_button(mq_button) {
+                Some(button) => button,
+                None => continue,
+            };
+            if macroquad::input::is_mouse_button_pressed(mq_button) {
+                input.on_mouse_down(button);
+            }
+            if macroquad::input::is_mouse_button_released(mq_button) {
+                input.on_mouse_up(button);
+            }
+        }
+    }
+}
+
+impl Backend for MiniquadBackend {
+    type Event = Event;
+    type Key = ExternalKey;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        match ev {
+            // consumed, but not a new press
+            Event::KeyDown { repeat: true, .. } => {}
+            Event::KeyDown {
+                keycode,
+                repeat: false,
+            } => {
+                if let Some(key) = self.translate(keycode) {
+                    input.kbd.on_key_down(key);
+                }
+            }
+            Event::KeyUp { keycode } => {
+                if let Some(key) = self.translate(keycode) {
+                    input.kbd.on_key_up(key);
+                }
+            }
+            Event::MouseMotion { x, y } => {
+                input.pointer.on_move(self::pos(*x, *y));
+                return EventOutcome::consumed(DeviceKind::Mouse);
+            }
+            Event::MouseButtonDown { button, x, y } => {
+                input.pointer.on_move(self::pos(*x, *y));
+                if let Some(button) = self::mouse_button(*button) {
+                    input.on_mouse_down(button);
+                }
+                return EventOutcome::consumed(DeviceKind::Mouse);
+            }
+            Event::MouseButtonUp { button, x, y } => {
+                input.pointer.on_move(self::pos(*x, *y));
+                if let Some(button) = self::mouse_button(*button) {
+                    input.on_mouse_up(button);
+                }
+                return EventOutcome::consumed(DeviceKind::Mouse);
+            }
+        }
+
+        EventOutcome::consumed(DeviceKind::Keyboard)
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
+
+/// Every key code is mapped to a unique [`Key`]. `KpEnter` and `KpEqual` are not mapped.
+pub(crate) const KEY_TRANSLATION: &[(KeyCode, Key)] = &[
+    (KeyCode::A, Key::A),
+    (KeyCode::B, Key::B),
+    (KeyCode::C, Key::C),
+    (KeyCode::D, Key::D),
+    (KeyCode::E, Key::E),
+    (KeyCode::F, Key::F),
+    (KeyCode::G, Key::G),
+    (KeyCode::H, Key::H),
+    (KeyCode::I, Key::I),
+    (KeyCode::J, Key::J),
+    (KeyCode::K, Key::K),
+    (KeyCode::L, Key::L),
+    (KeyCode::M, Key::M),
+    (KeyCode::N, Key::N),
+    (KeyCode::O, Key::O),
+    (KeyCode::P, Key::P),
+    (KeyCode::Q, Key::Q),
+    (KeyCode::R, Key::R),
+    (KeyCode::S, Key::S),
+    (KeyCode::T, Key::T),
+    (KeyCode::U, Key::U),
+    (KeyCode::V, Key::V),
+    (KeyCode::W, Key::W),
+    (KeyCode::X, Key::X),
+    (KeyCode::Y, Key::Y),
+    (KeyCode::Z, Key::Z),
+    (KeyCode::Key0, Key::D0),
+    (KeyCode::Key1, Key::D1),
+    (KeyCode::Key2, Key::D2),
+    (KeyCode::Key3, Key::D3),
+    (KeyCode::Key4, Key::D4),
+    (KeyCode::Key5, Key::D5),
+    (KeyCode::Key6, Key::D6),
+    (KeyCode::Key7, Key::D7),
+    (KeyCode::Key8, Key::D8),
+    (KeyCode::Key9, Key::D9),
+    (KeyCode::Kp0, Key::NumPad0),
+    (KeyCode::Kp1, Key::NumPad1),
+    (KeyCode::Kp2, Key::NumPad2),
+    (KeyCode::Kp3, Key::NumPad3),
+    (KeyCode::Kp4, Key::NumPad4),
+    (KeyCode::Kp5, Key::NumPad5),
+    (KeyCode::Kp6, Key::NumPad6),
+    (KeyCode::Kp7, Key::NumPad7),
+    (KeyCode::Kp8, Key::NumPad8),
+    (KeyCode::Kp9, Key::NumPad9),
+    (KeyCode::KpDecimal, Key::Decimal),
+    (KeyCode::KpDivide, Key::Slash),
+    (KeyCode::KpSubtract, Key::Minus),
+    (KeyCode::KpMultiply, Key::Star),
+    (KeyCode::KpAdd, Key::Plus),
+    (KeyCode::F1, Key::F1),
+    (KeyCode::F2, Key::F2),
+    (KeyCode::F3, Key::F3),
+    (KeyCode::F4, Key::F4),
+    (KeyCode::F5, Key::F5),
+    (KeyCode::F6, Key::F6),
+    (KeyCode::F7, Key::F7),
+    (KeyCode::F8, Key::F8),
+    (KeyCode::F9, Key::F9),
+    (KeyCode::F10, Key::F10),
+    (KeyCode::F11, Key::F11),
+    (KeyCode::F12, Key::F12),
+    (KeyCode::F13, Key::F13),
+    (KeyCode::F14, Key::F14),
+    (KeyCode::F15, Key::F15),
+    (KeyCode::F16, Key::F16),
+    (KeyCode::F17, Key::F17),
+    (KeyCode::F18, Key::F18),
+    (KeyCode::F19, Key::F19),
+    (KeyCode::F20, Key::F20),
+    (KeyCode::F21, Key::F21),
+    (KeyCode::F22, Key::F22),
+    (KeyCode::F23, Key::F23),
+    (KeyCode::F24, Key::F24),
+    (KeyCode::Space, Key::Space),
+    (KeyCode::Up, Key::Up),
+    (KeyCode::Down, Key::Down),
+    (KeyCode::Left, Key::Left),
+    (KeyCode::Right, Key::Right),
+    (KeyCode::LeftAlt, Key::LAlt),
+    (KeyCode::RightAlt, Key::RAlt),
+    (KeyCode::LeftControl, Key::LCtrl),
+    (KeyCode::RightControl, Key::RCtrl),
+    (KeyCode::LeftSuper, Key::LMeta),
+    (KeyCode::RightSuper, Key::RMeta),
+    (KeyCode::LeftShift, Key::LShift),
+    (KeyCode::RightShift, Key::RShift),
+    (KeyCode::Menu, Key::Apps),
+    (KeyCode::Slash, Key::OemQuestion),
+    (KeyCode::Backslash, Key::OemBackslash),
+    (KeyCode::LeftBracket, Key::OemOpenBrackets),
+    (KeyCode::RightBracket, Key::OemCloseBrackets),
+    (KeyCode::CapsLock, Key::CapsLock),
+    (KeyCode::Comma, Key::OemComma),
+    (KeyCode::Delete, Key::Delete),
+    (KeyCode::End, Key::End),
+    (KeyCode::Backspace, Key::Back),
+    (KeyCode::Enter, Key::Enter),
+    (KeyCode::Escape, Key::Escape),
+    (KeyCode::Home, Key::Home),
+    (KeyCode::Insert, Key::Insert),
+    (KeyCode::Minus, Key::OemMinus),
+    (KeyCode::NumLock, Key::NumLock),
+    (KeyCode::PageUp, Key::PageUp),
+    (KeyCode::PageDown, Key::PageDown),
+    (KeyCode::Pause, Key::Pause),
+    (KeyCode::Period, Key::OemPeriod),
+    (KeyCode::Equal, Key::OemPlus),
+    (KeyCode::PrintScreen, Key::PrintScreen),
+    (KeyCode::Apostrophe, Key::OemQuotes),
+    (KeyCode::ScrollLock, Key::Scroll),
+    (KeyCode::Semicolon, Key::OemSemicolon),
+    (KeyCode::Tab, Key::Tab),
+    (KeyCode::GraveAccent, Key::OemTilde),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_translation_is_unique() {
+        let backend = MiniquadBackend::new();
+        assert_eq!(backend.translation().len(), KEY_TRANSLATION.len());
+
+        let mut keys = Vec::new();
+        for (code, key) in KEY_TRANSLATION {
+            assert_eq!(backend.translate(code), Some(*key), "{:?}", code);
+            assert!(!keys.contains(key), "{:?} is mapped twice", key);
+            keys.push(*key);
+        }
+        assert_eq!(backend.translate(&KeyCode::KpEnter), None);
+    }
+
+    #[test]
+    fn repeated_key_down_is_not_a_press() {
+        let mut backend = MiniquadBackend::new();
+        let mut input = Input::new();
+        backend.key_down_event(&mut input, KeyCode::A, KeyMods::default(), false);
+        assert!(input.kbd.is_key_pressed(Key::A));
+        backend.on_end_frame(&mut input);
+
+        backend.key_down_event(&mut input, KeyCode::A, KeyMods::default(), true);
+        assert!(input.kbd.is_key_down(Key::A));
+        assert!(!input.kbd.is_key_pressed(Key::A));
+
+        // a repeat doesn't press a released key either
+        backend.key_up_event(&mut input, KeyCode::A, KeyMods::default());
+        backend.on_end_frame(&mut input);
+        backend.key_down_event(&mut input, KeyCode::A, KeyMods::default(), true);
+        assert!(!input.kbd.is_key_down(Key::A));
+    }
+
+    #[test]
+    fn mouse_events() {
+        let mut backend = MiniquadBackend::new();
+        let mut input = Input::new();
+        backend.mouse_motion_event(&mut input, 3.0, 7.5);
+        assert_eq!(input.pointer.pos(), [3, 7]);
+
+        backend.mouse_button_down_event(&mut input, miniquad::MouseButton::Right, 12.5, 4.0);
+        assert_eq!(input.pointer.pos(), [12, 4]);
+        assert!(input.pointer.is_pressed(MouseButton::Right));
+
+        backend.mouse_button_down_event(&mut input, miniquad::MouseButton::Unknown, 0.0, 0.0);
+        assert!(!input.pointer.is_down(MouseButton::Left));
+    }
+}
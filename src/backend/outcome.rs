@@ -0,0 +1,42 @@
+//! Re-exported to super module
+
+/// Kind of device an event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceKind {
+    Keyboard,
+    Mouse,
+    Gamepad,
+    Text,
+}
+
+/// Result of [`Backend::on_event`](crate::Backend::on_event)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct EventOutcome {
+    /// If the event is an input event relevant to xdl
+    pub consumed: bool,
+    pub device: Option<DeviceKind>,
+}
+
+impl EventOutcome {
+    /// Window, system or unknown events
+    pub const IGNORED: Self = Self {
+        consumed: false,
+        device: None,
+    };
+
+    pub fn consumed(device: DeviceKind) -> Self {
+        Self {
+            consumed: true,
+            device: Some(device),
+        }
+    }
+
+    /// Merges outcomes of multiple backends handling the same event
+    pub fn or(self, other: Self) -> Self {
+        if self.consumed {
+            self
+        } else {
+            other
+        }
+    }
+}
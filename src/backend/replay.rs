@@ -0,0 +1,609 @@
+//! Record and replay xdl-level key and mouse events
+//!
+//! Recordings are stored as text:
+//!
+//! ```text
+//! xdl-replay 1
+//! 0 +Space
+//! 4 @120,36
+//! 4 +mouse:Left
+//! 6 -mouse:Left
+//! 12 -Space
+//! end 30
+//! ```
+//!
+//! Each line is a frame index followed by a key name or a `mouse:` button name pressed (`+`) or
+//! released (`-`) on that frame, or the cursor position (`@x,y`) the pointer moved to. Lines are
+//! sorted by frame. The last line is the number of recorded frames.
+//!
+//! Raw keys ([`RawKey`](crate::RawKey)) are platform-dependent, so they're not recorded.
+
+use std::{
+    error, fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    backend::{Capabilities, EventOutcome},
+    input::{event::XdlEvent, keyboard::Key, pointer::MouseButton, Input},
+    Backend,
+};
+
+const HEADER: &str = "xdl-replay";
+const VERSION: u32 = 1;
+
+/// Input event on a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedEvent {
+    pub frame: u32,
+    pub input: RecordedInput,
+}
+
+/// Device state change of a [`RecordedEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordedInput {
+    Key {
+        key: Key,
+        down: bool,
+    },
+    Mouse {
+        button: MouseButton,
+        down: bool,
+    },
+    /// Cursor position in window pixels
+    Cursor([i32; 2]),
+}
+
+impl RecordedInput {
+    /// Applies the change without key remapping; recorded keys are already remapped
+    fn apply(self, input: &mut Input) {
+        match self {
+            RecordedInput::Key { key, down } => {
+                if down {
+                    input.kbd.press(key);
+                } else {
+                    input.kbd.release(key);
+                }
+            }
+            RecordedInput::Mouse { button, down } => {
+                if down {
+                    input.on_mouse_down(button);
+                } else {
+                    input.on_mouse_up(button);
+                }
+            }
+            RecordedInput::Cursor(pos) => input.pointer.on_move(pos),
+        }
+    }
+}
+
+/// Recorded key and mouse events
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// Sorted by frame
+    pub events: Vec<RecordedEvent>,
+    /// Number of recorded frames
+    pub frames: u32,
+}
+
+/// Error on loading a [`Recording`]
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// Missing or unknown header
+    Header(String),
+    /// Unsupported format version
+    Version(u32),
+    /// Malformed line (1-based line number)
+    Line {
+        line: usize,
+        text: String,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Header(s) => write!(f, "not an xdl recording: `{}`", s),
+            Self::Version(v) => write!(f, "unsupported recording version: {}", v),
+            Self::Line { line, text } => write!(f, "malformed line {}: `{}`", line, text),
+        }
+    }
+}
+
+impl error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Serialization
+impl Recording {
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(w, "{} {}", HEADER, VERSION)?;
+        let sign = |down: bool| if down { '+' } else { '-' };
+        for ev in &self.events {
+            match ev.input {
+                RecordedInput::Key { key, down } => {
+                    writeln!(w, "{} {}{}", ev.frame, sign(down), key.name())?
+                }
+                RecordedInput::Mouse { button, down } => {
+                    writeln!(w, "{} {}mouse:{}", ev.frame, sign(down), button.name())?
+                }
+                RecordedInput::Cursor([x, y]) => writeln!(w, "{} @{},{}", ev.frame, x, y)?,
+            }
+        }
+        writeln!(w, "end {}", self.frames)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    pub fn read_from(r: impl Read) -> Result<Self, ReplayError> {
+        let mut lines = BufReader::new(r).lines();
+
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let version = match header.split_once(' ') {
+            Some((HEADER, v)) => v
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| ReplayError::Header(header.clone()))?,
+            _ => return Err(ReplayError::Header(header)),
+        };
+        if version != VERSION {
+            return Err(ReplayError::Version(version));
+        }
+
+        let mut rec = Self::default();
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let malformed = || ReplayError::Line {
+                line: i + 2,
+                text: line.to_string(),
+            };
+
+            let (head, tail) = line.split_once(' ').ok_or_else(malformed)?;
+            if head == "end" {
+                rec.frames = tail.parse().map_err(|_| malformed())?;
+                continue;
+            }
+
+            let frame: u32 = head.parse().map_err(|_| malformed())?;
+            // replay reads the events in order
+            if rec.events.last().is_some_and(|ev| ev.frame > frame) {
+                return Err(malformed());
+            }
+
+            let input = match tail.chars().next() {
+                Some('@') => {
+                    let (x, y) = tail[1..].split_once(',').ok_or_else(malformed)?;
+                    let x = x.parse().map_err(|_| malformed())?;
+                    let y = y.parse().map_err(|_| malformed())?;
+                    RecordedInput::Cursor([x, y])
+                }
+                Some(sign @ ('+' | '-')) => {
+                    let down = sign == '+';
+                    match tail[1..].strip_prefix("mouse:") {
+                        Some(name) => RecordedInput::Mouse {
+                            button: MouseButton::from_name(name).ok_or_else(malformed)?,
+                            down,
+                        },
+                        None => RecordedInput::Key {
+                            key: Key::from_name(&tail[1..]).ok_or_else(malformed)?,
+                            down,
+                        },
+                    }
+                }
+                _ => return Err(malformed()),
+            };
+
+            rec.events.push(RecordedEvent { frame, input });
+        }
+
+        Ok(rec)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::read_from(File::open(path)?)
+    }
+}
+
+/// Wraps a [`Backend`] and records the key and mouse events it applies to [`Input`]
+///
+/// Raw keys are not recorded.
+#[derive(Debug)]
+pub struct RecordingBackend<B> {
+    inner: B,
+    rec: Recording,
+    frame: u32,
+    /// Last recorded cursor position
+    cursor: [i32; 2],
+}
+
+impl<B: Backend> RecordingBackend<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            rec: Recording::default(),
+            frame: 0,
+            cursor: [0, 0],
+        }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Current frame index
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.rec
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.rec
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.rec.save(path)
+    }
+
+    /// Injects a key down event without going through the inner backend
+    pub fn key_down(&mut self, input: &mut Input, key: Key) {
+        let mark = self::mark(input);
+        input.kbd.on_key_down(key);
+        self.record_since(input, mark);
+    }
+
+    /// Injects a key up event without going through the inner backend
+    pub fn key_up(&mut self, input: &mut Input, key: Key) {
+        let mark = self::mark(input);
+        input.kbd.on_key_up(key);
+        self.record_since(input, mark);
+    }
+
+    fn advance_frame(&mut self) {
+        self.frame += 1;
+        self.rec.frames = self.frame;
+    }
+
+    fn push(&mut self, input: RecordedInput) {
+        self.rec.events.push(RecordedEvent {
+            frame: self.frame,
+            input,
+        });
+    }
+
+    /// Records the cursor motion and the logged events since the mark
+    fn record_since(&mut self, input: &Input, (frame, len): (u64, usize)) {
+        // motion is not logged, and it comes first so that a click is recorded where it happened
+        let pos = input.pointer.pos();
+        if pos != self.cursor {
+            self.cursor = pos;
+            self.push(RecordedInput::Cursor(pos));
+        }
+
+        // the log is cleared if the frame has ended since the mark
+        let start = if input.frame() == frame { len } else { 0 };
+        for ev in &input.events()[start..] {
+            let recorded = match *ev {
+                XdlEvent::KeyDown(key, _) => RecordedInput::Key { key, down: true },
+                XdlEvent::KeyUp(key, _) => RecordedInput::Key { key, down: false },
+                XdlEvent::MouseDown(button, _) => RecordedInput::Mouse { button, down: true },
+                XdlEvent::MouseUp(button, _) => RecordedInput::Mouse {
+                    button,
+                    down: false,
+                },
+                // raw keys are platform-dependent and not recorded
+                XdlEvent::RawKeyDown(..) | XdlEvent::RawKeyUp(..) => continue,
+            };
+            self.push(recorded);
+        }
+    }
+}
+
+/// Position in the event log
+fn mark(input: &Input) -> (u64, usize) {
+    (input.frame(), input.events().len())
+}
+
+impl<B: Backend> Backend for RecordingBackend<B> {
+    type Event = B::Event;
+    type Key = B::Key;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        let mark = self::mark(input);
+        let outcome = self.inner.on_event(input, ev);
+        self.record_since(input, mark);
+        outcome
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // synthetic events on the end of frame belong to the next frame
+        self.advance_frame();
+        let mark = self::mark(input);
+        self.inner.on_end_frame(input);
+        self.record_since(input, mark);
+    }
+
+    fn post_end_frame(&mut self, input: &mut Input) {
+        self.advance_frame();
+        let mark = self::mark(input);
+        self.inner.post_end_frame(input);
+        self.record_since(input, mark);
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+}
+
+/// Plays a [`Recording`] back, independent of the platform it was recorded on
+///
+/// Call [`ReplayBackend::begin_frame`] at the start of each frame instead of handling platform
+/// events.
+#[derive(Debug)]
+pub struct ReplayBackend {
+    rec: Recording,
+    frame: u32,
+    /// Index of the next event to apply
+    cursor: usize,
+}
+
+impl ReplayBackend {
+    pub fn new(rec: Recording) -> Self {
+        Self {
+            rec,
+            frame: 0,
+            cursor: 0,
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.rec
+    }
+
+    /// Current frame index
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// If all the recorded frames are played
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.rec.frames
+    }
+
+    /// Applies the recorded events of the current frame
+    pub fn begin_frame(&mut self, input: &mut Input) {
+        while let Some(ev) = self.rec.events.get(self.cursor) {
+            if ev.frame > self.frame {
+                break;
+            }
+            ev.input.apply(input);
+            self.cursor += 1;
+        }
+    }
+}
+
+impl Backend for ReplayBackend {
+    /// Platform events are ignored
+    type Event = ();
+    type Key = Key;
+
+    fn on_event(&mut self, _input: &mut Input, _ev: &Self::Event) -> EventOutcome {
+        EventOutcome::IGNORED
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+        self.post_end_frame(input);
+    }
+
+    fn post_end_frame(&mut self, _input: &mut Input) {
+        self.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{ManualBackend, ManualEvent},
+        input::pointer::Pointer,
+        RawKey,
+    };
+
+    /// Frame index and key event: injected (`true`) or sent through the inner backend
+    const SCRIPT: &[(u32, Key, bool, bool)] = &[
+        (0, Key::Space, true, true),
+        (2, Key::Left, true, false),
+        (2, Key::Space, false, true),
+        (3, Key::Up, true, false),
+        (3, Key::Up, false, false),
+        (5, Key::Left, false, false),
+        (5, Key::Space, true, true),
+        (7, Key::Space, false, true),
+    ];
+
+    const FRAMES: u32 = 10;
+
+    fn record() -> (Recording, Vec<u64>) {
+        let mut backend = RecordingBackend::new(ManualBackend::new());
+        let mut input = Input::new();
+        let mut prints = Vec::new();
+
+        for frame in 0..FRAMES {
+            for &(_, key, down, inject) in SCRIPT.iter().filter(|ev| ev.0 == frame) {
+                match (inject, down) {
+                    (true, true) => backend.key_down(&mut input, key),
+                    (true, false) => backend.key_up(&mut input, key),
+                    (false, true) => {
+                        backend.on_event(&mut input, &ManualEvent::KeyDown(key));
+                    }
+                    (false, false) => {
+                        backend.on_event(&mut input, &ManualEvent::KeyUp(key));
+                    }
+                }
+            }
+            prints.push(input.fingerprint());
+            backend.on_end_frame(&mut input);
+        }
+
+        (backend.into_recording(), prints)
+    }
+
+    fn replay(rec: Recording) -> Vec<u64> {
+        let mut backend = ReplayBackend::new(rec);
+        let mut input = Input::new();
+        let mut prints = Vec::new();
+
+        while !backend.is_finished() {
+            backend.begin_frame(&mut input);
+            prints.push(input.fingerprint());
+            backend.on_end_frame(&mut input);
+        }
+
+        prints
+    }
+
+    #[test]
+    fn replay_reproduces_every_frame() {
+        let (rec, prints) = record();
+        assert_eq!(rec.frames, FRAMES);
+        assert_eq!(rec.events.len(), SCRIPT.len());
+
+        let mut buf = Vec::new();
+        rec.write_to(&mut buf).unwrap();
+        let loaded = Recording::read_from(buf.as_slice()).unwrap();
+        assert_eq!(loaded, rec);
+
+        // the press and release on frame 3 are not lost
+        assert_ne!(prints[2], prints[3]);
+        assert_eq!(replay(loaded), prints);
+    }
+
+    #[test]
+    fn format_is_stable() {
+        let (rec, _) = record();
+        let mut buf = Vec::new();
+        rec.write_to(&mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            format!(
+                "xdl-replay 1\n0 +{}\n2 +{}\n2 -{}\n3 +{}\n3 -{}\n5 -{}\n5 +{}\n7 -{}\nend 10\n",
+                Key::Space.name(),
+                Key::Left.name(),
+                Key::Space.name(),
+                Key::Up.name(),
+                Key::Up.name(),
+                Key::Left.name(),
+                Key::Space.name(),
+                Key::Space.name(),
+            )
+        );
+    }
+
+    #[test]
+    fn read_errors() {
+        let read = |s: &str| Recording::read_from(s.as_bytes());
+
+        assert!(matches!(read(""), Err(ReplayError::Header(_))));
+        assert!(matches!(read("xdl 1\n"), Err(ReplayError::Header(_))));
+        assert!(matches!(
+            read("xdl-replay 2\n"),
+            Err(ReplayError::Version(2))
+        ));
+        assert!(matches!(
+            read("xdl-replay 1\n0 +Space\n1 *Space\n"),
+            Err(ReplayError::Line { line: 3, .. })
+        ));
+        assert!(matches!(
+            read("xdl-replay 1\n0 +NoSuchKey\n"),
+            Err(ReplayError::Line { line: 2, .. })
+        ));
+        assert!(matches!(
+            read("xdl-replay 1\n0 +mouse:Space\n"),
+            Err(ReplayError::Line { line: 2, .. })
+        ));
+        assert!(matches!(
+            read("xdl-replay 1\n0 @12\n"),
+            Err(ReplayError::Line { line: 2, .. })
+        ));
+        // replay would stall on an event before the current frame
+        assert!(matches!(
+            read("xdl-replay 1\n3 +Space\n3 -Space\n1 +Up\n"),
+            Err(ReplayError::Line { line: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn mouse_is_recorded() {
+        let script: &[&[ManualEvent]] = &[
+            &[ManualEvent::MouseMove([12, 4])],
+            &[
+                ManualEvent::MouseDown(MouseButton::Left),
+                ManualEvent::RawKeyDown(RawKey(0x4000_0000)),
+            ],
+            &[
+                ManualEvent::MouseMove([-3, 40]),
+                ManualEvent::MouseUp(MouseButton::Left),
+            ],
+            &[],
+        ];
+
+        let mut backend = RecordingBackend::new(ManualBackend::new());
+        let mut input = Input::new();
+        let mut pointers = Vec::new();
+        for events in script {
+            for ev in *events {
+                backend.on_event(&mut input, ev);
+            }
+            pointers.push(input.pointer.clone());
+            backend.on_end_frame(&mut input);
+        }
+
+        let mut buf = Vec::new();
+        backend.recording().write_to(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        // raw keys are not recorded
+        assert_eq!(
+            text,
+            "xdl-replay 1\n0 @12,4\n1 +mouse:Left\n2 @-3,40\n2 -mouse:Left\nend 4\n"
+        );
+
+        let mut replay = ReplayBackend::new(Recording::read_from(text.as_bytes()).unwrap());
+        let mut input = Input::new();
+        let mut replayed: Vec<Pointer> = Vec::new();
+        while !replay.is_finished() {
+            replay.begin_frame(&mut input);
+            replayed.push(input.pointer.clone());
+            replay.on_end_frame(&mut input);
+        }
+        assert_eq!(replayed, pointers);
+    }
+}
@@ -0,0 +1,139 @@
+//! Scripted input for testing game code
+
+use std::{ops::Range, time::Duration};
+
+use crate::{
+    backend::{EventOutcome, RecordedEvent, RecordedInput},
+    input::{keyboard::Key, Input},
+    Backend,
+};
+
+/// Applies a timeline of key events, frame by frame
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{backend::ScriptBackend, Input, Key};
+///
+/// let mut script = ScriptBackend::new(Duration::from_millis(16));
+/// script.at_frame(1).press(Key::Space);
+/// script.hold(Key::Right, 2..4);
+///
+/// let mut input = Input::new();
+/// script.step(&mut input); // frame 0
+/// assert!(!input.kbd.is_key_down(Key::Space));
+/// script.step(&mut input); // frame 1
+/// assert!(input.kbd.is_key_pressed(Key::Space));
+/// script.step(&mut input); // frame 2
+/// assert!(input.kbd.is_key_pressed(Key::Right));
+/// script.step(&mut input); // frame 3
+/// script.step(&mut input); // frame 4
+/// assert!(input.kbd.is_key_released(Key::Right));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScriptBackend {
+    events: Vec<RecordedEvent>,
+    dt: Duration,
+    /// Next frame index
+    frame: u32,
+}
+
+/// Events scheduled on a frame
+#[derive(Debug)]
+pub struct FrameScript<'a> {
+    script: &'a mut ScriptBackend,
+    frame: u32,
+}
+
+impl<'a> FrameScript<'a> {
+    pub fn press(self, key: Key) -> Self {
+        self.script.push(self.frame, key, true);
+        self
+    }
+
+    pub fn release(self, key: Key) -> Self {
+        self.script.push(self.frame, key, false);
+        self
+    }
+}
+
+impl ScriptBackend {
+    /// `dt` is the time advanced per frame
+    pub fn new(dt: Duration) -> Self {
+        Self {
+            events: Vec::new(),
+            dt,
+            frame: 0,
+        }
+    }
+
+    pub fn at_frame(&mut self, frame: u32) -> FrameScript<'_> {
+        FrameScript {
+            script: self,
+            frame,
+        }
+    }
+
+    /// Presses the key on `frames.start` and releases it on `frames.end`
+    pub fn hold(&mut self, key: Key, frames: Range<u32>) -> &mut Self {
+        self.push(frames.start, key, true);
+        self.push(frames.end, key, false);
+        self
+    }
+
+    fn push(&mut self, frame: u32, key: Key, down: bool) {
+        // keep the insertion order within a frame
+        let ix = self.events.partition_point(|ev| ev.frame <= frame);
+        let input = RecordedInput::Key { key, down };
+        self.events.insert(ix, RecordedEvent { frame, input });
+    }
+
+    /// Index of the frame applied by the next [`ScriptBackend::step`]
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn dt(&self) -> Duration {
+        self.dt
+    }
+
+    /// Total time advanced so far
+    pub fn elapsed(&self) -> Duration {
+        self.dt * self.frame
+    }
+
+    /// Ends the previous frame and applies the events of the next frame. Returns the `dt` to
+    /// update `vi` buttons with
+    pub fn step(&mut self, input: &mut Input) -> Duration {
+        if self.frame > 0 {
+            self.on_end_frame(input);
+        }
+
+        for ev in self.events.iter().filter(|ev| ev.frame == self.frame) {
+            if let RecordedInput::Key { key, down } = ev.input {
+                if down {
+                    input.kbd.on_key_down(key);
+                } else {
+                    input.kbd.on_key_up(key);
+                }
+            }
+        }
+
+        self.frame += 1;
+        self.dt
+    }
+}
+
+impl Backend for ScriptBackend {
+    /// Platform events are ignored
+    type Event = ();
+    type Key = Key;
+
+    fn on_event(&mut self, _input: &mut Input, _ev: &Self::Event) -> EventOutcome {
+        EventOutcome::IGNORED
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
@@ -1,19 +1,34 @@
 //! Rust-SDL2 backend
 
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
+
+use sdl2::{keyboard::Keycode, rect::Rect, VideoSubsystem};
 
 use crate::{
-    input::{keyboard::Key, Input},
+    backend::{DeviceKind, EventOutcome, KeyTranslation},
+    input::{
+        keyboard::{Key, RawKey},
+        pointer::MouseButton,
+        Input,
+    },
     Backend,
 };
 
-pub type ExternalKey = sdl2::keyboard::Keycode;
+pub type ExternalKey = Keycode;
 pub type Event = sdl2::event::Event;
 
 /// [`sdl2`] backend
+///
+/// For multi-window apps, bind one backend (and one [`Input`]) per window with
+/// [`SdlBackend::bind_window`].
 #[derive(Debug, Clone)]
 pub struct SdlBackend {
-    map: HashMap<sdl2::keyboard::Keycode, Key>,
+    /// Keyed by `ExternalKey as i32`
+    map: KeyTranslation<i32>,
+    /// Events of other windows are ignored if any
+    window_id: Option<u32>,
+    /// Untranslated keycodes are applied as [`RawKey`]s if true
+    raw_passthrough: bool,
 }
 
 impl Default for SdlBackend {
@@ -25,178 +40,522 @@ impl Default for SdlBackend {
 impl SdlBackend {
     pub fn new() -> Self {
         Self {
-            map: self::create_key_translation(),
+            map: KEY_TRANSLATION
+                .iter()
+                .map(|(from, to)| (*from as i32, *to))
+                .collect(),
+            window_id: None,
+            raw_passthrough: false,
+        }
+    }
+
+    /// Applies keycodes without [`Key`] translation as [`RawKey`]s (`Keycode as i32`)
+    pub fn set_raw_passthrough(&mut self, b: bool) {
+        self.raw_passthrough = b;
+    }
+
+    pub fn raw_passthrough(&self) -> bool {
+        self.raw_passthrough
+    }
+
+    fn raw_key(&self, key: &ExternalKey) -> Option<RawKey> {
+        if self.raw_passthrough {
+            Some(RawKey(*key as i32 as u32))
+        } else {
+            None
         }
     }
 
+    /// Applies only the events of the window (and events without window ID)
+    pub fn bind_window(&mut self, window_id: u32) {
+        self.window_id = Some(window_id);
+    }
+
+    pub fn unbind_window(&mut self) {
+        self.window_id = None;
+    }
+
+    pub fn window_id(&self) -> Option<u32> {
+        self.window_id
+    }
+
     pub fn translate(&self, key: &ExternalKey) -> Option<Key> {
-        self.map.get(key).cloned()
+        self.map.translate(&(*key as i32))
+    }
+
+    /// Merges the mappings over the built-in table
+    pub fn with_overrides(mut self, overrides: HashMap<ExternalKey, Key>) -> Self {
+        for (from, to) in overrides {
+            self.map.add_mapping(from as i32, to);
+        }
+        self
+    }
+
+    pub fn translation(&self) -> &KeyTranslation<i32> {
+        &self.map
+    }
+
+    pub fn translation_mut(&mut self) -> &mut KeyTranslation<i32> {
+        &mut self.map
+    }
+
+    pub fn add_mapping(&mut self, from: ExternalKey, to: Key) {
+        self.map.add_mapping(from as i32, to);
+    }
+
+    pub fn remove_mapping(&mut self, from: ExternalKey) {
+        self.map.remove_mapping(&(from as i32));
+    }
+}
+
+/// Text input
+impl SdlBackend {
+    /// Enables SDL text input and [`Input`] text input
+    pub fn start_text_input(&self, video: &VideoSubsystem, input: &mut Input) {
+        video.text_input().start();
+        input.start_text_input();
+    }
+
+    pub fn stop_text_input(&self, video: &VideoSubsystem, input: &mut Input) {
+        video.text_input().stop();
+        input.stop_text_input();
+    }
+
+    /// Sets the rectangle the IME candidate window is placed around
+    pub fn set_ime_rect(&self, video: &VideoSubsystem, rect: Rect) {
+        video.text_input().set_rect(rect);
     }
 }
 
+fn mouse_button(button: sdl2::mouse::MouseButton) -> Option<MouseButton> {
+    use sdl2::mouse::MouseButton as M;
+
+    Some(match button {
+        M::Left => MouseButton::Left,
+        M::Right => MouseButton::Right,
+        M::Middle => MouseButton::Middle,
+        M::X1 => MouseButton::X1,
+        M::X2 => MouseButton::X2,
+        M::Unknown => return None,
+    })
+}
+
 impl Backend for SdlBackend {
     type Event = Event;
     type Key = ExternalKey;
 
-    fn on_event(&self, input: &mut Input, ev: &Self::Event) {
-        use sdl2::event::Event;
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        use sdl2::event::{Event, WindowEvent};
+
+        // SDL window IDs start from 1, and 0 is for no window
+        let window_id = ev.get_window_id().filter(|id| *id != 0);
+        if let (Some(bound), Some(id)) = (self.window_id, window_id) {
+            if bound != id {
+                return EventOutcome::IGNORED;
+            }
+        }
 
         match ev {
-            Event::KeyDown {
-                keycode: Some(sdl_key),
+            // we won't receive key up events while the window is not focused
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
                 ..
             } => {
-                if let Some(key) = self.translate(sdl_key) {
-                    input.kbd.on_key_down(key);
+                input.kbd.release_all();
+                EventOutcome::IGNORED
+            }
+            Event::KeyDown {
+                keycode, timestamp, ..
+            } => {
+                input.set_event_timestamp(Duration::from_millis(*timestamp as u64));
+                if let Some(keycode) = keycode {
+                    if let Some(key) = self.translate(keycode) {
+                        input.kbd.on_key_down(key);
+                    } else if let Some(raw) = self.raw_key(keycode) {
+                        input.kbd.on_raw_key_down(raw);
+                    }
                 }
+                EventOutcome::consumed(DeviceKind::Keyboard)
             }
             Event::KeyUp {
-                keycode: Some(sdl_key),
+                keycode, timestamp, ..
+            } => {
+                input.set_event_timestamp(Duration::from_millis(*timestamp as u64));
+                if let Some(keycode) = keycode {
+                    if let Some(key) = self.translate(keycode) {
+                        input.kbd.on_key_up(key);
+                    } else if let Some(raw) = self.raw_key(keycode) {
+                        input.kbd.on_raw_key_up(raw);
+                    }
+                }
+                EventOutcome::consumed(DeviceKind::Keyboard)
+            }
+            Event::TextInput { text, .. } => {
+                input.text.on_text(text);
+                EventOutcome::consumed(DeviceKind::Text)
+            }
+            Event::TextEditing {
+                text,
+                start,
+                length,
                 ..
             } => {
-                if let Some(key) = self.translate(sdl_key) {
-                    input.kbd.on_key_up(key);
+                input.text.on_editing(text, *start, *length);
+                EventOutcome::consumed(DeviceKind::Text)
+            }
+            Event::MouseMotion { x, y, .. } => {
+                input.pointer.on_move([*x, *y]);
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => {
+                input.pointer.on_move([*x, *y]);
+                if let Some(button) = self::mouse_button(*mouse_btn) {
+                    input.on_mouse_down(button);
+                }
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => {
+                input.pointer.on_move([*x, *y]);
+                if let Some(button) = self::mouse_button(*mouse_btn) {
+                    input.on_mouse_up(button);
                 }
+                EventOutcome::consumed(DeviceKind::Mouse)
             }
-            _ => {}
+            Event::MouseWheel { .. } => EventOutcome::consumed(DeviceKind::Mouse),
+            Event::ControllerAxisMotion { .. }
+            | Event::ControllerButtonDown { .. }
+            | Event::ControllerButtonUp { .. }
+            | Event::JoyAxisMotion { .. }
+            | Event::JoyBallMotion { .. }
+            | Event::JoyHatMotion { .. }
+            | Event::JoyButtonDown { .. }
+            | Event::JoyButtonUp { .. } => EventOutcome::consumed(DeviceKind::Gamepad),
+            _ => EventOutcome::IGNORED,
         }
     }
 
-    fn on_end_frame(&self, input: &mut Input) {
+    /// Only the last mouse motion event is processed
+    fn on_frame(&mut self, input: &mut Input, events: &[Self::Event]) {
+        let last_motion = events
+            .iter()
+            .rposition(|ev| matches!(ev, Event::MouseMotion { .. }));
+
+        for (i, ev) in events.iter().enumerate() {
+            if matches!(ev, Event::MouseMotion { .. }) && Some(i) != last_motion {
+                continue;
+            }
+            self.on_event(input, ev);
+        }
+
+        let end_frames = input.end_frames;
+        self.on_end_frame(input);
+        debug_assert_eq!(input.end_frames, end_frames + 1);
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
         // swap buffers
-        input.kbd.on_end_frame();
-        // input.mouse.on_end_frame();
+        input.on_end_frame();
     }
 }
 
-fn create_key_translation() -> HashMap<sdl2::keyboard::Keycode, Key> {
-    use sdl2::keyboard::Keycode;
-
-    [
-        (Keycode::A, Key::A),
-        (Keycode::B, Key::B),
-        (Keycode::C, Key::C),
-        (Keycode::D, Key::D),
-        (Keycode::E, Key::E),
-        (Keycode::F, Key::F),
-        (Keycode::G, Key::G),
-        (Keycode::H, Key::H),
-        (Keycode::I, Key::I),
-        (Keycode::J, Key::J),
-        (Keycode::K, Key::K),
-        (Keycode::L, Key::L),
-        (Keycode::M, Key::M),
-        (Keycode::N, Key::N),
-        (Keycode::O, Key::O),
-        (Keycode::P, Key::P),
-        (Keycode::Q, Key::Q),
-        (Keycode::R, Key::R),
-        (Keycode::S, Key::S),
-        (Keycode::T, Key::T),
-        (Keycode::U, Key::U),
-        (Keycode::V, Key::V),
-        (Keycode::W, Key::W),
-        (Keycode::X, Key::X),
-        (Keycode::Y, Key::Y),
-        (Keycode::Z, Key::Z),
-        (Keycode::Num0, Key::D0),
-        (Keycode::Num1, Key::D1),
-        (Keycode::Num2, Key::D2),
-        (Keycode::Num3, Key::D3),
-        (Keycode::Num4, Key::D4),
-        (Keycode::Num5, Key::D5),
-        (Keycode::Num6, Key::D6),
-        (Keycode::Num7, Key::D7),
-        (Keycode::Num8, Key::D8),
-        (Keycode::Num9, Key::D9),
-        (Keycode::Kp0, Key::NumPad0),
-        (Keycode::Kp1, Key::NumPad1),
-        (Keycode::Kp2, Key::NumPad2),
-        (Keycode::Kp3, Key::NumPad3),
-        (Keycode::Kp4, Key::NumPad4),
-        (Keycode::Kp5, Key::NumPad5),
-        (Keycode::Kp6, Key::NumPad6),
-        (Keycode::Kp7, Key::NumPad7),
-        (Keycode::Kp8, Key::NumPad8),
-        (Keycode::Kp9, Key::NumPad9),
-        (Keycode::KpClear, Key::OemClear),
-        (Keycode::KpDecimal, Key::Decimal),
-        (Keycode::KpDivide, Key::Slash),
-        (Keycode::KpEnter, Key::Enter),
-        (Keycode::KpMinus, Key::Minus),
-        (Keycode::KpMultiply, Key::Star),
-        (Keycode::KpPeriod, Key::OemPeriod),
-        (Keycode::KpPlus, Key::Plus),
-        (Keycode::F1, Key::F1),
-        (Keycode::F2, Key::F2),
-        (Keycode::F3, Key::F3),
-        (Keycode::F4, Key::F4),
-        (Keycode::F5, Key::F5),
-        (Keycode::F6, Key::F6),
-        (Keycode::F7, Key::F7),
-        (Keycode::F8, Key::F8),
-        (Keycode::F9, Key::F9),
-        (Keycode::F10, Key::F10),
-        (Keycode::F11, Key::F11),
-        (Keycode::F12, Key::F12),
-        (Keycode::F13, Key::F13),
-        (Keycode::F14, Key::F14),
-        (Keycode::F15, Key::F15),
-        (Keycode::F16, Key::F16),
-        (Keycode::F17, Key::F17),
-        (Keycode::F18, Key::F18),
-        (Keycode::F19, Key::F19),
-        (Keycode::F20, Key::F20),
-        (Keycode::F21, Key::F21),
-        (Keycode::F22, Key::F22),
-        (Keycode::F23, Key::F23),
-        (Keycode::F24, Key::F24),
-        (Keycode::Space, Key::Space),
-        (Keycode::Up, Key::Up),
-        (Keycode::Down, Key::Down),
-        (Keycode::Left, Key::Left),
-        (Keycode::Right, Key::Right),
-        (Keycode::LAlt, Key::LAlt),
-        (Keycode::RAlt, Key::RAlt),
-        (Keycode::LCtrl, Key::LCtrl),
-        (Keycode::RCtrl, Key::RCtrl),
-        (Keycode::LGui, Key::LMeta),
-        (Keycode::RGui, Key::RMeta),
-        (Keycode::LShift, Key::LShift),
-        (Keycode::RShift, Key::RShift),
-        (Keycode::Application, Key::Apps),
-        (Keycode::Slash, Key::OemQuestion),
-        (Keycode::Backslash, Key::OemBackslash),
-        (Keycode::LeftBracket, Key::OemOpenBrackets),
-        (Keycode::RightBracket, Key::OemCloseBrackets),
-        (Keycode::CapsLock, Key::CapsLock),
-        (Keycode::Comma, Key::OemComma),
-        (Keycode::Delete, Key::Delete),
-        (Keycode::End, Key::End),
-        (Keycode::Backspace, Key::Back),
-        (Keycode::Return, Key::Enter),
-        (Keycode::Escape, Key::Escape),
-        (Keycode::Home, Key::Home),
-        (Keycode::Insert, Key::Insert),
-        (Keycode::Minus, Key::OemMinus),
-        (Keycode::NumLockClear, Key::NumLock),
-        (Keycode::PageUp, Key::PageUp),
-        (Keycode::PageDown, Key::PageDown),
-        (Keycode::Pause, Key::Pause),
-        (Keycode::Period, Key::OemPeriod),
-        // FIXME:
-        (Keycode::Equals, Key::OemPlus),
-        (Keycode::PrintScreen, Key::PrintScreen),
-        (Keycode::Quote, Key::OemQuotes),
-        (Keycode::ScrollLock, Key::Scroll),
-        (Keycode::Semicolon, Key::OemSemicolon),
-        (Keycode::Sleep, Key::Sleep),
-        (Keycode::Tab, Key::Tab),
-        (Keycode::Backquote, Key::OemTilde),
-        (Keycode::VolumeUp, Key::VolumeUp),
-        (Keycode::VolumeDown, Key::VolumeDown),
-    ]
-    .iter()
-    .cloned()
-    .collect()
+/// Single source of the SDL2 key translation
+pub(crate) const KEY_TRANSLATION: &[(Keycode, Key)] = &[
+    (Keycode::A, Key::A),
+    (Keycode::B, Key::B),
+    (Keycode::C, Key::C),
+    (Keycode::D, Key::D),
+    (Keycode::E, Key::E),
+    (Keycode::F, Key::F),
+    (Keycode::G, Key::G),
+    (Keycode::H, Key::H),
+    (Keycode::I, Key::I),
+    (Keycode::J, Key::J),
+    (Keycode::K, Key::K),
+    (Keycode::L, Key::L),
+    (Keycode::M, Key::M),
+    (Keycode::N, Key::N),
+    (Keycode::O, Key::O),
+    (Keycode::P, Key::P),
+    (Keycode::Q, Key::Q),
+    (Keycode::R, Key::R),
+    (Keycode::S, Key::S),
+    (Keycode::T, Key::T),
+    (Keycode::U, Key::U),
+    (Keycode::V, Key::V),
+    (Keycode::W, Key::W),
+    (Keycode::X, Key::X),
+    (Keycode::Y, Key::Y),
+    (Keycode::Z, Key::Z),
+    (Keycode::Num0, Key::D0),
+    (Keycode::Num1, Key::D1),
+    (Keycode::Num2, Key::D2),
+    (Keycode::Num3, Key::D3),
+    (Keycode::Num4, Key::D4),
+    (Keycode::Num5, Key::D5),
+    (Keycode::Num6, Key::D6),
+    (Keycode::Num7, Key::D7),
+    (Keycode::Num8, Key::D8),
+    (Keycode::Num9, Key::D9),
+    (Keycode::Kp0, Key::NumPad0),
+    (Keycode::Kp1, Key::NumPad1),
+    (Keycode::Kp2, Key::NumPad2),
+    (Keycode::Kp3, Key::NumPad3),
+    (Keycode::Kp4, Key::NumPad4),
+    (Keycode::Kp5, Key::NumPad5),
+    (Keycode::Kp6, Key::NumPad6),
+    (Keycode::Kp7, Key::NumPad7),
+    (Keycode::Kp8, Key::NumPad8),
+    (Keycode::Kp9, Key::NumPad9),
+    (Keycode::KpClear, Key::OemClear),
+    (Keycode::KpDecimal, Key::Decimal),
+    (Keycode::KpDivide, Key::Slash),
+    (Keycode::KpEnter, Key::Enter),
+    (Keycode::KpMinus, Key::Minus),
+    (Keycode::KpMultiply, Key::Star),
+    (Keycode::KpPeriod, Key::OemPeriod),
+    (Keycode::KpPlus, Key::Plus),
+    (Keycode::F1, Key::F1),
+    (Keycode::F2, Key::F2),
+    (Keycode::F3, Key::F3),
+    (Keycode::F4, Key::F4),
+    (Keycode::F5, Key::F5),
+    (Keycode::F6, Key::F6),
+    (Keycode::F7, Key::F7),
+    (Keycode::F8, Key::F8),
+    (Keycode::F9, Key::F9),
+    (Keycode::F10, Key::F10),
+    (Keycode::F11, Key::F11),
+    (Keycode::F12, Key::F12),
+    (Keycode::F13, Key::F13),
+    (Keycode::F14, Key::F14),
+    (Keycode::F15, Key::F15),
+    (Keycode::F16, Key::F16),
+    (Keycode::F17, Key::F17),
+    (Keycode::F18, Key::F18),
+    (Keycode::F19, Key::F19),
+    (Keycode::F20, Key::F20),
+    (Keycode::F21, Key::F21),
+    (Keycode::F22, Key::F22),
+    (Keycode::F23, Key::F23),
+    (Keycode::F24, Key::F24),
+    (Keycode::Space, Key::Space),
+    (Keycode::Up, Key::Up),
+    (Keycode::Down, Key::Down),
+    (Keycode::Left, Key::Left),
+    (Keycode::Right, Key::Right),
+    (Keycode::LAlt, Key::LAlt),
+    (Keycode::RAlt, Key::RAlt),
+    (Keycode::LCtrl, Key::LCtrl),
+    (Keycode::RCtrl, Key::RCtrl),
+    (Keycode::LGui, Key::LMeta),
+    (Keycode::RGui, Key::RMeta),
+    (Keycode::LShift, Key::LShift),
+    (Keycode::RShift, Key::RShift),
+    (Keycode::Application, Key::Apps),
+    (Keycode::Slash, Key::OemQuestion),
+    (Keycode::Backslash, Key::OemBackslash),
+    (Keycode::LeftBracket, Key::OemOpenBrackets),
+    (Keycode::RightBracket, Key::OemCloseBrackets),
+    (Keycode::CapsLock, Key::CapsLock),
+    (Keycode::Comma, Key::OemComma),
+    (Keycode::Delete, Key::Delete),
+    (Keycode::End, Key::End),
+    (Keycode::Backspace, Key::Back),
+    (Keycode::Return, Key::Enter),
+    (Keycode::Escape, Key::Escape),
+    (Keycode::Home, Key::Home),
+    (Keycode::Insert, Key::Insert),
+    (Keycode::Minus, Key::OemMinus),
+    (Keycode::NumLockClear, Key::NumLock),
+    (Keycode::PageUp, Key::PageUp),
+    (Keycode::PageDown, Key::PageDown),
+    (Keycode::Pause, Key::Pause),
+    (Keycode::Period, Key::OemPeriod),
+    // FIXME:
+    (Keycode::Equals, Key::OemPlus),
+    (Keycode::PrintScreen, Key::PrintScreen),
+    (Keycode::Quote, Key::OemQuotes),
+    (Keycode::ScrollLock, Key::Scroll),
+    (Keycode::Semicolon, Key::OemSemicolon),
+    (Keycode::Sleep, Key::Sleep),
+    (Keycode::Tab, Key::Tab),
+    (Keycode::Backquote, Key::OemTilde),
+    (Keycode::VolumeUp, Key::VolumeUp),
+    (Keycode::VolumeDown, Key::VolumeDown),
+];
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{event::Event, keyboard::Mod};
+
+    use super::*;
+
+    fn key_down(keycode: Keycode) -> Event {
+        Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    }
+
+    fn text(text: &str) -> Event {
+        Event::TextInput {
+            timestamp: 0,
+            window_id: 0,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn text_input_suppresses_printable_keys() {
+        let mut backend = SdlBackend::new();
+        let mut input = Input::new();
+        input.set_suppress_printable_keys(true);
+
+        // not suppressed until text input starts
+        backend.on_event(&mut input, &key_down(Keycode::A));
+        assert!(input.kbd.is_key_down(Key::A));
+
+        input.start_text_input();
+        backend.on_event(&mut input, &key_down(Keycode::Left));
+        backend.on_event(&mut input, &text("a"));
+        assert!(!input.kbd.is_key_down(Key::A));
+        assert!(input.kbd.is_key_down(Key::Left));
+        assert_eq!(input.text.text(), "a");
+
+        // composition is committed by the text event
+        backend.on_event(
+            &mut input,
+            &Event::TextEditing {
+                timestamp: 0,
+                window_id: 0,
+                text: "か".to_string(),
+                start: 1,
+                length: 0,
+            },
+        );
+        assert_eq!(
+            input.text.composition().map(|c| c.text.as_str()),
+            Some("か")
+        );
+        backend.on_event(&mut input, &text("か"));
+        assert_eq!(input.text.composition(), None);
+        assert_eq!(input.text.text(), "aか");
+
+        input.stop_text_input();
+        assert!(input.kbd.is_key_down(Key::A));
+        assert_eq!(input.text.text(), "");
+
+        // the toggle applies while text input is active
+        input.start_text_input();
+        input.set_suppress_printable_keys(false);
+        assert!(input.kbd.is_key_down(Key::A));
+    }
+
+    #[test]
+    fn overrides_and_raw_passthrough() {
+        let mut overrides = HashMap::new();
+        overrides.insert(Keycode::AudioMute, Key::M);
+        overrides.insert(Keycode::A, Key::B);
+        let mut backend = SdlBackend::new().with_overrides(overrides);
+        assert_eq!(backend.translate(&Keycode::AudioMute), Some(Key::M));
+        assert_eq!(backend.translate(&Keycode::A), Some(Key::B));
+
+        backend.remove_mapping(Keycode::AudioMute);
+        assert_eq!(backend.translate(&Keycode::AudioMute), None);
+
+        let mut input = Input::new();
+        backend.on_event(&mut input, &key_down(Keycode::AudioMute));
+        assert!(input.kbd.raw_keys_down().is_empty());
+
+        backend.set_raw_passthrough(true);
+        backend.on_event(&mut input, &key_down(Keycode::AudioMute));
+        let raw = RawKey(Keycode::AudioMute as i32 as u32);
+        assert!(input.kbd.is_raw_key_down(raw));
+        // translated keys are not passed through
+        backend.on_event(&mut input, &key_down(Keycode::A));
+        assert!(input.kbd.is_key_down(Key::B));
+        assert_eq!(input.kbd.raw_keys_down(), &[raw]);
+    }
+
+    #[test]
+    fn device_events_are_consumed() {
+        use sdl2::mouse::MouseState;
+
+        let mut backend = SdlBackend::new();
+        let mut input = Input::new();
+
+        assert_eq!(
+            backend.on_event(&mut input, &key_down(Keycode::A)),
+            EventOutcome::consumed(DeviceKind::Keyboard)
+        );
+        let motion = Event::MouseMotion {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mousestate: MouseState::from_sdl_state(0),
+            x: 10,
+            y: 20,
+            xrel: 10,
+            yrel: 20,
+        };
+        assert_eq!(
+            backend.on_event(&mut input, &motion),
+            EventOutcome::consumed(DeviceKind::Mouse)
+        );
+        assert_eq!(
+            backend.on_event(&mut input, &Event::Quit { timestamp: 0 }),
+            EventOutcome::IGNORED
+        );
+    }
+
+    #[test]
+    fn bound_window_is_isolated() {
+        use sdl2::event::WindowEvent;
+
+        let in_window = |window_id: u32, keycode: Keycode| Event::KeyDown {
+            timestamp: 0,
+            window_id,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        };
+        let focus_lost = |window_id: u32| Event::Window {
+            timestamp: 0,
+            window_id,
+            win_event: WindowEvent::FocusLost,
+        };
+
+        let mut main = SdlBackend::new();
+        main.bind_window(1);
+        let mut sub = SdlBackend::new();
+        sub.bind_window(2);
+        let (mut main_input, mut sub_input) = (Input::new(), Input::new());
+
+        for ev in &[in_window(1, Keycode::W), in_window(2, Keycode::S)] {
+            main.on_event(&mut main_input, ev);
+            sub.on_event(&mut sub_input, ev);
+        }
+        assert!(main_input.kbd.is_key_down(Key::W) && !main_input.kbd.is_key_down(Key::S));
+        assert!(sub_input.kbd.is_key_down(Key::S) && !sub_input.kbd.is_key_down(Key::W));
+
+        // focus events clear only the window's keys
+        let ev = focus_lost(2);
+        main.on_event(&mut main_input, &ev);
+        sub.on_event(&mut sub_input, &ev);
+        assert!(main_input.kbd.is_key_down(Key::W));
+        assert!(!sub_input.kbd.is_key_down(Key::S));
+
+        // events without window ID are applied
+        main.on_event(&mut main_input, &key_down(Keycode::D));
+        assert!(main_input.kbd.is_key_down(Key::D));
+    }
 }
@@ -0,0 +1,149 @@
+//! Deterministic event streams for benchmarks and fuzzing
+
+use crate::{
+    backend::{DeviceKind, EventOutcome},
+    input::{keyboard::Key, Input},
+    utils, Backend,
+};
+
+/// Event generated by [`SyntheticBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntheticEvent {
+    Key {
+        key: Key,
+        down: bool,
+    },
+    /// Cursor position in window pixels
+    MouseMove {
+        pos: [i32; 2],
+    },
+}
+
+/// Generates random key toggles and mouse jitter from a seed, without a window
+///
+/// The same seed always produces the same stream, so a failing frame can be reproduced:
+///
+/// ```
+/// use xdl::{backend::SyntheticBackend, Backend, Input, Key};
+///
+/// let mut backend = SyntheticBackend::new(42)
+///     .with_toggles_per_frame(16)
+///     .with_mouse_jitter(4);
+/// let mut input = Input::new();
+/// let mut events = Vec::new();
+/// let mut last_pos = input.pointer.pos();
+///
+/// for _ in 0..100 {
+///     events.clear();
+///     backend.generate_frame(&mut events);
+///     backend.on_frame(&mut input, &events);
+///
+///     for &key in Key::ALL {
+///         if input.kbd.is_key_pressed(key) {
+///             assert!(input.kbd.is_key_down(key));
+///         }
+///     }
+///
+///     let pos = input.pointer.pos();
+///     assert!((pos[0] - last_pos[0]).abs() <= 4 && (pos[1] - last_pos[1]).abs() <= 4);
+///     last_pos = pos;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SyntheticBackend {
+    rng: u64,
+    toggles_per_frame: usize,
+    keys: Vec<Key>,
+    /// Parallel to `keys`
+    down: Vec<bool>,
+    /// Max cursor offset per frame on each axis. Zero disables mouse events
+    mouse_jitter: i32,
+    cursor: [i32; 2],
+}
+
+impl SyntheticBackend {
+    /// Toggles 8 keys per frame out of all the keys, without mouse events
+    pub fn new(seed: u64) -> Self {
+        Self {
+            // xorshift gets stuck on zero
+            rng: seed ^ 0x9E37_79B9_7F4A_7C15,
+            toggles_per_frame: 8,
+            keys: Key::ALL.to_vec(),
+            down: vec![false; Key::ALL.len()],
+            mouse_jitter: 0,
+            cursor: [0, 0],
+        }
+    }
+
+    pub fn with_toggles_per_frame(mut self, n: usize) -> Self {
+        self.toggles_per_frame = n;
+        self
+    }
+
+    /// Restricts the generated events to the keys (e.g. the ones bound to actions)
+    pub fn with_keys(mut self, keys: &[Key]) -> Self {
+        self.keys = keys.to_vec();
+        self.down = vec![false; keys.len()];
+        self
+    }
+
+    /// Moves the cursor by up to `max_offset` pixels on each axis every frame
+    pub fn with_mouse_jitter(mut self, max_offset: i32) -> Self {
+        self.mouse_jitter = max_offset.max(0);
+        self
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        utils::xorshift64(&mut self.rng)
+    }
+
+    /// Appends the events of the next frame
+    pub fn generate_frame(&mut self, out: &mut Vec<SyntheticEvent>) {
+        if !self.keys.is_empty() {
+            for _ in 0..self.toggles_per_frame {
+                let ix = (self.next_u64() % self.keys.len() as u64) as usize;
+                self.down[ix] = !self.down[ix];
+                out.push(SyntheticEvent::Key {
+                    key: self.keys[ix],
+                    down: self.down[ix],
+                });
+            }
+        }
+
+        if self.mouse_jitter > 0 {
+            let span = 2 * self.mouse_jitter as u64 + 1;
+            for i in 0..2 {
+                let offset = (self.next_u64() % span) as i32 - self.mouse_jitter;
+                self.cursor[i] = self.cursor[i].saturating_add(offset);
+            }
+            out.push(SyntheticEvent::MouseMove { pos: self.cursor });
+        }
+    }
+}
+
+impl Backend for SyntheticBackend {
+    type Event = SyntheticEvent;
+    type Key = Key;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        match *ev {
+            SyntheticEvent::Key { key, down } => {
+                if down {
+                    input.kbd.on_key_down(key);
+                } else {
+                    input.kbd.on_key_up(key);
+                }
+                EventOutcome::consumed(DeviceKind::Keyboard)
+            }
+            SyntheticEvent::MouseMove { pos } => {
+                input.pointer.on_move(pos);
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+        }
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
@@ -0,0 +1,187 @@
+//! Re-exported to super module
+
+use std::{borrow::Borrow, collections::HashMap, iter::FromIterator};
+
+use crate::input::keyboard::Key;
+
+/// Platform key to [`Key`] mapping: a backend's built-in table plus user overrides
+///
+/// Entries are kept sorted and looked up with binary search. Platform keys without an [`Ord`]
+/// implementation are keyed by their integer values (e.g. `Keycode as i32`).
+#[derive(Debug, Clone)]
+pub struct KeyTranslation<K> {
+    entries: Vec<(K, Key)>,
+}
+
+impl<K> Default for KeyTranslation<K> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<K: Ord> FromIterator<(K, Key)> for KeyTranslation<K> {
+    /// Later entries take precedence over earlier ones with the same platform key
+    fn from_iter<I: IntoIterator<Item = (K, Key)>>(iter: I) -> Self {
+        let mut entries: Vec<(K, Key)> = iter.into_iter().collect();
+        // stable sort keeps the insertion order of duplicates
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // keep the last one of duplicates
+        let mut deduped: Vec<(K, Key)> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match deduped.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => deduped.push(entry),
+            }
+        }
+
+        Self { entries: deduped }
+    }
+}
+
+impl<K: Ord + Clone> KeyTranslation<K> {
+    pub fn from_table(table: &[(K, Key)]) -> Self {
+        table.iter().cloned().collect()
+    }
+}
+
+impl<K: Ord> KeyTranslation<K> {
+    fn find<Q>(&self, from: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(from))
+    }
+
+    /// Merges the mappings over the current ones
+    pub fn with_overrides(mut self, overrides: HashMap<K, Key>) -> Self {
+        for (from, to) in overrides {
+            self.add_mapping(from, to);
+        }
+        self
+    }
+
+    /// Returns the previous mapping if any
+    pub fn add_mapping(&mut self, from: K, to: Key) -> Option<Key> {
+        match self.find(&from) {
+            Ok(ix) => Some(std::mem::replace(&mut self.entries[ix].1, to)),
+            Err(ix) => {
+                self.entries.insert(ix, (from, to));
+                None
+            }
+        }
+    }
+
+    pub fn remove_mapping<Q>(&mut self, from: &Q) -> Option<Key>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ix = self.find(from).ok()?;
+        Some(self.entries.remove(ix).1)
+    }
+
+    pub fn translate<Q>(&self, from: &Q) -> Option<Key>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ix = self.find(from).ok()?;
+        Some(self.entries[ix].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sorted by the platform key
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Key)> + '_ {
+        self.entries.iter().map(|(k, v)| (k, *v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &[(u32, Key)] = &[(3, Key::C), (1, Key::A), (2, Key::B)];
+
+    #[test]
+    fn overrides_take_precedence() {
+        let mut overrides = HashMap::new();
+        overrides.insert(2, Key::Enter);
+        overrides.insert(4, Key::D);
+        let mut map = KeyTranslation::from_table(TABLE).with_overrides(overrides);
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.translate(&1), Some(Key::A));
+        assert_eq!(map.translate(&2), Some(Key::Enter));
+        assert_eq!(map.translate(&4), Some(Key::D));
+
+        assert_eq!(map.add_mapping(1, Key::Space), Some(Key::A));
+        assert_eq!(map.add_mapping(0, Key::Z), None);
+        assert_eq!(map.translate(&1), Some(Key::Space));
+
+        // still sorted after insertions
+        let froms: Vec<u32> = map.iter().map(|(from, _)| *from).collect();
+        assert_eq!(froms, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn remove_mapping() {
+        let mut map = KeyTranslation::from_table(TABLE);
+        assert_eq!(map.remove_mapping(&2), Some(Key::B));
+        assert_eq!(map.remove_mapping(&2), None);
+        assert_eq!(map.translate(&2), None);
+        assert_eq!(map.translate(&3), Some(Key::C));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn later_duplicates_win() {
+        let map: KeyTranslation<u32> = vec![(1, Key::A), (0, Key::Z), (1, Key::B)]
+            .into_iter()
+            .collect();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.translate(&1), Some(Key::B));
+    }
+
+    /// Same mappings as collecting into a `HashMap` (the previous implementation)
+    fn assert_same_as_hash_map<K: Ord + Clone + std::hash::Hash>(table: &[(K, Key)]) {
+        let map: HashMap<K, Key> = table.iter().cloned().collect();
+        let sorted = KeyTranslation::from_table(table);
+
+        assert_eq!(sorted.len(), map.len());
+        for (from, to) in &map {
+            assert_eq!(sorted.translate(from), Some(*to));
+        }
+        assert!(sorted.iter().all(|(from, to)| map.get(from) == Some(&to)));
+    }
+
+    #[test]
+    fn same_as_hash_map() {
+        assert_same_as_hash_map(TABLE);
+        // duplicates and scattered values
+        assert_same_as_hash_map(&[
+            (0x4000_0050, Key::Left),
+            (u32::MAX, Key::A),
+            (0, Key::B),
+            (0x4000_0050, Key::Right),
+        ]);
+
+        #[cfg(feature = "sdl2")]
+        assert_same_as_hash_map(
+            &crate::backend::sdl2_support::KEY_TRANSLATION
+                .iter()
+                .map(|(from, to)| (*from as i32, *to))
+                .collect::<Vec<_>>(),
+        );
+    }
+}
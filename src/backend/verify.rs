@@ -0,0 +1,458 @@
+//! Detects replay desyncs by comparing per-frame fingerprints
+//!
+//! Stored as text:
+//!
+//! ```text
+//! xdl-verify 1
+//! interval 2
+//! 0 cbf29ce484222325 | | 3a0c6fc3ba37cc4a 87a4b25d1a3e8e4d 0d5bd9e08e2f4e26
+//! 1 5a1ed1e4e59a1b3f
+//! 2 01b3b1f0a1c2d3e4 | Space Left | 9c1f53f8c2b6b90e 87a4b25d1a3e8e4d 0d5bd9e08e2f4e26
+//! ```
+//!
+//! The second line is the checkpoint interval. Each line after it is a frame index and the
+//! [`Input::fingerprint`] on that frame. Checkpoint frames additionally list the down keys and
+//! then the [`Input::device_fingerprint`]s of the keyboard, the mouse and text input, each after
+//! `|`.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    backend::{DeviceKind, ReplayError},
+    input::Input,
+    Key,
+};
+
+const HEADER: &str = "xdl-verify";
+const VERSION: u32 = 1;
+
+/// Devices fingerprinted on checkpoints
+const DEVICES: [DeviceKind; 3] = [DeviceKind::Keyboard, DeviceKind::Mouse, DeviceKind::Text];
+
+/// Difference of a key on a desynced frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyDiff {
+    pub key: Key,
+    /// If the key was down on record
+    pub expected_down: bool,
+}
+
+/// Desync found by [`ReplayVerifier::verify_frame`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesyncReport {
+    pub frame: u32,
+    pub expected: u64,
+    pub actual: u64,
+    /// Devices whose states differ. `None` if the frame has no checkpoint
+    pub devices: Option<Vec<DeviceKind>>,
+    /// Keys that differ. `None` if the frame has no checkpoint
+    pub keys: Option<Vec<KeyDiff>>,
+}
+
+impl fmt::Display for DesyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "desync on frame {} (expected {:016x}, found {:016x})",
+            self.frame, self.expected, self.actual
+        )?;
+
+        let (devices, keys) = match (&self.devices, &self.keys) {
+            (Some(devices), Some(keys)) => (devices, keys),
+            _ => return write!(f, ": no checkpoint on the frame"),
+        };
+
+        let mut sep = ": ";
+        for device in devices {
+            match device {
+                DeviceKind::Keyboard if keys.is_empty() => {
+                    write!(f, "{}key states match (edges differ)", sep)?;
+                }
+                DeviceKind::Keyboard => {
+                    for diff in keys {
+                        let (expected, found) = if diff.expected_down {
+                            ("down", "up")
+                        } else {
+                            ("up", "down")
+                        };
+                        write!(
+                            f,
+                            "{}{} expected {}, found {}",
+                            sep,
+                            diff.key.name(),
+                            expected,
+                            found
+                        )?;
+                        sep = ", ";
+                    }
+                }
+                DeviceKind::Mouse => write!(f, "{}mouse state differs", sep)?,
+                DeviceKind::Text => write!(f, "{}text input differs", sep)?,
+                DeviceKind::Gamepad => write!(f, "{}gamepad state differs", sep)?,
+            }
+            sep = ", ";
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FrameRecord {
+    fingerprint: u64,
+    checkpoint: Option<Checkpoint>,
+}
+
+/// Per-device summary of the state
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Checkpoint {
+    /// Down keys
+    keys: Vec<Key>,
+    /// [`Input::device_fingerprint`] of each of [`DEVICES`]
+    devices: [u64; 3],
+}
+
+impl Checkpoint {
+    fn new(input: &Input) -> Self {
+        Self {
+            keys: self::down_keys(input),
+            devices: DEVICES.map(|device| input.device_fingerprint(device)),
+        }
+    }
+}
+
+fn down_keys(input: &Input) -> Vec<Key> {
+    Key::ALL
+        .iter()
+        .cloned()
+        .filter(|&key| input.kbd.is_key_down(key))
+        .collect()
+}
+
+/// Records [`Input::fingerprint`]s while recording a replay and checks them on playback
+///
+/// Per-device summaries (down keys and the fingerprint of each device) are stored every
+/// `checkpoint_interval` frames so that desync reports can tell which device and which keys
+/// diverged. Smaller intervals make larger files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayVerifier {
+    checkpoint_interval: u32,
+    frames: Vec<FrameRecord>,
+    /// Next frame to verify
+    cursor: u32,
+}
+
+impl ReplayVerifier {
+    /// `checkpoint_interval` of zero disables checkpoints
+    pub fn new(checkpoint_interval: u32) -> Self {
+        Self {
+            checkpoint_interval,
+            frames: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Appends the state of the next frame
+    pub fn record_frame(&mut self, input: &Input) {
+        let frame = self.frames.len() as u32;
+        let is_checkpoint =
+            self.checkpoint_interval != 0 && frame.is_multiple_of(self.checkpoint_interval);
+        self.frames.push(FrameRecord {
+            fingerprint: input.fingerprint(),
+            checkpoint: if is_checkpoint {
+                Some(Checkpoint::new(input))
+            } else {
+                None
+            },
+        });
+    }
+
+    /// Compares the state with the recorded one of the next frame. Frames beyond the record are
+    /// not checked
+    pub fn verify_frame(&mut self, input: &Input) -> Result<(), DesyncReport> {
+        let frame = self.cursor;
+        self.cursor += 1;
+
+        let rec = match self.frames.get(frame as usize) {
+            Some(rec) => rec,
+            None => return Ok(()),
+        };
+
+        let actual = input.fingerprint();
+        if actual == rec.fingerprint {
+            return Ok(());
+        }
+
+        let (devices, keys) = match &rec.checkpoint {
+            Some(expected) => {
+                let found = Checkpoint::new(input);
+                let devices = DEVICES
+                    .iter()
+                    .zip(expected.devices.iter().zip(found.devices.iter()))
+                    .filter(|(_, (expected, found))| expected != found)
+                    .map(|(device, _)| *device)
+                    .collect();
+                let keys = Key::ALL
+                    .iter()
+                    .cloned()
+                    .filter_map(|key| {
+                        let expected_down = expected.keys.contains(&key);
+                        if expected_down != found.keys.contains(&key) {
+                            Some(KeyDiff { key, expected_down })
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                (Some(devices), Some(keys))
+            }
+            None => (None, None),
+        };
+
+        Err(DesyncReport {
+            frame,
+            expected: rec.fingerprint,
+            actual,
+            devices,
+            keys,
+        })
+    }
+
+    /// Restarts verification from the first frame
+    pub fn rewind(&mut self) {
+        self.cursor = 0;
+    }
+}
+
+/// Serialization
+impl ReplayVerifier {
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(w, "{} {}", HEADER, VERSION)?;
+        writeln!(w, "interval {}", self.checkpoint_interval)?;
+        for (i, rec) in self.frames.iter().enumerate() {
+            write!(w, "{} {:016x}", i, rec.fingerprint)?;
+            if let Some(checkpoint) = &rec.checkpoint {
+                write!(w, " |")?;
+                for key in &checkpoint.keys {
+                    write!(w, " {}", key.name())?;
+                }
+                write!(w, " |")?;
+                for fingerprint in &checkpoint.devices {
+                    write!(w, " {:016x}", fingerprint)?;
+                }
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    pub fn read_from(r: impl Read) -> Result<Self, ReplayError> {
+        let mut lines = BufReader::new(r).lines();
+
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let version = match header.split_once(' ') {
+            Some((HEADER, v)) => v
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| ReplayError::Header(header.clone()))?,
+            _ => return Err(ReplayError::Header(header)),
+        };
+        if version != VERSION {
+            return Err(ReplayError::Version(version));
+        }
+
+        let mut verifier = Self::new(0);
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let malformed = || ReplayError::Line {
+                line: i + 2,
+                text: line.to_string(),
+            };
+
+            let (head, tail) = line.split_once(' ').ok_or_else(malformed)?;
+            if head == "interval" {
+                verifier.checkpoint_interval = tail.trim().parse().map_err(|_| malformed())?;
+                continue;
+            }
+
+            let frame: usize = head.parse().map_err(|_| malformed())?;
+            if frame != verifier.frames.len() {
+                return Err(malformed());
+            }
+
+            let mut parts = tail.split('|');
+            let fingerprint = parts.next().unwrap_or_default();
+            let checkpoint = match (parts.next(), parts.next(), parts.next()) {
+                (None, ..) => None,
+                (Some(keys), Some(devices), None) => {
+                    let keys = keys
+                        .split_whitespace()
+                        .map(Key::from_name)
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(malformed)?;
+                    let devices = devices
+                        .split_whitespace()
+                        .map(|s| u64::from_str_radix(s, 16).ok())
+                        .collect::<Option<Vec<_>>>()
+                        .and_then(|v| std::convert::TryInto::<[u64; 3]>::try_into(v).ok())
+                        .ok_or_else(malformed)?;
+                    Some(Checkpoint { keys, devices })
+                }
+                _ => return Err(malformed()),
+            };
+            let fingerprint =
+                u64::from_str_radix(fingerprint.trim(), 16).map_err(|_| malformed())?;
+
+            verifier.frames.push(FrameRecord {
+                fingerprint,
+                checkpoint,
+            });
+        }
+
+        Ok(verifier)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::read_from(File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::backend::ScriptBackend;
+
+    const DT: Duration = Duration::from_millis(16);
+
+    fn script() -> ScriptBackend {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 1..8).hold(Key::Space, 3..4);
+        script
+    }
+
+    fn record(checkpoint_interval: u32) -> ReplayVerifier {
+        let mut script = self::script();
+        let mut input = Input::new();
+        let mut verifier = ReplayVerifier::new(checkpoint_interval);
+        for _ in 0..10 {
+            script.step(&mut input);
+            verifier.record_frame(&input);
+        }
+        verifier
+    }
+
+    /// Replays the script with `X` pressed on frame 5
+    fn diverge(verifier: &mut ReplayVerifier) -> Vec<DesyncReport> {
+        let mut script = self::script();
+        script.at_frame(5).press(Key::X);
+        let mut input = Input::new();
+        (0..10)
+            .filter_map(|_| {
+                script.step(&mut input);
+                verifier.verify_frame(&input).err()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn report_pinpoints_the_frame_and_key() {
+        let mut verifier = self::record(1);
+
+        // identical playback
+        let mut script = self::script();
+        let mut input = Input::new();
+        for _ in 0..10 {
+            script.step(&mut input);
+            assert_eq!(verifier.verify_frame(&input), Ok(()));
+        }
+
+        verifier.rewind();
+        let reports = self::diverge(&mut verifier);
+        // every frame from the divergence is reported
+        assert_eq!(reports.len(), 5);
+        let report = &reports[0];
+        assert_eq!(report.frame, 5);
+        assert_eq!(report.devices, Some(vec![DeviceKind::Keyboard]));
+        assert_eq!(
+            report.keys,
+            Some(vec![KeyDiff {
+                key: Key::X,
+                expected_down: false,
+            }])
+        );
+        assert!(report.to_string().ends_with("X expected up, found down"));
+    }
+
+    #[test]
+    fn report_names_the_mouse() {
+        let mut verifier = self::record(1);
+        let mut script = self::script();
+        let mut input = Input::new();
+        let reports = (0..10)
+            .filter_map(|i| {
+                script.step(&mut input);
+                if i == 5 {
+                    input.pointer.on_move([40, 2]);
+                }
+                verifier.verify_frame(&input).err()
+            })
+            .collect::<Vec<_>>();
+
+        let report = &reports[0];
+        assert_eq!(report.frame, 5);
+        assert_eq!(report.devices, Some(vec![DeviceKind::Mouse]));
+        assert_eq!(report.keys, Some(vec![]));
+        assert!(report.to_string().ends_with(": mouse state differs"));
+    }
+
+    #[test]
+    fn checkpoint_interval() {
+        let mut verifier = self::record(4);
+        let reports = self::diverge(&mut verifier);
+        let keys = reports
+            .iter()
+            .map(|r| (r.frame, r.keys.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [(5, false), (6, false), (7, false), (8, true), (9, false)]
+        );
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let verifier = self::record(3);
+        let mut buf = Vec::new();
+        verifier.write_to(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("xdl-verify 1\ninterval 3\n0 "));
+
+        let loaded = ReplayVerifier::read_from(text.as_bytes()).unwrap();
+        assert_eq!(loaded, verifier);
+    }
+}
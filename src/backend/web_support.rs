@@ -0,0 +1,409 @@
+//! Web backend
+//!
+//! Keys are translated from [`KeyboardEvent.code`] strings, so the translation doesn't depend on
+//! a specific web framework. [`install_listeners`] forwards DOM events on `wasm32`.
+//!
+//! [`KeyboardEvent.code`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code
+
+use std::collections::HashMap;
+
+use crate::{
+    backend::{DeviceKind, EventOutcome, KeyTranslation},
+    input::{keyboard::Key, Input},
+    Backend,
+};
+
+#[cfg(target_arch = "wasm32")]
+pub use self::listeners::{install_listeners, WebListeners};
+
+pub type ExternalKey = String;
+
+/// DOM event forwarded to [`WebBackend`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// `keydown` (`pressed: true`) or `keyup` (`pressed: false`)
+    Key {
+        code: String,
+        pressed: bool,
+        repeat: bool,
+    },
+    /// `blur`; releases all the keys
+    Blur,
+}
+
+/// Web backend
+#[derive(Debug, Clone)]
+pub struct WebBackend {
+    map: KeyTranslation<String>,
+    prevent_default: Vec<String>,
+}
+
+impl Default for WebBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebBackend {
+    /// Arrow keys, space and tab don't scroll or move focus by default
+    pub fn new() -> Self {
+        Self {
+            map: KEY_TRANSLATION
+                .iter()
+                .map(|(code, key)| (code.to_string(), *key))
+                .collect(),
+            prevent_default: [
+                "ArrowUp",
+                "ArrowDown",
+                "ArrowLeft",
+                "ArrowRight",
+                "Space",
+                "Tab",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        }
+    }
+
+    /// Translates a `KeyboardEvent.code` string
+    pub fn translate(&self, code: &str) -> Option<Key> {
+        self.map.translate(code)
+    }
+
+    /// Merges the mappings over the built-in table
+    pub fn with_overrides(mut self, overrides: HashMap<ExternalKey, Key>) -> Self {
+        self.map = self.map.with_overrides(overrides);
+        self
+    }
+
+    pub fn translation(&self) -> &KeyTranslation<ExternalKey> {
+        &self.map
+    }
+
+    pub fn translation_mut(&mut self) -> &mut KeyTranslation<ExternalKey> {
+        &mut self.map
+    }
+
+    /// Handles `keydown` (`pressed: true`) and `keyup` (`pressed: false`) events
+    ///
+    /// Auto-repeated `keydown` events (`event.repeat`) should not be passed.
+    pub fn on_key_event(&self, input: &mut Input, code: &str, pressed: bool) {
+        let key = match self.translate(code) {
+            Some(key) => key,
+            None => return,
+        };
+
+        if pressed {
+            input.kbd.on_key_down(key);
+        } else {
+            input.kbd.on_key_up(key);
+        }
+    }
+
+    /// Codes of the keys whose default browser action is prevented
+    pub fn prevent_default(&self) -> &[String] {
+        &self.prevent_default
+    }
+
+    pub fn set_prevent_default(&mut self, codes: Vec<String>) {
+        self.prevent_default = codes;
+    }
+
+    pub fn should_prevent_default(&self, code: &str) -> bool {
+        self.prevent_default.iter().any(|c| c == code)
+    }
+
+    /// [`Backend::on_event`] without mutable access to the backend
+    pub fn on_web_event(&self, input: &mut Input, ev: &Event) -> EventOutcome {
+        match ev {
+            // consumed, but not a new press
+            Event::Key { repeat: true, .. } => EventOutcome::consumed(DeviceKind::Keyboard),
+            Event::Key { code, pressed, .. } => {
+                self.on_key_event(input, code, *pressed);
+                EventOutcome::consumed(DeviceKind::Keyboard)
+            }
+            // we won't receive key up events while the page is not focused
+            Event::Blur => {
+                input.kbd.release_all();
+                EventOutcome::IGNORED
+            }
+        }
+    }
+}
+
+impl Backend for WebBackend {
+    type Event = Event;
+    type Key = ExternalKey;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        self.on_web_event(input, ev)
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod listeners {
+    use std::{cell::RefCell, rc::Rc};
+
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+    use web_sys::{EventTarget, KeyboardEvent};
+
+    use super::{Event, WebBackend};
+    use crate::Input;
+
+    /// Registered DOM event listeners. They are removed on drop
+    pub struct WebListeners {
+        target: EventTarget,
+        keydown: Closure<dyn FnMut(KeyboardEvent)>,
+        keyup: Closure<dyn FnMut(KeyboardEvent)>,
+        blur: Closure<dyn FnMut(web_sys::Event)>,
+    }
+
+    impl Drop for WebListeners {
+        fn drop(&mut self) {
+            let _ = self.target.remove_event_listener_with_callback(
+                "keydown",
+                self.keydown.as_ref().unchecked_ref(),
+            );
+            let _ = self
+                .target
+                .remove_event_listener_with_callback("keyup", self.keyup.as_ref().unchecked_ref());
+            let _ = self
+                .target
+                .remove_event_listener_with_callback("blur", self.blur.as_ref().unchecked_ref());
+        }
+    }
+
+    fn key_listener(
+        backend: Rc<WebBackend>,
+        input: Rc<RefCell<Input>>,
+        pressed: bool,
+    ) -> Closure<dyn FnMut(KeyboardEvent)> {
+        Closure::wrap(Box::new(move |ev: KeyboardEvent| {
+            let code = ev.code();
+            if backend.should_prevent_default(&code) {
+                ev.prevent_default();
+            }
+
+            let ev = Event::Key {
+                code,
+                pressed,
+                repeat: ev.repeat(),
+            };
+            backend.on_web_event(&mut input.borrow_mut(), &ev);
+        }) as Box<dyn FnMut(KeyboardEvent)>)
+    }
+
+    /// Registers `keydown`, `keyup` and `blur` listeners on the target (typically a canvas)
+    ///
+    /// A canvas needs a `tabindex` attribute to receive keyboard events.
+    pub fn install_listeners(
+        target: &EventTarget,
+        backend: Rc<WebBackend>,
+        input: Rc<RefCell<Input>>,
+    ) -> Result<WebListeners, JsValue> {
+        let keydown = self::key_listener(backend.clone(), input.clone(), true);
+        let keyup = self::key_listener(backend.clone(), input.clone(), false);
+        let blur = Closure::wrap(Box::new(move |_ev: web_sys::Event| {
+            backend.on_web_event(&mut input.borrow_mut(), &Event::Blur);
+        }) as Box<dyn FnMut(web_sys::Event)>);
+
+        target.add_event_listener_with_callback("keydown", keydown.as_ref().unchecked_ref())?;
+        target.add_event_listener_with_callback("keyup", keyup.as_ref().unchecked_ref())?;
+        target.add_event_listener_with_callback("blur", blur.as_ref().unchecked_ref())?;
+
+        Ok(WebListeners {
+            target: target.clone(),
+            keydown,
+            keyup,
+            blur,
+        })
+    }
+}
+
+/// Every code is mapped to a unique [`Key`]. `NumpadEnter` is not mapped.
+pub(crate) const KEY_TRANSLATION: &[(&str, Key)] = &[
+    ("KeyA", Key::A),
+    ("KeyB", Key::B),
+    ("KeyC", Key::C),
+    ("KeyD", Key::D),
+    ("KeyE", Key::E),
+    ("KeyF", Key::F),
+    ("KeyG", Key::G),
+    ("KeyH", Key::H),
+    ("KeyI", Key::I),
+    ("KeyJ", Key::J),
+    ("KeyK", Key::K),
+    ("KeyL", Key::L),
+    ("KeyM", Key::M),
+    ("KeyN", Key::N),
+    ("KeyO", Key::O),
+    ("KeyP", Key::P),
+    ("KeyQ", Key::Q),
+    ("KeyR", Key::R),
+    ("KeyS", Key::S),
+    ("KeyT", Key::T),
+    ("KeyU", Key::U),
+    ("KeyV", Key::V),
+    ("KeyW", Key::W),
+    ("KeyX", Key::X),
+    ("KeyY", Key::Y),
+    ("KeyZ", Key::Z),
+    ("Digit0", Key::D0),
+    ("Digit1", Key::D1),
+    ("Digit2", Key::D2),
+    ("Digit3", Key::D3),
+    ("Digit4", Key::D4),
+    ("Digit5", Key::D5),
+    ("Digit6", Key::D6),
+    ("Digit7", Key::D7),
+    ("Digit8", Key::D8),
+    ("Digit9", Key::D9),
+    ("Numpad0", Key::NumPad0),
+    ("Numpad1", Key::NumPad1),
+    ("Numpad2", Key::NumPad2),
+    ("Numpad3", Key::NumPad3),
+    ("Numpad4", Key::NumPad4),
+    ("Numpad5", Key::NumPad5),
+    ("Numpad6", Key::NumPad6),
+    ("Numpad7", Key::NumPad7),
+    ("Numpad8", Key::NumPad8),
+    ("Numpad9", Key::NumPad9),
+    ("NumpadClear", Key::OemClear),
+    ("NumpadDecimal", Key::Decimal),
+    ("NumpadDivide", Key::Slash),
+    ("NumpadSubtract", Key::Minus),
+    ("NumpadMultiply", Key::Star),
+    ("NumpadAdd", Key::Plus),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+    ("F13", Key::F13),
+    ("F14", Key::F14),
+    ("F15", Key::F15),
+    ("F16", Key::F16),
+    ("F17", Key::F17),
+    ("F18", Key::F18),
+    ("F19", Key::F19),
+    ("F20", Key::F20),
+    ("F21", Key::F21),
+    ("F22", Key::F22),
+    ("F23", Key::F23),
+    ("F24", Key::F24),
+    ("Space", Key::Space),
+    ("ArrowUp", Key::Up),
+    ("ArrowDown", Key::Down),
+    ("ArrowLeft", Key::Left),
+    ("ArrowRight", Key::Right),
+    ("AltLeft", Key::LAlt),
+    ("AltRight", Key::RAlt),
+    ("ControlLeft", Key::LCtrl),
+    ("ControlRight", Key::RCtrl),
+    ("MetaLeft", Key::LMeta),
+    ("MetaRight", Key::RMeta),
+    ("ShiftLeft", Key::LShift),
+    ("ShiftRight", Key::RShift),
+    ("ContextMenu", Key::Apps),
+    ("Slash", Key::OemQuestion),
+    ("Backslash", Key::OemBackslash),
+    ("BracketLeft", Key::OemOpenBrackets),
+    ("BracketRight", Key::OemCloseBrackets),
+    ("CapsLock", Key::CapsLock),
+    ("Comma", Key::OemComma),
+    ("Delete", Key::Delete),
+    ("End", Key::End),
+    ("Backspace", Key::Back),
+    ("Enter", Key::Enter),
+    ("Escape", Key::Escape),
+    ("Home", Key::Home),
+    ("Insert", Key::Insert),
+    ("Minus", Key::OemMinus),
+    ("NumLock", Key::NumLock),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    ("Pause", Key::Pause),
+    ("Period", Key::OemPeriod),
+    ("Equal", Key::OemPlus),
+    ("PrintScreen", Key::PrintScreen),
+    ("Quote", Key::OemQuotes),
+    ("ScrollLock", Key::Scroll),
+    ("Semicolon", Key::OemSemicolon),
+    ("Sleep", Key::Sleep),
+    ("Tab", Key::Tab),
+    ("Backquote", Key::OemTilde),
+    ("AudioVolumeUp", Key::VolumeUp),
+    ("AudioVolumeDown", Key::VolumeDown),
+    ("AudioVolumeMute", Key::VolumeMute),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: &str, pressed: bool, repeat: bool) -> Event {
+        Event::Key {
+            code: code.to_string(),
+            pressed,
+            repeat,
+        }
+    }
+
+    #[test]
+    fn key_translation_is_unique() {
+        let backend = WebBackend::new();
+        assert_eq!(backend.translation().len(), KEY_TRANSLATION.len());
+
+        let mut keys = Vec::new();
+        for (code, key) in KEY_TRANSLATION {
+            assert_eq!(backend.translate(code), Some(*key), "{}", code);
+            assert!(!keys.contains(key), "{:?} is mapped twice", key);
+            keys.push(*key);
+        }
+
+        // codes are case-sensitive
+        assert_eq!(backend.translate("KeyA"), Some(Key::A));
+        assert_eq!(backend.translate("keya"), None);
+        assert_eq!(backend.translate("NumpadEnter"), None);
+    }
+
+    #[test]
+    fn repeat_is_not_a_press() {
+        let mut backend = WebBackend::new();
+        let mut input = Input::new();
+        backend.on_event(&mut input, &key("Space", true, false));
+        assert!(input.kbd.is_key_pressed(Key::Space));
+        backend.on_end_frame(&mut input);
+
+        backend.on_event(&mut input, &key("Space", true, true));
+        assert!(input.kbd.is_key_down(Key::Space));
+        assert!(!input.kbd.is_key_pressed(Key::Space));
+
+        backend.on_event(&mut input, &Event::Blur);
+        assert!(!input.kbd.is_key_down(Key::Space));
+    }
+
+    #[test]
+    fn prevent_default_is_configurable() {
+        let mut backend = WebBackend::new();
+        assert!(backend.should_prevent_default("ArrowUp"));
+        assert!(!backend.should_prevent_default("KeyA"));
+
+        backend.set_prevent_default(vec!["KeyA".to_string()]);
+        assert!(!backend.should_prevent_default("ArrowUp"));
+        assert!(backend.should_prevent_default("KeyA"));
+    }
+}
@@ -0,0 +1,300 @@
+//! winit backend
+
+use std::collections::HashMap;
+
+use winit::{
+    event::{ElementState, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::{
+    backend::{DeviceKind, EventOutcome, KeyTranslation},
+    input::{keyboard::Key, pointer::MouseButton, Input},
+    Backend,
+};
+
+pub type ExternalKey = KeyCode;
+pub type Event = WindowEvent;
+
+/// [`winit`] backend
+///
+/// Keys are translated from physical key codes, so bindings don't depend on the keyboard layout.
+#[derive(Debug, Clone)]
+pub struct WinitBackend {
+    map: KeyTranslation<ExternalKey>,
+}
+
+impl Default for WinitBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WinitBackend {
+    pub fn new() -> Self {
+        Self {
+            map: KeyTranslation::from_table(KEY_TRANSLATION),
+        }
+    }
+
+    pub fn translate(&self, key: &ExternalKey) -> Option<Key> {
+        self.map.translate(key)
+    }
+
+    /// Merges the mappings over the built-in table
+    pub fn with_overrides(mut self, overrides: HashMap<ExternalKey, Key>) -> Self {
+        self.map = self.map.with_overrides(overrides);
+        self
+    }
+
+    pub fn translation(&self) -> &KeyTranslation<ExternalKey> {
+        &self.map
+    }
+
+    pub fn translation_mut(&mut self) -> &mut KeyTranslation<ExternalKey> {
+        &mut self.map
+    }
+}
+
+fn mouse_button(button: winit::event::MouseButton) -> Option<MouseButton> {
+    use winit::event::MouseButton as M;
+
+    Some(match button {
+        M::Left => MouseButton::Left,
+        M::Right => MouseButton::Right,
+        M::Middle => MouseButton::Middle,
+        M::Back => MouseButton::X1,
+        M::Forward => MouseButton::X2,
+        M::Other(_) => return None,
+    })
+}
+
+impl Backend for WinitBackend {
+    type Event = Event;
+    type Key = ExternalKey;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> EventOutcome {
+        match ev {
+            WindowEvent::KeyboardInput { event, .. } => {
+                let key = match event.physical_key {
+                    PhysicalKey::Code(code) => self.translate(&code),
+                    PhysicalKey::Unidentified(_) => None,
+                };
+
+                if let Some(key) = key {
+                    match event.state {
+                        ElementState::Pressed => input.kbd.on_key_down(key),
+                        ElementState::Released => input.kbd.on_key_up(key),
+                    }
+                }
+
+                EventOutcome::consumed(DeviceKind::Keyboard)
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                input
+                    .pointer
+                    .on_move([position.x as i32, position.y as i32]);
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                if let Some(button) = self::mouse_button(*button) {
+                    match state {
+                        ElementState::Pressed => input.on_mouse_down(button),
+                        ElementState::Released => input.on_mouse_up(button),
+                    }
+                }
+                EventOutcome::consumed(DeviceKind::Mouse)
+            }
+            WindowEvent::MouseWheel { .. } => EventOutcome::consumed(DeviceKind::Mouse),
+            // we won't receive key up events while the window is not focused
+            WindowEvent::Focused(false) => {
+                input.kbd.release_all();
+                EventOutcome::IGNORED
+            }
+            _ => EventOutcome::IGNORED,
+        }
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        // swap buffers
+        input.on_end_frame();
+    }
+}
+
+/// Every key code is mapped to a unique [`Key`]. `NumpadEnter` is not mapped since [`Key`] has
+/// only one enter key.
+pub(crate) const KEY_TRANSLATION: &[(KeyCode, Key)] = &[
+    (KeyCode::KeyA, Key::A),
+    (KeyCode::KeyB, Key::B),
+    (KeyCode::KeyC, Key::C),
+    (KeyCode::KeyD, Key::D),
+    (KeyCode::KeyE, Key::E),
+    (KeyCode::KeyF, Key::F),
+    (KeyCode::KeyG, Key::G),
+    (KeyCode::KeyH, Key::H),
+    (KeyCode::KeyI, Key::I),
+    (KeyCode::KeyJ, Key::J),
+    (KeyCode::KeyK, Key::K),
+    (KeyCode::KeyL, Key::L),
+    (KeyCode::KeyM, Key::M),
+    (KeyCode::KeyN, Key::N),
+    (KeyCode::KeyO, Key::O),
+    (KeyCode::KeyP, Key::P),
+    (KeyCode::KeyQ, Key::Q),
+    (KeyCode::KeyR, Key::R),
+    (KeyCode::KeyS, Key::S),
+    (KeyCode::KeyT, Key::T),
+    (KeyCode::KeyU, Key::U),
+    (KeyCode::KeyV, Key::V),
+    (KeyCode::KeyW, Key::W),
+    (KeyCode::KeyX, Key::X),
+    (KeyCode::KeyY, Key::Y),
+    (KeyCode::KeyZ, Key::Z),
+    (KeyCode::Digit0, Key::D0),
+    (KeyCode::Digit1, Key::D1),
+    (KeyCode::Digit2, Key::D2),
+    (KeyCode::Digit3, Key::D3),
+    (KeyCode::Digit4, Key::D4),
+    (KeyCode::Digit5, Key::D5),
+    (KeyCode::Digit6, Key::D6),
+    (KeyCode::Digit7, Key::D7),
+    (KeyCode::Digit8, Key::D8),
+    (KeyCode::Digit9, Key::D9),
+    (KeyCode::Numpad0, Key::NumPad0),
+    (KeyCode::Numpad1, Key::NumPad1),
+    (KeyCode::Numpad2, Key::NumPad2),
+    (KeyCode::Numpad3, Key::NumPad3),
+    (KeyCode::Numpad4, Key::NumPad4),
+    (KeyCode::Numpad5, Key::NumPad5),
+    (KeyCode::Numpad6, Key::NumPad6),
+    (KeyCode::Numpad7, Key::NumPad7),
+    (KeyCode::Numpad8, Key::NumPad8),
+    (KeyCode::Numpad9, Key::NumPad9),
+    (KeyCode::NumpadClear, Key::OemClear),
+    (KeyCode::NumpadDecimal, Key::Decimal),
+    (KeyCode::NumpadDivide, Key::Slash),
+    (KeyCode::NumpadSubtract, Key::Minus),
+    (KeyCode::NumpadMultiply, Key::Star),
+    (KeyCode::NumpadAdd, Key::Plus),
+    (KeyCode::F1, Key::F1),
+    (KeyCode::F2, Key::F2),
+    (KeyCode::F3, Key::F3),
+    (KeyCode::F4, Key::F4),
+    (KeyCode::F5, Key::F5),
+    (KeyCode::F6, Key::F6),
+    (KeyCode::F7, Key::F7),
+    (KeyCode::F8, Key::F8),
+    (KeyCode::F9, Key::F9),
+    (KeyCode::F10, Key::F10),
+    (KeyCode::F11, Key::F11),
+    (KeyCode::F12, Key::F12),
+    (KeyCode::F13, Key::F13),
+    (KeyCode::F14, Key::F14),
+    (KeyCode::F15, Key::F15),
+    (KeyCode::F16, Key::F16),
+    (KeyCode::F17, Key::F17),
+    (KeyCode::F18, Key::F18),
+    (KeyCode::F19, Key::F19),
+    (KeyCode::F20, Key::F20),
+    (KeyCode::F21, Key::F21),
+    (KeyCode::F22, Key::F22),
+    (KeyCode::F23, Key::F23),
+    (KeyCode::F24, Key::F24),
+    (KeyCode::Space, Key::Space),
+    (KeyCode::ArrowUp, Key::Up),
+    (KeyCode::ArrowDown, Key::Down),
+    (KeyCode::ArrowLeft, Key::Left),
+    (KeyCode::ArrowRight, Key::Right),
+    (KeyCode::AltLeft, Key::LAlt),
+    (KeyCode::AltRight, Key::RAlt),
+    (KeyCode::ControlLeft, Key::LCtrl),
+    (KeyCode::ControlRight, Key::RCtrl),
+    (KeyCode::SuperLeft, Key::LMeta),
+    (KeyCode::SuperRight, Key::RMeta),
+    (KeyCode::ShiftLeft, Key::LShift),
+    (KeyCode::ShiftRight, Key::RShift),
+    (KeyCode::ContextMenu, Key::Apps),
+    (KeyCode::Slash, Key::OemQuestion),
+    (KeyCode::Backslash, Key::OemBackslash),
+    (KeyCode::BracketLeft, Key::OemOpenBrackets),
+    (KeyCode::BracketRight, Key::OemCloseBrackets),
+    (KeyCode::CapsLock, Key::CapsLock),
+    (KeyCode::Comma, Key::OemComma),
+    (KeyCode::Delete, Key::Delete),
+    (KeyCode::End, Key::End),
+    (KeyCode::Backspace, Key::Back),
+    (KeyCode::Enter, Key::Enter),
+    (KeyCode::Escape, Key::Escape),
+    (KeyCode::Home, Key::Home),
+    (KeyCode::Insert, Key::Insert),
+    (KeyCode::Minus, Key::OemMinus),
+    (KeyCode::NumLock, Key::NumLock),
+    (KeyCode::PageUp, Key::PageUp),
+    (KeyCode::PageDown, Key::PageDown),
+    (KeyCode::Pause, Key::Pause),
+    (KeyCode::Period, Key::OemPeriod),
+    (KeyCode::Equal, Key::OemPlus),
+    (KeyCode::PrintScreen, Key::PrintScreen),
+    (KeyCode::Quote, Key::OemQuotes),
+    (KeyCode::ScrollLock, Key::Scroll),
+    (KeyCode::Semicolon, Key::OemSemicolon),
+    (KeyCode::Sleep, Key::Sleep),
+    (KeyCode::Tab, Key::Tab),
+    (KeyCode::Backquote, Key::OemTilde),
+    (KeyCode::AudioVolumeUp, Key::VolumeUp),
+    (KeyCode::AudioVolumeDown, Key::VolumeDown),
+    (KeyCode::AudioVolumeMute, Key::VolumeMute),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_translation_is_unique() {
+        let backend = WinitBackend::new();
+        assert_eq!(backend.translation().len(), KEY_TRANSLATION.len());
+
+        let mut keys = Vec::new();
+        for (code, key) in KEY_TRANSLATION {
+            assert_eq!(backend.translate(code), Some(*key), "{:?}", code);
+            assert!(!keys.contains(key), "{:?} is mapped twice", key);
+            keys.push(*key);
+        }
+        assert_eq!(backend.translate(&KeyCode::NumpadEnter), None);
+    }
+
+    #[test]
+    fn focus_lost_releases_keys() {
+        let mut backend = WinitBackend::new();
+        let mut input = Input::new();
+        input.kbd.on_key_down(Key::A);
+        backend.on_event(&mut input, &WindowEvent::Focused(false));
+        assert!(!input.kbd.is_key_down(Key::A));
+    }
+
+    #[test]
+    fn mouse_events() {
+        use winit::{dpi::PhysicalPosition, event::DeviceId};
+
+        let device_id = unsafe { DeviceId::dummy() };
+        let mut backend = WinitBackend::new();
+        let mut input = Input::new();
+        let events = [
+            WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(12.5, 4.0),
+            },
+            WindowEvent::MouseInput {
+                device_id,
+                state: ElementState::Pressed,
+                button: winit::event::MouseButton::Back,
+            },
+        ];
+        for ev in &events {
+            backend.on_event(&mut input, ev);
+        }
+        assert_eq!(input.pointer.pos(), [12, 4]);
+        assert!(input.pointer.is_pressed(MouseButton::X1));
+    }
+}
@@ -0,0 +1,865 @@
+/*!
+Bindings files: actions mapped to [`InputBundle`]s
+
+[`BindingsDoc`] is the data model shared by every format. Enable the `ron`, `toml` or `json`
+feature for the loader and saver of the format. Errors of any format are reported as
+[`BindingsError`], located by the action and the path to the broken value.
+
+Use [`BindingsLoader`] to load files saved by older versions of the game, and
+[`BindingsDoc::lint`] to explain mistakes in files edited by players.
+*/
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::vi::InputBundle;
+
+mod lint;
+pub use lint::*;
+
+/// Actions mapped to inputs, for shared and per-player actions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindingsDoc {
+    /// Version of the file, for migrations
+    pub version: u32,
+    /// Actions shared by all players
+    pub actions: BTreeMap<String, InputBundle>,
+    /// Per-player actions keyed by player name (e.g. `p1`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub players: BTreeMap<String, BTreeMap<String, InputBundle>>,
+    /// Shared actions not in the defaults of [`BindingsLoader`], kept so that saving doesn't
+    /// lose them
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub unknown: BTreeMap<String, InputBundle>,
+}
+
+impl BindingsDoc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bundle of the action for the player, falling back to the shared actions
+    pub fn bundle(&self, player: Option<&str>, action: &str) -> Option<&InputBundle> {
+        player
+            .and_then(|p| self.players.get(p))
+            .and_then(|actions| actions.get(action))
+            .or_else(|| self.actions.get(action))
+    }
+
+    /// Applies the user's bindings over the defaults. The user document may be sparse
+    ///
+    /// Shared actions not in the defaults go to [`BindingsDoc::unknown`].
+    pub fn merge(defaults: &BindingsDoc, user: &BindingsDoc) -> MergedBindings {
+        let mut current = defaults.clone();
+        for (name, bundle) in user.actions.iter().chain(&user.unknown) {
+            if current.actions.contains_key(name) {
+                current.actions.insert(name.clone(), bundle.clone());
+            } else {
+                current.unknown.insert(name.clone(), bundle.clone());
+            }
+        }
+        for (player, actions) in &user.players {
+            let section = current.players.entry(player.clone()).or_default();
+            for (name, bundle) in actions {
+                section.insert(name.clone(), bundle.clone());
+            }
+        }
+
+        MergedBindings {
+            defaults: defaults.clone(),
+            current,
+        }
+    }
+
+    /// Actions whose bundles differ, in the order of shared actions and then players
+    pub fn diff(defaults: &BindingsDoc, current: &BindingsDoc) -> Vec<ActionDiff> {
+        let mut diffs = Vec::new();
+        self::diff_actions(None, &defaults.actions, &current.actions, &mut diffs);
+
+        let empty = BTreeMap::new();
+        let players = defaults.players.keys().chain(
+            current
+                .players
+                .keys()
+                .filter(|p| !defaults.players.contains_key(*p)),
+        );
+        for player in players {
+            self::diff_actions(
+                Some(player),
+                defaults.players.get(player).unwrap_or(&empty),
+                current.players.get(player).unwrap_or(&empty),
+                &mut diffs,
+            );
+        }
+
+        diffs
+    }
+}
+
+fn diff_actions(
+    player: Option<&String>,
+    before: &BTreeMap<String, InputBundle>,
+    after: &BTreeMap<String, InputBundle>,
+    diffs: &mut Vec<ActionDiff>,
+) {
+    let names = before
+        .keys()
+        .chain(after.keys().filter(|name| !before.contains_key(*name)));
+    for name in names {
+        let (b, a) = (before.get(name), after.get(name));
+        if b != a {
+            diffs.push(ActionDiff {
+                player: player.cloned(),
+                action: name.clone(),
+                before: b.cloned(),
+                after: a.cloned(),
+            });
+        }
+    }
+}
+
+/// Action changed from the defaults. See [`BindingsDoc::diff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionDiff {
+    /// `None` for shared actions
+    pub player: Option<String>,
+    pub action: String,
+    /// `None` if added
+    pub before: Option<InputBundle>,
+    /// `None` if removed
+    pub after: Option<InputBundle>,
+}
+
+/// Where the binding of an action comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindingSource {
+    Default,
+    UserOverride,
+}
+
+/// User's bindings over the defaults, e.g. for a settings screen. See [`BindingsDoc::merge`]
+///
+/// Save [`MergedBindings::doc`] for a full file or [`MergedBindings::overrides`] for a sparse
+/// file. Either one merged over the same defaults gives back the same bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedBindings {
+    defaults: BindingsDoc,
+    current: BindingsDoc,
+}
+
+impl MergedBindings {
+    pub fn defaults(&self) -> &BindingsDoc {
+        &self.defaults
+    }
+
+    /// Full document of the current bindings
+    pub fn doc(&self) -> &BindingsDoc {
+        &self.current
+    }
+
+    /// Bundle of the action for the player, falling back to the shared actions
+    pub fn bundle(&self, player: Option<&str>, action: &str) -> Option<&InputBundle> {
+        self.current.bundle(player, action)
+    }
+
+    /// `None` if the action is not bound
+    pub fn source(&self, player: Option<&str>, action: &str) -> Option<BindingSource> {
+        let bundle = self.current.bundle(player, action)?;
+        Some(if self.defaults.bundle(player, action) == Some(bundle) {
+            BindingSource::Default
+        } else {
+            BindingSource::UserOverride
+        })
+    }
+
+    /// Binds the action for the player, or the shared action if `player` is `None`
+    pub fn set(&mut self, player: Option<&str>, action: &str, bundle: InputBundle) {
+        let actions = match player {
+            Some(p) => self.current.players.entry(p.to_string()).or_default(),
+            None => &mut self.current.actions,
+        };
+        actions.insert(action.to_string(), bundle);
+    }
+
+    /// Resets the shared action and the players' to the defaults
+    pub fn reset_action(&mut self, action: &str) {
+        match self.defaults.actions.get(action) {
+            Some(bundle) => {
+                self.current
+                    .actions
+                    .insert(action.to_string(), bundle.clone());
+            }
+            None => {
+                self.current.actions.remove(action);
+            }
+        }
+
+        for (player, actions) in &mut self.current.players {
+            match self
+                .defaults
+                .players
+                .get(player)
+                .and_then(|d| d.get(action))
+            {
+                Some(bundle) => {
+                    actions.insert(action.to_string(), bundle.clone());
+                }
+                None => {
+                    actions.remove(action);
+                }
+            }
+        }
+    }
+
+    /// Resets every action to the defaults. Unknown actions are kept
+    pub fn reset_all(&mut self) {
+        let unknown = std::mem::take(&mut self.current.unknown);
+        self.current = self.defaults.clone();
+        self.current.unknown = unknown;
+    }
+
+    /// Changes from the defaults
+    pub fn diff(&self) -> Vec<ActionDiff> {
+        BindingsDoc::diff(&self.defaults, &self.current)
+    }
+
+    /// Sparse document of the changed actions (and the unknown ones)
+    pub fn overrides(&self) -> BindingsDoc {
+        let mut doc = BindingsDoc {
+            version: self.current.version,
+            unknown: self.current.unknown.clone(),
+            ..Default::default()
+        };
+        for diff in self.diff() {
+            // removed actions fall back to the defaults
+            let bundle = match diff.after {
+                Some(bundle) => bundle,
+                None => continue,
+            };
+            let actions = match diff.player {
+                Some(p) => doc.players.entry(p).or_default(),
+                None => &mut doc.actions,
+            };
+            actions.insert(diff.action, bundle);
+        }
+        doc
+    }
+}
+
+/// File format of a [`BindingsDoc`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindingsFormat {
+    Ron,
+    Toml,
+    Json,
+}
+
+impl fmt::Display for BindingsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ron => "RON",
+            Self::Toml => "TOML",
+            Self::Json => "JSON",
+        })
+    }
+}
+
+/// Error on loading or saving a [`BindingsDoc`], in the same shape for every format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingsError {
+    /// `None` for errors after parsing, such as unsupported versions
+    pub format: Option<BindingsFormat>,
+    /// Action the broken value belongs to, if any
+    pub action: Option<String>,
+    /// Path to the broken value such as `actions.jump.keys[0]`. Empty if unknown
+    pub path: String,
+    /// 1-based line, if the format reports it
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.format {
+            Some(format) => write!(f, "{} bindings", format)?,
+            None => f.write_str("bindings")?,
+        }
+        if let Some(line) = self.line {
+            write!(f, ", line {}", line)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, ", at `{}`", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+impl BindingsError {
+    fn new(
+        format: Option<BindingsFormat>,
+        message: impl fmt::Display,
+        line: Option<usize>,
+    ) -> Self {
+        Self {
+            format,
+            action: None,
+            path: String::new(),
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    #[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+    fn at<E: fmt::Display>(
+        format: BindingsFormat,
+        err: &serde_path_to_error::Error<E>,
+        line: Option<usize>,
+    ) -> Self {
+        use serde_path_to_error::Segment;
+
+        let keys = err
+            .path()
+            .iter()
+            .map_while(|seg| match seg {
+                Segment::Map { key } => Some(key.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let action = match keys.as_slice() {
+            ["actions", action, ..] | ["players", _, action, ..] => Some(action.to_string()),
+            _ => None,
+        };
+
+        Self {
+            format: Some(format),
+            action,
+            path: err.path().to_string(),
+            line,
+            message: err.inner().to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "ron")]
+pub fn load_ron(s: &str) -> Result<BindingsDoc, BindingsError> {
+    let format = BindingsFormat::Ron;
+    let mut de = ron::Deserializer::from_str(s)
+        .map_err(|e| BindingsError::new(Some(format), &e.code, Some(e.position.line)))?;
+    let doc = serde_path_to_error::deserialize(&mut de)
+        .map_err(|e| BindingsError::at(format, &e, None))?;
+    de.end()
+        .map_err(|e| BindingsError::new(Some(format), e, None))?;
+    Ok(doc)
+}
+
+#[cfg(feature = "ron")]
+pub fn save_ron(doc: &BindingsDoc) -> Result<String, BindingsError> {
+    ron::ser::to_string_pretty(doc, ron::ser::PrettyConfig::default())
+        .map_err(|e| BindingsError::new(Some(BindingsFormat::Ron), e, None))
+}
+
+#[cfg(feature = "toml")]
+pub fn load_toml(s: &str) -> Result<BindingsDoc, BindingsError> {
+    let format = BindingsFormat::Toml;
+    serde_path_to_error::deserialize(toml::Deserializer::new(s)).map_err(|e| {
+        let line = e
+            .inner()
+            .span()
+            .map(|span| s[..span.start].matches('\n').count() + 1);
+        BindingsError::at(format, &e, line)
+    })
+}
+
+#[cfg(feature = "toml")]
+pub fn save_toml(doc: &BindingsDoc) -> Result<String, BindingsError> {
+    toml::to_string_pretty(doc).map_err(|e| BindingsError::new(Some(BindingsFormat::Toml), e, None))
+}
+
+#[cfg(feature = "json")]
+pub fn load_json(s: &str) -> Result<BindingsDoc, BindingsError> {
+    let format = BindingsFormat::Json;
+    let mut de = serde_json::Deserializer::from_str(s);
+    let doc = serde_path_to_error::deserialize(&mut de).map_err(|e| {
+        let line = Some(e.inner().line());
+        BindingsError::at(format, &e, line)
+    })?;
+    de.end()
+        .map_err(|e| BindingsError::new(Some(format), &e, Some(e.line())))?;
+    Ok(doc)
+}
+
+#[cfg(feature = "json")]
+pub fn save_json(doc: &BindingsDoc) -> Result<String, BindingsError> {
+    serde_json::to_string_pretty(doc)
+        .map_err(|e| BindingsError::new(Some(BindingsFormat::Json), e, None))
+}
+
+type Migration = Box<dyn Fn(&mut BindingsDoc)>;
+
+/// Loads bindings files saved by any older version, upgrading them with migrations
+///
+/// ```
+/// use xdl::{bindings::{BindingsDoc, BindingsLoader}, vi::InputBundle, Key};
+///
+/// let bundle = |key: Key| InputBundle::from_keys([key]);
+///
+/// let mut defaults = BindingsDoc::new();
+/// defaults.actions.insert("jump".to_string(), bundle(Key::Space));
+/// defaults.actions.insert("dash".to_string(), bundle(Key::LShift));
+///
+/// let loader = BindingsLoader::new(defaults).migration(1, |doc| {
+///     // `hop` was renamed to `jump` in version 1
+///     if let Some(b) = doc.actions.remove("hop") {
+///         doc.actions.insert("jump".to_string(), b);
+///     }
+/// });
+///
+/// let mut old = BindingsDoc::new();
+/// old.actions.insert("hop".to_string(), bundle(Key::Z));
+/// old.actions.insert("mod_action".to_string(), bundle(Key::M));
+///
+/// let doc = loader.migrate(old).unwrap();
+/// assert_eq!(doc.version, 1);
+/// assert_eq!(doc.actions["jump"], bundle(Key::Z));
+/// assert_eq!(doc.actions["dash"], bundle(Key::LShift));
+/// assert!(doc.unknown.contains_key("mod_action"));
+/// ```
+pub struct BindingsLoader {
+    defaults: BindingsDoc,
+    /// Target version and the migration, in ascending order
+    migrations: Vec<(u32, Migration)>,
+}
+
+impl fmt::Debug for BindingsLoader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BindingsLoader")
+            .field("defaults", &self.defaults)
+            .field(
+                "migrations",
+                &self.migrations.iter().map(|(v, _)| v).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl BindingsLoader {
+    pub fn new(defaults: BindingsDoc) -> Self {
+        Self {
+            defaults,
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Registers a migration that upgrades a document to `version`. Register them in ascending
+    /// order
+    pub fn migration(mut self, version: u32, f: impl Fn(&mut BindingsDoc) + 'static) -> Self {
+        assert!(
+            self.migrations.last().is_none_or(|(v, _)| *v < version),
+            "migrations must be registered in ascending order"
+        );
+        self.migrations.push((version, Box::new(f)));
+        self
+    }
+
+    pub fn defaults(&self) -> &BindingsDoc {
+        &self.defaults
+    }
+
+    /// Latest version: the last migration's or the defaults', whichever is newer
+    pub fn version(&self) -> u32 {
+        self.migrations
+            .last()
+            .map_or(self.defaults.version, |(v, _)| {
+                (*v).max(self.defaults.version)
+            })
+    }
+
+    /// Upgrades a loaded document to the latest version and merges it with the defaults
+    ///
+    /// Actions missing in the document are filled in from the defaults. Shared actions not in the
+    /// defaults are moved to [`BindingsDoc::unknown`] (and back, once they're known).
+    pub fn migrate(&self, mut doc: BindingsDoc) -> Result<BindingsDoc, BindingsError> {
+        let version = self.version();
+        if doc.version > version {
+            return Err(BindingsError::new(
+                None,
+                format!(
+                    "unsupported version {} (the latest is {})",
+                    doc.version, version
+                ),
+                None,
+            ));
+        }
+
+        for (v, f) in &self.migrations {
+            if doc.version < *v {
+                f(&mut doc);
+                doc.version = *v;
+            }
+        }
+        doc.version = version;
+
+        let mut actions = std::mem::take(&mut doc.actions);
+        for (name, bundle) in std::mem::take(&mut doc.unknown) {
+            actions.entry(name).or_insert(bundle);
+        }
+        for (name, bundle) in actions {
+            if self.defaults.actions.contains_key(&name) {
+                doc.actions.insert(name, bundle);
+            } else {
+                doc.unknown.insert(name, bundle);
+            }
+        }
+
+        for (name, bundle) in &self.defaults.actions {
+            doc.actions
+                .entry(name.clone())
+                .or_insert_with(|| bundle.clone());
+        }
+        for (player, actions) in &self.defaults.players {
+            let section = doc.players.entry(player.clone()).or_default();
+            for (name, bundle) in actions {
+                section
+                    .entry(name.clone())
+                    .or_insert_with(|| bundle.clone());
+            }
+        }
+
+        Ok(doc)
+    }
+
+    #[cfg(any(feature = "ron", feature = "toml", feature = "json"))]
+    fn migrate_as(
+        &self,
+        format: BindingsFormat,
+        doc: BindingsDoc,
+    ) -> Result<BindingsDoc, BindingsError> {
+        self.migrate(doc).map_err(|e| BindingsError {
+            format: Some(format),
+            ..e
+        })
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn load_ron(&self, s: &str) -> Result<BindingsDoc, BindingsError> {
+        self.migrate_as(BindingsFormat::Ron, self::load_ron(s)?)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn load_toml(&self, s: &str) -> Result<BindingsDoc, BindingsError> {
+        self.migrate_as(BindingsFormat::Toml, self::load_toml(s)?)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn load_json(&self, s: &str) -> Result<BindingsDoc, BindingsError> {
+        self.migrate_as(BindingsFormat::Json, self::load_json(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entries,
+        vi::{presets::bundle, KeyChord, KeyEntry},
+        Key,
+    };
+
+    fn fixture() -> BindingsDoc {
+        let mut doc = BindingsDoc::new();
+        doc.version = 2;
+        doc.actions
+            .insert("jump".to_string(), bundle(&[Key::Space]));
+        doc.actions.insert(
+            "save".to_string(),
+            InputBundle {
+                keys: entries![KeyEntry {
+                    key: Key::S,
+                    ctrl: true,
+                    shift: false,
+                    alt: false,
+                    meta: false,
+                }],
+                chords: entries![KeyChord(vec![Key::LShift, Key::F5])],
+                ..Default::default()
+            },
+        );
+        doc.players
+            .entry("p2".to_string())
+            .or_default()
+            .insert("jump".to_string(), bundle(&[Key::Up]));
+        doc.unknown
+            .insert("mod_action".to_string(), bundle(&[Key::M]));
+        doc
+    }
+
+    #[test]
+    fn player_sections_fall_back_to_shared_actions() {
+        let doc = fixture();
+        assert_eq!(doc.bundle(Some("p2"), "jump"), Some(&bundle(&[Key::Up])));
+        assert_eq!(doc.bundle(Some("p1"), "jump"), Some(&bundle(&[Key::Space])));
+        assert_eq!(doc.bundle(None, "jump"), Some(&bundle(&[Key::Space])));
+        assert_eq!(doc.bundle(Some("p2"), "save"), doc.actions.get("save"));
+        // unknown actions are not bound
+        assert_eq!(doc.bundle(None, "mod_action"), None);
+    }
+
+    /// Version 2 renamed `hop` to `jump`, and version 3 split `attack` out of `jump`
+    fn loader() -> BindingsLoader {
+        let mut defaults = BindingsDoc::new();
+        defaults.version = 3;
+        defaults
+            .actions
+            .insert("jump".to_string(), bundle(&[Key::Space]));
+        defaults
+            .actions
+            .insert("attack".to_string(), bundle(&[Key::X]));
+        defaults
+            .actions
+            .insert("dash".to_string(), bundle(&[Key::LShift]));
+
+        BindingsLoader::new(defaults)
+            .migration(2, |doc| {
+                if let Some(b) = doc.actions.remove("hop") {
+                    doc.actions.insert("jump".to_string(), b);
+                }
+            })
+            .migration(3, |doc| {
+                if let Some(b) = doc.actions.get_mut("jump") {
+                    if let Some(i) = b.keys.iter().position(|e| e.key == Key::Z) {
+                        let attack = b.keys.remove(i);
+                        doc.actions.insert(
+                            "attack".to_string(),
+                            InputBundle {
+                                keys: entries![attack],
+                                ..Default::default()
+                            },
+                        );
+                    }
+                }
+            })
+    }
+
+    #[test]
+    fn migrations_run_in_order() {
+        let loader = loader();
+
+        let mut v1 = BindingsDoc::new();
+        v1.version = 1;
+        v1.actions.insert(
+            "hop".to_string(),
+            InputBundle {
+                keys: entries![Key::W.into(), Key::Z.into()],
+                ..Default::default()
+            },
+        );
+        v1.actions
+            .insert("mod_action".to_string(), bundle(&[Key::M]));
+        let doc = loader.migrate(v1).unwrap();
+        assert_eq!(doc.version, 3);
+        assert_eq!(doc.actions["jump"], bundle(&[Key::W]));
+        assert_eq!(doc.actions["attack"], bundle(&[Key::Z]));
+        // filled in from the defaults
+        assert_eq!(doc.actions["dash"], bundle(&[Key::LShift]));
+        assert!(!doc.actions.contains_key("hop"));
+        assert_eq!(doc.unknown["mod_action"], bundle(&[Key::M]));
+
+        // the v2 file skips the rename
+        let mut v2 = BindingsDoc::new();
+        v2.version = 2;
+        v2.actions.insert("hop".to_string(), bundle(&[Key::H]));
+        v2.actions.insert("jump".to_string(), bundle(&[Key::Z]));
+        let doc = loader.migrate(v2).unwrap();
+        assert_eq!(doc.actions["attack"], bundle(&[Key::Z]));
+        assert_eq!(doc.actions["jump"], InputBundle::default());
+        assert_eq!(doc.unknown["hop"], bundle(&[Key::H]));
+
+        // saving and loading again keeps the unknown actions
+        assert_eq!(loader.migrate(doc.clone()).unwrap(), doc);
+    }
+
+    #[test]
+    fn future_versions_are_rejected() {
+        let mut doc = BindingsDoc::new();
+        doc.version = 4;
+        let err = loader().migrate(doc).unwrap_err();
+        assert_eq!((err.format, err.action), (None, None));
+        assert_eq!(err.message, "unsupported version 4 (the latest is 3)");
+    }
+
+    #[test]
+    #[cfg(feature = "ron")]
+    fn load_fixtures_through_the_chain() {
+        let v1 = r#"(
+    version: 1,
+    actions: {
+        "hop": (keys: [(key: W), (key: Z)]),
+        "mod_action": (keys: [(key: M)]),
+    },
+)"#;
+        let v2 = r#"(
+    version: 2,
+    actions: {
+        "jump": (keys: [(key: W)]),
+        "attack": (keys: [(key: Z)]),
+    },
+    unknown: {
+        "mod_action": (keys: [(key: M)]),
+    },
+)"#;
+        let loader = loader();
+        let (v1, v2) = (loader.load_ron(v1).unwrap(), loader.load_ron(v2).unwrap());
+        assert_eq!(v1, v2);
+        assert_eq!(v1.version, 3);
+        assert_eq!(v1.actions.len(), 3);
+
+        let err = loader.load_ron("(version: 9)").unwrap_err();
+        assert_eq!(err.format, Some(BindingsFormat::Ron));
+    }
+
+    fn defaults() -> BindingsDoc {
+        let mut doc = BindingsDoc::new();
+        doc.actions
+            .insert("jump".to_string(), bundle(&[Key::Space]));
+        doc.actions
+            .insert("dash".to_string(), bundle(&[Key::LShift]));
+        doc.actions
+            .insert("menu".to_string(), bundle(&[Key::Escape]));
+        doc.players
+            .entry("p2".to_string())
+            .or_default()
+            .insert("jump".to_string(), bundle(&[Key::Up]));
+        doc
+    }
+
+    #[test]
+    fn sparse_save_reloads_over_defaults() {
+        let defaults = self::defaults();
+        let mut merged = BindingsDoc::merge(&defaults, &BindingsDoc::new());
+        assert!(merged.diff().is_empty());
+
+        merged.set(None, "jump", bundle(&[Key::Z]));
+        merged.set(Some("p2"), "jump", bundle(&[Key::W]));
+        assert_eq!(
+            merged.source(None, "jump"),
+            Some(BindingSource::UserOverride)
+        );
+        assert_eq!(merged.source(None, "dash"), Some(BindingSource::Default));
+        assert_eq!(merged.source(None, "nope"), None);
+        assert_eq!(
+            merged.diff(),
+            vec![
+                ActionDiff {
+                    player: None,
+                    action: "jump".to_string(),
+                    before: Some(bundle(&[Key::Space])),
+                    after: Some(bundle(&[Key::Z])),
+                },
+                ActionDiff {
+                    player: Some("p2".to_string()),
+                    action: "jump".to_string(),
+                    before: Some(bundle(&[Key::Up])),
+                    after: Some(bundle(&[Key::W])),
+                },
+            ]
+        );
+
+        // the sparse file has only the two overrides
+        let sparse = merged.overrides();
+        assert_eq!(sparse.actions.len(), 1);
+        assert_eq!(sparse.players["p2"].len(), 1);
+        let reloaded = BindingsDoc::merge(&defaults, &sparse);
+        assert_eq!(reloaded.doc(), merged.doc());
+        // so does the full file
+        assert_eq!(
+            BindingsDoc::merge(&defaults, merged.doc()).doc(),
+            merged.doc()
+        );
+
+        #[cfg(feature = "ron")]
+        {
+            let sparse = load_ron(&save_ron(&sparse).unwrap()).unwrap();
+            assert_eq!(BindingsDoc::merge(&defaults, &sparse), reloaded);
+        }
+    }
+
+    #[test]
+    fn reset_actions() {
+        let defaults = self::defaults();
+        let mut user = BindingsDoc::new();
+        user.actions.insert("jump".to_string(), bundle(&[Key::Z]));
+        user.actions.insert("dash".to_string(), bundle(&[Key::X]));
+        user.actions
+            .insert("mod_action".to_string(), bundle(&[Key::M]));
+        user.players
+            .entry("p2".to_string())
+            .or_default()
+            .insert("jump".to_string(), bundle(&[Key::W]));
+        let mut merged = BindingsDoc::merge(&defaults, &user);
+        assert_eq!(merged.doc().unknown["mod_action"], bundle(&[Key::M]));
+        assert_eq!(merged.diff().len(), 3);
+
+        // resets the shared action and the players'
+        merged.reset_action("jump");
+        assert_eq!(merged.source(None, "jump"), Some(BindingSource::Default));
+        assert_eq!(
+            merged.source(Some("p2"), "jump"),
+            Some(BindingSource::Default)
+        );
+        assert_eq!(merged.diff().len(), 1);
+
+        merged.reset_all();
+        assert!(merged.diff().is_empty());
+        assert_eq!(merged.doc().unknown["mod_action"], bundle(&[Key::M]));
+    }
+
+    #[test]
+    #[cfg(feature = "ron")]
+    fn ron_round_trip() {
+        let doc = fixture();
+        assert_eq!(load_ron(&save_ron(&doc).unwrap()).unwrap(), doc);
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn toml_round_trip() {
+        let doc = fixture();
+        assert_eq!(load_toml(&save_toml(&doc).unwrap()).unwrap(), doc);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_round_trip() {
+        let doc = fixture();
+        assert_eq!(load_json(&save_json(&doc).unwrap()).unwrap(), doc);
+    }
+
+    #[test]
+    #[cfg(all(feature = "ron", feature = "toml", feature = "json"))]
+    fn errors_are_located_uniformly() {
+        let errors = [
+            (
+                BindingsFormat::Ron,
+                load_ron("(actions: {\"jump\": (keys: [(key: Nope)])})"),
+            ),
+            (
+                BindingsFormat::Toml,
+                load_toml("[[actions.jump.keys]]\nkey = \"Nope\"\n"),
+            ),
+            (
+                BindingsFormat::Json,
+                load_json(r#"{"actions": {"jump": {"keys": [{"key": "Nope"}]}}}"#),
+            ),
+        ];
+        for (format, result) in errors {
+            let err = result.unwrap_err();
+            assert_eq!(err.format, Some(format));
+            assert_eq!(err.action.as_deref(), Some("jump"), "{}", err);
+            assert_eq!(err.path, "actions.jump.keys[0].key", "{}", err);
+        }
+    }
+}
@@ -0,0 +1,482 @@
+//! Re-exported to super module
+
+use std::fmt;
+
+use crate::{
+    bindings::BindingsDoc,
+    vi::{InputBundle, KeyChord},
+    Key,
+};
+
+/// What [`BindingsDoc::lint_with`] knows about the game
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Keys the backend can produce. `None` if any key can be
+    pub supported_keys: Option<Vec<Key>>,
+    /// Actions that repeat while held (e.g. with [`KeyRepeatConfig::Repeat`])
+    ///
+    /// [`KeyRepeatConfig::Repeat`]: crate::vi::KeyRepeatConfig::Repeat
+    pub repeating_actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// Likely a typo or a leftover; the bindings still work
+    Warning,
+    /// The entry can never be triggered
+    Error,
+}
+
+/// Entry of an [`InputBundle`] a [`Diagnostic`] points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BundleEntry {
+    /// Index into [`InputBundle::keys`]
+    Key(usize),
+    /// Index into [`InputBundle::chords`]
+    Chord(usize),
+    /// Index into [`InputBundle::raw`]
+    Raw(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The same input triggers another action in the same context
+    Duplicate { other: String },
+    /// The action has no input
+    EmptyBundle,
+    /// The backend can't produce the key
+    UnsupportedKey(Key),
+    /// The modifier key is also given as a modifier flag (e.g. `Ctrl+LCtrl`)
+    RedundantModifier(Key),
+    /// `Escape` repeats while held, which tends to close menus in a row
+    RepeatingEscape,
+}
+
+/// Problem found by [`BindingsDoc::lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// `None` for shared actions
+    pub player: Option<String>,
+    pub action: String,
+    /// `None` if it's about the whole bundle
+    pub entry: Option<BundleEntry>,
+    /// How to fix it, to be shown to the player
+    pub suggestion: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: ", severity)?;
+        if let Some(player) = &self.player {
+            write!(f, "{}.", player)?;
+        }
+        write!(f, "{}: ", self.action)?;
+
+        match &self.kind {
+            DiagnosticKind::Duplicate { other } => write!(f, "also bound to `{}`", other)?,
+            DiagnosticKind::EmptyBundle => f.write_str("no input is bound")?,
+            DiagnosticKind::UnsupportedKey(key) => write!(f, "`{}` is not supported", key)?,
+            DiagnosticKind::RedundantModifier(key) => {
+                write!(f, "`{}` is both the key and a modifier", key)?
+            }
+            DiagnosticKind::RepeatingEscape => f.write_str("`Escape` repeats while held")?,
+        }
+
+        write!(f, " ({})", self.suggestion)
+    }
+}
+
+/// Lints
+impl BindingsDoc {
+    /// [`BindingsDoc::lint_with`] without knowledge of the backend or the actions
+    pub fn lint(&self) -> Vec<Diagnostic> {
+        self.lint_with(&LintOptions::default())
+    }
+
+    /// Finds mistakes in the bindings, in the order of shared actions and then players
+    ///
+    /// Duplicates are reported on the latter action. A player's context is the player's
+    /// actions over the shared actions.
+    pub fn lint_with(&self, opts: &LintOptions) -> Vec<Diagnostic> {
+        let mut lint = Lint {
+            opts,
+            player: None,
+            out: Vec::new(),
+        };
+
+        let shared: Vec<_> = self.actions.iter().map(|(a, b)| (a, b, true)).collect();
+        lint.section(&shared);
+
+        for (player, actions) in &self.players {
+            lint.player = Some(player);
+            // only inputs involving the player's own actions are reported
+            let mut context: Vec<_> = actions.iter().map(|(a, b)| (a, b, true)).collect();
+            context.extend(
+                self.actions
+                    .iter()
+                    .filter(|(a, _)| !actions.contains_key(*a))
+                    .map(|(a, b)| (a, b, false)),
+            );
+            lint.section(&context);
+        }
+
+        lint.out
+    }
+}
+
+struct Lint<'a> {
+    opts: &'a LintOptions,
+    player: Option<&'a String>,
+    out: Vec<Diagnostic>,
+}
+
+impl<'a> Lint<'a> {
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: DiagnosticKind,
+        action: &str,
+        entry: Option<BundleEntry>,
+        suggestion: String,
+    ) {
+        self.out.push(Diagnostic {
+            severity,
+            kind,
+            player: self.player.cloned(),
+            action: action.to_string(),
+            entry,
+            suggestion,
+        });
+    }
+
+    /// Lints the actions of one context. Actions with `false` are only used for duplicates
+    fn section(&mut self, actions: &[(&String, &InputBundle, bool)]) {
+        for (i, &(action, bundle, own)) in actions.iter().enumerate() {
+            if !own {
+                continue;
+            }
+            self.bundle(action, bundle);
+
+            // pairs of own actions are reported once. Shared actions come last in a player's
+            // context
+            for (j, &(other, other_bundle, other_own)) in actions.iter().enumerate() {
+                if j < i || (j > i && !other_own) {
+                    self.duplicates(action, bundle, other, other_bundle);
+                }
+            }
+        }
+    }
+
+    fn bundle(&mut self, action: &str, bundle: &InputBundle) {
+        if bundle.keys.is_empty()
+            && bundle.chords.is_empty()
+            && bundle.raw.is_empty()
+            && bundle.custom.is_empty()
+        {
+            self.push(
+                Severity::Warning,
+                DiagnosticKind::EmptyBundle,
+                action,
+                None,
+                "bind a key or remove the action".to_string(),
+            );
+        }
+
+        for (i, entry) in bundle.keys.iter().enumerate() {
+            let at = Some(BundleEntry::Key(i));
+            self.key(action, entry.key, at);
+
+            let redundant = (entry.ctrl && matches!(entry.key, Key::LCtrl | Key::RCtrl))
+                || (entry.shift && matches!(entry.key, Key::LShift | Key::RShift))
+                || (entry.alt && matches!(entry.key, Key::LAlt | Key::RAlt))
+                || (entry.meta && matches!(entry.key, Key::LMeta | Key::RMeta));
+            if redundant {
+                self.push(
+                    Severity::Warning,
+                    DiagnosticKind::RedundantModifier(entry.key),
+                    action,
+                    at,
+                    format!("remove the modifier flag and bind `{}` alone", entry.key),
+                );
+            }
+
+            if entry.key == Key::Escape && self.is_repeating(action) {
+                self.push(
+                    Severity::Warning,
+                    DiagnosticKind::RepeatingEscape,
+                    action,
+                    at,
+                    "bind `Escape` to an action without key repeat".to_string(),
+                );
+            }
+        }
+
+        for (i, chord) in bundle.chords.iter().enumerate() {
+            for &key in &chord.0 {
+                self.key(action, key, Some(BundleEntry::Chord(i)));
+            }
+        }
+    }
+
+    fn key(&mut self, action: &str, key: Key, at: Option<BundleEntry>) {
+        let supported = match &self.opts.supported_keys {
+            Some(keys) => keys.contains(&key),
+            None => true,
+        };
+        if !supported {
+            self.push(
+                Severity::Error,
+                DiagnosticKind::UnsupportedKey(key),
+                action,
+                at,
+                format!(
+                    "`{}` can't be pressed on this platform; bind another key",
+                    key
+                ),
+            );
+        }
+    }
+
+    fn is_repeating(&self, action: &str) -> bool {
+        self.opts.repeating_actions.iter().any(|a| a == action)
+    }
+
+    /// Reports the entries of `bundle` also found in `other_bundle`
+    fn duplicates(
+        &mut self,
+        action: &str,
+        bundle: &InputBundle,
+        other: &str,
+        other_bundle: &InputBundle,
+    ) {
+        let mut found = Vec::new();
+        for (i, entry) in bundle.keys.iter().enumerate() {
+            if other_bundle.keys.contains(entry) {
+                found.push(BundleEntry::Key(i));
+            }
+        }
+        for (i, chord) in bundle.chords.iter().enumerate() {
+            if other_bundle
+                .chords
+                .iter()
+                .any(|c| self::same_chord(c, chord))
+            {
+                found.push(BundleEntry::Chord(i));
+            }
+        }
+        for (i, raw) in bundle.raw.iter().enumerate() {
+            if other_bundle.raw.contains(raw) {
+                found.push(BundleEntry::Raw(i));
+            }
+        }
+
+        for at in found {
+            self.push(
+                Severity::Warning,
+                DiagnosticKind::Duplicate {
+                    other: other.to_string(),
+                },
+                action,
+                Some(at),
+                format!("unbind it from `{}` or `{}`", other, action),
+            );
+        }
+    }
+}
+
+/// Chords are unordered
+fn same_chord(a: &KeyChord, b: &KeyChord) -> bool {
+    a.0.len() == b.0.len() && a.0.iter().all(|k| b.0.contains(k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entries, vi::KeyEntry};
+
+    fn bundle(keys: &[Key]) -> InputBundle {
+        InputBundle {
+            keys: keys.iter().map(|&key| key.into()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn doc(actions: Vec<(&str, InputBundle)>) -> BindingsDoc {
+        let mut doc = BindingsDoc::new();
+        for (action, bundle) in actions {
+            doc.actions.insert(action.to_string(), bundle);
+        }
+        doc
+    }
+
+    #[test]
+    fn clean_bindings() {
+        let doc = self::doc(vec![
+            ("jump", bundle(&[Key::Space])),
+            ("dash", bundle(&[Key::LShift])),
+        ]);
+        assert_eq!(doc.lint(), vec![]);
+    }
+
+    #[test]
+    fn duplicate() {
+        let doc = self::doc(vec![
+            ("attack", bundle(&[Key::X, Key::Z])),
+            ("jump", bundle(&[Key::Z])),
+        ]);
+        assert_eq!(
+            doc.lint(),
+            vec![Diagnostic {
+                severity: Severity::Warning,
+                kind: DiagnosticKind::Duplicate {
+                    other: "attack".to_string(),
+                },
+                player: None,
+                action: "jump".to_string(),
+                entry: Some(BundleEntry::Key(0)),
+                suggestion: "unbind it from `attack` or `jump`".to_string(),
+            }]
+        );
+
+        // chords are unordered
+        let chord = |keys: &[Key]| InputBundle {
+            chords: entries![KeyChord(keys.to_vec())],
+            ..Default::default()
+        };
+        let doc = self::doc(vec![
+            ("a", chord(&[Key::A, Key::S])),
+            ("b", chord(&[Key::S, Key::A])),
+        ]);
+        let diags = doc.lint();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].entry, Some(BundleEntry::Chord(0)));
+    }
+
+    #[test]
+    fn duplicate_in_player_context() {
+        let mut doc = self::doc(vec![("menu", bundle(&[Key::Escape]))]);
+        doc.players
+            .entry("p2".to_string())
+            .or_default()
+            .insert("pause".to_string(), bundle(&[Key::Escape]));
+        let diags = doc.lint();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].player.as_deref(), Some("p2"));
+        assert_eq!(diags[0].action, "pause");
+        assert_eq!(
+            diags[0].to_string(),
+            "warning: p2.pause: also bound to `menu` (unbind it from `menu` or `pause`)"
+        );
+    }
+
+    #[test]
+    fn empty_bundle() {
+        let doc = self::doc(vec![("jump", InputBundle::default())]);
+        let diags = doc.lint();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            (&diags[0].kind, diags[0].entry),
+            (&DiagnosticKind::EmptyBundle, None)
+        );
+        assert_eq!(
+            diags[0].to_string(),
+            "warning: jump: no input is bound (bind a key or remove the action)"
+        );
+    }
+
+    #[test]
+    fn unsupported_key() {
+        let doc = self::doc(vec![
+            ("jump", bundle(&[Key::Space, Key::F13])),
+            (
+                "save",
+                InputBundle {
+                    chords: entries![KeyChord(vec![Key::LCtrl, Key::F13])],
+                    ..Default::default()
+                },
+            ),
+        ]);
+        // any key is supported by default
+        assert_eq!(doc.lint(), vec![]);
+
+        let opts = LintOptions {
+            supported_keys: Some(vec![Key::Space, Key::LCtrl]),
+            ..Default::default()
+        };
+        let diags = doc.lint_with(&opts);
+        assert_eq!(
+            diags
+                .iter()
+                .map(|d| (d.severity, d.kind.clone(), d.action.as_str(), d.entry))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Severity::Error,
+                    DiagnosticKind::UnsupportedKey(Key::F13),
+                    "jump",
+                    Some(BundleEntry::Key(1))
+                ),
+                (
+                    Severity::Error,
+                    DiagnosticKind::UnsupportedKey(Key::F13),
+                    "save",
+                    Some(BundleEntry::Chord(0))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn redundant_modifier() {
+        let entry = KeyEntry {
+            key: Key::LCtrl,
+            ctrl: true,
+            shift: false,
+            alt: false,
+            meta: false,
+        };
+        let doc = self::doc(vec![(
+            "crouch",
+            InputBundle {
+                keys: entries![Key::C.into(), entry],
+                ..Default::default()
+            },
+        )]);
+        let diags = doc.lint();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            (&diags[0].kind, diags[0].entry),
+            (
+                &DiagnosticKind::RedundantModifier(Key::LCtrl),
+                Some(BundleEntry::Key(1))
+            )
+        );
+        assert_eq!(
+            diags[0].suggestion,
+            "remove the modifier flag and bind `LCtrl` alone"
+        );
+    }
+
+    #[test]
+    fn repeating_escape() {
+        let doc = self::doc(vec![("back", bundle(&[Key::Back, Key::Escape]))]);
+        assert_eq!(doc.lint(), vec![]);
+
+        let opts = LintOptions {
+            repeating_actions: vec!["back".to_string()],
+            ..Default::default()
+        };
+        let diags = doc.lint_with(&opts);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(
+            (&diags[0].kind, diags[0].entry),
+            (&DiagnosticKind::RepeatingEscape, Some(BundleEntry::Key(1)))
+        );
+    }
+}
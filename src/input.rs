@@ -1,27 +1,1488 @@
 #![allow(dead_code)]
 
+pub mod any;
+pub mod device;
+pub mod event;
+pub mod history;
 pub mod keyboard;
+pub mod remap;
 // pub mod mouse;
+pub mod pointer;
+pub mod shared;
+pub mod stats;
+pub mod text;
+pub mod threaded;
 
-use self::keyboard::Keyboard;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use self::{
+    any::{AnyInput, AnyInputFilter},
+    device::{Device, Devices},
+    event::XdlEvent,
+    history::KeyboardHistory,
+    keyboard::{
+        FreezeMode, Key, KeyState, Keyboard, KeyboardState, KeyboardStateSnapshot, KeyboardView,
+        RawKey,
+    },
+    pointer::{MouseButton, Pointer},
+    stats::InputStats,
+    text::{TextInput, TextState},
+};
+
+use crate::{
+    backend::DeviceKind,
+    utils::{BitSet256, Fnv1a},
+    vi::ActionMap,
+};
 
 /// All of the input states
+///
+/// # Frame protocol
+///
+/// A frame is: [`Input::begin_frame`], events fed by the backend, queries, and then
+/// [`Input::on_end_frame`]. Pressed and released states compare the current frame with the
+/// previous one, so queries after `on_end_frame` see nothing pressed.
+///
+/// Events applied outside of the window (after `on_end_frame` and before the next
+/// `begin_frame`) belong to the next frame; they're not lost. Once `begin_frame` is called (or
+/// [`InputBuilder::check_frames`] is set), debug builds panic on misuse such as ending a frame
+/// twice or querying [`Input`] out of a frame. Queries made through the `kbd` field directly are
+/// not checked.
+///
+/// With the `serde` feature, the device states can be saved mid-frame and restored: every query
+/// on the deserialized `Input` matches the original, except for the key history
+/// ([`Input::enable_history`]) and user-defined devices ([`Input::register_device`]), which are
+/// not saved. Missing fields fall back to defaults, so snapshots from older versions still load.
+/// Backend-side states (key translations) are not included either.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Input {
     pub kbd: Keyboard,
-    // pub mouse: Mouse,
+    pub pointer: Pointer,
+    pub text: TextInput,
+    /// Number of `on_end_frame` calls
+    pub(crate) end_frames: u64,
+    /// Last `dt` passed to [`Input::tick`]
+    dt: Duration,
+    /// Sum of `dt` passed to [`Input::tick`]
+    elapsed: Duration,
+    /// Time since the last press or release, advanced by [`Input::tick`]
+    idle: Duration,
+    /// Cursor position at the last pointer activity
+    idle_anchor: [i32; 2],
+    /// Cursor motion within the distance (in pixels) from the anchor is not an activity
+    idle_motion_threshold: u32,
+    /// `(backend time, elapsed time)` at the first timestamped event
+    clock_origin: Option<(Duration, Duration)>,
+    /// Collected only if enabled
+    stats: Option<Box<InputStats>>,
+    freeze_mode: FreezeMode,
+    /// User-defined devices
+    #[cfg_attr(feature = "serde", serde(skip))]
+    devices: Devices,
+    /// Recorded only if enabled
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: Option<KeyboardHistory>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frame_phase: FramePhase,
+    /// Set by a UI layer that has pointer focus
+    pointer_captured: bool,
+}
+
+/// Where an [`Input`] is in the frame protocol, tracked to catch misuse in debug builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum FramePhase {
+    /// [`Input::begin_frame`] was never called. Nothing is checked
+    #[default]
+    Unchecked,
+    /// Checked, but no frame has begun yet
+    NotStarted,
+    /// Between [`Input::begin_frame`] and [`Input::on_end_frame`]
+    Open,
+    /// After [`Input::on_end_frame`]. Events applied now belong to the next frame
+    Ended,
 }
 
 impl Input {
+    /// Input with all the devices enabled
     pub fn new() -> Self {
         Self {
             kbd: Keyboard::default(),
+            pointer: Pointer::default(),
+            text: TextInput::default(),
+            end_frames: 0,
+            dt: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            idle: Duration::ZERO,
+            idle_anchor: [0, 0],
+            idle_motion_threshold: 0,
+            clock_origin: None,
+            stats: None,
+            freeze_mode: FreezeMode::Hold,
+            devices: Devices::default(),
+            history: None,
+            frame_phase: FramePhase::Unchecked,
+            pointer_captured: false,
+        }
+    }
+
+    /// Builds an [`Input`] with some devices disabled
+    pub fn builder() -> InputBuilder {
+        InputBuilder::default()
+    }
+
+    /// Advances time-dependent states such as [`Keyboard::key_down_duration`]
+    ///
+    /// Call it once per frame after the frame's events are applied. Update `vi` objects with
+    /// the same `dt` (e.g. via [`InputSystem::tick`]), or their repeat timers drift from the
+    /// input's clock.
+    ///
+    /// [`InputSystem::tick`]: crate::vi::InputSystem::tick
+    pub fn tick(&mut self, dt: Duration) {
+        if self.is_frozen() {
+            // stop the timers
+            self.dt = Duration::ZERO;
+            return;
+        }
+
+        self.dt = dt;
+        self.elapsed += dt;
+        self.kbd.clock = self.elapsed;
+        self.kbd.tick(dt);
+
+        if self.kbd.has_changed() || self.has_pointer_activity() {
+            self.idle = Duration::ZERO;
+            self.idle_anchor = self.pointer.pos();
+        } else {
+            self.idle += dt;
+        }
+
+        if let Some(stats) = &mut self.stats {
+            stats.tick(&self.kbd, dt);
+        }
+    }
+
+    /// Index of the current frame: the number of [`Input::on_end_frame`] calls
+    pub fn frame(&self) -> u64 {
+        self.end_frames
+    }
+
+    /// Events on the current frame ([`Input::frame`]) in order. Cleared on
+    /// [`Input::on_end_frame`]
+    pub fn events(&self) -> &[XdlEvent] {
+        self.check_query();
+        self.kbd.events()
+    }
+
+    /// Hash of the device states as seen by queries, stable across platforms and builds
+    ///
+    /// Covers the keyboard, the pointer and text input as they look to queries (frozen, captured
+    /// or swallowed keys included). Two inputs with the same fingerprint answer queries the same
+    /// way (with high probability). Custom devices are not included.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+        for &device in &[DeviceKind::Keyboard, DeviceKind::Mouse, DeviceKind::Text] {
+            hash.write_u64(self.device_fingerprint(device));
+        }
+        hash.finish()
+    }
+
+    /// Hash of the state of one kind of device. See [`Input::fingerprint`]
+    ///
+    /// Custom devices ([`DeviceKind::Gamepad`]) are not tracked, so their hash is a constant.
+    pub fn device_fingerprint(&self, device: DeviceKind) -> u64 {
+        let mut hash = Fnv1a::new();
+        match device {
+            DeviceKind::Keyboard => {
+                let keys = [self.kbd.keys_down_bits(), self.kbd.keys_down_prev_bits()];
+                for bits in keys.iter().flat_map(|bits| bits.words().iter()) {
+                    hash.write_u32(*bits);
+                }
+                for raw in [self.kbd.raw_keys_down(), self.kbd.raw_keys_down_prev()].iter() {
+                    hash.write_u32(raw.len() as u32);
+                    for key in raw.iter() {
+                        hash.write_u32(key.0);
+                    }
+                }
+            }
+            DeviceKind::Mouse => {
+                hash.write_u32(self.pointer_captured as u32);
+                for [x, y] in [self.pointer.pos(), self.pointer.prev_pos()] {
+                    hash.write_u32(x as u32);
+                    hash.write_u32(y as u32);
+                }
+                for &button in MouseButton::ALL.iter() {
+                    let bits = self.pointer.is_down(button) as u32
+                        | (self.pointer.was_down(button) as u32) << 1;
+                    hash.write_u32(bits);
+                }
+            }
+            DeviceKind::Text => {
+                hash.write_u32(self.text.is_active() as u32);
+                hash.write_str(self.text.text());
+                match self.text.composition() {
+                    Some(c) => {
+                        hash.write_u32(1);
+                        hash.write_str(&c.text);
+                        hash.write_u32(c.start as u32);
+                        hash.write_u32(c.length as u32);
+                    }
+                    None => hash.write_u32(0),
+                }
+            }
+            DeviceKind::Gamepad => {}
+        }
+        hash.finish()
+    }
+
+    /// Timestamps the events applied next with the backend's clock (e.g. SDL's milliseconds since
+    /// init)
+    ///
+    /// Call it from [`Backend::on_event`] before applying the event. It holds until the next call
+    /// or the end of the frame. The backend's clock is mapped onto [`Input::elapsed`], aligned at
+    /// the first call; see [`XdlEvent::timestamp`].
+    ///
+    /// [`Backend::on_event`]: crate::Backend::on_event
+    pub fn set_event_timestamp(&mut self, backend_time: Duration) {
+        let (origin, at) = *self
+            .clock_origin
+            .get_or_insert((backend_time, self.elapsed));
+        self.kbd.stamp = Some(if backend_time >= origin {
+            at + (backend_time - origin)
+        } else {
+            at.saturating_sub(origin - backend_time)
+        });
+    }
+
+    /// Keyboard that sees only the keys, e.g. one player's half of a split keyboard
+    pub fn keyboard_view<'a>(&'a self, allowed: &'a [Key]) -> KeyboardView<'a> {
+        self.check_query();
+        KeyboardView::new(&self.kbd, allowed)
+    }
+
+    /// Time since the last key press or release, mouse button press or release, or cursor motion,
+    /// as of the last [`Input::tick`]
+    pub fn idle_time(&self) -> Duration {
+        self.idle
+    }
+
+    /// Cursor motion within the distance in pixels is ignored by [`Input::idle_time`], so that
+    /// sensor noise doesn't reset it. Motion accumulates until it goes beyond the distance.
+    /// Defaults to zero (any motion counts)
+    pub fn set_idle_motion_threshold(&mut self, pixels: u32) {
+        self.idle_motion_threshold = pixels;
+    }
+
+    pub fn idle_motion_threshold(&self) -> u32 {
+        self.idle_motion_threshold
+    }
+
+    fn has_pointer_activity(&self) -> bool {
+        let [x, y] = self.pointer.pos();
+        let [ax, ay] = self.idle_anchor;
+        let (dx, dy) = (x as i64 - ax as i64, y as i64 - ay as i64);
+        let threshold = self.idle_motion_threshold as i64;
+        // the squares of the widened distances can still overflow
+        let dist2 = dx.saturating_mul(dx).saturating_add(dy.saturating_mul(dy));
+        self.pointer.has_button_changed() || dist2 > threshold.saturating_mul(threshold)
+    }
+
+    /// `dt` of the last [`Input::tick`]
+    pub fn dt(&self) -> Duration {
+        self.dt
+    }
+
+    /// Total time advanced by [`Input::tick`]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Starts a frame. See the [frame protocol](Input#frame-protocol)
+    ///
+    /// It enables the frame protocol checks of debug builds. Debug builds panic if the previous
+    /// frame was not ended.
+    pub fn begin_frame(&mut self) {
+        debug_assert!(
+            self.frame_phase != FramePhase::Open,
+            "`Input::begin_frame` called twice; call `Input::on_end_frame` to end the previous frame"
+        );
+        self.frame_phase = FramePhase::Open;
+    }
+
+    /// Swaps the buffers of all the devices
+    ///
+    /// Once the frame protocol is checked, debug builds panic unless a frame was begun.
+    pub fn on_end_frame(&mut self) {
+        debug_assert!(
+            self.frame_phase != FramePhase::Ended,
+            "`Input::on_end_frame` called twice; call `Input::begin_frame` to start the next frame"
+        );
+        debug_assert!(
+            self.frame_phase != FramePhase::NotStarted,
+            "`Input::on_end_frame` called before any frame; call `Input::begin_frame` first"
+        );
+        if self.frame_phase != FramePhase::Unchecked {
+            self.frame_phase = FramePhase::Ended;
         }
+
+        if let Some(history) = &mut self.history {
+            history.push(self.kbd.cur().clone());
+        }
+        self.kbd.on_end_frame();
+        self.pointer.on_end_frame();
+        self.text.on_end_frame();
+        self.devices.on_end_frame();
+        self.end_frames += 1;
     }
 
-    /// Resets all the states
+    /// Resets all the device states and the history. The frame counter and elapsed time are kept
     pub fn clear(&mut self) {
         self.kbd.clear();
-        // self.mouse.clear();
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.pointer.clear();
+        self.text.clear();
+        self.devices.clear();
+    }
+
+    fn check_query(&self) {
+        debug_assert!(
+            self.frame_phase != FramePhase::NotStarted,
+            "`Input` queried before any frame; call `Input::begin_frame` first"
+        );
+        debug_assert!(
+            self.frame_phase != FramePhase::Ended,
+            "`Input` queried after `Input::on_end_frame`, which hides pressed and released keys; \
+             call `Input::begin_frame` to start the next frame first"
+        );
+    }
+}
+
+/// Custom devices
+impl Input {
+    /// Replaces the device with the same name if any
+    pub fn register_device(&mut self, device: Box<dyn Device>) {
+        self.devices.register(device);
+    }
+
+    pub fn unregister_device(&mut self, name: &str) -> Option<Box<dyn Device>> {
+        self.devices.remove(name)
+    }
+
+    pub fn device(&self, name: &str) -> Option<&dyn Device> {
+        self.devices.get(name)
+    }
+
+    /// Returns `None` if the device is not found or not of type `T`
+    pub fn device_mut<T: Device>(&mut self, name: &str) -> Option<&mut T> {
+        self.devices
+            .get_mut(name)
+            .and_then(|d| d.as_any_mut().downcast_mut::<T>())
+    }
+}
+
+/// Freeze
+impl Input {
+    /// Makes the input look frozen, e.g. while a debug overlay grabs input
+    ///
+    /// Events are still applied internally, but queries of the keyboard and the pointer return
+    /// the state on freeze and [`Input::tick`] doesn't advance time. On unfreeze, keys and mouse
+    /// buttons that went down while frozen are seen as already down rather than pressed.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        if frozen {
+            self.kbd.freeze(self.freeze_mode);
+            self.pointer.freeze(self.freeze_mode);
+        } else {
+            self.kbd.unfreeze();
+            self.pointer.unfreeze();
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.kbd.is_frozen()
+    }
+
+    /// Whether held keys and mouse buttons stay down or read as up while frozen. Applied on the
+    /// next freeze
+    pub fn set_freeze_mode(&mut self, mode: FreezeMode) {
+        self.freeze_mode = mode;
+    }
+}
+
+/// Mouse buttons
+///
+/// Backends apply button events through these so that they're logged to [`Input::events`].
+impl Input {
+    pub(crate) fn on_mouse_down(&mut self, button: MouseButton) {
+        if self.pointer.on_button_down(button) {
+            self.kbd.log_event(|t| XdlEvent::MouseDown(button, t));
+        }
+    }
+
+    pub(crate) fn on_mouse_up(&mut self, button: MouseButton) {
+        if self.pointer.on_button_up(button) {
+            self.kbd.log_event(|t| XdlEvent::MouseUp(button, t));
+        }
+    }
+}
+
+/// Capture
+///
+/// UI layers such as a debug overlay take focus by capturing devices. See
+/// [`integrations`](crate::integrations) for bridges to UI libraries.
+impl Input {
+    /// Hides every key from queries while a UI layer has keyboard focus. See
+    /// [`Keyboard::is_captured`]
+    pub fn set_keyboard_captured(&mut self, captured: bool) {
+        self.kbd.set_captured(captured);
+    }
+
+    pub fn is_keyboard_captured(&self) -> bool {
+        self.kbd.is_captured()
+    }
+
+    /// Hides the mouse from `vi` objects while a UI layer has pointer focus. See
+    /// [`KeyState::pointer`]
+    ///
+    /// The `pointer` field is not affected.
+    pub fn set_pointer_captured(&mut self, captured: bool) {
+        self.pointer_captured = captured;
+    }
+
+    pub fn is_pointer_captured(&self) -> bool {
+        self.pointer_captured
+    }
+}
+
+/// Dynamic states of an [`Input`], saved with [`Input::state_snapshot`]
+///
+/// Plain data without settings, custom devices or statistics, so it's cheap to take every frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InputState {
+    kbd: KeyboardState,
+    pointer: Pointer,
+    text: TextState,
+    end_frames: u64,
+    dt: Duration,
+    elapsed: Duration,
+    idle: Duration,
+    idle_anchor: [i32; 2],
+}
+
+/// Save states
+impl Input {
+    /// Copies the key, mouse and text states, the frame counter and the timers
+    pub fn state_snapshot(&self) -> InputState {
+        InputState {
+            kbd: self.kbd.state(),
+            pointer: self.pointer.clone(),
+            text: self.text.state(),
+            end_frames: self.end_frames,
+            dt: self.dt,
+            elapsed: self.elapsed,
+            idle: self.idle,
+            idle_anchor: self.idle_anchor,
+        }
+    }
+
+    /// Overwrites the dynamic states with the snapshot
+    ///
+    /// Settings (disabled devices, text input, freeze mode), custom devices and statistics are
+    /// left untouched. Pair it with [`ActionMap::restore_state`] to roll back a whole frame.
+    ///
+    /// [`ActionMap::restore_state`]: crate::vi::ActionMap::restore_state
+    pub fn restore_state(&mut self, state: &InputState) {
+        self.kbd.restore_state(&state.kbd);
+        self.pointer.clone_from(&state.pointer);
+        self.text.restore_state(&state.text);
+        self.end_frames = state.end_frames;
+        self.dt = state.dt;
+        self.elapsed = state.elapsed;
+        self.idle = state.idle;
+        self.idle_anchor = state.idle_anchor;
+        self.kbd.clock = state.elapsed;
+    }
+
+    /// [`Input::restore_state`], but the keys down before this are seen as the previous frame's
+    pub(crate) fn advance_to_state(&mut self, state: &InputState) {
+        self.kbd.advance_to_state(&state.kbd);
+        self.pointer.advance_to(&state.pointer);
+        self.text.restore_state(&state.text);
+        self.end_frames = state.end_frames;
+        self.dt = state.dt;
+        self.elapsed = state.elapsed;
+        self.idle = state.idle;
+        self.idle_anchor = state.idle_anchor;
+        self.kbd.clock = state.elapsed;
+    }
+}
+
+/// History
+impl Input {
+    /// Starts recording the keyboard state of the last `N` frames on [`Input::on_end_frame`].
+    /// Replaces the current history if any
+    pub fn enable_history<const N: usize>(&mut self) {
+        self.history = Some(KeyboardHistory::new::<N>());
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Keyboard state as seen by queries `n` frames ago. `0` is the current frame
+    ///
+    /// Returns `None` if the frame is not recorded.
+    pub fn keyboard_n_frames_ago(&self, n: usize) -> Option<&KeyboardStateSnapshot> {
+        match n {
+            0 => Some(self.kbd.cur()),
+            _ => self.history.as_ref()?.get(n - 1),
+        }
+    }
+}
+
+/// Statistics
+impl Input {
+    /// Starts collecting [`InputStats`] on [`Input::tick`]. Keeps the current stats if already
+    /// enabled
+    pub fn enable_stats(&mut self) {
+        if self.stats.is_none() {
+            self.stats = Some(Box::new(InputStats::default()));
+        }
+    }
+
+    /// Stops collecting and drops the stats
+    pub fn disable_stats(&mut self) {
+        self.stats = None;
+    }
+
+    pub fn stats(&self) -> Option<&InputStats> {
+        self.stats.as_deref()
+    }
+
+    pub fn reset_stats(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            **stats = InputStats::default();
+        }
+    }
+
+    /// Counts the pressed actions. Call it after updating the map
+    pub fn record_action_stats(&mut self, map: &ActionMap) {
+        if let Some(stats) = &mut self.stats {
+            stats.record_actions(map);
+        }
+    }
+}
+
+/// Any input
+///
+/// Queries over every device, e.g. for "press any key" screens.
+impl Input {
+    fn any_key(&self, filter: &AnyInputFilter, f: impl Fn(&Keyboard, Key) -> bool) -> Option<Key> {
+        Key::ALL
+            .iter()
+            .cloned()
+            .find(|&key| filter.accepts_key(key) && f(&self.kbd, key))
+    }
+
+    fn any_mouse(
+        &self,
+        filter: &AnyInputFilter,
+        f: impl Fn(&Pointer, MouseButton) -> bool,
+    ) -> Option<MouseButton> {
+        if filter.ignore_mouse {
+            return None;
+        }
+        // hidden while captured
+        let pointer = KeyState::pointer(self)?;
+        MouseButton::ALL
+            .iter()
+            .cloned()
+            .find(|&button| f(pointer, button))
+    }
+
+    pub fn any_down(&self, filter: &AnyInputFilter) -> bool {
+        self.check_query();
+        self.any_key(filter, Keyboard::is_key_down).is_some()
+            || (!filter.ignore_raw && !self.kbd.raw_keys_down().is_empty())
+            || self.any_mouse(filter, Pointer::is_down).is_some()
+    }
+
+    pub fn any_pressed(&self, filter: &AnyInputFilter) -> bool {
+        self.first_pressed(filter).is_some()
+    }
+
+    pub fn any_released(&self, filter: &AnyInputFilter) -> bool {
+        self.check_query();
+        self.any_key(filter, Keyboard::is_key_released).is_some()
+            || (!filter.ignore_raw
+                && self
+                    .kbd
+                    .raw_keys_down_prev()
+                    .iter()
+                    .any(|&raw| self.kbd.is_raw_key_released(raw)))
+            || self.any_mouse(filter, Pointer::is_released).is_some()
+    }
+
+    /// Input pressed on the current frame, if any. Keys are searched first, then raw keys and then
+    /// mouse buttons
+    pub fn first_pressed(&self, filter: &AnyInputFilter) -> Option<AnyInput> {
+        self.check_query();
+        if let Some(key) = self.any_key(filter, Keyboard::is_key_pressed) {
+            return Some(AnyInput::Key(key));
+        }
+        if !filter.ignore_raw {
+            let raw = self
+                .kbd
+                .raw_keys_down()
+                .iter()
+                .cloned()
+                .find(|&raw| self.kbd.is_raw_key_pressed(raw));
+            if let Some(raw) = raw {
+                return Some(AnyInput::Raw(raw));
+            }
+        }
+        self.any_mouse(filter, Pointer::is_pressed)
+            .map(AnyInput::Mouse)
+    }
+}
+
+impl KeyState for Input {
+    fn is_key_down(&self, key: Key) -> bool {
+        self.check_query();
+        self.kbd.is_key_down(key)
+    }
+
+    fn was_key_down(&self, key: Key) -> bool {
+        self.check_query();
+        self.kbd.was_key_down(key)
+    }
+
+    fn is_raw_key_down(&self, key: RawKey) -> bool {
+        self.check_query();
+        self.kbd.is_raw_key_down(key)
+    }
+
+    fn was_raw_key_down(&self, key: RawKey) -> bool {
+        self.check_query();
+        self.kbd.was_raw_key_down(key)
+    }
+
+    fn device(&self, name: &str) -> Option<&dyn Device> {
+        self.devices.get(name)
+    }
+
+    fn pointer(&self) -> Option<&Pointer> {
+        self.check_query();
+        if self.pointer_captured {
+            None
+        } else {
+            Some(&self.pointer)
+        }
+    }
+
+    fn key_bits(&self) -> Option<[BitSet256; 2]> {
+        self.check_query();
+        self.kbd.key_bits()
+    }
+}
+
+/// Builder of [`Input`]
+///
+/// Disabled devices ignore events fed by backends, so their queries always return neutral values
+/// (e.g. every key is up).
+#[derive(Debug, Clone)]
+pub struct InputBuilder {
+    keyboard: bool,
+    mouse: bool,
+    text: bool,
+    check_frames: bool,
+}
+
+impl Default for InputBuilder {
+    fn default() -> Self {
+        Self {
+            keyboard: true,
+            mouse: true,
+            text: true,
+            check_frames: false,
+        }
+    }
+}
+
+impl InputBuilder {
+    pub fn keyboard(mut self, b: bool) -> Self {
+        self.keyboard = b;
+        self
+    }
+
+    pub fn mouse(mut self, b: bool) -> Self {
+        self.mouse = b;
+        self
+    }
+
+    pub fn text(mut self, b: bool) -> Self {
+        self.text = b;
+        self
+    }
+
+    /// Checks the [frame protocol](Input#frame-protocol) from the start, so that queries before
+    /// the first [`Input::begin_frame`] panic in debug builds
+    pub fn check_frames(mut self, b: bool) -> Self {
+        self.check_frames = b;
+        self
+    }
+
+    pub fn build(self) -> Input {
+        let mut input = Input::new();
+        input.kbd.disabled = !self.keyboard;
+        input.pointer.disabled = !self.mouse;
+        input.text.disabled = !self.text;
+        if self.check_frames {
+            input.frame_phase = FramePhase::NotStarted;
+        }
+        input
+    }
+}
+
+/// Text input
+impl Input {
+    /// Starts receiving text. Platform-side text input (e.g. SDL's) has to be enabled separately
+    pub fn start_text_input(&mut self) {
+        self.text.is_active = true;
+        self.sync_text_suppression();
+    }
+
+    pub fn stop_text_input(&mut self) {
+        self.text.is_active = false;
+        self.text.clear();
+        self.sync_text_suppression();
+    }
+
+    /// Hides printable keys from [`Keyboard`] while text input is active, so that typing into a
+    /// text box doesn't trigger game actions
+    pub fn set_suppress_printable_keys(&mut self, b: bool) {
+        self.text.suppress_printable = b;
+        self.sync_text_suppression();
+    }
+
+    fn sync_text_suppression(&mut self) {
+        self.kbd.suppress_printable = self.text.is_active && self.text.suppress_printable;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::ManualBackend,
+        entries,
+        vi::{presets::bundle, Button, InputBundle, InputSystem, KeyRepeatConfig},
+    };
+
+    const DT: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn frame_loop_driven_by_tick() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut system = InputSystem::default();
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+        let jump = system.add(Button::new(bundle(&[Key::Space]), repeat));
+
+        let mut pressed = Vec::new();
+        for frame in 0..8u32 {
+            if frame == 1 {
+                backend.key_down(Key::Space);
+            }
+            backend.update(&mut input);
+            input.tick(DT);
+            system.tick(&input);
+
+            assert_eq!(input.dt(), DT);
+            assert_eq!(input.elapsed(), DT * (frame + 1));
+            // the press frame counts
+            if frame >= 1 {
+                assert_eq!(input.kbd.key_down_duration(Key::Space), DT * frame);
+            }
+            if system.get(jump).unwrap().is_pressed() {
+                pressed.push(frame);
+            }
+
+            backend.end_frame(&mut input);
+        }
+
+        // repeats after 30ms and then every 20ms of ticks
+        assert_eq!(pressed, [1, 5, 7]);
+    }
+
+    #[test]
+    fn frame_counts_end_frames_not_events() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        for (frame, n_events) in [0, 1, 5, 0, 20].iter().enumerate() {
+            assert_eq!(input.frame(), frame as u64);
+            for i in 0..*n_events {
+                if i % 2 == 0 {
+                    backend.key_down(Key::A);
+                } else {
+                    backend.key_up(Key::A);
+                }
+            }
+            backend.update(&mut input);
+            input.tick(DT);
+            assert_eq!(input.frame(), frame as u64);
+            backend.end_frame(&mut input);
+        }
+
+        // kept across `clear`
+        input.clear();
+        assert_eq!(input.frame(), 5);
+        assert_eq!(input.elapsed(), DT * 5);
+    }
+
+    /// Mid-frame state with pressed, held and released keys
+    #[cfg(feature = "serde")]
+    fn mid_frame_input() -> Input {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        backend.key_down(Key::A);
+        backend.key_down(Key::B);
+        backend.mouse_move([3, 4]);
+        backend.update(&mut input);
+        input.tick(DT);
+        backend.end_frame(&mut input);
+
+        backend.key_up(Key::A);
+        backend.key_down(Key::C);
+        backend.raw_key_down(RawKey(0x4000_0000));
+        backend.mouse_move([5, 7]);
+        backend.mouse_down(crate::MouseButton::Left);
+        backend.update(&mut input);
+        input.tick(DT);
+        input
+    }
+
+    #[cfg(feature = "serde")]
+    fn assert_same_queries(a: &Input, b: &Input) {
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        for &key in Key::ALL {
+            assert_eq!(a.kbd.is_key_pressed(key), b.kbd.is_key_pressed(key));
+            assert_eq!(a.kbd.is_key_down(key), b.kbd.is_key_down(key));
+            assert_eq!(a.kbd.is_key_released(key), b.kbd.is_key_released(key));
+            assert_eq!(a.kbd.key_down_duration(key), b.kbd.key_down_duration(key));
+        }
+        assert_eq!(a.pointer, b.pointer);
+        assert_eq!(crate::KeyState::pointer(a), crate::KeyState::pointer(b));
+        assert_eq!(a.frame(), b.frame());
+        assert_eq!(a.elapsed(), b.elapsed());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip_keeps_queries() {
+        let input = self::mid_frame_input();
+        assert!(input.kbd.is_key_released(Key::A));
+        assert_eq!(input.pointer.pos_delta(), [2, 3]);
+
+        let ron = ron::to_string(&input).unwrap();
+        self::assert_same_queries(&input, &ron::from_str(&ron).unwrap());
+
+        let json = serde_json::to_string(&input).unwrap();
+        self::assert_same_queries(&input, &serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn bincode_round_trip_keeps_queries() {
+        let mut input = self::mid_frame_input();
+        for captured in [false, true] {
+            input.set_pointer_captured(captured);
+            let bytes = bincode::serialize(&input).unwrap();
+            let loaded: Input = bincode::deserialize(&bytes).unwrap();
+            self::assert_same_queries(&input, &loaded);
+            assert_eq!(loaded.is_pointer_captured(), captured);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn older_snapshots_load() {
+        let input = self::mid_frame_input();
+
+        // a snapshot from before the pointer was added
+        let mut json = serde_json::to_value(&input).unwrap();
+        json.as_object_mut().unwrap().remove("pointer");
+        let loaded: Input = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.pointer, Pointer::default());
+        assert!(loaded.kbd.is_key_pressed(Key::C));
+        assert_eq!(loaded.frame(), 1);
+
+        let empty: Input = ron::from_str("()").unwrap();
+        assert_eq!(empty.fingerprint(), Input::new().fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_covers_the_pointer_and_text() {
+        let base = Input::new();
+        let device = |input: &Input| {
+            [DeviceKind::Keyboard, DeviceKind::Mouse, DeviceKind::Text]
+                .map(|device| input.device_fingerprint(device) != base.device_fingerprint(device))
+        };
+
+        let mut input = Input::new();
+        input.pointer.on_move([1, 0]);
+        assert_eq!(device(&input), [false, true, false]);
+
+        let mut input = Input::new();
+        input.set_pointer_captured(true);
+        assert_eq!(device(&input), [false, true, false]);
+        assert_ne!(input.fingerprint(), base.fingerprint());
+
+        let mut input = Input::new();
+        input.text.on_text("a");
+        assert_eq!(device(&input), [false, false, true]);
+
+        // a button pressed while frozen is invisible
+        let mut input = Input::new();
+        input.set_frozen(true);
+        let frozen = input.fingerprint();
+        input.on_mouse_down(MouseButton::Left);
+        assert_eq!(input.fingerprint(), frozen);
+        input.set_frozen(false);
+        assert_ne!(input.fingerprint(), frozen);
+    }
+
+    #[test]
+    fn disabled_devices_stay_neutral() {
+        for &(keyboard, text) in &[(true, true), (true, false), (false, true), (false, false)] {
+            let mut backend = ManualBackend::new();
+            let mut input = Input::builder().keyboard(keyboard).text(text).build();
+            input.start_text_input();
+
+            for frame in 0..4 {
+                match frame {
+                    1 => {
+                        backend.key_down(Key::Q);
+                        backend.text("q");
+                    }
+                    2 => backend.key_up(Key::Q),
+                    _ => {}
+                }
+                backend.update(&mut input);
+                input.tick(DT);
+
+                assert_eq!(input.kbd.is_enabled(), keyboard);
+                assert_eq!(input.kbd.is_key_pressed(Key::Q), keyboard && frame == 1);
+                assert_eq!(input.kbd.is_key_released(Key::Q), keyboard && frame == 2);
+                let typed = if text && frame == 1 { "q" } else { "" };
+                assert_eq!(input.text.text(), typed);
+
+                backend.end_frame(&mut input);
+            }
+        }
+    }
+
+    #[test]
+    fn disabled_mouse_stays_neutral() {
+        for &mouse in &[true, false] {
+            let mut backend = ManualBackend::new();
+            let mut input = Input::builder().mouse(mouse).build();
+
+            backend.mouse_move([4, 2]);
+            backend.mouse_down(MouseButton::Left);
+            backend.update(&mut input);
+
+            assert_eq!(input.pointer.is_enabled(), mouse);
+            assert_eq!(input.pointer.is_pressed(MouseButton::Left), mouse);
+            let pos = if mouse { [4, 2] } else { [0, 0] };
+            assert_eq!(input.pointer.pos(), pos);
+        }
+    }
+
+    #[test]
+    fn any_input_exclusions() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let raw = RawKey(0x4000_0000);
+        let filter = AnyInputFilter {
+            ignore_modifiers: true,
+            ignore_keys: vec![Key::Escape],
+            ..Default::default()
+        };
+
+        backend.key_down(Key::LShift);
+        backend.key_down(Key::Escape);
+        backend.update(&mut input);
+        assert!(!input.any_pressed(&filter) && !input.any_down(&filter));
+        assert_eq!(
+            input.first_pressed(&AnyInputFilter::default()),
+            Some(AnyInput::Key(Key::Escape))
+        );
+        backend.end_frame(&mut input);
+
+        backend.raw_key_down(raw);
+        backend.update(&mut input);
+        assert_eq!(input.first_pressed(&filter), Some(AnyInput::Raw(raw)));
+        let no_raw = AnyInputFilter {
+            ignore_raw: true,
+            ..filter.clone()
+        };
+        assert_eq!(input.first_pressed(&no_raw), None);
+        backend.end_frame(&mut input);
+
+        // keys are searched before raw keys
+        backend.key_down(Key::Enter);
+        backend.raw_key_up(raw);
+        backend.update(&mut input);
+        assert_eq!(
+            input.first_pressed(&filter),
+            Some(AnyInput::Key(Key::Enter))
+        );
+        assert!(input.any_released(&filter));
+        assert!(!input.any_released(&no_raw));
+    }
+
+    #[test]
+    fn any_input_clicks() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let filter = AnyInputFilter::default();
+        let no_mouse = AnyInputFilter {
+            ignore_mouse: true,
+            ..Default::default()
+        };
+
+        backend.mouse_down(MouseButton::Right);
+        backend.update(&mut input);
+        assert_eq!(
+            input.first_pressed(&filter),
+            Some(AnyInput::Mouse(MouseButton::Right))
+        );
+        assert!(input.any_down(&filter));
+        assert!(!input.any_pressed(&no_mouse) && !input.any_down(&no_mouse));
+
+        // clicks on a UI layer are not for the game
+        input.set_pointer_captured(true);
+        assert!(!input.any_pressed(&filter) && !input.any_down(&filter));
+        input.set_pointer_captured(false);
+        backend.end_frame(&mut input);
+
+        backend.mouse_up(MouseButton::Right);
+        backend.update(&mut input);
+        assert!(input.any_released(&filter));
+        assert!(!input.any_released(&no_mouse));
+    }
+
+    #[test]
+    fn idle_time_ignores_jitter() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        input.set_idle_motion_threshold(3);
+
+        let frame = |backend: &mut ManualBackend, input: &mut Input| {
+            backend.update(input);
+            input.tick(DT);
+            let idle = input.idle_time();
+            backend.end_frame(input);
+            idle
+        };
+
+        // quiet
+        for i in 1..=5 {
+            assert_eq!(frame(&mut backend, &mut input), DT * i);
+        }
+
+        // jitter within the threshold
+        backend.mouse_move([2, 0]);
+        assert_eq!(frame(&mut backend, &mut input), DT * 6);
+        backend.mouse_move([0, -2]);
+        assert_eq!(frame(&mut backend, &mut input), DT * 7);
+
+        // drifting beyond the threshold
+        backend.mouse_move([3, 2]);
+        assert_eq!(frame(&mut backend, &mut input), Duration::ZERO);
+        assert_eq!(frame(&mut backend, &mut input), DT);
+
+        backend.key_down(Key::A);
+        assert_eq!(frame(&mut backend, &mut input), Duration::ZERO);
+        // holding is not an activity
+        assert_eq!(frame(&mut backend, &mut input), DT);
+        backend.key_up(Key::A);
+        assert_eq!(frame(&mut backend, &mut input), Duration::ZERO);
+
+        backend.mouse_down(crate::MouseButton::Left);
+        assert_eq!(frame(&mut backend, &mut input), Duration::ZERO);
+
+        // relative-mode coordinates far apart don't overflow
+        backend.mouse_move([i32::MAX, i32::MIN]);
+        assert_eq!(frame(&mut backend, &mut input), Duration::ZERO);
+        backend.mouse_move([i32::MIN, i32::MAX]);
+        assert_eq!(frame(&mut backend, &mut input), Duration::ZERO);
+    }
+
+    #[test]
+    fn freeze_window_with_presses_and_releases() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        backend.key_down(Key::A);
+        backend.update(&mut input);
+        input.tick(DT);
+        backend.end_frame(&mut input);
+
+        input.set_frozen(true);
+        for frame in 0..3 {
+            match frame {
+                0 => {
+                    backend.key_down(Key::B);
+                    backend.key_up(Key::A);
+                }
+                1 => backend.key_down(Key::C),
+                _ => backend.key_up(Key::C),
+            }
+            backend.update(&mut input);
+            input.tick(DT);
+
+            // the state on freeze
+            assert!(input.kbd.is_key_down(Key::A) && !input.kbd.is_key_pressed(Key::A));
+            assert!(!input.kbd.is_key_down(Key::B) && !input.kbd.is_key_down(Key::C));
+            assert!(input.events().is_empty());
+            assert_eq!(input.elapsed(), DT);
+            assert_eq!(input.dt(), Duration::ZERO);
+
+            backend.end_frame(&mut input);
+        }
+
+        // no burst of edges
+        input.set_frozen(false);
+        backend.update(&mut input);
+        input.tick(DT);
+        assert!(input.kbd.is_key_down(Key::B) && !input.kbd.is_key_pressed(Key::B));
+        assert!(!input.kbd.is_key_down(Key::A) && !input.kbd.is_key_released(Key::A));
+        assert!(!input.kbd.is_key_down(Key::C) && !input.kbd.is_key_released(Key::C));
+        assert_eq!(input.elapsed(), DT * 2);
+
+        // every key is up while frozen with `Release`
+        input.set_freeze_mode(FreezeMode::Release);
+        input.set_frozen(true);
+        assert!(!input.kbd.is_key_down(Key::B) && !input.kbd.is_key_released(Key::B));
+        input.set_frozen(false);
+        assert!(input.kbd.is_key_down(Key::B) && !input.kbd.is_key_pressed(Key::B));
+    }
+
+    #[test]
+    fn freeze_window_with_a_click() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let bundle = InputBundle {
+            mouse: entries![MouseButton::Left.into()],
+            ..Default::default()
+        };
+        let mut click = Button::new(bundle, KeyRepeatConfig::NoRepeat);
+
+        backend.mouse_move([3, 4]);
+        backend.update(&mut input);
+        input.tick(DT);
+        backend.end_frame(&mut input);
+
+        input.set_frozen(true);
+        for frame in 0..3 {
+            match frame {
+                0 => {
+                    backend.mouse_move([10, 10]);
+                    backend.mouse_down(MouseButton::Left);
+                }
+                1 => backend.mouse_up(MouseButton::Left),
+                _ => backend.mouse_down(MouseButton::Left),
+            }
+            backend.update(&mut input);
+            input.tick(DT);
+            click.update(&input, DT);
+
+            // the state on freeze
+            assert!(!click.is_down());
+            assert!(!input.pointer.is_down(MouseButton::Left));
+            assert_eq!(input.pointer.pos(), [3, 4]);
+            assert_eq!(input.pointer.pos_delta(), [0, 0]);
+
+            backend.end_frame(&mut input);
+        }
+
+        // no burst of edges
+        input.set_frozen(false);
+        backend.update(&mut input);
+        input.tick(DT);
+        click.update(&input, DT);
+        assert!(click.is_down() && !click.is_pressed());
+        assert!(!input.pointer.is_pressed(MouseButton::Left));
+        assert_eq!(input.pointer.pos(), [10, 10]);
+        assert_eq!(input.pointer.pos_delta(), [0, 0]);
+        backend.end_frame(&mut input);
+
+        // every button is up while frozen with `Release`
+        input.set_freeze_mode(FreezeMode::Release);
+        input.set_frozen(true);
+        assert!(!input.pointer.is_down(MouseButton::Left));
+        assert!(!input.pointer.is_released(MouseButton::Left));
+        input.set_frozen(false);
+        assert!(input.pointer.is_down(MouseButton::Left));
+        assert!(!input.pointer.is_pressed(MouseButton::Left));
+    }
+
+    #[test]
+    fn late_events_belong_to_the_next_frame() {
+        let mut input = Input::builder().check_frames(true).build();
+        let mut backend = ManualBackend::new();
+
+        input.begin_frame();
+        backend.update(&mut input);
+        backend.end_frame(&mut input);
+
+        // between `on_end_frame` and `begin_frame`
+        backend.key_down(Key::Space);
+        backend.update(&mut input);
+        input.begin_frame();
+        assert!(input.is_key_pressed(Key::Space));
+        backend.end_frame(&mut input);
+
+        input.begin_frame();
+        assert!(input.is_key_down(Key::Space) && !input.is_key_pressed(Key::Space));
+    }
+
+    #[test]
+    fn unchecked_input_allows_any_order() {
+        let mut input = Input::new();
+        input.on_end_frame();
+        input.on_end_frame();
+        assert!(!input.is_key_down(Key::Space));
+        assert_eq!(input.frame(), 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`Input::begin_frame` called twice")]
+    fn begin_frame_twice() {
+        let mut input = Input::new();
+        input.begin_frame();
+        input.begin_frame();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`Input::on_end_frame` called twice")]
+    fn end_frame_twice() {
+        let mut input = Input::new();
+        input.begin_frame();
+        input.on_end_frame();
+        input.on_end_frame();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`Input::on_end_frame` called before any frame")]
+    fn end_frame_before_any_frame() {
+        let mut input = Input::builder().check_frames(true).build();
+        input.on_end_frame();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`Input` queried before any frame")]
+    fn query_before_any_frame() {
+        let input = Input::builder().check_frames(true).build();
+        input.is_key_down(Key::Space);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`Input` queried after `Input::on_end_frame`")]
+    fn query_after_end_frame() {
+        let mut input = Input::new();
+        input.begin_frame();
+        input.on_end_frame();
+        input.is_key_pressed(Key::Space);
+    }
+
+    #[test]
+    fn capture_window_overlapping_presses() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        // `(pressed, down, released)` of the key on the frame
+        let frame = |backend: &mut ManualBackend, input: &mut Input, captured: bool, key| {
+            backend.update(input);
+            input.set_keyboard_captured(captured);
+            let q = (
+                input.kbd.is_key_pressed(key),
+                input.kbd.is_key_down(key),
+                input.kbd.is_key_released(key),
+            );
+            backend.end_frame(input);
+            q
+        };
+
+        // `A` is held across the capture, `B` goes down while captured
+        backend.key_down(Key::A);
+        assert_eq!(
+            frame(&mut backend, &mut input, false, Key::A),
+            (true, true, false)
+        );
+        backend.key_down(Key::B);
+        assert_eq!(
+            frame(&mut backend, &mut input, true, Key::B),
+            (false, false, false)
+        );
+        assert_eq!(
+            frame(&mut backend, &mut input, true, Key::A),
+            (false, false, false)
+        );
+        // the raw snapshot is intact
+        assert!(input.kbd.cur().is_down(Key::B));
+
+        // no press fires when the capture ends
+        assert_eq!(
+            frame(&mut backend, &mut input, false, Key::B),
+            (false, false, false)
+        );
+        assert_eq!(
+            frame(&mut backend, &mut input, false, Key::A),
+            (false, false, false)
+        );
+        backend.key_up(Key::B);
+        assert_eq!(
+            frame(&mut backend, &mut input, false, Key::B),
+            (false, false, false)
+        );
+        backend.key_down(Key::B);
+        assert_eq!(
+            frame(&mut backend, &mut input, false, Key::B),
+            (true, true, false)
+        );
+
+        // a key pressed and released within the capture is never seen
+        backend.key_down(Key::C);
+        assert_eq!(
+            frame(&mut backend, &mut input, true, Key::C),
+            (false, false, false)
+        );
+        backend.key_up(Key::C);
+        assert_eq!(
+            frame(&mut backend, &mut input, true, Key::C),
+            (false, false, false)
+        );
+        assert_eq!(
+            frame(&mut backend, &mut input, false, Key::C),
+            (false, false, false)
+        );
+    }
+
+    #[test]
+    fn captured_pointer_is_hidden_from_vi() {
+        let mut input = Input::new();
+        assert!(input.pointer().is_some());
+        input.set_pointer_captured(true);
+        assert!(input.pointer().is_none());
+        assert!(input.is_pointer_captured());
+        input.set_pointer_captured(false);
+        assert!(input.pointer().is_some());
+    }
+
+    #[test]
+    fn keyboard_history_and_clear() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        input.enable_history::<2>();
+
+        // `A` down on frame 0, `B` down on frame 1 and `C` down on frame 2
+        for key in [Key::A, Key::B, Key::C] {
+            backend.key_down(key);
+            backend.update(&mut input);
+            input.tick(DT);
+            backend.key_up(key);
+            backend.end_frame(&mut input);
+        }
+        backend.update(&mut input);
+        input.tick(DT);
+
+        let down = |input: &Input, n: usize| {
+            input
+                .keyboard_n_frames_ago(n)
+                .map(|kbd| [Key::A, Key::B, Key::C].map(|key| kbd.is_down(key)))
+        };
+        assert_eq!(down(&input, 0), Some([false, false, false]));
+        assert_eq!(down(&input, 1), Some([false, false, true]));
+        assert_eq!(down(&input, 2), Some([false, true, false]));
+        // frame 0 is dropped by the wrap-around
+        assert_eq!(down(&input, 3), None);
+
+        input.clear();
+        assert_eq!(down(&input, 0), Some([false, false, false]));
+        assert_eq!(down(&input, 1), None);
+
+        // recording restarts after the clear
+        backend.end_frame(&mut input);
+        assert!(input.keyboard_n_frames_ago(1).is_some());
+        assert!(input.keyboard_n_frames_ago(2).is_none());
+
+        input.disable_history();
+        assert!(input.keyboard_n_frames_ago(1).is_none());
+    }
+
+    #[test]
+    fn restore_state_mid_hold() {
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+        let mut map = ActionMap::new();
+        map.buttons = vec![Button::new(bundle(&[Key::Space]), repeat)];
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        // `(pressed, down, held)` for the frames
+        let run = |backend: &mut ManualBackend, input: &mut Input, map: &mut ActionMap, n| {
+            (0..n)
+                .map(|_| {
+                    backend.update(input);
+                    input.tick(DT);
+                    map.update(input, DT);
+                    let q = (
+                        map.buttons[0].is_pressed(),
+                        input.kbd.is_key_down(Key::Space),
+                        input.kbd.key_down_duration(Key::Space),
+                    );
+                    backend.end_frame(input);
+                    q
+                })
+                .collect::<Vec<_>>()
+        };
+
+        backend.key_down(Key::Space);
+        run(&mut backend, &mut input, &mut map, 2);
+        let (state, map_state) = (input.state_snapshot(), map.state_snapshot());
+        let fingerprint = input.fingerprint();
+        let expected = run(&mut backend, &mut input, &mut map, 5);
+
+        // mutate
+        backend.key_up(Key::Space);
+        backend.key_down(Key::A);
+        run(&mut backend, &mut input, &mut map, 3);
+
+        input.restore_state(&state);
+        map.restore_state(&map_state);
+        assert_eq!(input.fingerprint(), fingerprint);
+        assert_eq!(input.frame(), 2);
+        assert_eq!(input.elapsed(), DT * 2);
+        assert!(!input.kbd.is_key_down(Key::A));
+
+        // the repeat timers are restored too
+        backend.key_down(Key::Space);
+        backend.key_up(Key::A);
+        assert_eq!(run(&mut backend, &mut input, &mut map, 5), expected);
     }
 }
@@ -0,0 +1,45 @@
+//! Re-exported to super module
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::input::{
+    keyboard::{Key, RawKey},
+    pointer::MouseButton,
+};
+
+/// Concrete input found by [`Input::first_pressed`]
+///
+/// [`Input::first_pressed`]: crate::Input::first_pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AnyInput {
+    Key(Key),
+    Raw(RawKey),
+    Mouse(MouseButton),
+}
+
+/// Inputs excluded from the "any input" queries such as [`Input::any_pressed`]
+///
+/// [`Input::any_pressed`]: crate::Input::any_pressed
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AnyInputFilter {
+    /// Ignores Ctrl, Shift, Alt and Meta keys
+    pub ignore_modifiers: bool,
+    /// Ignores raw keys without [`Key`] translation
+    pub ignore_raw: bool,
+    /// Ignores mouse buttons
+    pub ignore_mouse: bool,
+    pub ignore_keys: Vec<Key>,
+}
+
+impl AnyInputFilter {
+    pub(crate) fn accepts_key(&self, key: Key) -> bool {
+        if self.ignore_modifiers && key.is_modifier() {
+            return false;
+        }
+        !self.ignore_keys.contains(&key)
+    }
+}
@@ -0,0 +1,124 @@
+//! Re-exported to super module
+
+use std::{any::Any, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// User-defined input device such as a MIDI pedal
+///
+/// Devices are registered with [`Input::register_device`] and queried by name from
+/// [`InputBundle::custom`] entries. Each device exposes named channels and keeps its own
+/// previous-frame state for pressed/released edges.
+///
+/// [`Input::register_device`]: crate::Input::register_device
+/// [`InputBundle::custom`]: crate::vi::InputBundle::custom
+pub trait Device: Any + fmt::Debug + Send + Sync {
+    /// Unique name the bindings refer to
+    fn name(&self) -> &str;
+
+    /// Names of the channels, for UI and validation
+    fn channels(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// If the boolean channel is down on the current frame
+    fn is_down(&self, channel: &str) -> bool;
+
+    /// If the boolean channel was down on the previous frame
+    fn was_down(&self, channel: &str) -> bool;
+
+    /// Value of an axis channel
+    fn axis(&self, _channel: &str) -> f32 {
+        0.0
+    }
+
+    /// Called from [`Input::on_end_frame`]
+    ///
+    /// [`Input::on_end_frame`]: crate::Input::on_end_frame
+    fn on_end_frame(&mut self);
+
+    /// Called from [`Input::clear`]
+    ///
+    /// [`Input::clear`]: crate::Input::clear
+    fn clear(&mut self);
+
+    /// `Box::new(self.clone())`, so that [`Input`](crate::Input) stays `Clone`
+    fn clone_box(&self) -> Box<dyn Device>;
+
+    /// `self`, for [`Input::device_mut`](crate::Input::device_mut)
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Channel of a registered [`Device`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CustomInput {
+    pub device: String,
+    pub channel: String,
+}
+
+impl CustomInput {
+    pub fn new(device: impl Into<String>, channel: impl Into<String>) -> Self {
+        Self {
+            device: device.into(),
+            channel: channel.into(),
+        }
+    }
+}
+
+/// `device.channel`
+impl fmt::Display for CustomInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.device, self.channel)
+    }
+}
+
+/// Registered devices
+#[derive(Debug, Default)]
+pub(crate) struct Devices {
+    items: Vec<Box<dyn Device>>,
+}
+
+impl Clone for Devices {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.iter().map(|d| d.clone_box()).collect(),
+        }
+    }
+}
+
+impl Devices {
+    /// Replaces the device with the same name if any
+    pub fn register(&mut self, device: Box<dyn Device>) {
+        match self.items.iter().position(|d| d.name() == device.name()) {
+            Some(ix) => self.items[ix] = device,
+            None => self.items.push(device),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Device>> {
+        let ix = self.items.iter().position(|d| d.name() == name)?;
+        Some(self.items.remove(ix))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Device> {
+        self.items.iter().find(|d| d.name() == name).map(|d| &**d)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Box<dyn Device>> {
+        self.items.iter_mut().find(|d| d.name() == name)
+    }
+
+    pub fn on_end_frame(&mut self) {
+        for d in &mut self.items {
+            d.on_end_frame();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for d in &mut self.items {
+            d.clear();
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Re-exported to super module
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::time::Duration;
+
+use crate::input::{
+    keyboard::{Key, RawKey},
+    pointer::MouseButton,
+};
+
+/// Backend-agnostic input event, logged in order on the frame it happened
+///
+/// See [`Input::events`]. Only actual state changes are logged: repeated key down events and key
+/// up events of keys that are already up are not. Cursor motion is not logged either.
+///
+/// The second field is the timestamp. See [`XdlEvent::timestamp`].
+///
+/// [`Input::events`]: crate::Input::events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum XdlEvent {
+    KeyDown(Key, Duration),
+    KeyUp(Key, Duration),
+    RawKeyDown(RawKey, Duration),
+    RawKeyUp(RawKey, Duration),
+    MouseDown(MouseButton, Duration),
+    MouseUp(MouseButton, Duration),
+}
+
+impl XdlEvent {
+    /// When it happened, on the clock of [`Input::elapsed`]
+    ///
+    /// If the backend provides event timestamps (see [`Input::set_event_timestamp`]), they're
+    /// mapped onto the clock so that the first timestamped event lines up with the elapsed time
+    /// at that point. Otherwise it's the elapsed time of the frame, as of the last
+    /// [`Input::tick`].
+    ///
+    /// [`Input::elapsed`]: crate::Input::elapsed
+    /// [`Input::set_event_timestamp`]: crate::Input::set_event_timestamp
+    /// [`Input::tick`]: crate::Input::tick
+    pub fn timestamp(&self) -> Duration {
+        match *self {
+            XdlEvent::KeyDown(_, t)
+            | XdlEvent::KeyUp(_, t)
+            | XdlEvent::RawKeyDown(_, t)
+            | XdlEvent::RawKeyUp(_, t)
+            | XdlEvent::MouseDown(_, t)
+            | XdlEvent::MouseUp(_, t) => t,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ManualBackend, Input};
+
+    /// Events without timestamps
+    fn events(input: &Input) -> Vec<(bool, Key)> {
+        input
+            .events()
+            .iter()
+            .map(|ev| match *ev {
+                XdlEvent::KeyDown(key, _) => (true, key),
+                XdlEvent::KeyUp(key, _) => (false, key),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn log_is_ordered_and_per_frame() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        backend.key_down(Key::B);
+        backend.key_down(Key::A);
+        // redundant
+        backend.key_down(Key::B);
+        backend.key_up(Key::C);
+        backend.key_up(Key::B);
+        backend.update(&mut input);
+        assert_eq!(
+            self::events(&input),
+            [(true, Key::B), (true, Key::A), (false, Key::B)]
+        );
+
+        backend.end_frame(&mut input);
+        backend.update(&mut input);
+        assert!(input.events().is_empty());
+
+        backend.key_up(Key::A);
+        backend.update(&mut input);
+        assert_eq!(self::events(&input), [(false, Key::A)]);
+    }
+
+    #[test]
+    fn mouse_buttons_are_logged_in_order() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        backend.key_down(Key::A);
+        backend.mouse_down(MouseButton::Left);
+        // redundant
+        backend.mouse_down(MouseButton::Left);
+        backend.mouse_move([3, 4]);
+        backend.mouse_up(MouseButton::Right);
+        backend.key_up(Key::A);
+        backend.mouse_up(MouseButton::Left);
+        backend.update(&mut input);
+        assert_eq!(
+            input.events(),
+            [
+                XdlEvent::KeyDown(Key::A, Duration::ZERO),
+                XdlEvent::MouseDown(MouseButton::Left, Duration::ZERO),
+                XdlEvent::KeyUp(Key::A, Duration::ZERO),
+                XdlEvent::MouseUp(MouseButton::Left, Duration::ZERO),
+            ]
+        );
+
+        backend.end_frame(&mut input);
+        backend.update(&mut input);
+        assert!(input.events().is_empty());
+    }
+
+    #[test]
+    fn timestamps_from_the_backend() {
+        use std::time::Duration;
+
+        let ms = Duration::from_millis;
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        for _ in 0..10 {
+            input.tick(ms(10));
+        }
+
+        // without backend timestamps
+        backend.key_down(Key::A);
+        backend.update(&mut input);
+        assert_eq!(input.events()[0].timestamp(), ms(100));
+        backend.end_frame(&mut input);
+
+        // the first timestamp lines up with the elapsed time
+        backend.timestamp(ms(5000));
+        backend.key_down(Key::B);
+        backend.timestamp(ms(5030));
+        backend.key_up(Key::A);
+        backend.update(&mut input);
+        let stamps = input
+            .events()
+            .iter()
+            .map(XdlEvent::timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(stamps, [ms(100), ms(130)]);
+        assert_eq!(input.kbd.last_pressed(Key::B), Some(ms(100)));
+        assert_eq!(input.kbd.last_pressed(Key::A), Some(ms(100)));
+        backend.end_frame(&mut input);
+
+        // earlier than the origin
+        input.tick(ms(10));
+        backend.timestamp(ms(4990));
+        backend.key_down(Key::C);
+        backend.update(&mut input);
+        assert_eq!(input.kbd.last_pressed(Key::C), Some(ms(90)));
+    }
+}
@@ -0,0 +1,66 @@
+//! Keyboard snapshots of the last frames, opted in with [`Input::enable_history`]
+//!
+//! [`Input::enable_history`]: crate::Input::enable_history
+
+use std::fmt;
+
+use crate::{input::keyboard::KeyboardStateSnapshot, utils::History};
+
+/// [`History`] of any capacity
+trait Snapshots: fmt::Debug + Send + Sync {
+    fn push(&mut self, snapshot: KeyboardStateSnapshot);
+    fn get(&self, back: usize) -> Option<&KeyboardStateSnapshot>;
+    fn clear(&mut self);
+    fn clone_box(&self) -> Box<dyn Snapshots>;
+}
+
+impl<const N: usize> Snapshots for History<KeyboardStateSnapshot, N> {
+    fn push(&mut self, snapshot: KeyboardStateSnapshot) {
+        History::push(self, snapshot);
+    }
+
+    fn get(&self, back: usize) -> Option<&KeyboardStateSnapshot> {
+        History::get(self, back)
+    }
+
+    fn clear(&mut self) {
+        History::clear(self);
+    }
+
+    fn clone_box(&self) -> Box<dyn Snapshots> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct KeyboardHistory {
+    inner: Box<dyn Snapshots>,
+}
+
+impl Clone for KeyboardHistory {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone_box(),
+        }
+    }
+}
+
+impl KeyboardHistory {
+    pub fn new<const N: usize>() -> Self {
+        Self {
+            inner: Box::new(History::<KeyboardStateSnapshot, N>::new()),
+        }
+    }
+
+    pub fn push(&mut self, snapshot: KeyboardStateSnapshot) {
+        self.inner.push(snapshot);
+    }
+
+    pub fn get(&self, back: usize) -> Option<&KeyboardStateSnapshot> {
+        self.inner.get(back)
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+}
@@ -5,14 +5,20 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use {num_enum::TryFromPrimitive, std::convert::TryFrom};
+use {
+    num_enum::TryFromPrimitive,
+    std::{convert::TryFrom, fmt, str::FromStr, time::Duration},
+};
 
-use crate::utils::Double;
+use crate::{
+    input::{device::Device, event::XdlEvent, pointer::Pointer, remap::KeyRemap},
+    utils::{BitSet256, Double},
+};
 
 /// XDL keycode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive)]
 #[repr(u32)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "igri", derive(igri::Inspect))]
 pub enum Key {
     /// TODO: delete?
@@ -238,16 +244,694 @@ impl Key {
     }
 }
 
+impl Key {
+    /// All of the keys in declaration order
+    pub const ALL: &'static [Key] = &[
+        Key::None,
+        Key::Back,
+        Key::Tab,
+        Key::Enter,
+        Key::CapsLock,
+        Key::Escape,
+        Key::Space,
+        Key::PageUp,
+        Key::PageDown,
+        Key::End,
+        Key::Home,
+        Key::Left,
+        Key::Up,
+        Key::Right,
+        Key::Down,
+        Key::Select,
+        Key::Print,
+        Key::Execute,
+        Key::PrintScreen,
+        Key::Insert,
+        Key::Delete,
+        Key::Help,
+        Key::D0,
+        Key::D1,
+        Key::D2,
+        Key::D3,
+        Key::D4,
+        Key::D5,
+        Key::D6,
+        Key::D7,
+        Key::D8,
+        Key::D9,
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::LMeta,
+        Key::RMeta,
+        Key::Apps,
+        Key::Sleep,
+        Key::NumPad0,
+        Key::NumPad1,
+        Key::NumPad2,
+        Key::NumPad3,
+        Key::NumPad4,
+        Key::NumPad5,
+        Key::NumPad6,
+        Key::NumPad7,
+        Key::NumPad8,
+        Key::NumPad9,
+        Key::Star,
+        Key::Plus,
+        Key::Minus,
+        Key::Decimal,
+        Key::Slash,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::F13,
+        Key::F14,
+        Key::F15,
+        Key::F16,
+        Key::F17,
+        Key::F18,
+        Key::F19,
+        Key::F20,
+        Key::F21,
+        Key::F22,
+        Key::F23,
+        Key::F24,
+        Key::NumLock,
+        Key::Scroll,
+        Key::LShift,
+        Key::RShift,
+        Key::LCtrl,
+        Key::RCtrl,
+        Key::LAlt,
+        Key::RAlt,
+        Key::BrowserBack,
+        Key::BrowserForward,
+        Key::BrowserRefresh,
+        Key::BrowserStop,
+        Key::BrowserSearch,
+        Key::BrowserFavorites,
+        Key::BrowserHome,
+        Key::VolumeMute,
+        Key::VolumeDown,
+        Key::VolumeUp,
+        Key::MediaNextTrack,
+        Key::MediaPreviousTrack,
+        Key::MediaStop,
+        Key::MediaPlayPause,
+        Key::LaunchMail,
+        Key::SelectMedia,
+        Key::LaunchApplication1,
+        Key::LaunchApplication2,
+        Key::OemSemicolon,
+        Key::OemPlus,
+        Key::OemComma,
+        Key::OemMinus,
+        Key::OemPeriod,
+        Key::OemQuestion,
+        Key::OemTilde,
+        Key::OemOpenBrackets,
+        Key::OemPipe,
+        Key::OemCloseBrackets,
+        Key::OemQuotes,
+        Key::Oem8,
+        Key::OemBackslash,
+        Key::ProcessKey,
+        Key::Attn,
+        Key::Crsel,
+        Key::Exsel,
+        Key::EraseEof,
+        Key::Play,
+        Key::Zoom,
+        Key::Pa1,
+        Key::OemClear,
+        Key::ChatPadGreen,
+        Key::ChatPadOrange,
+        Key::Pause,
+        Key::ImeConvert,
+        Key::ImeNoConvert,
+        Key::Kana,
+        Key::Kanji,
+        Key::OemAuto,
+        Key::OemCopy,
+        Key::OemEnlW,
+    ];
+
+    /// Variant name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Key::None => "None",
+            Key::Back => "Back",
+            Key::Tab => "Tab",
+            Key::Enter => "Enter",
+            Key::CapsLock => "CapsLock",
+            Key::Escape => "Escape",
+            Key::Space => "Space",
+            Key::PageUp => "PageUp",
+            Key::PageDown => "PageDown",
+            Key::End => "End",
+            Key::Home => "Home",
+            Key::Left => "Left",
+            Key::Up => "Up",
+            Key::Right => "Right",
+            Key::Down => "Down",
+            Key::Select => "Select",
+            Key::Print => "Print",
+            Key::Execute => "Execute",
+            Key::PrintScreen => "PrintScreen",
+            Key::Insert => "Insert",
+            Key::Delete => "Delete",
+            Key::Help => "Help",
+            Key::D0 => "D0",
+            Key::D1 => "D1",
+            Key::D2 => "D2",
+            Key::D3 => "D3",
+            Key::D4 => "D4",
+            Key::D5 => "D5",
+            Key::D6 => "D6",
+            Key::D7 => "D7",
+            Key::D8 => "D8",
+            Key::D9 => "D9",
+            Key::A => "A",
+            Key::B => "B",
+            Key::C => "C",
+            Key::D => "D",
+            Key::E => "E",
+            Key::F => "F",
+            Key::G => "G",
+            Key::H => "H",
+            Key::I => "I",
+            Key::J => "J",
+            Key::K => "K",
+            Key::L => "L",
+            Key::M => "M",
+            Key::N => "N",
+            Key::O => "O",
+            Key::P => "P",
+            Key::Q => "Q",
+            Key::R => "R",
+            Key::S => "S",
+            Key::T => "T",
+            Key::U => "U",
+            Key::V => "V",
+            Key::W => "W",
+            Key::X => "X",
+            Key::Y => "Y",
+            Key::Z => "Z",
+            Key::LMeta => "LMeta",
+            Key::RMeta => "RMeta",
+            Key::Apps => "Apps",
+            Key::Sleep => "Sleep",
+            Key::NumPad0 => "NumPad0",
+            Key::NumPad1 => "NumPad1",
+            Key::NumPad2 => "NumPad2",
+            Key::NumPad3 => "NumPad3",
+            Key::NumPad4 => "NumPad4",
+            Key::NumPad5 => "NumPad5",
+            Key::NumPad6 => "NumPad6",
+            Key::NumPad7 => "NumPad7",
+            Key::NumPad8 => "NumPad8",
+            Key::NumPad9 => "NumPad9",
+            Key::Star => "Star",
+            Key::Plus => "Plus",
+            Key::Minus => "Minus",
+            Key::Decimal => "Decimal",
+            Key::Slash => "Slash",
+            Key::F1 => "F1",
+            Key::F2 => "F2",
+            Key::F3 => "F3",
+            Key::F4 => "F4",
+            Key::F5 => "F5",
+            Key::F6 => "F6",
+            Key::F7 => "F7",
+            Key::F8 => "F8",
+            Key::F9 => "F9",
+            Key::F10 => "F10",
+            Key::F11 => "F11",
+            Key::F12 => "F12",
+            Key::F13 => "F13",
+            Key::F14 => "F14",
+            Key::F15 => "F15",
+            Key::F16 => "F16",
+            Key::F17 => "F17",
+            Key::F18 => "F18",
+            Key::F19 => "F19",
+            Key::F20 => "F20",
+            Key::F21 => "F21",
+            Key::F22 => "F22",
+            Key::F23 => "F23",
+            Key::F24 => "F24",
+            Key::NumLock => "NumLock",
+            Key::Scroll => "Scroll",
+            Key::LShift => "LShift",
+            Key::RShift => "RShift",
+            Key::LCtrl => "LCtrl",
+            Key::RCtrl => "RCtrl",
+            Key::LAlt => "LAlt",
+            Key::RAlt => "RAlt",
+            Key::BrowserBack => "BrowserBack",
+            Key::BrowserForward => "BrowserForward",
+            Key::BrowserRefresh => "BrowserRefresh",
+            Key::BrowserStop => "BrowserStop",
+            Key::BrowserSearch => "BrowserSearch",
+            Key::BrowserFavorites => "BrowserFavorites",
+            Key::BrowserHome => "BrowserHome",
+            Key::VolumeMute => "VolumeMute",
+            Key::VolumeDown => "VolumeDown",
+            Key::VolumeUp => "VolumeUp",
+            Key::MediaNextTrack => "MediaNextTrack",
+            Key::MediaPreviousTrack => "MediaPreviousTrack",
+            Key::MediaStop => "MediaStop",
+            Key::MediaPlayPause => "MediaPlayPause",
+            Key::LaunchMail => "LaunchMail",
+            Key::SelectMedia => "SelectMedia",
+            Key::LaunchApplication1 => "LaunchApplication1",
+            Key::LaunchApplication2 => "LaunchApplication2",
+            Key::OemSemicolon => "OemSemicolon",
+            Key::OemPlus => "OemPlus",
+            Key::OemComma => "OemComma",
+            Key::OemMinus => "OemMinus",
+            Key::OemPeriod => "OemPeriod",
+            Key::OemQuestion => "OemQuestion",
+            Key::OemTilde => "OemTilde",
+            Key::OemOpenBrackets => "OemOpenBrackets",
+            Key::OemPipe => "OemPipe",
+            Key::OemCloseBrackets => "OemCloseBrackets",
+            Key::OemQuotes => "OemQuotes",
+            Key::Oem8 => "Oem8",
+            Key::OemBackslash => "OemBackslash",
+            Key::ProcessKey => "ProcessKey",
+            Key::Attn => "Attn",
+            Key::Crsel => "Crsel",
+            Key::Exsel => "Exsel",
+            Key::EraseEof => "EraseEof",
+            Key::Play => "Play",
+            Key::Zoom => "Zoom",
+            Key::Pa1 => "Pa1",
+            Key::OemClear => "OemClear",
+            Key::ChatPadGreen => "ChatPadGreen",
+            Key::ChatPadOrange => "ChatPadOrange",
+            Key::Pause => "Pause",
+            Key::ImeConvert => "ImeConvert",
+            Key::ImeNoConvert => "ImeNoConvert",
+            Key::Kana => "Kana",
+            Key::Kanji => "Kanji",
+            Key::OemAuto => "OemAuto",
+            Key::OemCopy => "OemCopy",
+            Key::OemEnlW => "OemEnlW",
+        }
+    }
+
+    /// Keys that type characters: letters, digits, space and symbols
+    pub fn is_printable(&self) -> bool {
+        let k = *self as u32;
+        (Key::A as u32..=Key::Z as u32).contains(&k)
+            || (Key::D0 as u32..=Key::D9 as u32).contains(&k)
+            || (Key::NumPad0 as u32..=Key::NumPad9 as u32).contains(&k)
+            || matches!(
+                self,
+                Key::Space
+                    | Key::Star
+                    | Key::Plus
+                    | Key::Minus
+                    | Key::Decimal
+                    | Key::Slash
+                    | Key::OemSemicolon
+                    | Key::OemPlus
+                    | Key::OemComma
+                    | Key::OemMinus
+                    | Key::OemPeriod
+                    | Key::OemQuestion
+                    | Key::OemTilde
+                    | Key::OemOpenBrackets
+                    | Key::OemPipe
+                    | Key::OemCloseBrackets
+                    | Key::OemQuotes
+                    | Key::OemBackslash
+            )
+    }
+
+    /// Ctrl, Shift, Alt or Meta
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self,
+            Key::LCtrl
+                | Key::RCtrl
+                | Key::LShift
+                | Key::RShift
+                | Key::LAlt
+                | Key::RAlt
+                | Key::LMeta
+                | Key::RMeta
+        )
+    }
+
+    /// Inverse of [`Key::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|k| k.name() == name).cloned()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Lowercase aliases accepted on parsing
+const KEY_ALIASES: &[(&str, Key)] = &[
+    ("esc", Key::Escape),
+    ("return", Key::Enter),
+    ("spacebar", Key::Space),
+    ("backspace", Key::Back),
+    ("bksp", Key::Back),
+    ("del", Key::Delete),
+    ("ins", Key::Insert),
+    ("pgup", Key::PageUp),
+    ("pgdn", Key::PageDown),
+    ("pgdown", Key::PageDown),
+    ("ctrl", Key::LCtrl),
+    ("lctrl", Key::LCtrl),
+    ("leftctrl", Key::LCtrl),
+    ("rightctrl", Key::RCtrl),
+    ("shift", Key::LShift),
+    ("leftshift", Key::LShift),
+    ("rightshift", Key::RShift),
+    ("alt", Key::LAlt),
+    ("leftalt", Key::LAlt),
+    ("rightalt", Key::RAlt),
+    ("meta", Key::LMeta),
+    ("leftmeta", Key::LMeta),
+    ("rightmeta", Key::RMeta),
+    ("0", Key::D0),
+    ("1", Key::D1),
+    ("2", Key::D2),
+    ("3", Key::D3),
+    ("4", Key::D4),
+    ("5", Key::D5),
+    ("6", Key::D6),
+    ("7", Key::D7),
+    ("8", Key::D8),
+    ("9", Key::D9),
+];
+
+/// Error on parsing [`Key`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError {
+    pub token: String,
+    /// Close matches, the closest first
+    pub suggestions: Vec<Key>,
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown key '{}'", self.token)?;
+        for (i, key) in self.suggestions.iter().enumerate() {
+            let sep = match i {
+                0 => ", did you mean",
+                _ if i + 1 == self.suggestions.len() => " or",
+                _ => ",",
+            };
+            write!(f, "{} '{}'", sep, key)?;
+        }
+        if !self.suggestions.is_empty() {
+            f.write_str("?")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+/// Accepts variant names case-insensitively and some aliases such as `esc` or `pgup`
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(key) = Self::from_name(s) {
+            return Ok(key);
+        }
+
+        let lower = s.trim().to_ascii_lowercase();
+        let names = Self::ALL
+            .iter()
+            .map(|&key| (key.name().to_ascii_lowercase(), key))
+            .chain(
+                KEY_ALIASES
+                    .iter()
+                    .map(|&(alias, key)| (alias.to_string(), key)),
+            );
+
+        let mut candidates = Vec::new();
+        for (name, key) in names {
+            if name == lower {
+                return Ok(key);
+            }
+            candidates.push((self::edit_distance(&name, &lower), key));
+        }
+
+        // close enough to be a typo
+        let max = 2.max(lower.len() / 3);
+        candidates.retain(|(d, _)| *d <= max);
+        candidates.sort_by_key(|(d, _)| *d);
+        let mut suggestions = Vec::new();
+        for (_, key) in candidates {
+            if suggestions.len() < 3 && !suggestions.contains(&key) {
+                suggestions.push(key);
+            }
+        }
+
+        Err(ParseKeyError {
+            token: s.to_string(),
+            suggestions,
+        })
+    }
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let sub = diag + (ca != cb) as usize;
+            diag = row[j + 1];
+            row[j + 1] = sub.min(row[j] + 1).min(diag + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Parses the name with [`Key::from_str`], so bindings files can say `space` or `esc`
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{self, EnumAccess, VariantAccess, Visitor};
+        use std::sync::OnceLock;
+
+        static NAMES: OnceLock<Vec<&'static str>> = OnceLock::new();
+        let names = NAMES.get_or_init(|| Self::ALL.iter().map(|k| k.name()).collect());
+
+        /// Variant name or index
+        struct KeyIdent(Key);
+
+        impl<'de> Deserialize<'de> for KeyIdent {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer
+                    .deserialize_identifier(KeyVisitor)
+                    .map(KeyIdent)
+            }
+        }
+
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("key name")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Key, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Key, E> {
+                Key::ALL
+                    .get(v as usize)
+                    .cloned()
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Key, A::Error> {
+                let (KeyIdent(key), variant) = data.variant()?;
+                variant.unit_variant()?;
+                Ok(key)
+            }
+        }
+
+        deserializer.deserialize_enum("Key", names, KeyVisitor)
+    }
+}
+
+/// Platform key code that has no [`Key`] translation
+///
+/// Written as `raw:<code>` in bindings files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RawKey(pub u32);
+
+impl fmt::Display for RawKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "raw:{}", self.0)
+    }
+}
+
+/// Error on parsing `raw:<code>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRawKeyError {
+    pub token: String,
+}
+
+impl fmt::Display for ParseRawKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected `raw:<code>`, found `{}`", self.token)
+    }
+}
+
+impl std::error::Error for ParseRawKeyError {}
+
+impl FromStr for RawKey {
+    type Err = ParseRawKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .strip_prefix("raw:")
+            .and_then(|code| code.trim().parse().ok())
+            .map(RawKey)
+            .ok_or_else(|| ParseRawKeyError {
+                token: s.to_string(),
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for RawKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RawKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// All of the keyboard states
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Keyboard {
     pub(crate) states: Double<KeyboardStateSnapshot>,
+    /// Down raw keys (sorted), tracked separately since the snapshot can't hold arbitrary codes
+    pub(crate) raw: Double<Vec<RawKey>>,
+    /// Hides printable keys while text input is active
+    pub(crate) suppress_printable: bool,
+    /// Hides every key while a UI layer has keyboard focus
+    captured: bool,
+    /// Keys down when the capture ended, hidden until they're released
+    swallowed: BitSet256,
+    /// Down keys and how long they've been held, advanced by [`Keyboard::tick`]
+    held: Vec<(Key, Duration)>,
+    /// Ignores key down events if true
+    pub(crate) disabled: bool,
+    /// State changes on the current frame, in order
+    log: Vec<XdlEvent>,
+    /// Snapshot returned by queries while frozen
+    frozen: Option<FrozenKeys>,
+    /// Timestamp of the last key down of each key
+    pressed_at: Vec<(Key, Duration)>,
+    /// Fallback timestamp: the elapsed time as of the last tick
+    pub(crate) clock: Duration,
+    /// Timestamp given by the backend, until the end of the frame
+    pub(crate) stamp: Option<Duration>,
+    /// `None` if it's the identity
+    remap: Option<Box<KeyRemap>>,
+    /// `(physical, remapped)` keys down since a remap was set, so that releases follow the
+    /// remap at the time of the press
+    remapped: Vec<(Key, Key)>,
+}
+
+/// How a frozen [`Keyboard`] and [`Pointer`](crate::Pointer) look
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FreezeMode {
+    /// Keys and mouse buttons down on freeze stay down
+    #[default]
+    Hold,
+    /// Every key and mouse button is up
+    Release,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct FrozenKeys {
+    keys: KeyboardStateSnapshot,
+    raw: Vec<RawKey>,
 }
 
 impl Default for Keyboard {
     fn default() -> Self {
         Self {
             states: Double::default(),
+            raw: Double::default(),
+            suppress_printable: false,
+            captured: false,
+            swallowed: BitSet256::new(),
+            held: Vec::new(),
+            disabled: false,
+            log: Vec::new(),
+            frozen: None,
+            pressed_at: Vec::new(),
+            clock: Duration::ZERO,
+            stamp: None,
+            remap: None,
+            remapped: Vec::new(),
         }
     }
 }
@@ -255,41 +939,412 @@ impl Default for Keyboard {
 /// Lifecycle
 impl Keyboard {
     pub fn on_end_frame(&mut self) {
-        self.states.b.bits = self.states.a.bits;
+        debug_assert!(
+            self.states.current().invalid_bits().is_empty(),
+            "key state has bits that are not keys"
+        );
+        self.states.store_current_to_prev();
+        self.raw.store_current_to_prev();
+        self.log.clear();
+        self.stamp = None;
+        // released keys are visible again from the next frame, so they never look released
+        self.swallowed = self.swallowed.intersection(&self.states.current().bits);
     }
 
+    fn now(&self) -> Duration {
+        self.stamp.unwrap_or(self.clock)
+    }
+
+    /// Key down event from the backend, to be remapped
     pub(crate) fn on_key_down(&mut self, key: Key) {
-        self.states.a.on_key_down(key);
+        if self.remap.is_none() && self.remapped.is_empty() {
+            return self.press(key);
+        }
+        if self.disabled || self.remapped.iter().any(|(k, _)| *k == key) {
+            return;
+        }
+
+        let target = self.remap.as_ref().map_or(key, |remap| remap.get(key));
+        self.remapped.push((key, target));
+        self.press(target);
     }
 
+    /// Key up event from the backend, to be remapped
     pub(crate) fn on_key_up(&mut self, key: Key) {
-        self.states.a.on_key_up(key);
+        if self.remap.is_none() && self.remapped.is_empty() {
+            return self.release(key);
+        }
+
+        let target = match self.remapped.iter().position(|(k, _)| *k == key) {
+            Some(ix) => self.remapped.swap_remove(ix).1,
+            None => self.remap.as_ref().map_or(key, |remap| remap.get(key)),
+        };
+        // another physical key may still hold it
+        if !self.remapped.iter().any(|(_, t)| *t == target) {
+            self.release(target);
+        }
+    }
+
+    /// Presses the key without remapping
+    pub(crate) fn press(&mut self, key: Key) {
+        if self.disabled || self.states.current().is_down(key) {
+            return;
+        }
+        self.states.current_mut().on_key_down(key);
+        let now = self.now();
+        self.log.push(XdlEvent::KeyDown(key, now));
+        match self.pressed_at.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, t)) => *t = now,
+            None => self.pressed_at.push((key, now)),
+        }
+    }
+
+    /// Releases the key without remapping
+    pub(crate) fn release(&mut self, key: Key) {
+        if !self.states.current().is_down(key) {
+            return;
+        }
+        self.states.current_mut().on_key_up(key);
+        self.log.push(XdlEvent::KeyUp(key, self.now()));
+    }
+
+    /// Advances the hold durations of the down keys
+    pub(crate) fn tick(&mut self, dt: Duration) {
+        let prev = std::mem::take(&mut self.held);
+        for &key in Key::ALL {
+            if !self.states.current().is_down(key) {
+                continue;
+            }
+            let held = prev
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map_or(Duration::ZERO, |(_, held)| *held);
+            self.held.push((key, held + dt));
+        }
+    }
+
+    pub(crate) fn on_raw_key_down(&mut self, key: RawKey) {
+        if self.disabled {
+            return;
+        }
+        let raw = self.raw.current_mut();
+        if let Err(ix) = raw.binary_search(&key) {
+            raw.insert(ix, key);
+            self.log.push(XdlEvent::RawKeyDown(key, self.now()));
+        }
+    }
+
+    pub(crate) fn on_raw_key_up(&mut self, key: RawKey) {
+        let raw = self.raw.current_mut();
+        if let Ok(ix) = raw.binary_search(&key) {
+            raw.remove(ix);
+            self.log.push(XdlEvent::RawKeyUp(key, self.now()));
+        }
+    }
+
+    /// Logs an event of another device, so that the log keeps the order across devices
+    pub(crate) fn log_event(&mut self, event: impl FnOnce(Duration) -> XdlEvent) {
+        let now = self.now();
+        self.log.push(event(now));
     }
 }
 
 impl Keyboard {
+    /// Releases all the keys on the current frame (they will be seen as released)
+    pub(crate) fn release_all(&mut self) {
+        for &key in Key::ALL {
+            self.release(key);
+        }
+        self.remapped.clear();
+        for key in std::mem::take(self.raw.current_mut()) {
+            self.log.push(XdlEvent::RawKeyUp(key, self.now()));
+        }
+    }
+
+    /// State changes on the current frame, in order. Empty while frozen or captured
+    pub fn events(&self) -> &[XdlEvent] {
+        if self.frozen.is_some() || self.captured {
+            return &[];
+        }
+        &self.log
+    }
+
+    /// [`Keyboard::events`], even while frozen or captured
+    pub(crate) fn log(&self) -> &[XdlEvent] {
+        &self.log
+    }
+
+    pub(crate) fn held(&self) -> &[(Key, Duration)] {
+        &self.held
+    }
+
+    /// If any key was pressed or released on the current frame
+    pub(crate) fn has_changed(&self) -> bool {
+        self.states.current() != self.states.previous() || self.raw.current() != self.raw.previous()
+    }
+
+    /// Disabled keyboard ignores key presses and every key is seen as up
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
     pub fn clear(&mut self) {
-        self.states.a = KeyboardStateSnapshot { bits: [0; 8] };
-        self.states.b = KeyboardStateSnapshot { bits: [0; 8] };
+        self.states = Double::default();
+        self.raw.current_mut().clear();
+        self.raw.previous_mut().clear();
+        self.held.clear();
+        self.log.clear();
+        self.pressed_at.clear();
+        self.swallowed.clear_all();
+        self.remapped.clear();
+    }
+}
+
+/// Dynamic states of a [`Keyboard`], without settings such as text suppression
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct KeyboardState {
+    keys: BitSet256,
+    keys_prev: BitSet256,
+    raw: Vec<RawKey>,
+    raw_prev: Vec<RawKey>,
+    held: Vec<(Key, Duration)>,
+    log: Vec<XdlEvent>,
+    frozen: Option<FrozenKeys>,
+    pressed_at: Vec<(Key, Duration)>,
+    swallowed: BitSet256,
+    remapped: Vec<(Key, Key)>,
+}
+
+/// Save states
+impl Keyboard {
+    pub(crate) fn state(&self) -> KeyboardState {
+        KeyboardState {
+            keys: self.states.current().bits,
+            keys_prev: self.states.previous().bits,
+            raw: self.raw.current().clone(),
+            raw_prev: self.raw.previous().clone(),
+            held: self.held.clone(),
+            log: self.log.clone(),
+            frozen: self.frozen.clone(),
+            pressed_at: self.pressed_at.clone(),
+            swallowed: self.swallowed,
+            remapped: self.remapped.clone(),
+        }
+    }
+
+    /// Overwrites the states, reusing the allocations
+    pub(crate) fn restore_state(&mut self, state: &KeyboardState) {
+        self.states.current_mut().bits = state.keys;
+        self.states.previous_mut().bits = state.keys_prev;
+        self.states.current_mut().sanitize();
+        self.states.previous_mut().sanitize();
+        self.raw.current_mut().clone_from(&state.raw);
+        self.raw.previous_mut().clone_from(&state.raw_prev);
+        self.held.clone_from(&state.held);
+        self.log.clone_from(&state.log);
+        self.frozen.clone_from(&state.frozen);
+        self.pressed_at.clone_from(&state.pressed_at);
+        self.swallowed = state.swallowed;
+        self.remapped.clone_from(&state.remapped);
+    }
+
+    /// Restores the current state only, the previous state being the current one before this
+    pub(crate) fn advance_to_state(&mut self, state: &KeyboardState) {
+        self.states.store_current_to_prev();
+        self.raw.store_current_to_prev();
+        self.states.current_mut().bits = state.keys;
+        self.states.current_mut().sanitize();
+        self.raw.current_mut().clone_from(&state.raw);
+        self.held.clone_from(&state.held);
+        self.log.clone_from(&state.log);
+        self.frozen.clone_from(&state.frozen);
+        self.pressed_at.clone_from(&state.pressed_at);
+        self.swallowed = state.swallowed;
+        self.remapped.clone_from(&state.remapped);
+    }
+}
+
+/// Freeze
+///
+/// While frozen, queries return the snapshot taken on freeze, without pressed or released edges.
+/// Events are still applied internally.
+impl Keyboard {
+    pub(crate) fn freeze(&mut self, mode: FreezeMode) {
+        if self.frozen.is_some() {
+            return;
+        }
+        self.frozen = Some(match mode {
+            FreezeMode::Hold => FrozenKeys {
+                keys: self.states.current().clone(),
+                raw: self.raw.current().clone(),
+            },
+            FreezeMode::Release => FrozenKeys::default(),
+        });
+    }
+
+    /// Keys that went down while frozen are seen as already down (not pressed)
+    pub(crate) fn unfreeze(&mut self) {
+        if self.frozen.take().is_some() {
+            self.states.store_current_to_prev();
+            self.raw.store_current_to_prev();
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    pub(crate) fn cur(&self) -> &KeyboardStateSnapshot {
+        match &self.frozen {
+            Some(frozen) => &frozen.keys,
+            None => self.states.current(),
+        }
+    }
+
+    pub(crate) fn prev(&self) -> &KeyboardStateSnapshot {
+        match &self.frozen {
+            Some(frozen) => &frozen.keys,
+            None => self.states.previous(),
+        }
+    }
+}
+
+/// Capture
+///
+/// While captured by a UI layer, every key is seen as up, without pressed or released edges.
+/// Events are still applied internally. Keys that went down while captured stay hidden until
+/// they're released, so they don't fire presses when the capture ends. Raw keys are not hidden.
+impl Keyboard {
+    pub(crate) fn set_captured(&mut self, captured: bool) {
+        if self.captured && !captured {
+            self.swallowed = self.states.current().bits;
+        }
+        self.captured = captured;
+    }
+
+    pub fn is_captured(&self) -> bool {
+        self.captured
+    }
+}
+
+/// Remap
+///
+/// Key events from the backend are remapped before they're applied. A key that is down keeps the
+/// remapped key it was pressed as, so its release goes to the same key after a remap change.
+impl Keyboard {
+    /// Sets the global remap. [`KeyRemap::new`] is the identity
+    pub fn set_remap(&mut self, remap: KeyRemap) {
+        if self.remap.is_none() {
+            // keys down so far were pressed as themselves
+            for &key in Key::ALL {
+                if self.states.current().is_down(key)
+                    && !self.remapped.iter().any(|(_, t)| *t == key)
+                {
+                    self.remapped.push((key, key));
+                }
+            }
+        }
+        self.remap = if remap.is_empty() {
+            None
+        } else {
+            Some(Box::new(remap))
+        };
+    }
+
+    /// The global remap, `None` if it's the identity
+    pub fn remap(&self) -> Option<&KeyRemap> {
+        self.remap.as_deref()
     }
 }
 
 /// Single key
+///
+/// Printable keys are seen as up while they're suppressed by text input. Every key is seen as up
+/// while captured.
 impl Keyboard {
+    fn is_suppressed(&self, key: Key) -> bool {
+        self.captured
+            || (self.suppress_printable && key.is_printable())
+            || self.swallowed.get(key as usize)
+    }
+
     pub fn is_key_down(&self, key: Key) -> bool {
-        self.states.a.is_down(key)
+        !self.is_suppressed(key) && self.cur().is_down(key)
     }
 
     pub fn is_key_up(&self, key: Key) -> bool {
-        self.states.a.is_up(key)
+        !self.is_key_down(key)
     }
 
     pub fn is_key_pressed(&self, key: Key) -> bool {
-        self.states.b.is_up(key) && self.states.a.is_down(key)
+        !self.is_suppressed(key) && self.prev().is_up(key) && self.cur().is_down(key)
     }
 
     pub fn is_key_released(&self, key: Key) -> bool {
-        self.states.b.is_down(key) && self.states.a.is_up(key)
+        !self.is_suppressed(key) && self.prev().is_down(key) && self.cur().is_up(key)
+    }
+
+    /// Time the key has been down, including the current frame. Advanced by [`Input::tick`]
+    ///
+    /// [`Input::tick`]: crate::Input::tick
+    pub fn key_down_duration(&self, key: Key) -> Duration {
+        if !self.is_key_down(key) {
+            return Duration::ZERO;
+        }
+        self.held
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map_or(Duration::ZERO, |(_, held)| *held)
+    }
+
+    /// Timestamp of the last press of the key, if any. See [`XdlEvent::timestamp`]
+    pub fn last_pressed(&self, key: Key) -> Option<Duration> {
+        self.pressed_at
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, t)| *t)
+    }
+
+    /// If the key was down on the previous frame
+    pub fn was_key_down(&self, key: Key) -> bool {
+        !self.is_suppressed(key) && self.prev().is_down(key)
+    }
+}
+
+/// Raw key
+impl Keyboard {
+    pub fn is_raw_key_down(&self, key: RawKey) -> bool {
+        self.raw_keys_down().binary_search(&key).is_ok()
+    }
+
+    pub fn is_raw_key_pressed(&self, key: RawKey) -> bool {
+        self.is_raw_key_down(key) && !self.was_raw_key_down(key)
+    }
+
+    pub fn is_raw_key_released(&self, key: RawKey) -> bool {
+        !self.is_raw_key_down(key) && self.was_raw_key_down(key)
+    }
+
+    pub fn was_raw_key_down(&self, key: RawKey) -> bool {
+        self.raw_keys_down_prev().binary_search(&key).is_ok()
+    }
+
+    /// Raw keys down on the current frame
+    pub fn raw_keys_down(&self) -> &[RawKey] {
+        match &self.frozen {
+            Some(frozen) => &frozen.raw,
+            None => self.raw.current(),
+        }
+    }
+
+    /// Raw keys down on the previous frame
+    pub fn raw_keys_down_prev(&self) -> &[RawKey] {
+        match &self.frozen {
+            Some(frozen) => &frozen.raw,
+            None => self.raw.previous(),
+        }
     }
 }
 
@@ -312,82 +1367,457 @@ impl Keyboard {
     }
 }
 
-/// 256 bits for key states (up or down)
+/// Key sets
 ///
-/// Compare two snapshots to see if the key is pressed or released.
+/// Bits are indexed by [`Key`]. Suppressed keys are up, like the single key queries.
+impl Keyboard {
+    pub fn keys_down_bits(&self) -> BitSet256 {
+        self.visible_bits(self.cur())
+    }
+
+    pub fn keys_down_prev_bits(&self) -> BitSet256 {
+        self.visible_bits(self.prev())
+    }
+
+    /// Keys pressed or released on the current frame
+    pub fn changed_keys(&self) -> BitSet256 {
+        self.keys_down_bits()
+            .symmetric_diff(&self.keys_down_prev_bits())
+    }
+
+    fn visible_bits(&self, snapshot: &KeyboardStateSnapshot) -> BitSet256 {
+        if self.captured {
+            return BitSet256::new();
+        }
+        let bits = snapshot.bits.diff(&self.swallowed);
+        if self.suppress_printable {
+            bits.diff(self::printable_keys())
+        } else {
+            bits
+        }
+    }
+}
+
+fn printable_keys() -> &'static BitSet256 {
+    use std::sync::OnceLock;
+
+    static PRINTABLE: OnceLock<BitSet256> = OnceLock::new();
+    PRINTABLE.get_or_init(|| {
+        let mut bits = BitSet256::new();
+        for key in Key::ALL.iter().filter(|key| key.is_printable()) {
+            bits.set(*key as usize);
+        }
+        bits
+    })
+}
+
+/// Read access to key states (and custom devices)
+///
+/// Implemented by [`Input`], [`Keyboard`] and [`KeyboardView`], so that `vi` objects can be
+/// updated with any of them.
 ///
-/// Based on: http://graphics.stxdlord.edu/~seander/bithacks.html#CountBitsSetParallel
-#[derive(Debug, Clone, Default)]
+/// [`Input`]: crate::Input
+pub trait KeyState {
+    fn is_key_down(&self, key: Key) -> bool;
+    fn was_key_down(&self, key: Key) -> bool;
+    fn is_raw_key_down(&self, key: RawKey) -> bool;
+    fn was_raw_key_down(&self, key: RawKey) -> bool;
+
+    /// Registered custom device. None by default
+    fn device(&self, _name: &str) -> Option<&dyn Device> {
+        None
+    }
+
+    /// Mouse state. None by default, or while the pointer is captured by a UI layer
+    fn pointer(&self) -> Option<&Pointer> {
+        None
+    }
+
+    /// `[current, previous]` down keys as bitsets, consistent with [`KeyState::is_key_down`] and
+    /// [`KeyState::was_key_down`]. None by default
+    ///
+    /// Lets [`Button`] skip bundles whose keys didn't change.
+    ///
+    /// [`Button`]: crate::vi::Button
+    fn key_bits(&self) -> Option<[BitSet256; 2]> {
+        None
+    }
+
+    fn is_key_pressed(&self, key: Key) -> bool {
+        self.is_key_down(key) && !self.was_key_down(key)
+    }
+
+    fn is_key_released(&self, key: Key) -> bool {
+        !self.is_key_down(key) && self.was_key_down(key)
+    }
+
+    fn is_raw_key_pressed(&self, key: RawKey) -> bool {
+        self.is_raw_key_down(key) && !self.was_raw_key_down(key)
+    }
+
+    fn is_raw_key_released(&self, key: RawKey) -> bool {
+        !self.is_raw_key_down(key) && self.was_raw_key_down(key)
+    }
+}
+
+impl KeyState for Keyboard {
+    fn is_key_down(&self, key: Key) -> bool {
+        Keyboard::is_key_down(self, key)
+    }
+
+    fn was_key_down(&self, key: Key) -> bool {
+        Keyboard::was_key_down(self, key)
+    }
+
+    fn is_raw_key_down(&self, key: RawKey) -> bool {
+        Keyboard::is_raw_key_down(self, key)
+    }
+
+    fn was_raw_key_down(&self, key: RawKey) -> bool {
+        Keyboard::was_raw_key_down(self, key)
+    }
+
+    fn key_bits(&self) -> Option<[BitSet256; 2]> {
+        Some([self.keys_down_bits(), self.keys_down_prev_bits()])
+    }
+}
+
+/// Read-only view of a [`Keyboard`] that sees only the allowed keys
+///
+/// Keys outside of the set (and raw keys) are always up. Useful for splitting one keyboard
+/// between local players.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyboardView<'a> {
+    kbd: &'a Keyboard,
+    allowed: &'a [Key],
+}
+
+impl<'a> KeyboardView<'a> {
+    pub fn new(kbd: &'a Keyboard, allowed: &'a [Key]) -> Self {
+        Self { kbd, allowed }
+    }
+
+    pub fn allowed(&self) -> &'a [Key] {
+        self.allowed
+    }
+
+    fn sees(&self, key: Key) -> bool {
+        self.allowed.contains(&key)
+    }
+}
+
+impl<'a> KeyState for KeyboardView<'a> {
+    fn is_key_down(&self, key: Key) -> bool {
+        self.sees(key) && self.kbd.is_key_down(key)
+    }
+
+    fn was_key_down(&self, key: Key) -> bool {
+        self.sees(key) && self.kbd.was_key_down(key)
+    }
+
+    fn is_raw_key_down(&self, _key: RawKey) -> bool {
+        false
+    }
+
+    fn was_raw_key_down(&self, _key: RawKey) -> bool {
+        false
+    }
+}
+
+/// 256 bits for key states (up or down), indexed by [`Key`]
+///
+/// Compare two snapshots to see if the key is pressed or released. Bits that are not [`Key`]s
+/// are ignored by the queries; see [`KeyboardStateSnapshot::sanitize`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct KeyboardStateSnapshot {
-    pub bits: [u32; 8],
+    pub bits: BitSet256,
 }
 
 impl KeyboardStateSnapshot {
     // fn from_keys(akeys: &[Keycode]) -> Self {}
 
     pub fn on_key_down(&mut self, key: Key) {
-        let mask = 1 << ((key as u32) & 0x1f);
-        let ix = key as usize >> 5;
-        self.bits[ix] |= mask;
+        self.bits.set(key as usize);
     }
 
     pub fn on_key_up(&mut self, key: Key) {
-        let mask = 1 << ((key as u32) & 0x1f);
-        let ix = key as usize >> 5;
-        self.bits[ix] &= !mask;
+        self.bits.clear(key as usize);
     }
 
     pub fn is_down(&self, key: Key) -> bool {
-        let mask: u32 = 1 << ((key as u32) & 0x1f);
-        let ix = key as usize >> 5;
-        (self.bits[ix] & mask) != 0
+        self.bits.get(key as usize)
     }
 
     pub fn is_up(&self, key: Key) -> bool {
         !self.is_down(key)
     }
 
+    /// Down keys in the order of their codes. Bits that are not [`Key`]s are skipped
     pub fn pressed_keys(&self) -> Vec<Key> {
-        let count = self
-            .bits
-            .iter()
-            .map(|bits| Self::count_bits(*bits) as usize)
-            .sum();
+        self.keys().collect()
+    }
 
-        if count == 0 {
-            return Vec::new();
-        }
+    /// Iterates the down keys in the order of their codes. Bits that are not [`Key`]s are
+    /// skipped
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.bits
+            .iter_ones()
+            .filter_map(|i| Key::try_from(i as u32).ok())
+    }
 
-        let mut keys = Vec::with_capacity(count);
+    /// Bits that are not [`Key`]s, e.g. from an untrusted replay file
+    pub fn invalid_bits(&self) -> BitSet256 {
+        self.bits.diff(self::valid_keys())
+    }
 
-        let mut ix = 0;
-        for bits in self.bits.iter() {
-            if *bits != 0 {
-                ix = Self::store_keys(*bits, 0 * 32, &mut keys, ix);
-            }
+    /// Clears the bits that are not [`Key`]s. Deserialized snapshots are already sanitized
+    pub fn sanitize(&mut self) {
+        self.bits = self.bits.intersection(self::valid_keys());
+    }
+}
+
+/// Bits of every [`Key`]
+fn valid_keys() -> &'static BitSet256 {
+    use std::sync::OnceLock;
+
+    static VALID: OnceLock<BitSet256> = OnceLock::new();
+    VALID.get_or_init(|| {
+        let mut bits = BitSet256::new();
+        for key in Key::ALL {
+            bits.set(*key as usize);
+        }
+        bits
+    })
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeyboardStateSnapshot {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Repr {
+            bits: BitSet256,
         }
 
-        keys
+        let Repr { bits } = Repr::deserialize(deserializer)?;
+        let mut snapshot = Self { bits };
+        snapshot.sanitize();
+        Ok(snapshot)
     }
 }
 
-impl KeyboardStateSnapshot {
-    /// http://graphics.stxdlord.edu/~seander/bithacks.html#CountBitsSetParallel
-    fn count_bits(key: u32) -> u32 {
-        let mut v = key as u32;
-        v = v - ((v >> 1) & 0x55555555);
-        v = (v & 0x33333333) + ((v >> 2) & 0x33333333);
-        ((v + (v >> 4) & 0xF0F0F0F) * 0x1010101) >> 24
-    }
-
-    fn store_keys(keys: u32, offset: u32, pressed_keys: &mut [Key], mut ix: usize) -> usize {
-        for i in 0..32 {
-            if (keys & (1 << i)) != 0 {
-                pressed_keys[ix] = Key::try_from(offset + i).unwrap();
-                ix += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::xorshift64;
+
+    fn check(bits: BitSet256) {
+        let mut snapshot = KeyboardStateSnapshot { bits };
+        let keys = snapshot.pressed_keys();
+        assert!(keys.iter().all(|k| Key::ALL.contains(k)), "{:?}", keys);
+        assert!(keys.windows(2).all(|w| (w[0] as u32) < (w[1] as u32)));
+        assert!(keys.iter().all(|k| snapshot.is_down(*k)));
+
+        snapshot.sanitize();
+        assert!(snapshot.invalid_bits().is_empty());
+        assert_eq!(snapshot.pressed_keys(), keys);
+        assert_eq!(snapshot.bits.iter_ones().count(), keys.len());
+    }
+
+    #[test]
+    fn key_aliases_and_case() {
+        for (s, key) in [
+            ("space", Key::Space),
+            ("SPACE", Key::Space),
+            ("esc", Key::Escape),
+            ("Return", Key::Enter),
+            ("spacebar", Key::Space),
+            ("ctrl", Key::LCtrl),
+            ("lctrl", Key::LCtrl),
+            ("LeftCtrl", Key::LCtrl),
+            ("pgup", Key::PageUp),
+            (" pgdn ", Key::PageDown),
+            ("7", Key::D7),
+        ] {
+            assert_eq!(s.parse::<Key>(), Ok(key), "{}", s);
+        }
+    }
+
+    #[test]
+    fn unknown_key_suggestions() {
+        for (s, msg) in [
+            ("shfit", "unknown key 'shfit', did you mean 'LShift'?"),
+            ("spcae", "unknown key 'spcae', did you mean 'Space'?"),
+            ("esacpe", "unknown key 'esacpe', did you mean 'Escape'?"),
+        ] {
+            assert_eq!(s.parse::<Key>().unwrap_err().to_string(), msg);
+        }
+
+        let err = ParseKeyError {
+            token: "x".to_string(),
+            suggestions: vec![Key::A, Key::B, Key::C],
+        };
+        assert_eq!(
+            err.to_string(),
+            "unknown key 'x', did you mean 'A', 'B' or 'C'?"
+        );
+
+        let err = "xyzzy_nothing".parse::<Key>().unwrap_err();
+        assert!(err.suggestions.is_empty());
+        assert_eq!(err.to_string(), "unknown key 'xyzzy_nothing'");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn canonical_names_round_trip() {
+        for &key in Key::ALL.iter() {
+            assert_eq!(key.name().parse::<Key>(), Ok(key));
+            let s = ron::to_string(&key).unwrap();
+            assert_eq!(s, key.name());
+            assert_eq!(ron::from_str::<Key>(&s).unwrap(), key);
+        }
+        assert_eq!(ron::from_str::<Key>("esc").unwrap(), Key::Escape);
+        assert!(ron::from_str::<Key>("shfit").is_err());
+    }
+
+    #[test]
+    fn random_snapshots_report_only_keys() {
+        check(BitSet256::from_words([u32::MAX; 8]));
+        for i in 0..256 {
+            let mut bits = BitSet256::new();
+            bits.set(i);
+            check(bits);
+        }
+
+        let mut seed = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..10_000 {
+            let mut words = [0; 8];
+            for w in &mut words {
+                *w = xorshift64(&mut seed) as u32;
             }
+            check(BitSet256::from_words(words));
         }
+    }
+
+    #[test]
+    fn key_tables_cover_the_enum() {
+        let keys = (0..=u16::MAX as u32)
+            .filter_map(|i| Key::try_from(i).ok())
+            .collect::<Vec<_>>();
+        let mut all = Key::ALL.to_vec();
+        all.sort_by_key(|k| *k as u32);
+        all.dedup();
+        assert_eq!(all.len(), Key::ALL.len(), "duplicates in `Key::ALL`");
+        assert_eq!(all, keys, "`Key::ALL` doesn't match the enum");
+
+        for key in keys {
+            assert_eq!(key.name(), format!("{:?}", key));
+            assert_eq!(Key::from_name(key.name()), Some(key));
+        }
+    }
+
+    #[test]
+    fn raw_key_strings() {
+        let raw = RawKey(1_073_741_824);
+        assert_eq!(raw.to_string(), "raw:1073741824");
+        assert_eq!("raw:1073741824".parse(), Ok(raw));
+        assert_eq!(" raw: 7 ".parse(), Ok(RawKey(7)));
+
+        for s in &["1073741824", "raw:", "raw:-1", "raw:0x10", "Raw:1"] {
+            assert!(s.parse::<RawKey>().is_err(), "{}", s);
+        }
+    }
+
+    /// Keys down after applying the events of a frame
+    fn down_after(
+        backend: &mut crate::backend::ManualBackend,
+        input: &mut crate::Input,
+        f: impl FnOnce(&mut crate::backend::ManualBackend),
+    ) -> Vec<Key> {
+        backend.end_frame(input);
+        f(backend);
+        backend.update(input);
+        input.kbd.cur().pressed_keys()
+    }
+
+    #[test]
+    fn remap_swap_pairs() {
+        let mut backend = crate::backend::ManualBackend::new();
+        let mut input = crate::Input::new();
+        let mut remap = KeyRemap::new();
+        remap.swap(Key::Y, Key::Z).unwrap();
+        remap.insert(Key::CapsLock, Key::LCtrl).unwrap();
+        input.kbd.set_remap(remap);
+
+        let down = down_after(&mut backend, &mut input, |b| b.key_down(Key::Y));
+        assert_eq!(down, vec![Key::Z]);
+        assert!(input.kbd.is_key_pressed(Key::Z));
+        assert!(input
+            .events()
+            .contains(&XdlEvent::KeyDown(Key::Z, Duration::ZERO)));
+
+        let down = down_after(&mut backend, &mut input, |b| b.key_down(Key::Z));
+        assert_eq!(down, vec![Key::Y, Key::Z]);
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::Y));
+        assert_eq!(down, vec![Key::Y]);
+        assert!(input.kbd.is_key_released(Key::Z));
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::Z));
+        assert_eq!(down, vec![]);
+
+        // CapsLock and LCtrl both hold LCtrl
+        let down = down_after(&mut backend, &mut input, |b| {
+            b.key_down(Key::CapsLock);
+            b.key_down(Key::LCtrl);
+        });
+        assert_eq!(down, vec![Key::LCtrl]);
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::CapsLock));
+        assert_eq!(down, vec![Key::LCtrl]);
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::LCtrl));
+        assert_eq!(down, vec![]);
+
+        // the identity is stored as no remap
+        input.kbd.set_remap(KeyRemap::new());
+        assert!(input.kbd.remap().is_none());
+        let down = down_after(&mut backend, &mut input, |b| b.key_down(Key::Y));
+        assert_eq!(down, vec![Key::Y]);
+    }
+
+    #[test]
+    fn remap_change_mid_hold() {
+        let mut backend = crate::backend::ManualBackend::new();
+        let mut input = crate::Input::new();
+        let swap = KeyRemap::from_pairs([(Key::Y, Key::Z), (Key::Z, Key::Y)]).unwrap();
+
+        // pressed before the remap: released as itself
+        down_after(&mut backend, &mut input, |b| b.key_down(Key::Y));
+        input.kbd.set_remap(swap.clone());
+        let down = down_after(&mut backend, &mut input, |_| {});
+        assert_eq!(down, vec![Key::Y]);
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::Y));
+        assert_eq!(down, vec![]);
+        assert!(input.kbd.is_key_released(Key::Y));
+        assert!(!input.kbd.is_key_released(Key::Z));
+
+        // pressed under the remap: released as the remapped key after the remap is removed
+        down_after(&mut backend, &mut input, |b| b.key_down(Key::Y));
+        input.kbd.set_remap(KeyRemap::new());
+        let down = down_after(&mut backend, &mut input, |b| b.key_down(Key::Z));
+        assert_eq!(down, vec![Key::Z]);
+        // `Z` is held by both physical keys
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::Y));
+        assert_eq!(down, vec![Key::Z]);
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::Z));
+        assert_eq!(down, vec![]);
 
-        ix
+        // switching between remaps
+        input.kbd.set_remap(swap);
+        down_after(&mut backend, &mut input, |b| b.key_down(Key::Y));
+        input
+            .kbd
+            .set_remap(KeyRemap::from_pairs([(Key::Y, Key::X)]).unwrap());
+        let down = down_after(&mut backend, &mut input, |b| b.key_up(Key::Y));
+        assert_eq!(down, vec![]);
+        let down = down_after(&mut backend, &mut input, |b| b.key_down(Key::Y));
+        assert_eq!(down, vec![Key::X]);
     }
 }
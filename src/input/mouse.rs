@@ -53,7 +53,7 @@ impl Mouse {
         match ev {
             Event::MouseWheel { y, .. } => {
                 // 120 units per notch
-                self.wheels.b += y * 120;
+                *self.wheels.current_mut() += y * 120;
             }
             _ => {}
         }
@@ -86,32 +86,32 @@ impl Mouse {
         // y = (i32) ((f32) y * INTERNAL_BackBufferHeight / INTERNAL_WindowHeight);
 
         let snapshot = MouseSnapshot { x, y, flags };
-        self.mouses.b = snapshot;
+        *self.mouses.current_mut() = snapshot;
     }
 
     pub fn on_end_frame(&mut self) {
-        self.mouses.a = self.mouses.b.clone();
-        self.wheels.a = self.wheels.b.clone();
+        self.mouses.store_current_to_prev();
+        self.wheels.store_current_to_prev();
     }
 }
 
 impl Mouse {
     pub fn x(&self) -> i32 {
-        self.mouses.b.x()
+        self.mouses.current().x()
     }
 
     pub fn y(&self) -> i32 {
-        self.mouses.b.y()
+        self.mouses.current().y()
     }
 
     pub fn pos(&self) -> [i32; 2] {
-        [self.mouses.b.x(), self.mouses.b.y()]
+        [self.mouses.current().x(), self.mouses.current().y()]
     }
 
     pub fn pos_delta(&self) -> [i32; 2] {
         [
-            self.mouses.b.x() - self.mouses.a.x(),
-            self.mouses.b.y() - self.mouses.a.y(),
+            self.mouses.current().x() - self.mouses.previous().x(),
+            self.mouses.current().y() - self.mouses.previous().y(),
         ]
     }
 
@@ -121,108 +121,108 @@ impl Mouse {
 /// Down
 impl Mouse {
     pub fn is_left_down(&self) -> bool {
-        self.mouses.b.is_left_down()
+        self.mouses.current().is_left_down()
     }
 
     pub fn is_mid_down(&self) -> bool {
-        self.mouses.b.is_mid_down()
+        self.mouses.current().is_mid_down()
     }
 
     pub fn is_right_down(&self) -> bool {
-        self.mouses.b.is_right_down()
+        self.mouses.current().is_right_down()
     }
 
     pub fn is_x1_down(&self) -> bool {
-        self.mouses.b.is_x1_down()
+        self.mouses.current().is_x1_down()
     }
 
     pub fn is_x2_down(&self) -> bool {
-        self.mouses.b.is_x2_down()
+        self.mouses.current().is_x2_down()
     }
 }
 
 /// Up
 impl Mouse {
     pub fn is_left_up(&self) -> bool {
-        self.mouses.b.is_left_up()
+        self.mouses.current().is_left_up()
     }
 
     pub fn is_mid_up(&self) -> bool {
-        self.mouses.b.is_mid_up()
+        self.mouses.current().is_mid_up()
     }
 
     pub fn is_right_up(&self) -> bool {
-        self.mouses.b.is_right_up()
+        self.mouses.current().is_right_up()
     }
 
     pub fn is_x1_up(&self) -> bool {
-        self.mouses.b.is_x1_up()
+        self.mouses.current().is_x1_up()
     }
 
     pub fn is_x2_up(&self) -> bool {
-        self.mouses.b.is_x2_up()
+        self.mouses.current().is_x2_up()
     }
 }
 
 /// Pressed/released
 impl Mouse {
     pub fn is_left_pressed(&self) -> bool {
-        self.mouses.b.is_left_down() && !self.mouses.a.is_left_down()
+        self.mouses.current().is_left_down() && !self.mouses.previous().is_left_down()
     }
 
     pub fn is_left_released(&self) -> bool {
-        !self.mouses.b.is_left_down() && self.mouses.a.is_left_down()
+        !self.mouses.current().is_left_down() && self.mouses.previous().is_left_down()
     }
 
     pub fn is_right_pressed(&self) -> bool {
-        self.mouses.b.is_right_down() && !self.mouses.a.is_right_down()
+        self.mouses.current().is_right_down() && !self.mouses.previous().is_right_down()
     }
 
     pub fn is_right_released(&self) -> bool {
-        !self.mouses.b.is_right_down() && self.mouses.a.is_right_down()
+        !self.mouses.current().is_right_down() && self.mouses.previous().is_right_down()
     }
 
     pub fn is_mid_pressed(&self) -> bool {
-        self.mouses.b.is_mid_down() && !self.mouses.a.is_mid_down()
+        self.mouses.current().is_mid_down() && !self.mouses.previous().is_mid_down()
     }
 
     pub fn is_mid_released(&self) -> bool {
-        !self.mouses.b.is_mid_down() && self.mouses.a.is_mid_down()
+        !self.mouses.current().is_mid_down() && self.mouses.previous().is_mid_down()
     }
 
     pub fn is_x1_pressed(&self) -> bool {
-        self.mouses.b.is_x1_down() && !self.mouses.a.is_x1_down()
+        self.mouses.current().is_x1_down() && !self.mouses.previous().is_x1_down()
     }
 
     pub fn is_x1_released(&self) -> bool {
-        !self.mouses.b.is_x1_down() && self.mouses.a.is_x1_down()
+        !self.mouses.current().is_x1_down() && self.mouses.previous().is_x1_down()
     }
 
     pub fn is_x2_pressed(&self) -> bool {
-        self.mouses.b.is_x2_down() && !self.mouses.a.is_x2_down()
+        self.mouses.current().is_x2_down() && !self.mouses.previous().is_x2_down()
     }
 
     pub fn is_x2_released(&self) -> bool {
-        !self.mouses.b.is_x2_down() && self.mouses.a.is_x2_down()
+        !self.mouses.current().is_x2_down() && self.mouses.previous().is_x2_down()
     }
 }
 
 /// [`MouseInput`]
 impl Mouse {
     pub fn is_down(&self, input: MouseInput) -> bool {
-        self.mouses.b.is_down(input)
+        self.mouses.current().is_down(input)
     }
 
     pub fn is_up(&self, input: MouseInput) -> bool {
-        self.mouses.b.is_up(input)
+        self.mouses.current().is_up(input)
     }
 
     pub fn is_pressed(&self, input: MouseInput) -> bool {
-        self.mouses.b.is_down(input) && !self.mouses.a.is_down(input)
+        self.mouses.current().is_down(input) && !self.mouses.previous().is_down(input)
     }
 
     pub fn is_released(&self, input: MouseInput) -> bool {
-        !self.mouses.b.is_down(input) && self.mouses.a.is_down(input)
+        !self.mouses.current().is_down(input) && self.mouses.previous().is_down(input)
     }
 }
 
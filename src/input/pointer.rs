@@ -0,0 +1,202 @@
+//! Mouse cursor and buttons, independent of backends
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::input::keyboard::FreezeMode;
+
+/// Left | Right | Middle | X1 | X2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// First extended button
+    X1,
+    /// Second extended button
+    X2,
+}
+
+impl MouseButton {
+    pub const ALL: [MouseButton; 5] = [
+        MouseButton::Left,
+        MouseButton::Right,
+        MouseButton::Middle,
+        MouseButton::X1,
+        MouseButton::X2,
+    ];
+
+    /// Variant name
+    pub fn name(&self) -> &'static str {
+        match self {
+            MouseButton::Left => "Left",
+            MouseButton::Right => "Right",
+            MouseButton::Middle => "Middle",
+            MouseButton::X1 => "X1",
+            MouseButton::X2 => "X2",
+        }
+    }
+
+    /// Inverse of [`MouseButton::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|b| b.name() == name).cloned()
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Cursor position and mouse buttons (current/previous), fed by backends
+///
+/// Positions are in window pixels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Pointer {
+    pos: [i32; 2],
+    prev_pos: [i32; 2],
+    /// Bits of [`MouseButton`]s
+    down: u8,
+    prev_down: u8,
+    /// Ignores mouse events if true
+    pub(crate) disabled: bool,
+    /// Position and buttons returned by queries while frozen
+    frozen: Option<([i32; 2], u8)>,
+}
+
+/// Lifecycle
+impl Pointer {
+    pub(crate) fn on_move(&mut self, pos: [i32; 2]) {
+        if self.disabled {
+            return;
+        }
+        self.pos = pos;
+    }
+
+    /// Returns `true` if the button was up
+    pub(crate) fn on_button_down(&mut self, button: MouseButton) -> bool {
+        if self.disabled || self.down & button.bit() != 0 {
+            return false;
+        }
+        self.down |= button.bit();
+        true
+    }
+
+    /// Returns `true` if the button was down
+    pub(crate) fn on_button_up(&mut self, button: MouseButton) -> bool {
+        if self.down & button.bit() == 0 {
+            return false;
+        }
+        self.down &= !button.bit();
+        true
+    }
+
+    pub(crate) fn on_end_frame(&mut self) {
+        self.prev_pos = self.pos;
+        self.prev_down = self.down;
+    }
+
+    /// Releases every button. The cursor position is kept
+    pub(crate) fn clear(&mut self) {
+        self.down = 0;
+        self.prev_down = 0;
+        self.prev_pos = self.pos;
+    }
+
+    /// Takes the state, seeing the current one as the previous frame's
+    pub(crate) fn advance_to(&mut self, state: &Pointer) {
+        let (prev_pos, prev_down, disabled) = (self.pos, self.down, self.disabled);
+        self.clone_from(state);
+        self.prev_pos = prev_pos;
+        self.prev_down = prev_down;
+        self.disabled = disabled;
+    }
+}
+
+/// Freeze
+///
+/// While frozen, queries return the state on freeze, without pressed or released edges. Events
+/// are still applied internally.
+impl Pointer {
+    pub(crate) fn freeze(&mut self, mode: FreezeMode) {
+        if self.frozen.is_some() {
+            return;
+        }
+        let down = match mode {
+            FreezeMode::Hold => self.down,
+            FreezeMode::Release => 0,
+        };
+        self.frozen = Some((self.pos, down));
+    }
+
+    /// Buttons that went down while frozen are seen as already down (not pressed)
+    pub(crate) fn unfreeze(&mut self) {
+        if self.frozen.take().is_some() {
+            self.prev_pos = self.pos;
+            self.prev_down = self.down;
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+}
+
+/// Accessors
+impl Pointer {
+    /// Disabled pointer ignores mouse events, so the cursor stays at the origin and every button
+    /// is up
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    pub fn pos(&self) -> [i32; 2] {
+        match self.frozen {
+            Some((pos, _)) => pos,
+            None => self.pos,
+        }
+    }
+
+    pub fn prev_pos(&self) -> [i32; 2] {
+        match self.frozen {
+            Some((pos, _)) => pos,
+            None => self.prev_pos,
+        }
+    }
+
+    pub fn pos_delta(&self) -> [i32; 2] {
+        let (pos, prev_pos) = (self.pos(), self.prev_pos());
+        [pos[0] - prev_pos[0], pos[1] - prev_pos[1]]
+    }
+
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        let down = match self.frozen {
+            Some((_, down)) => down,
+            None => self.down,
+        };
+        down & button.bit() != 0
+    }
+
+    pub fn was_down(&self, button: MouseButton) -> bool {
+        let prev_down = match self.frozen {
+            Some((_, down)) => down,
+            None => self.prev_down,
+        };
+        prev_down & button.bit() != 0
+    }
+
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.is_down(button) && !self.was_down(button)
+    }
+
+    pub fn is_released(&self, button: MouseButton) -> bool {
+        !self.is_down(button) && self.was_down(button)
+    }
+
+    /// If any button was pressed or released on the current frame
+    pub(crate) fn has_button_changed(&self) -> bool {
+        self.down != self.prev_down
+    }
+}
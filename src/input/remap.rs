@@ -0,0 +1,216 @@
+//! Re-exported to super module
+
+use std::{error, fmt};
+
+use crate::input::keyboard::Key;
+
+/// Global `Key -> Key` table, set with [`Keyboard::set_remap`]
+///
+/// Applied where the backend key events come in, so every query and virtual input sees the
+/// remapped keys. Entries are applied once, not transitively, so swaps and longer cycles such as
+/// `Y -> Z, Z -> Y` are fine. Two keys can't be remapped to the same key, but a key can be
+/// remapped onto a key that is not remapped (`CapsLock -> LCtrl`).
+///
+/// On `serde`, it's a map from the physical key to the remapped key.
+///
+/// ```
+/// use xdl::{Key, KeyRemap};
+///
+/// let mut remap = KeyRemap::new();
+/// remap.swap(Key::Y, Key::Z).unwrap();
+/// remap.insert(Key::CapsLock, Key::LCtrl).unwrap();
+///
+/// assert_eq!(remap.get(Key::Y), Key::Z);
+/// assert_eq!(remap.get(Key::CapsLock), Key::LCtrl);
+/// assert_eq!(remap.get(Key::LCtrl), Key::LCtrl);
+/// assert!(remap.insert(Key::X, Key::LCtrl).is_err());
+/// ```
+///
+/// [`Keyboard::set_remap`]: crate::Keyboard::set_remap
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyRemap {
+    /// `(from, to)` sorted by `from`. Identity entries are not stored
+    entries: Vec<(Key, Key)>,
+}
+
+/// Error on adding an entry to [`KeyRemap`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemapError {
+    /// [`Key::None`] can't be remapped or be a target
+    NoneKey,
+    /// Two keys would be remapped to the same key
+    Conflict { target: Key, sources: [Key; 2] },
+}
+
+impl fmt::Display for RemapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoneKey => write!(f, "`None` key can't be remapped"),
+            Self::Conflict { target, sources } => write!(
+                f,
+                "both `{}` and `{}` are remapped to `{}`",
+                sources[0], sources[1], target
+            ),
+        }
+    }
+}
+
+impl error::Error for RemapError {}
+
+impl KeyRemap {
+    /// Identity
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (Key, Key)>) -> Result<Self, RemapError> {
+        let mut remap = Self::new();
+        for (from, to) in pairs {
+            remap.insert(from, to)?;
+        }
+        Ok(remap)
+    }
+
+    /// If it's the identity
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// `(from, to)` pairs sorted by `from`
+    pub fn entries(&self) -> &[(Key, Key)] {
+        &self.entries
+    }
+
+    /// The key seen when `key` is pressed
+    pub fn get(&self, key: Key) -> Key {
+        match self
+            .entries
+            .binary_search_by_key(&(key as u32), |(k, _)| *k as u32)
+        {
+            Ok(ix) => self.entries[ix].1,
+            Err(_) => key,
+        }
+    }
+
+    /// Remaps `from` to `to`, replacing the previous entry of `from`. `from == to` removes it
+    pub fn insert(&mut self, from: Key, to: Key) -> Result<(), RemapError> {
+        if from == Key::None || to == Key::None {
+            return Err(RemapError::NoneKey);
+        }
+        if from != to {
+            if let Some((other, _)) = self.entries.iter().find(|(k, t)| *t == to && *k != from) {
+                return Err(RemapError::Conflict {
+                    target: to,
+                    sources: [*other, from],
+                });
+            }
+        }
+
+        self.remove(from);
+        if from != to {
+            let ix = self
+                .entries
+                .partition_point(|(k, _)| (*k as u32) < from as u32);
+            self.entries.insert(ix, (from, to));
+        }
+        Ok(())
+    }
+
+    /// Swaps the two keys. Nothing is changed on error
+    pub fn swap(&mut self, a: Key, b: Key) -> Result<(), RemapError> {
+        let mut next = self.clone();
+        next.remove(a);
+        next.remove(b);
+        next.insert(a, b)?;
+        next.insert(b, a)?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Removes the entry of `from`, making it the identity
+    pub fn remove(&mut self, from: Key) {
+        self.entries.retain(|(k, _)| *k != from);
+    }
+}
+
+#[cfg(feature = "serde")]
+mod remap_serde {
+    //! `serde` [`KeyRemap`] as a map
+
+    use super::*;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    impl Serialize for KeyRemap {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_map(self.entries.iter().map(|(from, to)| (from, to)))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for KeyRemap {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let map = HashMap::<Key, Key>::deserialize(deserializer)?;
+            KeyRemap::from_pairs(map).map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_and_conflicts() {
+        let cycle = KeyRemap::from_pairs([(Key::X, Key::Y), (Key::Y, Key::Z), (Key::Z, Key::X)]);
+        let cycle = cycle.unwrap();
+        assert_eq!(cycle.get(Key::X), Key::Y);
+        assert_eq!(cycle.get(Key::Z), Key::X);
+
+        let err = KeyRemap::from_pairs([(Key::CapsLock, Key::LCtrl), (Key::X, Key::LCtrl)]);
+        let err = err.unwrap_err();
+        assert_eq!(
+            err,
+            RemapError::Conflict {
+                target: Key::LCtrl,
+                sources: [Key::CapsLock, Key::X],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "both `CapsLock` and `X` are remapped to `LCtrl`"
+        );
+        assert_eq!(
+            KeyRemap::new().insert(Key::None, Key::A),
+            Err(RemapError::NoneKey)
+        );
+
+        // a failed swap changes nothing
+        let mut remap = KeyRemap::from_pairs([(Key::CapsLock, Key::Y)]).unwrap();
+        let before = remap.clone();
+        assert!(remap.swap(Key::Y, Key::Z).is_err());
+        assert_eq!(remap, before);
+
+        // identity entries are not stored
+        remap.insert(Key::CapsLock, Key::CapsLock).unwrap();
+        assert!(remap.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_as_a_map() {
+        let mut remap = KeyRemap::new();
+        remap.swap(Key::Y, Key::Z).unwrap();
+        let ron = ron::to_string(&remap).unwrap();
+        assert_eq!(ron, "{Y:Z,Z:Y}");
+        assert_eq!(ron::from_str::<KeyRemap>(&ron).unwrap(), remap);
+
+        let err = ron::from_str::<KeyRemap>("{Y:Z,X:Z}").unwrap_err();
+        assert!(err.to_string().contains("remapped to `Z`"), "{}", err);
+    }
+}
@@ -0,0 +1,123 @@
+//! Re-exported to super module
+
+use std::sync::{Arc, Mutex};
+
+use crate::input::{FramePhase, Input};
+
+/// [`Input`] snapshots published to other threads
+///
+/// The event loop thread calls [`SharedInput::publish`] once per frame. Readers grab the latest
+/// immutable snapshot with [`SharedInput::read`] and query it as a normal [`Input`]. The lock is
+/// only held to swap or clone an `Arc`, so readers never wait for event handling.
+///
+/// ```
+/// use std::thread;
+/// use xdl::{Input, SharedInput};
+///
+/// let shared = SharedInput::new();
+/// let reader = {
+///     let shared = shared.clone();
+///     thread::spawn(move || shared.read().frame())
+/// };
+///
+/// let mut input = Input::new();
+/// shared.publish(&input);
+/// input.on_end_frame();
+/// assert!(reader.join().unwrap() <= 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedInput {
+    latest: Arc<Mutex<Arc<Input>>>,
+}
+
+impl SharedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publishes a copy of the input. Call it once the frame's events are applied and before
+    /// [`Input::on_end_frame`], which would hide the pressed and released keys from readers
+    ///
+    /// The snapshot is outside of the [frame protocol](Input#frame-protocol), so readers can query
+    /// it whenever it was taken.
+    pub fn publish(&self, input: &Input) {
+        let mut snapshot = input.clone();
+        snapshot.frame_phase = FramePhase::Unchecked;
+        *self.latest.lock().unwrap() = Arc::new(snapshot);
+    }
+
+    /// Latest published snapshot
+    pub fn read(&self) -> Arc<Input> {
+        Arc::clone(&self.latest.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{backend::ManualBackend, Key, KeyState};
+
+    #[test]
+    fn readers_see_consistent_snapshots() {
+        const FRAMES: u64 = 200;
+
+        let shared = SharedInput::new();
+        let readers = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while last < FRAMES - 1 {
+                        let input = shared.read();
+                        let frame = input.frame();
+                        assert!(frame >= last, "snapshots went back in time");
+                        // key A is down on odd frames (the initial snapshot is frame 0)
+                        assert_eq!(input.kbd.is_key_down(Key::A), frame % 2 == 1);
+                        last = frame;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        for frame in 0..FRAMES {
+            if frame % 2 == 1 {
+                backend.key_down(Key::A);
+            } else {
+                backend.key_up(Key::A);
+            }
+            backend.update(&mut input);
+            shared.publish(&input);
+            backend.end_frame(&mut input);
+        }
+
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn readers_see_edges() {
+        let shared = SharedInput::new();
+        let mut backend = ManualBackend::new();
+        let mut input = Input::builder().check_frames(true).build();
+
+        input.begin_frame();
+        backend.key_down(Key::A);
+        backend.update(&mut input);
+        shared.publish(&input);
+        input.on_end_frame();
+        assert!(shared.read().is_key_pressed(Key::A));
+
+        // published out of the frame
+        input.begin_frame();
+        backend.key_up(Key::A);
+        backend.update(&mut input);
+        input.on_end_frame();
+        shared.publish(&input);
+        assert!(!shared.read().is_key_down(Key::A));
+    }
+}
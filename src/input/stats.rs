@@ -0,0 +1,140 @@
+//! Re-exported to super module
+
+use std::{collections::HashMap, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::keyboard::{Key, Keyboard},
+    vi::ActionMap,
+};
+
+/// Usage of a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyStats {
+    pub presses: u32,
+    /// Total time the key has been down
+    pub held: Duration,
+}
+
+/// Input usage collected by [`Input::tick`] after [`Input::enable_stats`]
+///
+/// [`Input::tick`]: crate::Input::tick
+/// [`Input::enable_stats`]: crate::Input::enable_stats
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InputStats {
+    keys: HashMap<Key, KeyStats>,
+    /// Indexed by [`ActionMap::buttons`]
+    action_presses: Vec<u32>,
+    /// Sum of `dt`
+    session: Duration,
+}
+
+impl InputStats {
+    pub(crate) fn tick(&mut self, kbd: &Keyboard, dt: Duration) {
+        self.session += dt;
+        for &(key, _) in kbd.held() {
+            let stats = self.keys.entry(key).or_default();
+            stats.held += dt;
+            if kbd.is_key_pressed(key) {
+                stats.presses += 1;
+            }
+        }
+    }
+
+    pub(crate) fn record_actions(&mut self, map: &ActionMap) {
+        if self.action_presses.len() < map.buttons.len() {
+            self.action_presses.resize(map.buttons.len(), 0);
+        }
+        for (count, button) in self.action_presses.iter_mut().zip(&map.buttons) {
+            if button.is_pressed() {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Zero if the key was never used
+    pub fn key(&self, key: Key) -> KeyStats {
+        self.keys.get(&key).cloned().unwrap_or_default()
+    }
+
+    /// Used keys in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = (Key, KeyStats)> + '_ {
+        self.keys.iter().map(|(key, stats)| (*key, *stats))
+    }
+
+    /// Press count of the action by index in [`ActionMap::buttons`]
+    pub fn action_presses(&self, action: usize) -> u32 {
+        self.action_presses.get(action).cloned().unwrap_or(0)
+    }
+
+    /// Time advanced since the stats were enabled or reset
+    pub fn session(&self) -> Duration {
+        self.session
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backend::ScriptBackend,
+        vi::{ActionMap, Button, KeyRepeatConfig},
+        Input, Key,
+    };
+    use std::time::Duration;
+
+    const DT: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn scripted_session_counts() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::Z, 1..4)
+            .hold(Key::Z, 6..7)
+            .hold(Key::X, 2..3)
+            .hold(Key::Z, 9..10);
+        let mut input = Input::new();
+        let mut map = ActionMap::new();
+        map.buttons = vec![Button::single(Key::Z, KeyRepeatConfig::NoRepeat)];
+        assert!(input.stats().is_none());
+
+        input.enable_stats();
+        for _ in 0..8 {
+            let dt = script.step(&mut input);
+            input.tick(dt);
+            map.update(&input, dt);
+            input.record_action_stats(&map);
+        }
+
+        let stats = input.stats().unwrap();
+        assert_eq!(stats.session(), DT * 8);
+        assert_eq!(stats.key(Key::Z).presses, 2);
+        assert_eq!(stats.key(Key::Z).held, DT * 4);
+        assert_eq!(stats.key(Key::X).presses, 1);
+        assert_eq!(stats.key(Key::X).held, DT);
+        assert_eq!(stats.key(Key::C).presses, 0);
+        assert_eq!(stats.keys().count(), 2);
+        assert_eq!(stats.action_presses(0), 2);
+        assert_eq!(stats.action_presses(1), 0);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(stats).unwrap();
+            let loaded: super::InputStats = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded.key(Key::Z), stats.key(Key::Z));
+        }
+
+        input.reset_stats();
+        assert_eq!(input.stats().unwrap().session(), Duration::ZERO);
+        assert_eq!(input.stats().unwrap().key(Key::Z).presses, 0);
+        for _ in 0..2 {
+            let dt = script.step(&mut input);
+            input.tick(dt);
+        }
+        assert_eq!(input.stats().unwrap().key(Key::Z).presses, 1);
+    }
+}
@@ -0,0 +1,120 @@
+//! Text input states
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// IME composition (pre-edit) text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Composition {
+    pub text: String,
+    /// Cursor position in the composition
+    pub start: i32,
+    /// Selection length in the composition
+    pub length: i32,
+}
+
+/// Characters typed on the current frame and the IME composition state
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TextInput {
+    pub(crate) is_active: bool,
+    pub(crate) suppress_printable: bool,
+    /// Ignores text events if true
+    pub(crate) disabled: bool,
+    buf: String,
+    composition: Option<Composition>,
+}
+
+/// Lifecycle
+impl TextInput {
+    pub(crate) fn on_text(&mut self, text: &str) {
+        if self.disabled {
+            return;
+        }
+        self.buf.push_str(text);
+        // composed text is committed
+        self.composition = None;
+    }
+
+    pub(crate) fn on_editing(&mut self, text: &str, start: i32, length: i32) {
+        if self.disabled {
+            return;
+        }
+        self.composition = if text.is_empty() {
+            None
+        } else {
+            Some(Composition {
+                text: text.to_string(),
+                start,
+                length,
+            })
+        };
+    }
+
+    pub(crate) fn on_end_frame(&mut self) {
+        self.buf.clear();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buf.clear();
+        self.composition = None;
+    }
+}
+
+/// Dynamic states of a [`TextInput`], without settings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct TextState {
+    buf: String,
+    composition: Option<Composition>,
+}
+
+/// Save states
+impl TextInput {
+    pub(crate) fn state(&self) -> TextState {
+        TextState {
+            buf: self.buf.clone(),
+            composition: self.composition.clone(),
+        }
+    }
+
+    pub(crate) fn restore_state(&mut self, state: &TextState) {
+        self.buf.clone_from(&state.buf);
+        self.composition.clone_from(&state.composition);
+    }
+}
+
+/// Accessors
+impl TextInput {
+    /// Text committed on the current frame
+    pub fn text(&self) -> &str {
+        &self.buf
+    }
+
+    /// Takes the text committed on the current frame
+    pub fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.buf)
+    }
+
+    /// Ongoing IME composition, if any
+    pub fn composition(&self) -> Option<&Composition> {
+        self.composition.as_ref()
+    }
+
+    /// Disabled text input ignores text events and stays empty
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// If printable keys are hidden from the keyboard while text input is active
+    pub fn suppresses_printable_keys(&self) -> bool {
+        self.suppress_printable
+    }
+}
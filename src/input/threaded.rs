@@ -0,0 +1,157 @@
+//! Re-exported to super module
+
+use crate::{
+    input::{Input, InputState},
+    utils::{TripleBuffer, TripleReader, TripleWriter},
+};
+
+/// [`Input`] states handed from the event loop thread to a game thread without locking
+///
+/// The backend thread owns the [`InputWriter`] and publishes after applying each frame's events.
+/// The game thread owns the [`InputReader`] and calls [`InputReader::update`] once per
+/// simulation step before querying. Neither side blocks the other.
+///
+/// Pressed and released edges seen by the reader are relative to the last snapshot the reader
+/// observed, not to the writer's previous frame. So a press is seen exactly once even if the
+/// reader skips frames, but a key pressed and released between two reads is missed.
+///
+/// ```
+/// use std::thread;
+/// use xdl::{backend::ManualBackend, Input, Key, ThreadedInput};
+///
+/// let (mut writer, mut reader) = ThreadedInput::new().split();
+/// let pump = thread::spawn(move || {
+///     let mut backend = ManualBackend::new();
+///     let mut input = Input::new();
+///     backend.key_down(Key::Space);
+///     backend.update(&mut input);
+///     writer.publish(&input);
+///     backend.end_frame(&mut input);
+/// });
+/// pump.join().unwrap();
+///
+/// reader.update();
+/// assert!(reader.input().kbd.is_key_pressed(Key::Space));
+/// reader.update();
+/// assert!(reader.input().kbd.is_key_down(Key::Space));
+/// assert!(!reader.input().kbd.is_key_pressed(Key::Space));
+/// ```
+#[derive(Debug)]
+pub struct ThreadedInput {
+    writer: InputWriter,
+    reader: InputReader,
+}
+
+impl ThreadedInput {
+    pub fn new() -> Self {
+        let (tx, rx) = TripleBuffer::split(InputState::default());
+        Self {
+            writer: InputWriter { tx },
+            reader: InputReader {
+                rx,
+                input: Input::new(),
+            },
+        }
+    }
+
+    pub fn split(self) -> (InputWriter, InputReader) {
+        (self.writer, self.reader)
+    }
+}
+
+impl Default for ThreadedInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Publishing side of [`ThreadedInput`], used by the backend thread
+#[derive(Debug)]
+pub struct InputWriter {
+    tx: TripleWriter<InputState>,
+}
+
+impl InputWriter {
+    /// Publishes the state of the input (see [`Input::state_snapshot`])
+    pub fn publish(&mut self, input: &Input) {
+        *self.tx.buffer_mut() = input.state_snapshot();
+        self.tx.publish();
+    }
+}
+
+/// Reading side of [`ThreadedInput`], used by the game thread
+#[derive(Debug)]
+pub struct InputReader {
+    rx: TripleReader<InputState>,
+    /// Latest observed state
+    input: Input,
+}
+
+impl InputReader {
+    /// Observes the latest published state. Returns `false` if nothing was published since the
+    /// last call, in which case the edges are cleared
+    pub fn update(&mut self) -> bool {
+        let updated = self.rx.update();
+        self.input.advance_to_state(self.rx.read());
+        updated
+    }
+
+    /// Input as of the last [`InputReader::update`]. Custom devices and statistics are not
+    /// shared
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{backend::ManualBackend, Key};
+
+    #[test]
+    fn reader_sees_ordered_snapshots_and_edges() {
+        const FRAMES: u64 = 10_000;
+
+        let (mut writer, mut reader) = ThreadedInput::new().split();
+        let pump = thread::spawn(move || {
+            let mut backend = ManualBackend::new();
+            let mut input = Input::new();
+            for frame in 0..FRAMES {
+                // key A is down on odd frames
+                if frame % 2 == 1 {
+                    backend.key_down(Key::A);
+                } else {
+                    backend.key_up(Key::A);
+                }
+                backend.update(&mut input);
+                writer.publish(&input);
+                backend.end_frame(&mut input);
+            }
+        });
+
+        let (mut last, mut was_down) = (0, false);
+        while last < FRAMES - 1 {
+            reader.update();
+            let input = reader.input();
+            let frame = input.frame();
+            assert!(frame >= last, "snapshots went back in time");
+
+            let down = input.kbd.is_key_down(Key::A);
+            assert_eq!(down, frame % 2 == 1, "torn snapshot on frame {}", frame);
+            // edges are relative to the last observed snapshot
+            assert_eq!(input.kbd.is_key_pressed(Key::A), down && !was_down);
+            assert_eq!(input.kbd.is_key_released(Key::A), !down && was_down);
+
+            last = frame;
+            was_down = down;
+        }
+        pump.join().unwrap();
+
+        // nothing new: the edges are cleared
+        assert!(!reader.update());
+        assert!(reader.input().kbd.is_key_down(Key::A));
+        assert!(!reader.input().kbd.is_key_pressed(Key::A));
+    }
+}
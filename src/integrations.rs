@@ -0,0 +1,17 @@
+/*!
+Bridges to UI libraries
+
+UI layers drawn over the game (e.g. a debug overlay) take input focus. The bridges capture
+[`Input`] while they do, so that typing into a text field doesn't trigger game actions. They
+share the semantics of the [`capture`] module.
+
+[`Input`]: crate::Input
+*/
+
+pub mod capture;
+
+#[cfg(feature = "egui")]
+pub mod egui;
+
+#[cfg(feature = "imgui")]
+pub mod imgui;
@@ -0,0 +1,223 @@
+//! Capture semantics shared by the UI bridges
+
+use std::{cell::Cell, rc::Rc};
+
+use crate::Input;
+
+/// Captures [`Input`] while a UI layer wants keyboard or pointer input
+///
+/// Keyboard queries see every key up until the capture ends, and keys that went down while
+/// captured don't fire presses after that. Raw keys and the state snapshots are left intact.
+/// See [`Input::set_keyboard_captured`].
+pub fn apply_capture(input: &mut Input, wants_keyboard: bool, wants_pointer: bool) {
+    input.set_keyboard_captured(wants_keyboard);
+    input.set_pointer_captured(wants_pointer);
+}
+
+/// Capture requests shared with event filters of [`FilteredBackend`]
+///
+/// Update it with [`SharedCapture::set`] each frame after the UI has run. The filters drop presses
+/// while captured, but let releases pass so that keys held before the capture don't get stuck.
+///
+/// [`FilteredBackend`]: crate::backend::FilteredBackend
+#[derive(Debug, Clone, Default)]
+pub struct SharedCapture {
+    keyboard: Rc<Cell<bool>>,
+    pointer: Rc<Cell<bool>>,
+}
+
+impl SharedCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, wants_keyboard: bool, wants_pointer: bool) {
+        self.keyboard.set(wants_keyboard);
+        self.pointer.set(wants_pointer);
+    }
+
+    pub fn wants_keyboard(&self) -> bool {
+        self.keyboard.get()
+    }
+
+    pub fn wants_pointer(&self) -> bool {
+        self.pointer.get()
+    }
+
+    /// Event filter for [`SdlBackend`](crate::backend::SdlBackend)
+    #[cfg(feature = "sdl2")]
+    pub fn sdl2_filter(&self) -> impl FnMut(&sdl2::event::Event) -> bool + 'static {
+        use sdl2::event::Event;
+
+        let capture = self.clone();
+        move |ev| match ev {
+            Event::KeyDown { .. } | Event::TextInput { .. } | Event::TextEditing { .. } => {
+                !capture.wants_keyboard()
+            }
+            Event::MouseButtonDown { .. } | Event::MouseWheel { .. } => !capture.wants_pointer(),
+            _ => true,
+        }
+    }
+
+    /// Event filter for [`WinitBackend`](crate::backend::WinitBackend)
+    #[cfg(feature = "winit")]
+    pub fn winit_filter(&self) -> impl FnMut(&winit::event::WindowEvent) -> bool + 'static {
+        use winit::event::{ElementState, WindowEvent};
+
+        let capture = self.clone();
+        move |ev| match ev {
+            WindowEvent::KeyboardInput { event, .. } => {
+                event.state == ElementState::Released || !capture.wants_keyboard()
+            }
+            WindowEvent::Ime(_) => !capture.wants_keyboard(),
+            WindowEvent::MouseInput { state, .. } => {
+                *state == ElementState::Released || !capture.wants_pointer()
+            }
+            WindowEvent::MouseWheel { .. } => !capture.wants_pointer(),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ManualBackend, Key, KeyState};
+
+    #[test]
+    fn apply_capture_sets_both_devices() {
+        let mut input = Input::new();
+        for (keyboard, pointer) in [(true, false), (false, true), (true, true), (false, false)] {
+            apply_capture(&mut input, keyboard, pointer);
+            assert_eq!(
+                (input.is_keyboard_captured(), input.is_pointer_captured()),
+                (keyboard, pointer)
+            );
+            assert_eq!(input.pointer().is_none(), pointer);
+        }
+    }
+
+    #[test]
+    fn shared_capture_is_shared() {
+        let capture = SharedCapture::new();
+        let other = capture.clone();
+        capture.set(true, false);
+        assert_eq!(
+            (other.wants_keyboard(), other.wants_pointer()),
+            (true, false)
+        );
+        other.set(false, true);
+        assert_eq!(
+            (capture.wants_keyboard(), capture.wants_pointer()),
+            (false, true)
+        );
+    }
+
+    #[test]
+    fn capture_semantics_over_frames() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let capture = SharedCapture::new();
+
+        let mut run = |captured: bool, f: &dyn Fn(&mut ManualBackend)| {
+            f(&mut backend);
+            backend.update(&mut input);
+            capture.set(captured, captured);
+            apply_capture(
+                &mut input,
+                capture.wants_keyboard(),
+                capture.wants_pointer(),
+            );
+            let q = (
+                input.is_key_pressed(Key::Enter),
+                input.is_key_down(Key::Enter),
+                input.is_raw_key_down(crate::RawKey(1)),
+            );
+            backend.end_frame(&mut input);
+            q
+        };
+
+        // raw keys are not hidden
+        assert_eq!(
+            run(true, &|b| {
+                b.key_down(Key::Enter);
+                b.raw_key_down(crate::RawKey(1));
+            }),
+            (false, false, true)
+        );
+        assert_eq!(run(false, &|_| {}), (false, false, true));
+        assert_eq!(run(false, &|b| b.key_up(Key::Enter)), (false, false, true));
+        assert_eq!(run(false, &|b| b.key_down(Key::Enter)), (true, true, true));
+    }
+
+    #[test]
+    #[cfg(feature = "sdl2")]
+    fn sdl2_filter_drops_presses_while_captured() {
+        use sdl2::{
+            event::Event,
+            keyboard::{Keycode, Mod},
+            mouse::MouseButton,
+        };
+
+        use crate::{
+            backend::{FilteredBackend, SdlBackend},
+            Backend,
+        };
+
+        let key = |down: bool| {
+            let (timestamp, window_id, keycode, scancode, keymod, repeat) =
+                (0, 0, Some(Keycode::Return), None, Mod::NOMOD, false);
+            if down {
+                Event::KeyDown {
+                    timestamp,
+                    window_id,
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                }
+            } else {
+                Event::KeyUp {
+                    timestamp,
+                    window_id,
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                }
+            }
+        };
+        let click = Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x: 0,
+            y: 0,
+        };
+
+        let capture = SharedCapture::new();
+        let mut backend = FilteredBackend::new(SdlBackend::new());
+        backend.set_event_filter(capture.sdl2_filter());
+        let mut input = Input::new();
+
+        // held before the capture
+        backend.on_event(&mut input, &key(true));
+        assert!(input.kbd.cur().is_down(Key::Enter));
+
+        capture.set(true, false);
+        // the release passes so that the key doesn't get stuck
+        backend.on_event(&mut input, &key(false));
+        assert!(!input.kbd.cur().is_down(Key::Enter));
+        backend.on_event(&mut input, &key(true));
+        assert!(!input.kbd.cur().is_down(Key::Enter));
+        // the pointer is not captured
+        backend.on_event(&mut input, &click);
+        assert!(input.pointer.is_down(crate::MouseButton::Left));
+
+        capture.set(false, false);
+        backend.on_event(&mut input, &key(true));
+        assert!(input.kbd.cur().is_down(Key::Enter));
+    }
+}
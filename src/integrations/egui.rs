@@ -0,0 +1,20 @@
+/*!
+[`egui`] bridge
+
+Each frame, call [`apply_context`] (or [`apply_capture`]) after egui has run and before the
+game reads [`Input`].
+
+To keep captured events from reaching [`Input`] at all, wrap the backend with
+[`FilteredBackend`] and a filter made by [`SharedCapture`].
+
+[`FilteredBackend`]: crate::backend::FilteredBackend
+*/
+
+use crate::Input;
+
+pub use super::capture::{apply_capture, SharedCapture};
+
+/// [`apply_capture`] with the requests of the [`egui::Context`]
+pub fn apply_context(input: &mut Input, ctx: &egui::Context) {
+    self::apply_capture(input, ctx.wants_keyboard_input(), ctx.wants_pointer_input());
+}
@@ -0,0 +1,105 @@
+/*!
+[`imgui`] bridge
+
+Call [`init_key_map`] once after creating the imgui context. Each frame, call
+[`forward_events`] before `imgui::Context::frame` so that imgui sees the keys and text of
+[`Input`] without a second event pump. After imgui has run, call [`sync`] before the game reads
+[`Input`].
+
+To keep captured events from reaching [`Input`] at all, wrap the backend with
+[`FilteredBackend`] and a filter made by [`SharedCapture`].
+
+[`FilteredBackend`]: crate::backend::FilteredBackend
+*/
+
+use crate::{Input, Key, XdlEvent};
+
+pub use super::capture::{apply_capture, SharedCapture};
+
+/// [`apply_capture`] with `io.want_capture_keyboard` and `io.want_capture_mouse`
+pub fn sync(input: &mut Input, io: &imgui::Io) {
+    self::apply_capture(input, io.want_capture_keyboard, io.want_capture_mouse);
+}
+
+/// Points imgui's `key_map` at [`Key`] indices. Call it once after creating the context.
+///
+/// imgui 0.8 reads `keys_down` through the `key_map`, and [`forward_events`] indexes `keys_down`
+/// by [`Key`].
+pub fn init_key_map(io: &mut imgui::Io) {
+    for &key in &imgui::Key::VARIANTS {
+        if let Some(xdl_key) = self::xdl_key(key) {
+            io[key] = xdl_key as u32;
+        }
+    }
+}
+
+/// Adds the key events and the text of the current frame to imgui
+///
+/// Events are forwarded even while [`Input`] is captured; imgui is the one taking them.
+pub fn forward_events(input: &Input, io: &mut imgui::Io) {
+    for ev in input.kbd.log() {
+        let (key, down) = match *ev {
+            XdlEvent::KeyDown(key, _) => (key, true),
+            XdlEvent::KeyUp(key, _) => (key, false),
+            _ => continue,
+        };
+        io.keys_down[key as usize] = down;
+    }
+
+    // modifier state is either side of the modifier
+    let cur = input.kbd.states.current();
+    io.key_ctrl = cur.is_down(Key::LCtrl) || cur.is_down(Key::RCtrl);
+    io.key_shift = cur.is_down(Key::LShift) || cur.is_down(Key::RShift);
+    io.key_alt = cur.is_down(Key::LAlt) || cur.is_down(Key::RAlt);
+    io.key_super = cur.is_down(Key::LMeta) || cur.is_down(Key::RMeta);
+
+    for c in input.text.text().chars() {
+        io.add_input_character(c);
+    }
+}
+
+/// The [`Key`] for imgui's named key
+pub fn xdl_key(key: imgui::Key) -> Option<Key> {
+    use imgui::Key as I;
+
+    Some(match key {
+        I::Tab => Key::Tab,
+        I::LeftArrow => Key::Left,
+        I::RightArrow => Key::Right,
+        I::UpArrow => Key::Up,
+        I::DownArrow => Key::Down,
+        I::PageUp => Key::PageUp,
+        I::PageDown => Key::PageDown,
+        I::Home => Key::Home,
+        I::End => Key::End,
+        I::Insert => Key::Insert,
+        I::Delete => Key::Delete,
+        I::Backspace => Key::Back,
+        I::Space => Key::Space,
+        I::Enter => Key::Enter,
+        I::Escape => Key::Escape,
+        I::A => Key::A,
+        I::C => Key::C,
+        I::V => Key::V,
+        I::X => Key::X,
+        I::Y => Key::Y,
+        I::Z => Key::Z,
+        // keypad enter is not distinguished from enter
+        I::KeyPadEnter => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_map_is_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for &key in &imgui::Key::VARIANTS {
+            if let Some(xdl_key) = self::xdl_key(key) {
+                assert!(seen.insert(xdl_key), "{:?} is mapped twice", xdl_key);
+            }
+        }
+    }
+}
@@ -11,6 +11,9 @@ easier serde, mouse, gamepad, touchpad, more virtual input, ..
 */
 
 pub mod backend;
+#[cfg(feature = "serde")]
+pub mod bindings;
+pub mod integrations;
 pub mod utils;
 pub mod vi;
 
@@ -20,16 +23,87 @@ mod input;
 pub use crate::{
     axis::*,
     input::{
-        keyboard::{Key, Keyboard},
-        Input,
+        any::{AnyInput, AnyInputFilter},
+        device::{CustomInput, Device},
+        event::XdlEvent,
+        keyboard::{
+            FreezeMode, Key, KeyState, Keyboard, KeyboardStateSnapshot, KeyboardView,
+            ParseKeyError, ParseRawKeyError, RawKey,
+        },
+        pointer::{MouseButton, Pointer},
+        remap::{KeyRemap, RemapError},
+        shared::SharedInput,
+        stats::{InputStats, KeyStats},
+        text::{Composition, TextInput},
+        threaded::{InputReader, InputWriter, ThreadedInput},
+        Input, InputBuilder, InputState,
     },
 };
 
 /// Updates [`Input`] for a specific platform such as SDL2
+///
+/// Each frame, pass the frame's events to [`Backend::on_event`] and then call
+/// [`Backend::on_end_frame`] exactly once, after the game has read the [`Input`].
+/// [`Backend::on_frame`] does both.
 pub trait Backend {
     type Event;
     type Key;
 
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> backend::EventOutcome;
+
+    /// Swaps the device buffers and calls [`Backend::post_end_frame`]
+    fn on_end_frame(&mut self, input: &mut Input);
+
+    /// Backend-specific work after the device buffers are swapped
+    fn post_end_frame(&mut self, _input: &mut Input) {}
+
+    /// Devices the backend writes to
+    fn capabilities(&self) -> backend::Capabilities {
+        backend::Capabilities::KEYBOARD
+    }
+
+    /// Processes all the events of a frame and then ends the frame
+    ///
+    /// Debug builds assert that the device buffers are swapped exactly once.
+    fn on_frame(&mut self, input: &mut Input, events: &[Self::Event]) {
+        for ev in events {
+            self.on_event(input, ev);
+        }
+
+        let end_frames = input.end_frames;
+        self.on_end_frame(input);
+        debug_assert_eq!(
+            input.end_frames,
+            end_frames + 1,
+            "`on_end_frame` has to swap the device buffers exactly once"
+        );
+    }
+}
+
+/// [`Backend`] with `&self` receivers
+#[deprecated(
+    since = "0.4.0",
+    note = "implement `Backend`, which takes `&mut self`, instead"
+)]
+pub trait SharedBackend {
+    type Event;
+    type Key;
+
     fn on_event(&self, input: &mut Input, ev: &Self::Event);
     fn on_end_frame(&self, input: &mut Input);
 }
+
+#[allow(deprecated)]
+impl<T: SharedBackend> Backend for T {
+    type Event = T::Event;
+    type Key = T::Key;
+
+    fn on_event(&mut self, input: &mut Input, ev: &Self::Event) -> backend::EventOutcome {
+        SharedBackend::on_event(self, input, ev);
+        backend::EventOutcome::IGNORED
+    }
+
+    fn on_end_frame(&mut self, input: &mut Input) {
+        SharedBackend::on_end_frame(self, input);
+    }
+}
@@ -2,8 +2,36 @@
 Internal utilities
 */
 
-/// Double buffer
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Double buffer of the current and previous frame's states
+///
+/// The end-of-frame step is either [`Double::store_current_to_prev`] (the current state carries
+/// over) or [`Double::swap`] (the current state is rebuilt each frame).
+///
+/// ```
+/// use xdl::utils::Double;
+///
+/// let mut x = Double::new(1, 0);
+/// x.store_current_to_prev();
+/// *x.current_mut() += 1;
+/// assert_eq!((*x.current(), *x.previous()), (2, 1));
+///
+/// x.swap();
+/// assert_eq!((*x.current(), *x.previous()), (1, 2));
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Double<T> {
     /// Front buffer at initial state
     pub a: T,
@@ -37,6 +65,33 @@ impl<T> Double<T> {
         self.counter = !self.counter;
     }
 
+    /// Front buffer
+    pub fn current(&self) -> &T {
+        self.front()
+    }
+
+    pub fn current_mut(&mut self) -> &mut T {
+        self.front_mut()
+    }
+
+    /// Back buffer
+    pub fn previous(&self) -> &T {
+        self.back()
+    }
+
+    pub fn previous_mut(&mut self) -> &mut T {
+        self.back_mut()
+    }
+
+    /// Applies the function to both buffers, keeping which one is front
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Double<U> {
+        Double {
+            a: f(self.a),
+            b: f(self.b),
+            counter: self.counter,
+        }
+    }
+
     pub fn into_front(self) -> T {
         if self.counter {
             self.a
@@ -85,3 +140,627 @@ impl<T> Double<T> {
         }
     }
 }
+
+impl<T: Clone> Double<T> {
+    /// Copies the front buffer to the back buffer, reusing its allocation
+    pub fn store_current_to_prev(&mut self) {
+        if self.counter {
+            self.b.clone_from(&self.a);
+        } else {
+            self.a.clone_from(&self.b);
+        }
+    }
+}
+
+/// Last `N` items in a fixed-size ring buffer, without allocation
+///
+/// ```
+/// use xdl::utils::History;
+///
+/// let mut h = History::<u32, 3>::new();
+/// for i in 0..5 {
+///     h.push(i);
+/// }
+/// assert_eq!(h.latest(), Some(&4));
+/// assert_eq!(h.get(2), Some(&2));
+/// assert_eq!(h.get(3), None);
+/// assert_eq!(h.iter().copied().collect::<Vec<_>>(), vec![4, 3, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct History<T, const N: usize> {
+    items: [Option<T>; N],
+    /// Index of the next write
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Default for History<T, N> {
+    fn default() -> Self {
+        Self {
+            items: std::array::from_fn(|_| None),
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> History<T, N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overwrites the oldest item if full
+    pub fn push(&mut self, item: T) {
+        if N == 0 {
+            return;
+        }
+        self.items[self.head] = Some(item);
+        self.head = (self.head + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    pub fn latest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Item pushed `back` pushes before the latest one. `None` if it's not recorded
+    pub fn get(&self, back: usize) -> Option<&T> {
+        if back >= self.len {
+            return None;
+        }
+        let ix = (self.head + N - 1 - back) % N;
+        self.items[ix].as_ref()
+    }
+
+    /// Newest to oldest
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len).filter_map(move |back| self.get(back))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        for item in &mut self.items {
+            *item = None;
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+const DIRTY: u8 = 0b100;
+
+/// Lock-free triple buffer for one writer thread and one reader thread
+///
+/// The writer fills its own buffer and publishes it; the reader picks up the latest published
+/// buffer. Neither side ever waits for the other, and frames published while the reader is busy
+/// are skipped.
+///
+/// ```
+/// use std::thread;
+/// use xdl::utils::TripleBuffer;
+///
+/// let (mut tx, mut rx) = TripleBuffer::split(0);
+/// let writer = thread::spawn(move || {
+///     for i in 1..=100 {
+///         tx.write(i);
+///     }
+/// });
+/// writer.join().unwrap();
+/// assert!(rx.update());
+/// assert_eq!(*rx.read(), 100);
+/// ```
+///
+/// The reader hands out `&T`, so it's shared between threads only if `T` is `Sync`:
+///
+/// ```compile_fail
+/// use std::{cell::Cell, thread};
+/// use xdl::utils::TripleBuffer;
+///
+/// let (_tx, rx) = TripleBuffer::split(Cell::new(0));
+/// thread::scope(|s| {
+///     s.spawn(|| rx.read().set(1));
+///     rx.read().set(2);
+/// });
+/// ```
+pub struct TripleBuffer<T> {
+    slots: [UnsafeCell<T>; 3],
+    /// Index of the back buffer, with [`DIRTY`] set if it's newer than the reader's
+    back: AtomicU8,
+}
+
+// The writer and the reader each own one slot at a time, exchanged only through `back`. `T` is
+// `Sync` since `TripleReader::read` lends `&T` through `&self`
+unsafe impl<T: Send + Sync> Sync for TripleBuffer<T> {}
+
+impl<T> fmt::Debug for TripleBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TripleBuffer")
+            .field("back", &self.back)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Clone> TripleBuffer<T> {
+    /// Creates the writer and the reader, every buffer starting as `initial`
+    pub fn split(initial: T) -> (TripleWriter<T>, TripleReader<T>) {
+        let shared = Arc::new(Self {
+            slots: [
+                UnsafeCell::new(initial.clone()),
+                UnsafeCell::new(initial.clone()),
+                UnsafeCell::new(initial),
+            ],
+            back: AtomicU8::new(1),
+        });
+        let writer = TripleWriter {
+            shared: Arc::clone(&shared),
+            ix: 0,
+        };
+        let reader = TripleReader { shared, ix: 2 };
+        (writer, reader)
+    }
+}
+
+/// Writing side of a [`TripleBuffer`]
+#[derive(Debug)]
+pub struct TripleWriter<T> {
+    shared: Arc<TripleBuffer<T>>,
+    ix: u8,
+}
+
+impl<T> TripleWriter<T> {
+    /// Buffer to be published next. It holds an older value, so overwrite all of it
+    pub fn buffer_mut(&mut self) -> &mut T {
+        // the slot is owned by the writer until published
+        unsafe { &mut *self.shared.slots[self.ix as usize].get() }
+    }
+
+    /// Hands the buffer to the reader and takes the back buffer
+    pub fn publish(&mut self) {
+        let old = self.shared.back.swap(self.ix | DIRTY, Ordering::AcqRel);
+        self.ix = old & !DIRTY;
+    }
+
+    pub fn write(&mut self, value: T) {
+        *self.buffer_mut() = value;
+        self.publish();
+    }
+}
+
+/// Reading side of a [`TripleBuffer`]
+#[derive(Debug)]
+pub struct TripleReader<T> {
+    shared: Arc<TripleBuffer<T>>,
+    ix: u8,
+}
+
+impl<T> TripleReader<T> {
+    /// If the writer has published since the last [`TripleReader::update`]
+    pub fn has_update(&self) -> bool {
+        self.shared.back.load(Ordering::Relaxed) & DIRTY != 0
+    }
+
+    /// Takes the latest published buffer. Returns `false` if there's nothing new
+    pub fn update(&mut self) -> bool {
+        if !self.has_update() {
+            return false;
+        }
+        let old = self.shared.back.swap(self.ix, Ordering::AcqRel);
+        self.ix = old & !DIRTY;
+        true
+    }
+
+    /// Buffer taken on the last [`TripleReader::update`]
+    pub fn read(&self) -> &T {
+        // the slot is owned by the reader until the next update
+        unsafe { &*self.shared.slots[self.ix as usize].get() }
+    }
+}
+
+/// Set of indices in `0..256`, used for key states
+///
+/// On `serde`, it's written as 32 bytes (little endian).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BitSet256 {
+    words: [u32; 8],
+}
+
+impl BitSet256 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_words(words: [u32; 8]) -> Self {
+        Self { words }
+    }
+
+    pub fn words(&self) -> &[u32; 8] {
+        &self.words
+    }
+
+    /// Panics if the index is out of range
+    pub fn set(&mut self, index: usize) {
+        self.words[index >> 5] |= 1 << (index & 0x1f);
+    }
+
+    /// Panics if the index is out of range
+    pub fn clear(&mut self, index: usize) {
+        self.words[index >> 5] &= !(1 << (index & 0x1f));
+    }
+
+    /// `false` if the index is out of range
+    pub fn get(&self, index: usize) -> bool {
+        index < 256 && self.words[index >> 5] & (1 << (index & 0x1f)) != 0
+    }
+
+    pub fn clear_all(&mut self) {
+        self.words = [0; 8];
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// If every bit set in `self` is set in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .all(|(w, o)| w & !o == 0)
+    }
+
+    /// If no bit is set in both
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .all(|(w, o)| w & o == 0)
+    }
+
+    /// Number of the set bits
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Indices of the set bits in ascending order
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                // clear the lowest set bit
+                word &= word - 1;
+                Some(i * 32 + bit)
+            })
+        })
+    }
+
+    /// Bits set in `self` but not in `other`, e.g. pressed keys from the current and previous
+    /// states
+    pub fn diff(&self, other: &Self) -> Self {
+        let mut words = self.words;
+        for (w, o) in words.iter_mut().zip(other.words.iter()) {
+            *w &= !o;
+        }
+        Self { words }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut words = self.words;
+        for (w, o) in words.iter_mut().zip(other.words.iter()) {
+            *w |= o;
+        }
+        Self { words }
+    }
+
+    /// Bits set in either but not both, e.g. changed keys from the current and previous states
+    pub fn symmetric_diff(&self, other: &Self) -> Self {
+        let mut words = self.words;
+        for (w, o) in words.iter_mut().zip(other.words.iter()) {
+            *w ^= o;
+        }
+        Self { words }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut words = self.words;
+        for (w, o) in words.iter_mut().zip(other.words.iter()) {
+            *w &= o;
+        }
+        Self { words }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, word) in bytes.chunks_mut(4).zip(self.words.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        let mut words = [0; 8];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        Self { words }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for BitSet256 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for BitSet256 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{self, SeqAccess, Visitor};
+        use std::convert::TryFrom;
+
+        struct BitSetVisitor;
+
+        impl<'de> Visitor<'de> for BitSetVisitor {
+            type Value = BitSet256;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("32 bytes or 8 words")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<BitSet256, E> {
+                let mut bytes = [0; 32];
+                if v.len() != bytes.len() {
+                    return Err(E::invalid_length(v.len(), &self));
+                }
+                bytes.copy_from_slice(v);
+                Ok(BitSet256::from_bytes(bytes))
+            }
+
+            /// 32 bytes, or 8 words written by older versions
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BitSet256, A::Error> {
+                let mut items = Vec::with_capacity(32);
+                while let Some(x) = seq.next_element::<u32>()? {
+                    items.push(x);
+                }
+                match items.len() {
+                    32 => {
+                        let mut bytes = [0; 32];
+                        for (b, x) in bytes.iter_mut().zip(items.iter()) {
+                            *b = u8::try_from(*x).map_err(<A::Error as de::Error>::custom)?;
+                        }
+                        Ok(BitSet256::from_bytes(bytes))
+                    }
+                    8 => {
+                        let mut words = [0; 8];
+                        words.copy_from_slice(&items);
+                        Ok(BitSet256::from_words(words))
+                    }
+                    len => Err(de::Error::invalid_length(len, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_bytes(BitSetVisitor)
+    }
+}
+
+/// FNV-1a hasher. Unlike `DefaultHasher`, the output is stable across platforms and builds
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub fn write_u32(&mut self, x: u32) {
+        self.write(&x.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, x: u64) {
+        self.write(&x.to_le_bytes());
+    }
+
+    /// Length-prefixed, so that adjacent strings don't run into each other
+    pub fn write_str(&mut self, s: &str) {
+        self.write_u32(s.len() as u32);
+        self.write(s.as_bytes());
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+/// Advances the xorshift64 state and returns it. A zero state stays zero
+///
+/// It's the random number generator of the reproducible event streams and of the tests.
+pub(crate) fn xorshift64(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn double_swap_semantics() {
+        let mut x = Double::new(vec![1], vec![0]);
+        x.swap();
+        assert_eq!((x.current(), x.previous()), (&vec![0], &vec![1]));
+        x.swap();
+        assert_eq!((x.current(), x.previous()), (&vec![1], &vec![0]));
+
+        // the current state carries over after the store
+        x.swap();
+        x.current_mut().push(2);
+        x.store_current_to_prev();
+        x.current_mut().push(3);
+        assert_eq!((x.current(), x.previous()), (&vec![0, 2, 3], &vec![0, 2]));
+
+        // mutation through the back buffer
+        x.previous_mut().clear();
+        assert_eq!(x.previous(), &Vec::<i32>::new());
+
+        let y = x.clone().map(|v| v.len());
+        assert_eq!((*y.current(), *y.previous()), (3, 0));
+        assert_eq!(
+            (x.clone().into_front(), x.into_back()),
+            (vec![0, 2, 3], vec![])
+        );
+    }
+
+    #[test]
+    fn triple_buffer_stress() {
+        const N: u64 = 100_000;
+
+        let (mut tx, mut rx) = TripleBuffer::split([0; 16]);
+        let writer = std::thread::spawn(move || {
+            for i in 1..=N {
+                *tx.buffer_mut() = [i; 16];
+                tx.publish();
+            }
+        });
+
+        let mut last = 0;
+        while last < N {
+            rx.update();
+            let buf = rx.read();
+            assert!(buf.iter().all(|x| *x == buf[0]), "torn read: {:?}", buf);
+            assert!(buf[0] >= last, "went back in time");
+            last = buf[0];
+        }
+        writer.join().unwrap();
+        assert!(!rx.update());
+    }
+
+    fn to_model(bits: &BitSet256) -> HashSet<u16> {
+        (0..256)
+            .filter(|i| bits.get(*i))
+            .map(|i| i as u16)
+            .collect()
+    }
+
+    fn check_against(bits: &BitSet256, model: &HashSet<u16>) {
+        let mut ones = model.iter().map(|i| *i as usize).collect::<Vec<_>>();
+        ones.sort_unstable();
+        assert_eq!(bits.iter_ones().collect::<Vec<_>>(), ones);
+        assert_eq!(bits.count(), model.len());
+        assert_eq!(bits.is_empty(), model.is_empty());
+        assert_eq!(&BitSet256::from_bytes(bits.to_bytes()), bits);
+    }
+
+    #[test]
+    fn bitset_matches_hash_set_model() {
+        let mut seed = 0x2545_f491_4f6c_dd1d;
+        let (mut a, mut b) = (BitSet256::new(), BitSet256::new());
+        let (mut ma, mut mb) = (HashSet::new(), HashSet::new());
+
+        for _ in 0..10_000 {
+            let r = self::xorshift64(&mut seed) as u32;
+            let index = (r >> 8) as u16 % 256;
+            let (bits, model) = if r & 1 == 0 {
+                (&mut a, &mut ma)
+            } else {
+                (&mut b, &mut mb)
+            };
+            match (r >> 1) % 16 {
+                0 => {
+                    bits.clear_all();
+                    model.clear();
+                }
+                1..=8 => {
+                    bits.set(index as usize);
+                    model.insert(index);
+                }
+                _ => {
+                    bits.clear(index as usize);
+                    model.remove(&index);
+                }
+            }
+            assert_eq!(bits.get(index as usize), model.contains(&index));
+            check_against(bits, model);
+
+            assert_eq!(to_model(&a.diff(&b)), &ma - &mb);
+            assert_eq!(to_model(&a.union(&b)), &ma | &mb);
+            assert_eq!(to_model(&a.symmetric_diff(&b)), &ma ^ &mb);
+            assert_eq!(to_model(&a.intersection(&b)), &ma & &mb);
+            assert_eq!(a.is_subset(&b), ma.is_subset(&mb));
+            assert_eq!(a.is_disjoint(&b), ma.is_disjoint(&mb));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn bitset_serde_round_trip() {
+        let bits = BitSet256::from_words([1, 0, 0, 0, 0, 0, 0, 1 << 31]);
+        let s = ron::to_string(&bits).unwrap();
+        assert_eq!(ron::from_str::<BitSet256>(&s).unwrap(), bits);
+    }
+
+    #[test]
+    fn history_wrap_around_and_underflow() {
+        let mut h = History::<u32, 3>::new();
+        assert_eq!((h.len(), h.latest(), h.get(0)), (0, None, None));
+
+        h.push(0);
+        h.push(1);
+        // underflow: further back than recorded
+        assert_eq!((h.len(), h.get(1), h.get(2)), (2, Some(&0), None));
+
+        // wraps around twice
+        for i in 2..8 {
+            h.push(i);
+        }
+        assert_eq!(h.len(), h.capacity());
+        assert_eq!(h.iter().copied().collect::<Vec<_>>(), vec![7, 6, 5]);
+        assert_eq!((h.get(3), h.get(usize::MAX)), (None, None));
+
+        h.clear();
+        assert!(h.is_empty());
+        assert_eq!((h.latest(), h.iter().count()), (None, 0));
+        h.push(8);
+        assert_eq!(h.iter().copied().collect::<Vec<_>>(), vec![8]);
+
+        // zero capacity records nothing
+        let mut h = History::<u32, 0>::new();
+        h.push(0);
+        assert_eq!((h.len(), h.latest()), (0, None));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn double_serde_keeps_the_front() {
+        let mut x = Double::new(1, 2);
+        x.swap();
+        let y: Double<i32> = ron::from_str(&ron::to_string(&x).unwrap()).unwrap();
+        assert_eq!((*y.current(), *y.previous()), (2, 1));
+    }
+}
@@ -28,41 +28,119 @@ Latest inputs always come as current state.
 TODO: add RON examples
 */
 
-use std::time::Duration;
+use std::{
+    convert::TryFrom,
+    fmt,
+    str::FromStr,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
 
 use crate::{
-    axis::{Dir4, Dir8, Sign},
-    Input, Key,
+    axis::{Axis2d, Dir4, Dir8, Sign},
+    utils::BitSet256,
+    CustomInput, Key, KeyState, MouseButton, Pointer, RawKey,
 };
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+mod batch;
+mod cheat_sheet;
+mod cooldown;
+mod group;
+mod key_names;
+mod packed;
+mod playback;
+pub mod presets;
+mod prompt;
+mod rollback;
+mod step;
+mod sticky;
+mod system;
+mod tap_hold;
+mod text_edit;
+mod toggle;
+
+pub use self::{
+    batch::{BatchButton, ButtonBatch},
+    cheat_sheet::{
+        CheatSheet, CheatSheetCategory, CheatSheetEntry, CheatSheetOptions, CheatSheetRow,
+    },
+    cooldown::CooldownButton,
+    group::{ExclusiveGroup, ExclusiveMode},
+    key_names::{KeyNameTable, LocalizedDisplay},
+    packed::{ActionMap, ActionMapState, ActionRegistration, PackedInput, RemoteActions},
+    playback::Macro,
+    prompt::{PromptHint, PromptId, PromptMap},
+    rollback::{ActionMapLayout, PayloadError, RollbackAdapter, RollbackState},
+    step::FixedStepDriver,
+    sticky::{Modifier, StickyModifiers, StickyState, StickyView},
+    system::{Handle, InputSystem, ViItem, ViObject},
+    tap_hold::TapHoldButton,
+    text_edit::TextEdit,
+    toggle::ToggleButton,
+};
+
 /// Key repeat settings
-#[derive(Debug, Clone, Copy)]
+///
+/// `Repeat` is driven by the `dt` passed to [`Button::update`]. `RepeatFrames` counts ticks
+/// instead: one tick per [`Button::update`] call or `ticks` per [`Button::update_ticks`] call, so
+/// it never drifts on a fixed timestep.
+///
+/// A zero period repeats on every tick, which is every update for `Repeat`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
 pub enum KeyRepeatConfig {
-    Repeat { first: Duration, multi: Duration },
+    Repeat {
+        first: Duration,
+        multi: Duration,
+    },
+    RepeatFrames {
+        first: u32,
+        multi: u32,
+    },
+    #[default]
     NoRepeat,
 }
 
-impl Default for KeyRepeatConfig {
-    fn default() -> Self {
-        Self::NoRepeat
-    }
-}
-
 /// Constructors
 impl KeyRepeatConfig {
     pub fn repeat(first: Duration, multi: Duration) -> Self {
         KeyRepeatConfig::Repeat { first, multi }
     }
 
+    pub fn repeat_frames(first: u32, multi: u32) -> Self {
+        KeyRepeatConfig::RepeatFrames { first, multi }
+    }
+
     pub fn no_repeat() -> Self {
         KeyRepeatConfig::NoRepeat
     }
 }
 
+/// Key repeat settings shared among buttons
+///
+/// Cloning the handle shares the config. [`SharedRepeatConfig::set`] takes effect on the next
+/// `update` of every button created with the handle.
+#[derive(Debug, Clone, Default)]
+pub struct SharedRepeatConfig(Arc<RwLock<KeyRepeatConfig>>);
+
+impl SharedRepeatConfig {
+    pub fn new(cfg: KeyRepeatConfig) -> Self {
+        Self(Arc::new(RwLock::new(cfg)))
+    }
+
+    // the config is plain `Copy` data, so a panicked holder can't leave it broken
+    pub fn get(&self) -> KeyRepeatConfig {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set(&self, cfg: KeyRepeatConfig) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = cfg;
+    }
+}
+
 // --------------------------------------------------------------------------------
 // State
 
@@ -77,50 +155,132 @@ enum RawButtonState {
     Released,
 }
 
-#[derive(Debug, Clone, Default)]
+/// Key repeat timers of a [`Button`]
+///
+/// Time is accumulated in `u64` nanoseconds instead of `Duration`s. It's as precise as
+/// `Duration` in half the size, and it saturates after 584 years. Milliseconds in `u32` would save
+/// another 8 bytes, but then `dt`s such as 16.67ms would be rounded on every update and the
+/// repeat timing would drift from the `Duration` config, so they are not used.
+#[derive(Debug, Clone)]
 struct KeyRepeatState {
     /// Key repeat configuration
     config: KeyRepeatConfig,
-    /// Loops when it repeats
-    accum_repeat: Duration,
+    /// Overwrites `config` on each update if any
+    shared: Option<SharedRepeatConfig>,
+    /// Loops when it repeats. Nanoseconds
+    accum_repeat: u64,
+    /// Does not loop. Nanoseconds
+    accum_down: u64,
+    /// Loops when it repeats (frame-based repeat)
+    accum_repeat_ticks: u32,
     /// Does not loop
-    accum_down: Duration,
+    down_ticks: u32,
     /// True until first repeat
     is_on_first_repeat: bool,
+    /// Upper bound of the repeat count returned from an update
+    max_repeats: u32,
+}
+
+impl Default for KeyRepeatState {
+    fn default() -> Self {
+        Self::new(KeyRepeatConfig::default())
+    }
+}
+
+/// Saturates after 584 years
+fn nanos(d: Duration) -> u64 {
+    u64::try_from(d.as_nanos()).unwrap_or(u64::MAX)
 }
 
 impl KeyRepeatState {
     pub fn new(repeat: KeyRepeatConfig) -> Self {
         Self {
             config: repeat,
-            accum_repeat: Duration::new(0, 0),
-            accum_down: Duration::new(0, 0),
+            shared: None,
+            accum_repeat: 0,
+            accum_down: 0,
+            accum_repeat_ticks: 0,
+            down_ticks: 0,
             is_on_first_repeat: false,
+            max_repeats: 4,
+        }
+    }
+
+    pub fn new_shared(shared: SharedRepeatConfig) -> Self {
+        Self {
+            config: shared.get(),
+            shared: Some(shared),
+            ..Default::default()
+        }
+    }
+
+    /// Resets to the config, keeping the repeat cap
+    fn reconfigure(&mut self, repeat: Self) {
+        *self = Self {
+            max_repeats: self.max_repeats,
+            ..repeat
+        };
+    }
+
+    /// Pulls the latest shared config. Accumulators are kept as-is.
+    fn sync_config(&mut self) {
+        if let Some(shared) = &self.shared {
+            self.config = shared.get();
         }
     }
 }
 
 /// Lifecycle
 impl KeyRepeatState {
-    /// Returns if it's repeating or not
-    fn update(&mut self, state: RawButtonState, dt: Duration) -> bool {
+    fn reset(&mut self, is_pressed: bool) {
+        self.accum_repeat = 0;
+        self.accum_down = 0;
+        self.accum_repeat_ticks = 0;
+        self.down_ticks = 0;
+        self.is_on_first_repeat = is_pressed;
+    }
+
+    /// Starts waiting for the first repeat again, keeping the down time
+    fn restart_repeat(&mut self) {
+        self.accum_repeat = 0;
+        self.accum_repeat_ticks = 0;
+        self.is_on_first_repeat = true;
+    }
+
+    /// Returns the number of repeats, clamped to `max_repeats`
+    fn update(&mut self, state: RawButtonState, dt: Duration) -> u32 {
+        self.sync_config();
         match state {
-            RawButtonState::Up | RawButtonState::Released => {
-                self.accum_repeat = Duration::new(0, 0);
-                self.accum_down = Duration::new(0, 0);
-                self.is_on_first_repeat = false;
-                false
+            RawButtonState::Up => {
+                self.reset(false);
+                0
+            }
+            // keep the down time on the released frame
+            RawButtonState::Released => {
+                let accum_down = self.accum_down;
+                let down_ticks = self.down_ticks;
+                self.reset(false);
+                self.accum_down = accum_down;
+                self.down_ticks = down_ticks;
+                0
             }
             RawButtonState::Pressed => {
-                self.accum_repeat = Duration::new(0, 0);
-                self.accum_down = Duration::new(0, 0);
-                self.is_on_first_repeat = true;
-                false
+                self.reset(true);
+                0
             }
             // Down state may be repeating
             RawButtonState::Down => {
                 let repeat_duration = match self.config {
-                    KeyRepeatConfig::NoRepeat => return false,
+                    KeyRepeatConfig::NoRepeat => {
+                        self.accum_down = self.accum_down.saturating_add(self::nanos(dt));
+                        self.down_ticks = self.down_ticks.saturating_add(1);
+                        return 0;
+                    }
+                    // one `update` call is one tick
+                    KeyRepeatConfig::RepeatFrames { .. } => {
+                        self.accum_down = self.accum_down.saturating_add(self::nanos(dt));
+                        return self.tick(1);
+                    }
                     KeyRepeatConfig::Repeat { first, multi } => {
                         if self.is_on_first_repeat {
                             first
@@ -130,40 +290,113 @@ impl KeyRepeatState {
                     }
                 };
 
-                self.accum_repeat += dt;
-                self.accum_down += dt;
+                let repeat_duration = self::nanos(repeat_duration);
+                self.accum_repeat = self.accum_repeat.saturating_add(self::nanos(dt));
+                self.accum_down = self.accum_down.saturating_add(self::nanos(dt));
+                self.down_ticks = self.down_ticks.saturating_add(1);
 
-                let mut is_repeating = false;
-
-                // basically it's just an if branch but in case too long time passed
-                while self.accum_repeat > repeat_duration {
-                    is_repeating = true;
-                    self.is_on_first_repeat = false;
-                    self.accum_repeat -= repeat_duration;
+                if self.accum_repeat <= repeat_duration {
+                    return 0;
                 }
+                self.is_on_first_repeat = false;
+
+                // the period in use repeats as many times as it fits in after a long frame stall
+                let count = match (self.accum_repeat - 1).checked_div(repeat_duration) {
+                    Some(count) => {
+                        self.accum_repeat -= count * repeat_duration;
+                        count
+                    }
+                    // zero period repeats once per update
+                    None => {
+                        self.accum_repeat = 0;
+                        1
+                    }
+                };
+
+                self.clamp(count)
+            }
+        }
+    }
+
+    /// Returns the number of repeats. Only [`KeyRepeatConfig::RepeatFrames`] repeats.
+    fn update_ticks(&mut self, state: RawButtonState, ticks: u32) -> u32 {
+        self.sync_config();
+        match state {
+            RawButtonState::Up => {
+                self.reset(false);
+                0
+            }
+            // keep the down time on the released frame
+            RawButtonState::Released => {
+                let accum_down = self.accum_down;
+                let down_ticks = self.down_ticks;
+                self.reset(false);
+                self.accum_down = accum_down;
+                self.down_ticks = down_ticks;
+                0
+            }
+            RawButtonState::Pressed => {
+                self.reset(true);
+                0
+            }
+            RawButtonState::Down => self.tick(ticks),
+        }
+    }
+
+    /// Advances the frame-based repeat while the button is down
+    fn tick(&mut self, ticks: u32) -> u32 {
+        self.down_ticks = self.down_ticks.saturating_add(ticks);
+
+        // zero period repeats on every tick
+        let (first, multi) = match self.config {
+            KeyRepeatConfig::RepeatFrames { first, multi } => (first.max(1), multi.max(1)),
+            _ => return 0,
+        };
+
+        self.accum_repeat_ticks = self.accum_repeat_ticks.saturating_add(ticks);
 
-                is_repeating
+        let mut count = 0u32;
+        if self.is_on_first_repeat {
+            if self.accum_repeat_ticks < first {
+                return 0;
             }
+            count += 1;
+            self.is_on_first_repeat = false;
+            self.accum_repeat_ticks -= first;
         }
+
+        let n = self.accum_repeat_ticks / multi;
+        count = count.saturating_add(n);
+        self.accum_repeat_ticks -= n * multi;
+
+        self.clamp(u64::from(count))
+    }
+
+    fn clamp(&self, count: u64) -> u32 {
+        u32::try_from(count).map_or(self.max_repeats, |c| c.min(self.max_repeats))
     }
 }
 
 /// [`Key`] with optionally modifier keys
+///
+/// It's pressed when the key is pressed while the modifiers are down, either the left or the
+/// right key of each. The modifiers can be pressed before the key.
+///
+/// On `serde`, it's deserialized from either the struct form or a string such as `"Ctrl+S"`. Use
+/// `key_entry_serde_str` or `key_entries_serde_str` to serialize as strings. Formats that are
+/// not human-readable always use the string.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyEntry {
-    key: Key,
-    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
-    ctrl: bool,
-    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
-    shift: bool,
-    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
-    meta: bool,
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
 }
 
 #[cfg(feature = "serde")]
 fn is_false(b: &bool) -> bool {
-    *b == false
+    !*b
 }
 
 impl From<Key> for KeyEntry {
@@ -172,6 +405,7 @@ impl From<Key> for KeyEntry {
             key,
             ctrl: false,
             shift: false,
+            alt: false,
             meta: false,
         }
     }
@@ -183,214 +417,1316 @@ impl KeyEntry {
             key,
             ctrl: false,
             shift: false,
+            alt: false,
             meta: false,
         }
     }
+
+    /// `[left, right]` keys of the modifiers. Either key of each modifier has to be down
+    fn modifier_keys(&self) -> impl Iterator<Item = [Key; 2]> {
+        let mods = [
+            (self.ctrl, [Key::LCtrl, Key::RCtrl]),
+            (self.shift, [Key::LShift, Key::RShift]),
+            (self.alt, [Key::LAlt, Key::RAlt]),
+            (self.meta, [Key::LMeta, Key::RMeta]),
+        ];
+        IntoIterator::into_iter(mods)
+            .filter(|(on, _)| *on)
+            .map(|(_, keys)| keys)
+    }
 }
 
-/// Set of any kind of inputs
-#[derive(Debug, Clone, Default)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct InputBundle {
-    pub keys: Vec<KeyEntry>,
-    // pub mouse: Vec<MouseInput>,
+/// `Ctrl+Shift+Alt+Meta+Key`
+impl fmt::Display for KeyEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.meta {
+            f.write_str("Meta+")?;
+        }
+        write!(f, "{}", self.key)
+    }
 }
 
-impl InputBundle {
-    fn state(&self, input: &Input) -> RawButtonState {
-        let mut is_any_down = false;
-        let mut is_any_released = false;
+/// Error on parsing [`KeyEntry`] from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyEntryError {
+    /// The token that could not be parsed
+    pub token: String,
+}
 
-        for entry in self.keys.iter() {
-            let mut is_pressed = true;
-            let mut is_down = true;
-            let mut is_down_prev = true;
-
-            macro_rules! _add {
-                ($($key:expr),+ $(,)?) => {
-                    $(
-                        is_pressed &= input.kbd.is_key_pressed($key);
-                        is_down &= input.kbd.is_key_down($key);
-                        is_down_prev |= input.kbd.states.b.is_down($key);
-                    )+
-                };
-            }
+impl fmt::Display for ParseKeyEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown key or modifier: `{}`", self.token)
+    }
+}
 
-            _add!(entry.key);
-            if entry.ctrl {
-                _add!(Key::LCtrl, Key::RCtrl);
-            }
-            if entry.shift {
-                _add!(Key::LShift, Key::RShift);
-            }
-            if entry.meta {
-                _add!(Key::LMeta, Key::RMeta);
+impl std::error::Error for ParseKeyEntryError {}
+
+/// Parses `Ctrl+S`-style strings. Modifiers are case-insensitive.
+impl FromStr for KeyEntry {
+    type Err = ParseKeyEntryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split('+').map(str::trim).collect::<Vec<_>>();
+        let err = |token: &str| ParseKeyEntryError {
+            token: token.to_string(),
+        };
+
+        let key = tokens.pop().ok_or_else(|| err(s))?;
+        let mut entry = KeyEntry::key(key.parse().map_err(|_| err(key))?);
+
+        for m in tokens {
+            match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => entry.ctrl = true,
+                "shift" => entry.shift = true,
+                "alt" | "option" | "opt" => entry.alt = true,
+                "meta" | "cmd" | "super" | "win" => entry.meta = true,
+                _ => return Err(err(m)),
             }
+        }
 
-            if is_pressed {
-                return RawButtonState::Pressed;
+        Ok(entry)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod key_entry_serde {
+    //! [`KeyEntry`] as the struct form or a string
+
+    use super::*;
+    use serde::{
+        de::{self, value::MapAccessDeserializer, Deserializer, MapAccess, Visitor},
+        Serializer,
+    };
+
+    #[derive(Serialize, Deserialize)]
+    struct KeyEntryRepr {
+        key: Key,
+        #[serde(default, skip_serializing_if = "is_false")]
+        ctrl: bool,
+        #[serde(default, skip_serializing_if = "is_false")]
+        shift: bool,
+        #[serde(default, skip_serializing_if = "is_false")]
+        alt: bool,
+        #[serde(default, skip_serializing_if = "is_false")]
+        meta: bool,
+    }
+
+    impl Serialize for KeyEntry {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            if !serializer.is_human_readable() {
+                return serializer.collect_str(self);
             }
-            is_any_down |= is_down;
-            is_any_released |= is_down_prev && is_down;
+            let repr = KeyEntryRepr {
+                key: self.key,
+                ctrl: self.ctrl,
+                shift: self.shift,
+                alt: self.alt,
+                meta: self.meta,
+            };
+            repr.serialize(serializer)
         }
+    }
 
-        // for m in self.mouse.iter().map(|m| m.clone()) {
-        //     if input.mouse.is_pressed(m) {
-        //         return RawButtonState::Pressed;
-        //     }
-        //     is_any_down |= input.mouse.is_down(m);
-        //     is_any_released |= input.mouse.is_released(m);
-        // }
+    struct KeyEntryVisitor;
 
-        if is_any_down {
-            RawButtonState::Down
-        } else {
-            if is_any_released {
-                RawButtonState::Released
+    impl<'de> Visitor<'de> for KeyEntryVisitor {
+        type Value = KeyEntry;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("key entry struct or string such as \"Ctrl+S\"")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<KeyEntry, E> {
+            s.parse().map_err(E::custom)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<KeyEntry, A::Error> {
+            let repr = KeyEntryRepr::deserialize(MapAccessDeserializer::new(map))?;
+            Ok(KeyEntry {
+                key: repr.key,
+                ctrl: repr.ctrl,
+                shift: repr.shift,
+                alt: repr.alt,
+                meta: repr.meta,
+            })
+        }
+    }
+
+    impl<'de> Deserialize<'de> for KeyEntry {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            // non-self-describing formats can't tell the forms apart
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_any(KeyEntryVisitor)
             } else {
-                RawButtonState::Up
+                deserializer.deserialize_str(KeyEntryVisitor)
             }
         }
     }
 }
 
-/// Down | Up | Pressed | Repeating | Released
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub enum StrictButtonState {
-    Down,
-    Up,
-    Pressed,
-    Repeating,
-    Released,
+#[cfg(feature = "serde")]
+pub mod key_entry_serde_str {
+    //! `serde` [`KeyEntry`] as a string such as `"Ctrl+S"`
+
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &KeyEntry, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<KeyEntry, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        KeyEntry::deserialize(deserializer)
+    }
 }
 
 #[cfg(feature = "serde")]
-pub mod button_serde_with {
-    //! `serde` `Button` as [`InputBundle`]. NOTE: `KeyRepeatState` will be `skip`ped
+pub mod key_entries_serde_str {
+    //! `serde` `Vec<KeyEntry>` as strings such as `["Ctrl+S", "Enter"]`
 
     use super::*;
-    use serde::{
-        de::{Deserialize, Deserializer},
-        ser::{Serialize, Serializer},
-    };
+    use serde::{ser::SerializeSeq, Deserializer, Serializer};
 
-    pub fn serialize<S>(value: &Button, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(value: &[KeyEntry], serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        value.input.serialize(serializer)
+        let mut seq = serializer.serialize_seq(Some(value.len()))?;
+        for entry in value {
+            seq.serialize_element(&entry.to_string())?;
+        }
+        seq.end()
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Button, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<KeyEntry>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let input = InputBundle::deserialize(deserializer)?;
-        Ok(Button::new(input, KeyRepeatConfig::NoRepeat))
+        Vec::<KeyEntry>::deserialize(deserializer)
     }
 }
 
-/// Input bundle with repeat state
-#[derive(Debug, Clone)]
+/// Keys that have to be down at the same time
+///
+/// It's pressed on the frame the last key arrives. Serialized as a list of keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct KeyChord(pub Vec<Key>);
+
+/// `Key+Key`
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, key) in self.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str("+")?;
+            }
+            write!(f, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl KeyChord {
+    fn is_down(&self, keys: &impl KeyState) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|k| keys.is_key_down(*k))
+    }
+
+    fn is_down_prev(&self, keys: &impl KeyState) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|k| keys.was_key_down(*k))
+    }
+}
+
+/// Rectangle in window pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct Button {
-    pub input: InputBundle,
-    pub state: StrictButtonState,
-    #[cfg_attr(feature = "serde", serde(skip))]
-    repeat: KeyRepeatState,
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
 }
 
-impl Button {
-    pub fn new(bundle: InputBundle, repeat_cfg: KeyRepeatConfig) -> Self {
-        Self {
-            input: bundle,
-            state: StrictButtonState::Up,
-            repeat: KeyRepeatState::new(repeat_cfg),
-        }
+impl Region {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { x, y, w, h }
     }
 
-    pub fn set_repeat_config(&mut self, cfg: KeyRepeatConfig) {
-        self.repeat = KeyRepeatState::new(cfg);
+    /// Excludes the right and bottom edges
+    pub fn contains(&self, pos: [i32; 2]) -> bool {
+        (self.x..self.x + self.w).contains(&pos[0]) && (self.y..self.y + self.h).contains(&pos[1])
     }
+}
 
-    pub fn is_down(&self) -> bool {
-        matches!(
-            self.state,
-            StrictButtonState::Down | StrictButtonState::Pressed | StrictButtonState::Repeating
-        )
+/// Region updated by the game, e.g. per frame for a moving widget
+///
+/// Cloning the handle shares the region. Handles are equal if they share the region.
+#[derive(Debug, Clone, Default)]
+pub struct SharedRegion(Arc<RwLock<Region>>);
+
+impl SharedRegion {
+    pub fn new(region: Region) -> Self {
+        Self(Arc::new(RwLock::new(region)))
     }
 
-    pub fn is_pressed(&self) -> bool {
-        matches!(
-            self.state,
-            StrictButtonState::Pressed | StrictButtonState::Repeating
-        )
+    pub fn get(&self) -> Region {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
     }
 
-    /// How long it's been down
-    pub fn accum_down(&self) -> Duration {
-        self.repeat.accum_down
+    pub fn set(&self, region: Region) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = region;
     }
 }
 
-/// Lifecycle
-impl Button {
-    pub fn update(&mut self, input: &Input, dt: Duration) {
-        let state = self.input.state(input);
+impl PartialEq for SharedRegion {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
 
-        let is_repeating = self.repeat.update(state, dt);
+impl Eq for SharedRegion {}
 
-        self.state = if is_repeating {
-            StrictButtonState::Repeating
-        } else {
-            match state {
-                RawButtonState::Down => StrictButtonState::Down,
-                RawButtonState::Up => StrictButtonState::Up,
-                RawButtonState::Pressed => StrictButtonState::Pressed,
-                RawButtonState::Released => StrictButtonState::Released,
-            }
-        };
+/// Static or shared [`Region`] of a [`MouseEntry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RegionRef {
+    Rect(Region),
+    /// Fails to serialize, so set it after loading the bindings
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Shared(SharedRegion),
+}
+
+impl RegionRef {
+    pub fn get(&self) -> Region {
+        match self {
+            Self::Rect(region) => *region,
+            Self::Shared(shared) => shared.get(),
+        }
     }
 }
 
-// --------------------------------------------------------------------------------
-// Higher-level buttons
+impl From<Region> for RegionRef {
+    fn from(region: Region) -> Self {
+        Self::Rect(region)
+    }
+}
 
-/// Neg | Pos | Neutral
+impl From<SharedRegion> for RegionRef {
+    fn from(shared: SharedRegion) -> Self {
+        Self::Shared(shared)
+    }
+}
+
+/// Mouse button, optionally only over a region
 ///
-/// WARNING: It doesn't store key repeat configuration on `serde`.
-#[derive(Debug, Clone)]
+/// With a region, a press counts only if the cursor is in the region on the frame of the press.
+/// The entry is then down while the cursor stays in the region, so dragging out releases it and
+/// coming back presses it again, unless `track_outside` is set. The region is read on each
+/// update, so update a [`SharedRegion`] before the buttons.
+///
+/// Whether the press started in the region is kept by the [`Button`] (or other virtual input)
+/// that updates the bundle, for the first 32 mouse entries of the bundle. Entries past them with
+/// a region are never down.
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct AxisButton {
-    /// Positive input
-    #[cfg_attr(feature = "serde", serde(with = "button_serde_with"))]
-    pub pos: Button,
-    /// Negative input
-    #[cfg_attr(feature = "serde", serde(with = "button_serde_with"))]
-    pub neg: Button,
+pub struct MouseEntry {
+    pub button: MouseButton,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub region: Option<RegionRef>,
+    /// Keeps a press that started inside down while the cursor is outside, so that dragging out
+    /// and releasing completes the click
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
+    pub track_outside: bool,
 }
 
-/// Lifecycle
-impl AxisButton {
-    pub fn update(&mut self, input: &Input, dt: Duration) {
-        self.pos.update(input, dt);
-        self.neg.update(input, dt);
+impl From<MouseButton> for MouseEntry {
+    fn from(button: MouseButton) -> Self {
+        Self {
+            button,
+            region: None,
+            track_outside: false,
+        }
     }
 }
 
-impl AxisButton {
-    /// Selects down sign pressed lately
-    pub fn sign_down(&self) -> Sign {
-        match [self.pos.is_down(), self.neg.is_down()] {
-            [true, true] => {
-                if self.pos.repeat.accum_down <= self.neg.repeat.accum_down {
-                    Sign::Pos
-                } else {
-                    Sign::Neg
-                }
-            }
-            [true, false] => Sign::Pos,
+impl MouseEntry {
+    pub fn with_region(button: MouseButton, region: impl Into<RegionRef>) -> Self {
+        Self {
+            region: Some(region.into()),
+            ..Self::from(button)
+        }
+    }
+
+    pub fn track_outside(mut self, b: bool) -> Self {
+        self.track_outside = b;
+        self
+    }
+
+    /// `[is_down, was_down]`. `grabbed` is if the current press started in the region
+    fn state(&self, pointer: &Pointer, grabbed: &mut bool) -> [bool; 2] {
+        let is_down = pointer.is_down(self.button);
+        let was_down = pointer.was_down(self.button);
+        let region = match &self.region {
+            Some(region) => region.get(),
+            None => return [is_down, was_down],
+        };
+
+        // kept on the frame of the release, so that the state is the same however many times
+        // it's evaluated in a frame
+        if is_down && !was_down {
+            *grabbed = region.contains(pointer.pos());
+        } else if !is_down && !was_down {
+            *grabbed = false;
+        }
+
+        let grabbed = *grabbed;
+        let is_inside = |pos| grabbed && (self.track_outside || region.contains(pos));
+        [
+            is_down && is_inside(pointer.pos()),
+            was_down && is_inside(pointer.prev_pos()),
+        ]
+    }
+}
+
+/// Entry list of an [`InputBundle`]
+///
+/// A `Vec` by default. With the `smallvec` feature, it's a `SmallVec` that stores up to two
+/// entries inline, so that most bundles don't allocate. In exchange, an [`InputBundle`] grows
+/// from 120 to 280 bytes on 64-bit targets. Build lists with [`entries!`] or `collect` to support
+/// both.
+///
+/// [`entries!`]: crate::entries
+#[cfg(not(feature = "smallvec"))]
+pub type EntryList<T> = Vec<T>;
+
+/// Entry list of an [`InputBundle`]
+///
+/// A `Vec` by default. With the `smallvec` feature, it's a `SmallVec` that stores up to two
+/// entries inline, so that most bundles don't allocate. In exchange, an [`InputBundle`] grows
+/// from 120 to 280 bytes on 64-bit targets. Build lists with [`entries!`] or `collect` to support
+/// both.
+///
+/// [`entries!`]: crate::entries
+#[cfg(feature = "smallvec")]
+pub type EntryList<T> = smallvec::SmallVec<[T; 2]>;
+
+/// Creates an [`EntryList`](crate::vi::EntryList) like `vec!`, with or without the `smallvec`
+/// feature
+///
+/// ```
+/// use xdl::{entries, vi::InputBundle, Key, MouseButton};
+///
+/// let select = InputBundle {
+///     keys: entries![Key::Enter.into(), Key::Space.into()],
+///     mouse: entries![MouseButton::Left.into()],
+///     ..Default::default()
+/// };
+/// ```
+#[macro_export]
+macro_rules! entries {
+    ($($x:expr),* $(,)?) => {
+        <$crate::vi::EntryList<_> as ::std::iter::FromIterator<_>>::from_iter([$($x),*])
+    };
+}
+
+/// Set of any kind of inputs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputBundle {
+    pub keys: EntryList<KeyEntry>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "<[_]>::is_empty")
+    )]
+    pub chords: EntryList<KeyChord>,
+    /// Platform keys without [`Key`] translation
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "<[_]>::is_empty")
+    )]
+    pub raw: EntryList<RawKey>,
+    /// Channels of devices registered with [`Input::register_device`]
+    ///
+    /// [`Input::register_device`]: crate::Input::register_device
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "<[_]>::is_empty")
+    )]
+    pub custom: EntryList<CustomInput>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "<[_]>::is_empty")
+    )]
+    pub mouse: EntryList<MouseEntry>,
+}
+
+/// Constructors
+impl InputBundle {
+    /// Bundle of key entries only
+    ///
+    /// ```
+    /// use xdl::{vi::InputBundle, Key};
+    ///
+    /// let select = InputBundle::from_keys([Key::Enter, Key::Space]);
+    /// assert_eq!(select.keys.len(), 2);
+    /// ```
+    pub fn from_keys(keys: impl IntoIterator<Item = impl Into<KeyEntry>>) -> Self {
+        Self {
+            keys: keys.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl InputBundle {
+    /// Keys the bundle depends on. `None` if it has raw keys, custom inputs or mouse entries,
+    /// which can't be tracked by [`Key`]
+    pub fn key_deps(&self) -> Option<BitSet256> {
+        self.compile().map(|compiled| compiled.deps)
+    }
+
+    /// `None` if it has raw keys, custom inputs or mouse entries
+    fn compile(&self) -> Option<CompiledBundle> {
+        if !self.raw.is_empty() || !self.custom.is_empty() || !self.mouse.is_empty() {
+            return None;
+        }
+
+        let mut compiled = CompiledBundle::default();
+        for entry in &self.keys {
+            let mods = entry
+                .modifier_keys()
+                .map(|[l, r]| {
+                    let mut mask = BitSet256::new();
+                    mask.set(l as usize);
+                    mask.set(r as usize);
+                    mask
+                })
+                .collect::<Vec<_>>();
+            compiled.deps.set(entry.key as usize);
+            for mask in &mods {
+                compiled.deps = compiled.deps.union(mask);
+            }
+            compiled.entries.push(CompiledEntry {
+                key: entry.key as usize,
+                mods,
+            });
+        }
+        // empty chords are never down
+        for chord in self.chords.iter().filter(|c| !c.0.is_empty()) {
+            let mut mask = BitSet256::new();
+            for key in &chord.0 {
+                mask.set(*key as usize);
+            }
+            compiled.deps = compiled.deps.union(&mask);
+            compiled.chords.push(mask);
+        }
+        Some(compiled)
+    }
+
+    /// `grabs` has the bits of the mouse entries whose press started in their region
+    fn state(&self, keys: &impl KeyState, grabs: &mut u32) -> RawButtonState {
+        let mut is_any_down = false;
+        let mut is_any_released = false;
+
+        // before anything can return early, so that every grab sees the press
+        if let Some(pointer) = keys.pointer() {
+            let mut is_any_pressed = false;
+            for (i, entry) in self.mouse.iter().enumerate() {
+                if i >= 32 && entry.region.is_some() {
+                    continue;
+                }
+                let mut grabbed = i < 32 && *grabs & (1 << i) != 0;
+                let [is_down, was_down] = entry.state(pointer, &mut grabbed);
+                if i < 32 {
+                    *grabs = (*grabs & !(1 << i)) | (u32::from(grabbed) << i);
+                }
+                is_any_pressed |= is_down && !was_down;
+                is_any_down |= is_down;
+                is_any_released |= was_down && !is_down;
+            }
+            if is_any_pressed {
+                return RawButtonState::Pressed;
+            }
+        }
+
+        for entry in self.keys.iter() {
+            let mut mods_down = true;
+            let mut mods_down_prev = true;
+            for [l, r] in entry.modifier_keys() {
+                mods_down &= keys.is_key_down(l) || keys.is_key_down(r);
+                mods_down_prev &= keys.was_key_down(l) || keys.was_key_down(r);
+            }
+
+            let is_down = mods_down && keys.is_key_down(entry.key);
+            let is_down_prev = mods_down_prev && keys.was_key_down(entry.key);
+            // the modifiers can be held before the key
+            if is_down && keys.is_key_pressed(entry.key) {
+                return RawButtonState::Pressed;
+            }
+            is_any_down |= is_down;
+            is_any_released |= is_down_prev && !is_down;
+        }
+
+        for chord in self.chords.iter() {
+            let is_down = chord.is_down(keys);
+            let is_down_prev = chord.is_down_prev(keys);
+
+            if is_down && !is_down_prev {
+                return RawButtonState::Pressed;
+            }
+            is_any_down |= is_down;
+            is_any_released |= is_down_prev && !is_down;
+        }
+
+        for raw in self.raw.iter().cloned() {
+            if keys.is_raw_key_pressed(raw) {
+                return RawButtonState::Pressed;
+            }
+            is_any_down |= keys.is_raw_key_down(raw);
+            is_any_released |= keys.is_raw_key_released(raw);
+        }
+
+        for custom in &self.custom {
+            let device = match keys.device(&custom.device) {
+                Some(device) => device,
+                None => continue,
+            };
+            let is_down = device.is_down(&custom.channel);
+            let was_down = device.was_down(&custom.channel);
+            if is_down && !was_down {
+                return RawButtonState::Pressed;
+            }
+            is_any_down |= is_down;
+            is_any_released |= was_down && !is_down;
+        }
+
+        if is_any_down {
+            RawButtonState::Down
+        } else {
+            if is_any_released {
+                RawButtonState::Released
+            } else {
+                RawButtonState::Up
+            }
+        }
+    }
+}
+
+/// Problem found by [`InputBundle::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleIssue {
+    /// The bundle has no entry
+    Empty,
+    /// `keys[index]` is the same as `keys[first]`
+    DuplicateEntry { index: usize, first: usize },
+    /// `keys[index]` is [`Key::None`]
+    NoneKey { index: usize },
+    /// `chords[index]` is the same as `chords[first]`
+    DuplicateChord { index: usize, first: usize },
+    /// `chords[chord]` contains [`Key::None`]
+    ChordNoneKey { chord: usize },
+    /// `chords[chord]` lists the key twice
+    ChordDuplicateKey { chord: usize, key: Key },
+    /// `chords[chord]` contains a modifier key, which should probably be a [`KeyEntry`] flag
+    ChordModifierKey { chord: usize, key: Key },
+}
+
+/// Validation
+impl InputBundle {
+    /// Reports problems that silently degrade the bundle
+    pub fn validate(&self) -> Vec<BundleIssue> {
+        let mut issues = Vec::new();
+
+        if self.keys.is_empty()
+            && self.chords.is_empty()
+            && self.raw.is_empty()
+            && self.custom.is_empty()
+            && self.mouse.is_empty()
+        {
+            issues.push(BundleIssue::Empty);
+        }
+
+        for (index, entry) in self.keys.iter().enumerate() {
+            if let Some(first) = self.keys[..index].iter().position(|e| e == entry) {
+                issues.push(BundleIssue::DuplicateEntry { index, first });
+            }
+            if entry.key == Key::None {
+                issues.push(BundleIssue::NoneKey { index });
+            }
+        }
+
+        for (index, chord) in self.chords.iter().enumerate() {
+            if let Some(first) = self.chords[..index].iter().position(|c| c == chord) {
+                issues.push(BundleIssue::DuplicateChord { index, first });
+            }
+
+            for (i, key) in chord.0.iter().enumerate() {
+                if *key == Key::None {
+                    issues.push(BundleIssue::ChordNoneKey { chord: index });
+                } else if chord.0[..i].contains(key) {
+                    issues.push(BundleIssue::ChordDuplicateKey {
+                        chord: index,
+                        key: *key,
+                    });
+                } else if key.is_modifier() {
+                    issues.push(BundleIssue::ChordModifierKey {
+                        chord: index,
+                        key: *key,
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Removes duplicates and no-op entries (order is kept)
+    pub fn normalize(&mut self) {
+        let mut keys = EntryList::with_capacity(self.keys.len());
+        for entry in self.keys.drain(..) {
+            if entry.key != Key::None && !keys.contains(&entry) {
+                keys.push(entry);
+            }
+        }
+        self.keys = keys;
+
+        let mut chords = EntryList::with_capacity(self.chords.len());
+        for chord in self.chords.drain(..) {
+            let mut chord_keys = Vec::with_capacity(chord.0.len());
+            for key in chord.0 {
+                if key != Key::None && !chord_keys.contains(&key) {
+                    chord_keys.push(key);
+                }
+            }
+
+            let chord = KeyChord(chord_keys);
+            if !chord.0.is_empty() && !chords.contains(&chord) {
+                chords.push(chord);
+            }
+        }
+        self.chords = chords;
+
+        let mut raw = EntryList::with_capacity(self.raw.len());
+        for key in self.raw.drain(..) {
+            if !raw.contains(&key) {
+                raw.push(key);
+            }
+        }
+        self.raw = raw;
+
+        let mut custom = EntryList::with_capacity(self.custom.len());
+        for input in self.custom.drain(..) {
+            if !custom.contains(&input) {
+                custom.push(input);
+            }
+        }
+        self.custom = custom;
+
+        let mut mouse = EntryList::with_capacity(self.mouse.len());
+        for entry in self.mouse.drain(..) {
+            if !mouse.contains(&entry) {
+                mouse.push(entry);
+            }
+        }
+        self.mouse = mouse;
+    }
+}
+
+/// Down | Up | Pressed | Repeating | Released
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StrictButtonState {
+    Down,
+    Up,
+    Pressed,
+    Repeating,
+    Released,
+}
+
+impl StrictButtonState {
+    fn from_raw(state: RawButtonState, is_repeating: bool) -> Self {
+        if is_repeating {
+            return Self::Repeating;
+        }
+        match state {
+            RawButtonState::Down => Self::Down,
+            RawButtonState::Up => Self::Up,
+            RawButtonState::Pressed => Self::Pressed,
+            RawButtonState::Released => Self::Released,
+        }
+    }
+
+    fn is_down(self) -> bool {
+        matches!(self, Self::Down | Self::Pressed | Self::Repeating)
+    }
+
+    fn is_pressed(self) -> bool {
+        matches!(self, Self::Pressed | Self::Repeating)
+    }
+}
+
+#[cfg(feature = "serde")]
+pub mod button_serde_with {
+    //! `serde` `Button` as [`InputBundle`]. NOTE: `KeyRepeatState` will be `skip`ped
+
+    use super::*;
+    use serde::{
+        de::{Deserialize, Deserializer},
+        ser::{Serialize, Serializer},
+    };
+
+    pub fn serialize<S>(value: &Button, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        value.input.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Button, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let input = InputBundle::deserialize(deserializer)?;
+        Ok(Button::new(input, KeyRepeatConfig::NoRepeat))
+    }
+}
+
+/// Key-only [`InputBundle`] compiled into masks over the key bits
+///
+/// Evaluates the same as [`InputBundle::state`], with a few word-wise `AND`s per entry instead of
+/// per-key queries.
+#[derive(Debug, Clone, Default)]
+struct CompiledBundle {
+    entries: Vec<CompiledEntry>,
+    chords: Vec<BitSet256>,
+    /// Union of the masks
+    deps: BitSet256,
+}
+
+/// [`KeyEntry`] of a [`CompiledBundle`]
+#[derive(Debug, Clone)]
+struct CompiledEntry {
+    key: usize,
+    /// Left and right keys of each modifier, either of which has to be down
+    mods: Vec<BitSet256>,
+}
+
+impl CompiledEntry {
+    fn is_down(&self, bits: &BitSet256) -> bool {
+        bits.get(self.key) && self.mods.iter().all(|m| !m.is_disjoint(bits))
+    }
+}
+
+impl CompiledBundle {
+    fn state(&self, cur: &BitSet256, prev: &BitSet256) -> RawButtonState {
+        let mut is_any_down = false;
+        let mut is_any_released = false;
+
+        for entry in &self.entries {
+            let is_down = entry.is_down(cur);
+            let is_down_prev = entry.is_down(prev);
+            // the edge is the key's
+            if is_down && !prev.get(entry.key) {
+                return RawButtonState::Pressed;
+            }
+            is_any_down |= is_down;
+            is_any_released |= is_down_prev && !is_down;
+        }
+
+        for mask in &self.chords {
+            let is_down = mask.is_subset(cur);
+            let is_down_prev = mask.is_subset(prev);
+            if is_down && !is_down_prev {
+                return RawButtonState::Pressed;
+            }
+            is_any_down |= is_down;
+            is_any_released |= is_down_prev && !is_down;
+        }
+
+        if is_any_down {
+            RawButtonState::Down
+        } else if is_any_released {
+            RawButtonState::Released
+        } else {
+            RawButtonState::Up
+        }
+    }
+}
+
+/// Input bundle with repeat state
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Button {
+    input: InputBundle,
+    pub state: StrictButtonState,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    repeat: KeyRepeatState,
+    /// State transition on last update
+    #[cfg_attr(feature = "serde", serde(skip))]
+    transition: Option<(StrictButtonState, StrictButtonState)>,
+    /// Freezes key repeat timers while true
+    #[cfg_attr(feature = "serde", serde(skip))]
+    paused: bool,
+    /// Compiled bundle, built on the first update and dropped by [`Button::input_mut`]. `None` if
+    /// it has raw keys, custom inputs or mouse entries
+    #[cfg_attr(feature = "serde", serde(skip))]
+    memo: Option<Box<BundleMemo>>,
+    /// Number of repeats on last update
+    #[cfg_attr(feature = "serde", serde(skip))]
+    repeat_count: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    turbo: Option<Box<Turbo>>,
+    /// Mouse entries whose press started in their region, by index
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mouse_grabs: u32,
+}
+
+// `Button`s are embedded by the thousands (e.g. one per UI widget), so keep them small. Inline
+// entries make them larger
+#[cfg(all(target_pointer_width = "64", not(feature = "smallvec")))]
+const _: () = assert!(std::mem::size_of::<Button>() <= 224);
+#[cfg(all(target_pointer_width = "64", feature = "smallvec"))]
+const _: () = assert!(std::mem::size_of::<Button>() <= 384);
+
+/// Autofire of a [`Button`], set with [`Button::set_turbo`]
+///
+/// While the button is held, it alternates between pressed and not pressed with a period of
+/// `rate`. `duty` is the pressed fraction of each period, in `0.0..=1.0`; `0.5` alternates
+/// evenly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurboConfig {
+    pub rate: Duration,
+    pub duty: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Turbo {
+    config: TurboConfig,
+    /// Time into the current period. Nanoseconds
+    phase: u64,
+}
+
+impl Turbo {
+    /// Returns `1` if the synthetic press is on
+    fn update(&mut self, state: RawButtonState, dt: Duration) -> u32 {
+        if state != RawButtonState::Down {
+            self.phase = 0;
+            return 0;
+        }
+
+        let rate = self::nanos(self.config.rate);
+        if rate == 0 {
+            return 1;
+        }
+        self.phase = (self.phase + self::nanos(dt) % rate) % rate;
+
+        let on = (rate as f64 * f64::from(self.config.duty.clamp(0.0, 1.0))) as u64;
+        u32::from(self.phase < on)
+    }
+}
+
+/// Compiled [`InputBundle`] of a [`Button`] and its last evaluation
+#[derive(Debug, Clone)]
+struct BundleMemo {
+    compiled: CompiledBundle,
+    /// Down keys among the dependencies and the state on the last evaluation
+    ///
+    /// If the keys are still down and didn't change on this frame, no entry can be pressed or
+    /// released and any down entry is still down. So the state is `Down` if it was `Pressed` or
+    /// `Down`, and `Up` otherwise.
+    last: Option<(BitSet256, RawButtonState)>,
+}
+
+impl BundleMemo {
+    fn new(bundle: &InputBundle) -> Option<Self> {
+        Some(Self {
+            compiled: bundle.compile()?,
+            last: None,
+        })
+    }
+
+    fn state(&mut self, cur: &BitSet256, prev: &BitSet256) -> RawButtonState {
+        let down = cur.intersection(&self.compiled.deps);
+        let state = match self.last {
+            Some((last_down, last_state))
+                if last_down == down && prev.intersection(&self.compiled.deps) == down =>
+            {
+                match last_state {
+                    RawButtonState::Pressed | RawButtonState::Down => RawButtonState::Down,
+                    RawButtonState::Released | RawButtonState::Up => RawButtonState::Up,
+                }
+            }
+            _ => self.compiled.state(cur, prev),
+        };
+        self.last = Some((down, state));
+        state
+    }
+}
+
+/// Evaluates the compiled bundle, or reuses the last evaluation if none of its keys changed
+///
+/// `bits` is [`KeyState::key_bits`] of the `input`.
+fn eval_bundle(
+    bundle: &InputBundle,
+    memo: &mut Option<Box<BundleMemo>>,
+    input: &impl KeyState,
+    bits: Option<[BitSet256; 2]>,
+    mouse_grabs: &mut u32,
+) -> RawButtonState {
+    let [cur, prev] = match bits {
+        Some(bits) => bits,
+        None => return bundle.state(input, mouse_grabs),
+    };
+
+    // the memo is built lazily and dropped when the bindings are borrowed mutably. Bundles that
+    // can't be compiled are turned down before anything is allocated
+    if memo.is_none() {
+        *memo = BundleMemo::new(bundle).map(Box::new);
+    }
+
+    match memo {
+        Some(memo) => memo.state(&cur, &prev),
+        None => bundle.state(input, mouse_grabs),
+    }
+}
+
+impl Button {
+    pub fn new(bundle: InputBundle, repeat_cfg: KeyRepeatConfig) -> Self {
+        Self {
+            input: bundle,
+            state: StrictButtonState::Up,
+            repeat: KeyRepeatState::new(repeat_cfg),
+            transition: None,
+            paused: false,
+            memo: None,
+            repeat_count: 0,
+            turbo: None,
+            mouse_grabs: 0,
+        }
+    }
+
+    /// Button of one key entry, such as a UI widget's shortcut
+    ///
+    /// It allocates the one-entry key list and nothing else, and with the `smallvec` feature it
+    /// doesn't allocate at all (see [`EntryList`]). The compiled bundle is built on the first
+    /// update.
+    pub fn single(entry: impl Into<KeyEntry>, repeat_cfg: KeyRepeatConfig) -> Self {
+        let bundle = InputBundle {
+            keys: std::iter::once(entry.into()).collect(),
+            ..Default::default()
+        };
+        Self::new(bundle, repeat_cfg)
+    }
+
+    /// Creates a button that follows the shared key repeat config
+    pub fn new_shared(bundle: InputBundle, shared: SharedRepeatConfig) -> Self {
+        Self {
+            input: bundle,
+            state: StrictButtonState::Up,
+            repeat: KeyRepeatState::new_shared(shared),
+            transition: None,
+            paused: false,
+            memo: None,
+            repeat_count: 0,
+            turbo: None,
+            mouse_grabs: 0,
+        }
+    }
+
+    pub fn input(&self) -> &InputBundle {
+        &self.input
+    }
+
+    /// Bindings of the button. Changes are picked up on the next update
+    pub fn input_mut(&mut self) -> &mut InputBundle {
+        self.memo = None;
+        &mut self.input
+    }
+
+    pub fn set_repeat_config(&mut self, cfg: KeyRepeatConfig) {
+        self.repeat.reconfigure(KeyRepeatState::new(cfg));
+    }
+
+    pub fn set_shared_repeat_config(&mut self, shared: SharedRepeatConfig) {
+        self.repeat.reconfigure(KeyRepeatState::new_shared(shared));
+    }
+
+    /// Upper bound of [`Button::repeat_count_this_update`]. Defaults to 4
+    pub fn max_repeats_per_update(&self) -> u32 {
+        self.repeat.max_repeats
+    }
+
+    /// Caps the repeats after a long frame stall. Zero disables key repeat
+    pub fn set_max_repeats_per_update(&mut self, max: u32) {
+        self.repeat.max_repeats = max;
+    }
+
+    pub fn repeat_config(&self) -> KeyRepeatConfig {
+        match &self.repeat.shared {
+            Some(shared) => shared.get(),
+            None => self.repeat.config,
+        }
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.state.is_down()
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.state.is_pressed()
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.state == StrictButtonState::Released
+    }
+
+    /// How long it's been down
+    ///
+    /// On the released frame, it's how long it was held. It's tracked for every repeat config,
+    /// including `NoRepeat`.
+    pub fn accum_down(&self) -> Duration {
+        Duration::from_nanos(self.repeat.accum_down)
+    }
+
+    /// How many ticks it's been down
+    pub fn down_ticks(&self) -> u32 {
+        self.repeat.down_ticks
+    }
+
+    pub fn turbo(&self) -> Option<TurboConfig> {
+        self.turbo.as_ref().map(|turbo| turbo.config)
+    }
+
+    /// Enables autofire while the button is held, or disables it with `None`
+    ///
+    /// Turbo takes precedence over the key repeat config, which is ignored while turbo is set.
+    /// The synthetic presses are `Repeating` states, so [`Button::is_pressed`] follows the duty
+    /// cycle while [`Button::is_down`] keeps the physical state. Turbo is driven by the `dt` of
+    /// [`Button::update`] and doesn't progress on [`Button::update_ticks`].
+    pub fn set_turbo(&mut self, cfg: Option<TurboConfig>) {
+        self.turbo = cfg.map(|config| Box::new(Turbo { config, phase: 0 }));
+    }
+
+    /// Number of key repeats on last update. It's non-zero if the state is
+    /// [`StrictButtonState::Repeating`]
+    ///
+    /// It can be more than one after a long frame, so menus can move multiple steps at once.
+    pub fn repeat_count_this_update(&self) -> u32 {
+        self.repeat_count
+    }
+}
+
+/// Lifecycle
+impl Button {
+    /// `dt` should match the one passed to [`Input::tick`] if you use it
+    ///
+    /// [`Input::tick`]: crate::Input::tick
+    pub fn update(&mut self, input: &impl KeyState, dt: Duration) {
+        let state = self::eval_bundle(
+            &self.input,
+            &mut self.memo,
+            input,
+            input.key_bits(),
+            &mut self.mouse_grabs,
+        );
+        let repeats = if self.paused && state == RawButtonState::Down {
+            0
+        } else {
+            // the down time is still tracked while turbo is on
+            let repeats = self.repeat.update(state, dt);
+            match &mut self.turbo {
+                Some(turbo) => turbo.update(state, dt),
+                None => repeats,
+            }
+        };
+        self.apply(state, repeats);
+    }
+
+    /// Updates the button by a number of fixed ticks
+    ///
+    /// Use it with [`KeyRepeatConfig::RepeatFrames`]; `Duration` based repeat does not progress.
+    pub fn update_ticks(&mut self, input: &impl KeyState, ticks: u32) {
+        let state = self::eval_bundle(
+            &self.input,
+            &mut self.memo,
+            input,
+            input.key_bits(),
+            &mut self.mouse_grabs,
+        );
+        let repeats = if self.paused && state == RawButtonState::Down {
+            0
+        } else {
+            let repeats = self.repeat.update_ticks(state, ticks);
+            if self.turbo.is_some() {
+                0
+            } else {
+                repeats
+            }
+        };
+        self.apply(state, repeats);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Freezes key repeat timers while paused, still tracking the button state
+    ///
+    /// Time passed while paused is not counted, so no backlog of repeats fires on resume.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    fn apply(&mut self, state: RawButtonState, repeats: u32) {
+        let prev = self.state;
+        self.repeat_count = repeats;
+        self.state = StrictButtonState::from_raw(state, repeats >= 1);
+        self.transition = if self.state != prev {
+            Some((prev, self.state))
+        } else {
+            None
+        };
+    }
+
+    /// `(from, to)` state transitions that happened on last update
+    pub fn transitions(&self) -> &[(StrictButtonState, StrictButtonState)] {
+        self.transition.as_slice()
+    }
+
+    /// Takes the `(from, to)` state transitions that happened on last update
+    pub fn take_transitions(&mut self) -> Vec<(StrictButtonState, StrictButtonState)> {
+        self.transition.take().into_iter().collect()
+    }
+}
+
+/// Dynamic states of a [`Button`], without the bindings or the repeat config
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct ButtonSnapshot {
+    state: StrictButtonState,
+    accum_repeat: Duration,
+    accum_down: Duration,
+    accum_repeat_ticks: u32,
+    down_ticks: u32,
+    is_on_first_repeat: bool,
+    transitions: Vec<(StrictButtonState, StrictButtonState)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    repeat_count: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    turbo_phase: Duration,
+}
+
+/// Save states
+impl Button {
+    pub(crate) fn snapshot(&self) -> ButtonSnapshot {
+        ButtonSnapshot {
+            state: self.state,
+            accum_repeat: Duration::from_nanos(self.repeat.accum_repeat),
+            accum_down: Duration::from_nanos(self.repeat.accum_down),
+            accum_repeat_ticks: self.repeat.accum_repeat_ticks,
+            down_ticks: self.repeat.down_ticks,
+            is_on_first_repeat: self.repeat.is_on_first_repeat,
+            transitions: self.transition.into_iter().collect(),
+            repeat_count: self.repeat_count,
+            turbo_phase: Duration::from_nanos(self.turbo.as_ref().map_or(0, |turbo| turbo.phase)),
+        }
+    }
+
+    pub(crate) fn restore(&mut self, snapshot: &ButtonSnapshot) {
+        self.state = snapshot.state;
+        self.repeat.accum_repeat = self::nanos(snapshot.accum_repeat);
+        self.repeat.accum_down = self::nanos(snapshot.accum_down);
+        self.repeat.accum_repeat_ticks = snapshot.accum_repeat_ticks;
+        self.repeat.down_ticks = snapshot.down_ticks;
+        self.repeat.is_on_first_repeat = snapshot.is_on_first_repeat;
+        self.transition = snapshot.transitions.last().cloned();
+        self.repeat_count = snapshot.repeat_count;
+        if let Some(turbo) = &mut self.turbo {
+            turbo.phase = self::nanos(snapshot.turbo_phase);
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Higher-level buttons
+
+/// Neg | Pos | Neutral
+///
+/// WARNING: It doesn't store key repeat configuration on `serde`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AxisButton {
+    /// Positive input
+    #[cfg_attr(feature = "serde", serde(with = "button_serde_with"))]
+    pub pos: Button,
+    /// Negative input
+    #[cfg_attr(feature = "serde", serde(with = "button_serde_with"))]
+    pub neg: Button,
+}
+
+impl AxisButton {
+    /// Sets the same key repeat config to both buttons
+    pub fn set_repeat_config(&mut self, cfg: KeyRepeatConfig) {
+        self.pos.set_repeat_config(cfg);
+        self.neg.set_repeat_config(cfg);
+    }
+}
+
+/// Lifecycle
+impl AxisButton {
+    pub fn update(&mut self, input: &impl KeyState, dt: Duration) {
+        self.pos.update(input, dt);
+        self.neg.update(input, dt);
+    }
+}
+
+impl AxisButton {
+    /// True if both buttons are paused
+    pub fn is_paused(&self) -> bool {
+        self.pos.is_paused() && self.neg.is_paused()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.pos.set_paused(paused);
+        self.neg.set_paused(paused);
+    }
+}
+
+impl AxisButton {
+    /// Selects down sign pressed lately
+    pub fn sign_down(&self) -> Sign {
+        match [self.pos.is_down(), self.neg.is_down()] {
+            [true, true] => {
+                if self.pos.repeat.accum_down <= self.neg.repeat.accum_down {
+                    Sign::Pos
+                } else {
+                    Sign::Neg
+                }
+            }
+            [true, false] => Sign::Pos,
             [false, true] => Sign::Neg,
             [false, false] => Sign::Neutral,
         }
@@ -432,10 +1768,32 @@ impl AxisButton {
         }
     }
 
+    /// Selects released sign released this frame
+    ///
+    /// If both signs are released at once, the one pressed lately is selected.
+    pub fn sign_released(&self) -> Sign {
+        match [self.pos.is_released(), self.neg.is_released()] {
+            [true, true] => {
+                if self.pos.repeat.accum_down <= self.neg.repeat.accum_down {
+                    Sign::Pos
+                } else {
+                    Sign::Neg
+                }
+            }
+            [true, false] => Sign::Pos,
+            [false, true] => Sign::Neg,
+            [false, false] => Sign::Neutral,
+        }
+    }
+
     /// How long it's been down
     pub fn accum_down(&self) -> Duration {
         // select sign down lately
-        std::cmp::min(self.pos.repeat.accum_down, self.neg.repeat.accum_down)
+        match [self.pos.is_down(), self.neg.is_down()] {
+            [true, false] => self.pos.accum_down(),
+            [false, true] => self.neg.accum_down(),
+            _ => std::cmp::min(self.pos.accum_down(), self.neg.accum_down()),
+        }
     }
 }
 
@@ -443,14 +1801,62 @@ impl AxisButton {
 ///
 /// [x, y] components are "mixed" to make directions. For example, [1, 1] is interpreted as
 /// south-east.
+///
+/// On `serde`, the key repeat configuration of each button is stored in `repeat`. Buttons that
+/// follow a [`SharedRepeatConfig`] are stored without one and get it on load. See
+/// `AxisDirButton::deserialize_with_repeat` and `AxisDirButton::deserialize_with_shared`.
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AxisDirButton {
     x: AxisButton,
     y: AxisButton,
+    analog: AnalogConfig,
+    /// Output of [`AxisDirButton::value_smoothed`]
+    smoothed: [f32; 2],
+    /// Diagonal grace window. Zero means disabled.
+    grace: Duration,
+    /// Cardinal press waiting for the other axis and the time elapsed
+    grace_pending: Option<(Dir8, Duration)>,
+    /// Output of [`AxisDirButton::dir8_pressed`] while the diagonal grace is enabled
+    grace_out: Option<Dir8>,
+    /// Treats direction changes as press events
+    emit_on_dir_change: bool,
+    /// Down directions on last update
+    last_dirs: (Option<Dir4>, Option<Dir8>),
+    /// If the down directions changed on last update
+    dirs_changed: (bool, bool),
+    /// Direction that returned to neutral on last update
+    released_dir: Option<Dir8>,
+}
+
+/// Keyboard-to-analog emulation settings of [`AxisDirButton`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnalogConfig {
+    /// Time to ramp from zero to full magnitude. Zero means no smoothing.
+    pub attack: Duration,
+    /// Time to ramp from full magnitude to zero. Zero means no smoothing.
+    pub release: Duration,
+    /// Scales diagonal output to unit length
+    pub normalize_diagonal: bool,
 }
 
 impl AxisDirButton {
+    fn from_axes(x: AxisButton, y: AxisButton) -> Self {
+        Self {
+            x,
+            y,
+            analog: AnalogConfig::default(),
+            smoothed: [0.0, 0.0],
+            grace: Duration::new(0, 0),
+            grace_pending: None,
+            grace_out: None,
+            emit_on_dir_change: false,
+            last_dirs: (None, None),
+            dirs_changed: (false, false),
+            released_dir: None,
+        }
+    }
+
     /// Creates axis from [positive, negative] input bundle in (x, y) axis
     ///
     /// Makes sure that the key repeat configuration is shared among buttons (while the states are
@@ -461,59 +1867,544 @@ impl AxisDirButton {
         let y_pos = Button::new(ys[0].clone(), repeat);
         let y_neg = Button::new(ys[1].clone(), repeat);
 
-        Self {
-            x: AxisButton {
+        Self::from_axes(
+            AxisButton {
                 pos: x_pos,
                 neg: x_neg,
             },
-            y: AxisButton {
+            AxisButton {
                 pos: y_pos,
                 neg: y_neg,
             },
+        )
+    }
+
+    /// Creates axis from [positive, negative] input bundle in (x, y) axis
+    ///
+    /// All the buttons follow the shared key repeat config.
+    pub fn new_shared(
+        repeat: SharedRepeatConfig,
+        xs: [InputBundle; 2],
+        ys: [InputBundle; 2],
+    ) -> Self {
+        let [x_pos, x_neg] = xs;
+        let [y_pos, y_neg] = ys;
+
+        Self::from_axes(
+            AxisButton {
+                pos: Button::new_shared(x_pos, repeat.clone()),
+                neg: Button::new_shared(x_neg, repeat.clone()),
+            },
+            AxisButton {
+                pos: Button::new_shared(y_pos, repeat.clone()),
+                neg: Button::new_shared(y_neg, repeat),
+            },
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+mod axis_dir_serde {
+    //! `serde` [`AxisDirButton`] with key repeat configuration
+
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    /// Key repeat configs of the four buttons
+    ///
+    /// A missing config is supplied on load (see [`AxisDirButton::deserialize_with_repeat`]).
+    /// `NoRepeat` is written as unit, so it's never confused with a missing config.
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum RepeatRepr {
+        /// The same config for all of the buttons
+        All(KeyRepeatConfig),
+        PerButton {
+            x: AxisRepeat,
+            y: AxisRepeat,
+        },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct AxisRepeat {
+        #[serde(default, skip_serializing_if = "Option::is_none", with = "present")]
+        pos: Option<KeyRepeatConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none", with = "present")]
+        neg: Option<KeyRepeatConfig>,
+    }
+
+    /// `Some` while the field is present, even if the value is unit (`NoRepeat`)
+    mod present {
+        use super::*;
+
+        pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: Serialize,
+            S: Serializer,
+        {
+            match value {
+                Some(value) => value.serialize(serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            T: Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            T::deserialize(deserializer).map(Some)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct AxisDirButtonSer<'a> {
+        #[serde(skip_serializing_if = "Option::is_none", with = "present")]
+        repeat: Option<RepeatRepr>,
+        x: &'a AxisButton,
+        y: &'a AxisButton,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct AxisDirButtonDe {
+        #[serde(default, with = "present")]
+        repeat: Option<RepeatRepr>,
+        x: AxisButton,
+        y: AxisButton,
+    }
+
+    /// Key repeat config of buttons without a serialized one
+    pub(super) enum Fallback {
+        Config(KeyRepeatConfig),
+        Shared(SharedRepeatConfig),
+    }
+
+    impl AxisDirButtonDe {
+        pub fn into_button(self, fallback: Fallback) -> AxisDirButton {
+            let mut button = AxisDirButton::from_axes(self.x, self.y);
+
+            let cfgs = match self.repeat {
+                None => [None; 4],
+                Some(RepeatRepr::All(cfg)) => [Some(cfg); 4],
+                Some(RepeatRepr::PerButton { x, y }) => [x.pos, x.neg, y.pos, y.neg],
+            };
+
+            for (b, cfg) in button.buttons_mut().iter_mut().zip(cfgs.iter()) {
+                match (cfg, &fallback) {
+                    (Some(cfg), _) => b.set_repeat_config(*cfg),
+                    (None, Fallback::Config(cfg)) => b.set_repeat_config(*cfg),
+                    (None, Fallback::Shared(shared)) => b.set_shared_repeat_config(shared.clone()),
+                }
+            }
+
+            button
+        }
+    }
+
+    impl Serialize for AxisDirButton {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            // buttons following a shared config get it from the loader
+            let cfg = |b: &Button| match b.repeat.shared {
+                Some(_) => None,
+                None => Some(b.repeat.config),
+            };
+            let cfgs = [
+                cfg(&self.x.pos),
+                cfg(&self.x.neg),
+                cfg(&self.y.pos),
+                cfg(&self.y.neg),
+            ];
+
+            let repeat = if cfgs.iter().all(Option::is_none) {
+                None
+            } else if cfgs.iter().all(|c| c.is_some() && *c == cfgs[0]) {
+                cfgs[0].map(RepeatRepr::All)
+            } else {
+                Some(RepeatRepr::PerButton {
+                    x: AxisRepeat {
+                        pos: cfgs[0],
+                        neg: cfgs[1],
+                    },
+                    y: AxisRepeat {
+                        pos: cfgs[2],
+                        neg: cfgs[3],
+                    },
+                })
+            };
+
+            AxisDirButtonSer {
+                repeat,
+                x: &self.x,
+                y: &self.y,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for AxisDirButton {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let de = AxisDirButtonDe::deserialize(deserializer)?;
+            Ok(de.into_button(Fallback::Config(KeyRepeatConfig::NoRepeat)))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl AxisDirButton {
+    /// Deserializes [`AxisDirButton`], using `repeat` for the buttons without a serialized key
+    /// repeat configuration
+    pub fn deserialize_with_repeat<'de, D>(
+        deserializer: D,
+        repeat: KeyRepeatConfig,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let de = self::axis_dir_serde::AxisDirButtonDe::deserialize(deserializer)?;
+        Ok(de.into_button(self::axis_dir_serde::Fallback::Config(repeat)))
+    }
+
+    /// Deserializes [`AxisDirButton`]. The buttons without a serialized key repeat configuration
+    /// follow the `shared` config
+    pub fn deserialize_with_shared<'de, D>(
+        deserializer: D,
+        shared: SharedRepeatConfig,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let de = self::axis_dir_serde::AxisDirButtonDe::deserialize(deserializer)?;
+        Ok(de.into_button(self::axis_dir_serde::Fallback::Shared(shared)))
+    }
+}
+
+/// Accessors
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{vi::*, Key};
+///
+/// let bundle = |key: Key| InputBundle::from_keys([key]);
+///
+/// let mut dir = AxisDirButton::new(
+///     KeyRepeatConfig::repeat(Duration::from_millis(300), Duration::from_millis(100)),
+///     [bundle(Key::D), bundle(Key::A)],
+///     [bundle(Key::S), bundle(Key::W)],
+/// );
+///
+/// // slower repeat for the vertical axis only
+/// dir.y_mut().set_repeat_config(KeyRepeatConfig::repeat(
+///     Duration::from_millis(500),
+///     Duration::from_millis(200),
+/// ));
+///
+/// assert_eq!(dir.x().pos.accum_down(), Duration::new(0, 0));
+/// ```
+impl AxisDirButton {
+    pub fn x(&self) -> &AxisButton {
+        &self.x
+    }
+
+    pub fn y(&self) -> &AxisButton {
+        &self.y
+    }
+
+    pub fn x_mut(&mut self) -> &mut AxisButton {
+        &mut self.x
+    }
+
+    pub fn y_mut(&mut self) -> &mut AxisButton {
+        &mut self.y
+    }
+
+    /// Sets the same key repeat config to all of the buttons
+    pub fn set_repeat_config_all(&mut self, cfg: KeyRepeatConfig) {
+        self.x.set_repeat_config(cfg);
+        self.y.set_repeat_config(cfg);
+    }
+
+    /// Sets key repeat configs per axis
+    pub fn set_repeat_config_xy(&mut self, cfg_x: KeyRepeatConfig, cfg_y: KeyRepeatConfig) {
+        self.x.set_repeat_config(cfg_x);
+        self.y.set_repeat_config(cfg_y);
+    }
+
+    /// x positive, x negative, y positive and y negative
+    fn buttons_mut(&mut self) -> [&mut Button; 4] {
+        [
+            &mut self.x.pos,
+            &mut self.x.neg,
+            &mut self.y.pos,
+            &mut self.y.neg,
+        ]
+    }
+}
+
+/// Lifecycle
+impl AxisDirButton {
+    pub fn update(&mut self, input: &impl KeyState, dt: Duration) {
+        self.x.update(input, dt);
+        self.y.update(input, dt);
+
+        let dt = if self.is_paused() {
+            Duration::new(0, 0)
+        } else {
+            dt
+        };
+        self.update_smoothed(dt);
+        self.update_grace(dt);
+        self.update_dir_change();
+    }
+
+    fn update_dir_change(&mut self) {
+        let dirs = (self.dir4_down(), self.dir8_down());
+        self.dirs_changed = (
+            dirs.0.is_some() && dirs.0 != self.last_dirs.0,
+            dirs.1.is_some() && dirs.1 != self.last_dirs.1,
+        );
+        self.released_dir = if dirs.1.is_none() {
+            self.last_dirs.1
+        } else {
+            None
+        };
+        self.last_dirs = dirs;
+
+        if self.emit_on_dir_change && self.dirs_changed.1 {
+            for b in self.buttons_mut().iter_mut() {
+                if b.is_down() {
+                    b.repeat.restart_repeat();
+                }
+            }
+        }
+    }
+
+    fn update_grace(&mut self, dt: Duration) {
+        self.grace_out = None;
+        if self.grace == Duration::new(0, 0) {
+            self.grace_pending = None;
+            return;
+        }
+
+        if let Some((dir, elapsed)) = self.grace_pending.take() {
+            let elapsed = elapsed + dt;
+            match self.dir8_down() {
+                // the other axis arrived in time
+                Some(down) if down.is_diagonal() => self.grace_out = Some(down),
+                // released in the window
+                None => self.grace_out = Some(dir),
+                Some(down) if elapsed >= self.grace => self.grace_out = Some(down),
+                Some(_) => self.grace_pending = Some((dir, elapsed)),
+            }
+            return;
+        }
+
+        let dir = match self.raw_dir8_pressed() {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        // only fresh press edges are delayed (repeats are not)
+        let is_fresh_press = [&self.x.pos, &self.x.neg, &self.y.pos, &self.y.neg]
+            .iter()
+            .any(|b| b.state == StrictButtonState::Pressed);
+
+        if is_fresh_press && dir.is_cardinal() && self.dir8_down() == Some(dir) {
+            self.grace_pending = Some((dir, Duration::new(0, 0)));
+        } else {
+            self.grace_out = Some(dir);
+        }
+    }
+
+    fn update_smoothed(&mut self, dt: Duration) {
+        let target: [f32; 2] = self.value().into();
+        for (smoothed, dst) in self.smoothed.iter_mut().zip(target.iter().cloned()) {
+            let cur = *smoothed;
+            // ramp up while heading outwards in the same direction, otherwise ramp down
+            let time = if dst != 0.0 && cur * dst >= 0.0 && dst.abs() >= cur.abs() {
+                self.analog.attack
+            } else {
+                self.analog.release
+            };
+
+            *smoothed = if time == Duration::new(0, 0) {
+                dst
+            } else {
+                let step = dt.as_secs_f32() / time.as_secs_f32();
+                if cur < dst {
+                    (cur + step).min(dst)
+                } else {
+                    (cur - step).max(dst)
+                }
+            };
+        }
+    }
+}
+
+/// Analog output
+impl AxisDirButton {
+    pub fn analog_config(&self) -> &AnalogConfig {
+        &self.analog
+    }
+
+    pub fn set_analog_config(&mut self, cfg: AnalogConfig) {
+        self.analog = cfg;
+    }
+
+    /// Down signs of [x, y] axes in -1.0..=1.0
+    ///
+    /// Diagonals are scaled to unit length if [`AnalogConfig::normalize_diagonal`] is set.
+    pub fn value(&self) -> Axis2d {
+        let v = Axis2d::new(self.x.sign_down().to_f32(), self.y.sign_down().to_f32());
+
+        if self.analog.normalize_diagonal {
+            v.normalized()
+        } else {
+            v
+        }
+    }
+
+    /// [`AxisDirButton::value`] ramped over the attack/release time of [`AnalogConfig`]
+    ///
+    /// It's advanced with the `dt` passed to [`AxisDirButton::update`].
+    pub fn value_smoothed(&self) -> Axis2d {
+        self.smoothed.into()
+    }
+}
+
+impl AxisDirButton {
+    /// Creates a directional output mixing axis inputs
+    pub fn dir4_down(&self) -> Option<Dir4> {
+        // mix down inputs (not pressed inputs)
+        self.dir4(self.x.sign_down(), self.y.sign_down())
+    }
+
+    /// Creates a directional output mixing axis inputs
+    ///
+    /// See [`AxisDirButton::dir8_pressed`] for when a press is reported. A diagonal resolves to
+    /// the axis pressed lately.
+    pub fn dir4_pressed(&self) -> Option<Dir4> {
+        if let Some(out) = self.grace_window() {
+            let [x, y] = out?.signs();
+            return self.dir4(x, y);
+        }
+
+        if self.emit_on_dir_change && self.dirs_changed.0 {
+            return self.last_dirs.0;
+        }
+
+        if self.grace != Duration::new(0, 0) {
+            return None;
+        }
+
+        let pressed = self.dir4(self.x.sign_pressed(), self.y.sign_pressed());
+        self.mix_pressed(pressed, self.dir4_down())
+    }
+
+    /// Creates a directional output mixing axis inputs
+    pub fn dir8_down(&self) -> Option<Dir8> {
+        Dir8::from_signs([self.x.sign_down(), self.y.sign_down()])
+    }
+
+    /// Creates a directional output mixing axis inputs
+    ///
+    /// A press is reported only if it's the down direction, and key repeats report the down
+    /// direction. So holding right and then adding up reports nothing, unless
+    /// [`AxisDirButton::set_emit_on_dir_change`] is set.
+    ///
+    /// Cardinal presses are delayed while the diagonal grace window is enabled. See
+    /// [`AxisDirButton::set_diagonal_grace`].
+    pub fn dir8_pressed(&self) -> Option<Dir8> {
+        if let Some(out) = self.grace_window() {
+            return out;
+        }
+
+        if self.emit_on_dir_change && self.dirs_changed.1 {
+            return self.last_dirs.1;
+        }
+
+        if self.grace != Duration::new(0, 0) {
+            return None;
+        }
+
+        self.raw_dir8_pressed()
+    }
+
+    fn raw_dir8_pressed(&self) -> Option<Dir8> {
+        let pressed = Dir8::from_signs([self.x.sign_pressed(), self.y.sign_pressed()]);
+        self.mix_pressed(pressed, self.dir8_down())
+    }
+
+    /// Output of the diagonal grace window while it's waiting or emitting
+    ///
+    /// The window takes precedence over direction changes, so that the first press of a diagonal
+    /// is emitted once.
+    fn grace_window(&self) -> Option<Option<Dir8>> {
+        if self.grace_out.is_some() || self.grace_pending.is_some() {
+            Some(self.grace_out)
+        } else {
+            None
+        }
+    }
+
+    fn mix_pressed<D: PartialEq>(&self, pressed: Option<D>, down: Option<D>) -> Option<D> {
+        let is_repeating = [&self.x.pos, &self.x.neg, &self.y.pos, &self.y.neg]
+            .iter()
+            .any(|b| b.state == StrictButtonState::Repeating);
+
+        if is_repeating {
+            down
+        } else if pressed.is_some() && pressed == down {
+            pressed
+        } else {
+            None
         }
     }
-}
 
-/// Lifecycle
-impl AxisDirButton {
-    pub fn update(&mut self, input: &Input, dt: Duration) {
-        self.x.update(input, dt);
-        self.y.update(input, dt);
+    /// True if all four buttons are paused, which freezes the smoothing and grace timers
+    pub fn is_paused(&self) -> bool {
+        self.x.is_paused() && self.y.is_paused()
     }
-}
 
-impl AxisDirButton {
-    /// Creates a directional output mixing axis inputs
-    pub fn dir4_down(&self) -> Option<Dir4> {
-        // mix down inputs (not pressed inputs)
-        let x = self.x.sign_down().to_i8();
-        let y = self.y.sign_down().to_i8();
-        self.dir4(x, y)
+    /// Freezes key repeat, smoothing and grace timers. See [`Button::set_paused`].
+    pub fn set_paused(&mut self, paused: bool) {
+        self.x.set_paused(paused);
+        self.y.set_paused(paused);
     }
 
-    /// Creates a directional output mixing axis inputs
-    pub fn dir4_pressed(&self) -> Option<Dir4> {
-        // mix down inputs (not pressed inputs)
-        let x = self.x.sign_pressed().to_i8();
-        let y = self.y.sign_pressed().to_i8();
-        self.dir4(x, y)
+    /// Direction that was down on the previous update if it returned to neutral on this update
+    pub fn dir_released(&self) -> Option<Dir8> {
+        self.released_dir
     }
 
-    /// Creates a directional output mixing axis inputs
-    pub fn dir8_down(&self) -> Option<Dir8> {
-        let x = self.x.sign_down().to_i8();
-        let y = self.y.sign_down().to_i8();
-        self.dir8(x, y)
+    /// If true, any change of the down direction counts as a press and restarts the key repeat
+    ///
+    /// For example, holding right and then adding up emits NE, and releasing up emits E again.
+    ///
+    /// The diagonal grace window takes precedence: while it's waiting for the other axis, the
+    /// changes are not emitted, and the window emits the direction once.
+    pub fn set_emit_on_dir_change(&mut self, b: bool) {
+        self.emit_on_dir_change = b;
     }
 
-    /// Creates a directional output mixing axis inputs
-    pub fn dir8_pressed(&self) -> Option<Dir8> {
-        let x = self.x.sign_pressed().to_i8();
-        let y = self.y.sign_pressed().to_i8();
-        self.dir8(x, y)
+    /// Sets the diagonal grace window for [`AxisDirButton::dir8_pressed`] and
+    /// [`AxisDirButton::dir4_pressed`]
+    ///
+    /// When a cardinal direction is pressed, the output is delayed for up to `grace` waiting for
+    /// the other axis. If it arrives in the window, the diagonal is emitted immediately. Otherwise
+    /// the cardinal is emitted when the window ends or the key is released. Zero disables it.
+    pub fn set_diagonal_grace(&mut self, grace: Duration) {
+        self.grace = grace;
+        self.grace_pending = None;
     }
 
-    fn dir4(&self, x: i32, y: i32) -> Option<Dir4> {
+    fn dir4(&self, x: Sign, y: Sign) -> Option<Dir4> {
+        let (x, y) = (x.to_i8(), y.to_i8());
         Some(match [x, y] {
             [0, 0] => return None,
             // clockwise
@@ -539,20 +2430,1391 @@ impl AxisDirButton {
             }
         })
     }
+}
 
-    fn dir8(&self, x: i32, y: i32) -> Option<Dir8> {
-        Some(match [x, y] {
-            [0, 0] => return None,
-            // clockwise
-            [0, -1] => Dir8::N,
-            [1, -1] => Dir8::NE,
-            [1, 0] => Dir8::E,
-            [1, 1] => Dir8::SE,
-            [0, 1] => Dir8::S,
-            [-1, 1] => Dir8::SW,
-            [-1, 0] => Dir8::W,
-            [-1, -1] => Dir8::NW,
-            _ => unreachable!("unable to create Dir8 from virtual input"),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{ManualBackend, ScriptBackend},
+        utils::xorshift64,
+        Input,
+    };
+
+    const DT: Duration = Duration::from_millis(10);
+
+    /// Raw states of the bundle, queueing the events of each frame with `frame`
+    fn mouse_states(
+        bundle: &InputBundle,
+        n: usize,
+        mut frame: impl FnMut(usize, &mut ManualBackend),
+    ) -> Vec<RawButtonState> {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut grabs = 0;
+        (0..n)
+            .map(|i| {
+                frame(i, &mut backend);
+                backend.update(&mut input);
+                let state = bundle.state(&input, &mut grabs);
+                let mut again = grabs;
+                assert_eq!(bundle.state(&input, &mut again), state, "frame {}", i);
+                assert_eq!(again, grabs, "frame {}", i);
+                backend.end_frame(&mut input);
+                state
+            })
+            .collect()
+    }
+
+    fn mouse_bundle(entry: MouseEntry) -> InputBundle {
+        InputBundle {
+            mouse: entries![entry],
+            ..Default::default()
+        }
+    }
+
+    /// Keyboard driven by a [`ScriptBackend`] timeline
+    struct Frames {
+        script: ScriptBackend,
+        input: Input,
+    }
+
+    impl Frames {
+        fn new(script: ScriptBackend) -> Self {
+            Self {
+                script,
+                input: Input::new(),
+            }
+        }
+
+        /// Applies the next frame of the script
+        fn next(&mut self) -> &Input {
+            self.script.step(&mut self.input);
+            &self.input
+        }
+    }
+
+    /// Script with a key pressed on the first frame and never released
+    fn press(key: Key, dt: Duration) -> Frames {
+        let mut script = ScriptBackend::new(dt);
+        script.at_frame(0).press(key);
+        Frames::new(script)
+    }
+
+    #[test]
+    fn mouse_region_is_read_on_press() {
+        use RawButtonState::*;
+
+        let region = SharedRegion::new(Region::new(0, 0, 10, 10));
+        let bundle = self::mouse_bundle(MouseEntry::with_region(MouseButton::Left, region.clone()));
+
+        // the cursor stays still while the region moves
+        let states = self::mouse_states(&bundle, 7, |i, backend| match i {
+            0 => {
+                backend.mouse_move([15, 5]);
+                backend.mouse_down(MouseButton::Left);
+            }
+            // arriving under the held button is not a press
+            1 => region.set(Region::new(10, 0, 10, 10)),
+            2 => backend.mouse_up(MouseButton::Left),
+            3 => backend.mouse_down(MouseButton::Left),
+            5 => backend.mouse_up(MouseButton::Left),
+            6 => {
+                region.set(Region::new(20, 0, 10, 10));
+                backend.mouse_down(MouseButton::Left);
+            }
+            _ => {}
+        });
+        assert_eq!(states, vec![Up, Up, Up, Pressed, Down, Released, Up]);
+    }
+
+    #[test]
+    fn mouse_region_drag_out() {
+        use RawButtonState::*;
+
+        let drag_out = |i, backend: &mut ManualBackend| match i {
+            0 => {
+                backend.mouse_move([5, 5]);
+                backend.mouse_down(MouseButton::Left);
+            }
+            1 => backend.mouse_move([15, 5]),
+            2 => backend.mouse_up(MouseButton::Left),
+            _ => {}
+        };
+        let entry = MouseEntry::with_region(MouseButton::Left, Region::new(0, 0, 10, 10));
+
+        let bundle = self::mouse_bundle(entry.clone());
+        let states = self::mouse_states(&bundle, 4, drag_out);
+        assert_eq!(states, vec![Pressed, Released, Up, Up]);
+
+        let bundle = self::mouse_bundle(entry.clone().track_outside(true));
+        let states = self::mouse_states(&bundle, 4, drag_out);
+        assert_eq!(states, vec![Pressed, Down, Released, Up]);
+
+        // dragging in is not a press
+        let states = self::mouse_states(&bundle, 3, |i, backend| match i {
+            0 => {
+                backend.mouse_move([15, 5]);
+                backend.mouse_down(MouseButton::Left);
+            }
+            1 => backend.mouse_move([5, 5]),
+            _ => {}
+        });
+        assert_eq!(states, vec![Up, Up, Up]);
+    }
+
+    #[test]
+    fn mouse_grabs_are_kept_by_the_button() {
+        let entry = MouseEntry::with_region(MouseButton::Left, Region::new(0, 0, 10, 10))
+            .track_outside(true);
+        let bundle = InputBundle {
+            keys: entries![KeyEntry::from(Key::Space)],
+            mouse: entries![entry],
+            ..Default::default()
+        };
+        let mut button = Button::new(bundle.clone(), KeyRepeatConfig::NoRepeat);
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        // a key press on the same frame doesn't hide the grab
+        backend.key_down(Key::Space);
+        backend.mouse_move([5, 5]);
+        backend.mouse_down(MouseButton::Left);
+        backend.update(&mut input);
+        button.update(&input, DT);
+        assert_eq!(button.state, StrictButtonState::Pressed);
+        backend.end_frame(&mut input);
+
+        backend.key_up(Key::Space);
+        backend.mouse_move([15, 5]);
+        backend.update(&mut input);
+        button.update(&input, DT);
+        // evaluating the bundle elsewhere doesn't touch the grab of the button
+        assert_eq!(bundle.state(&input, &mut 0), RawButtonState::Released);
+        assert_eq!(button.state, StrictButtonState::Down);
+        backend.end_frame(&mut input);
+
+        // a button created mid-press has not seen it start in the region
+        let mut late = Button::new(bundle, KeyRepeatConfig::NoRepeat);
+        backend.update(&mut input);
+        button.update(&input, DT);
+        late.update(&input, DT);
+        assert_eq!(button.state, StrictButtonState::Down);
+        assert_eq!(late.state, StrictButtonState::Up);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn mouse_entry_serde() {
+        let bundle = InputBundle {
+            mouse: entries![
+                MouseEntry::from(MouseButton::Right),
+                MouseEntry::with_region(MouseButton::Left, Region::new(0, 0, 10, 10))
+                    .track_outside(true),
+            ],
+            ..Default::default()
+        };
+        let ron = ron::to_string(&bundle).unwrap();
+        assert_eq!(ron::from_str::<InputBundle>(&ron).unwrap(), bundle);
+
+        // shared regions are set by the game
+        let shared = MouseEntry::with_region(MouseButton::Left, SharedRegion::default());
+        assert!(ron::to_string(&self::mouse_bundle(shared)).is_err());
+    }
+
+    #[test]
+    fn frame_repeat_is_exact_over_10k_ticks() {
+        let cfg = KeyRepeatConfig::repeat_frames(30, 5);
+        let mut by_update = Button::single(Key::A, cfg);
+        let mut by_ticks = Button::single(Key::A, cfg);
+        // an odd dt, which must not matter for frame-based repeat
+        let mut frames = self::press(Key::A, Duration::from_nanos(16_666_667));
+
+        let input = frames.next();
+        by_update.update(input, Duration::from_nanos(16_666_667));
+        by_ticks.update_ticks(input, 1);
+        assert!(by_update.is_pressed() && by_ticks.is_pressed());
+
+        let (mut n_update, mut n_ticks) = (0, 0);
+        for i in 0..10_000 {
+            let input = frames.next();
+            by_update.update(input, Duration::from_nanos(16_666_667));
+            n_update += by_update.repeat_count_this_update();
+            // uneven batches of ticks
+            if i % 3 == 2 {
+                by_ticks.update_ticks(input, 3);
+                n_ticks += by_ticks.repeat_count_this_update();
+            }
+        }
+
+        // first repeat on the 30th tick, then every 5 ticks
+        assert_eq!(n_update, 1 + (10_000 - 30) / 5);
+        assert_eq!(n_ticks, 1 + (9_999 - 30) / 5);
+        assert_eq!(by_update.down_ticks(), 10_000);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn button_size_report() {
+        use std::mem::size_of;
+
+        let sizes = [
+            ("Button", size_of::<Button>()),
+            ("KeyRepeatState", size_of::<KeyRepeatState>()),
+            ("InputBundle", size_of::<InputBundle>()),
+        ];
+        // `Button` was 408 bytes with `Duration` accumulators and a `Vec` of transitions. Update
+        // the figures deliberately
+        #[cfg(not(feature = "smallvec"))]
+        let expected = [
+            ("Button", 224),
+            ("KeyRepeatState", 72),
+            ("InputBundle", 120),
+        ];
+        // inline entries trade size for no allocation
+        #[cfg(feature = "smallvec")]
+        let expected = [
+            ("Button", 384),
+            ("KeyRepeatState", 72),
+            ("InputBundle", 280),
+        ];
+        assert_eq!(sizes, expected, "sizes changed");
+    }
+
+    #[test]
+    #[cfg(feature = "smallvec")]
+    fn single_button_is_inline() {
+        let single = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        assert!(!single.input.keys.spilled());
+        let two = InputBundle::from_keys([Key::A, Key::B]);
+        assert!(!two.keys.spilled());
+        let three = InputBundle::from_keys([Key::A, Key::B, Key::C]);
+        assert!(three.keys.spilled());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn single_button_serde_is_unchanged() {
+        let single = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        let new = Button::new(self::keys(&[Key::A]), KeyRepeatConfig::NoRepeat);
+        let ron = ron::to_string(&single).unwrap();
+        assert_eq!(ron, ron::to_string(&new).unwrap());
+        assert_eq!(ron, "(input:(keys:[(key:A)]),state:Up)");
+
+        let de: Button = ron::from_str(&ron).unwrap();
+        assert_eq!((de.input, de.state), (single.input, single.state));
+    }
+
+    /// `is_pressed` of a turbo button over the frames of the script
+    fn turbo_presses(turbo: TurboConfig, script: ScriptBackend, n: usize) -> Vec<bool> {
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(10), Duration::from_millis(10));
+        let mut button = Button::single(Key::A, repeat);
+        button.set_turbo(Some(turbo));
+        let mut frames = Frames::new(script);
+        (0..n)
+            .map(|_| {
+                let input = frames.next();
+                button.update(input, DT);
+                // the physical state is kept
+                assert_eq!(button.is_down(), input.kbd.is_key_down(Key::A));
+                button.is_pressed()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn turbo_press_cadence() {
+        let (t, f) = (true, false);
+        let half = TurboConfig {
+            rate: Duration::from_millis(40),
+            duty: 0.5,
+        };
+        let quarter = TurboConfig { duty: 0.25, ..half };
+
+        // the key repeat config is ignored
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..10);
+        assert_eq!(
+            self::turbo_presses(half, script.clone(), 11),
+            vec![t, t, f, f, t, t, f, f, t, t, f]
+        );
+        assert_eq!(
+            self::turbo_presses(quarter, script, 11),
+            vec![t, f, f, f, t, f, f, f, t, f, f]
+        );
+
+        // the phase restarts on every press
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..3).hold(Key::A, 4..8);
+        assert_eq!(
+            self::turbo_presses(half, script, 8),
+            vec![t, t, f, f, t, t, f, f]
+        );
+
+        let mut button = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        button.set_turbo(Some(half));
+        assert_eq!(button.turbo(), Some(half));
+        button.set_turbo(None);
+        assert_eq!(button.turbo(), None);
+    }
+
+    #[test]
+    fn no_repeat_tracks_down_time() {
+        let mut button = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..4);
+        let mut frames = Frames::new(script);
+
+        button.update(frames.next(), DT);
+        for _ in 0..3 {
+            button.update(frames.next(), DT);
+            assert_eq!(button.state, StrictButtonState::Down);
+        }
+        assert_eq!(button.accum_down(), DT * 3);
+
+        button.update(frames.next(), DT);
+        assert!(button.is_released());
+        assert_eq!(button.accum_down(), DT * 3);
+    }
+
+    #[test]
+    fn shared_repeat_config_survives_poison() {
+        let shared = SharedRepeatConfig::new(KeyRepeatConfig::NoRepeat);
+        let poisoner = shared.clone();
+        let res = std::thread::spawn(move || {
+            let _guard = poisoner.0.write().unwrap();
+            panic!("poison the lock");
         })
+        .join();
+        assert!(res.is_err());
+        assert!(shared.0.is_poisoned());
+
+        shared.set(KeyRepeatConfig::repeat_frames(2, 1));
+        assert!(matches!(
+            shared.get(),
+            KeyRepeatConfig::RepeatFrames { first: 2, multi: 1 }
+        ));
+    }
+
+    #[test]
+    fn shared_repeat_config_applies_mid_hold() {
+        let ms = Duration::from_millis;
+        let shared = SharedRepeatConfig::new(KeyRepeatConfig::repeat(ms(100), ms(100)));
+        let mut button = Button::new_shared(keys(&[Key::A]), shared.clone());
+        let mut dir = AxisDirButton::new_shared(
+            shared.clone(),
+            [keys(&[Key::Right]), keys(&[Key::Left])],
+            [keys(&[Key::Down]), keys(&[Key::Up])],
+        );
+
+        let mut script = ScriptBackend::new(DT);
+        script.at_frame(0).press(Key::A).press(Key::Right);
+        let mut frames = Frames::new(script);
+
+        let mut repeats = Vec::new();
+        for i in 0..40 {
+            // right after the second repeat
+            if i == 22 {
+                shared.set(KeyRepeatConfig::repeat(ms(100), ms(30)));
+            }
+            let input = frames.next();
+            button.update(input, DT);
+            dir.update(input, DT);
+            assert_eq!(dir.x().pos.state, button.state);
+            if button.state == StrictButtonState::Repeating {
+                repeats.push(i);
+            }
+        }
+
+        // the in-flight accumulator is kept and the next repeat is 30ms after the last one
+        assert_eq!(repeats[..5], [11, 21, 24, 27, 30]);
+        assert!(repeats.windows(2).skip(2).all(|w| w[1] - w[0] == 3));
+    }
+
+    #[test]
+    fn repeat_count_is_capped() {
+        let cfg = KeyRepeatConfig::repeat(Duration::from_millis(100), Duration::from_millis(30));
+        let mut button = Button::single(Key::A, cfg);
+        let dt = Duration::from_millis(16);
+        let mut frames = self::press(Key::A, dt);
+
+        button.update(frames.next(), dt);
+        let mut counts = Vec::new();
+        for _ in 0..60 {
+            button.update(frames.next(), dt);
+            counts.push(button.repeat_count_this_update());
+        }
+        assert!(counts.iter().all(|n| *n <= 1), "{:?}", counts);
+        assert!(counts.contains(&1));
+
+        button.update(frames.next(), Duration::from_secs(600));
+        assert_eq!(button.repeat_count_this_update(), 4);
+
+        button.set_max_repeats_per_update(7);
+        button.update(frames.next(), Duration::from_secs(600));
+        assert_eq!(button.repeat_count_this_update(), 7);
+        button.update(frames.next(), dt);
+        assert!(button.repeat_count_this_update() <= 1);
+    }
+
+    #[test]
+    fn huge_steps_saturate() {
+        let configs = [
+            KeyRepeatConfig::NoRepeat,
+            KeyRepeatConfig::repeat(Duration::from_millis(100), Duration::from_millis(30)),
+            KeyRepeatConfig::repeat_frames(0, 0),
+        ];
+        for cfg in configs {
+            let mut by_time = Button::single(Key::A, cfg);
+            let mut by_ticks = Button::single(Key::A, cfg);
+            let mut frames = self::press(Key::A, DT);
+            for _ in 0..3 {
+                let input = frames.next();
+                by_time.update(input, Duration::MAX);
+                by_ticks.update_ticks(input, u32::MAX);
+            }
+            assert_eq!(by_time.repeat.down_ticks, 2, "{:?}", cfg);
+            assert_eq!(by_ticks.repeat.down_ticks, u32::MAX, "{:?}", cfg);
+        }
+    }
+
+    #[test]
+    fn zero_period_repeats_every_tick() {
+        let mut by_time = Button::single(
+            Key::A,
+            KeyRepeatConfig::repeat(Duration::ZERO, Duration::ZERO),
+        );
+        let mut by_frames = Button::single(Key::A, KeyRepeatConfig::repeat_frames(0, 0));
+        let dt = Duration::from_millis(16);
+        let mut frames = self::press(Key::A, dt);
+
+        let input = frames.next();
+        by_time.update(input, dt);
+        by_frames.update(input, dt);
+        for _ in 0..3 {
+            let input = frames.next();
+            by_time.update(input, dt);
+            by_frames.update(input, dt);
+            assert_eq!(by_time.repeat_count_this_update(), 1);
+            assert_eq!(by_frames.repeat_count_this_update(), 1);
+        }
+
+        by_frames.update_ticks(frames.next(), 3);
+        assert_eq!(by_frames.repeat_count_this_update(), 3);
+    }
+
+    #[test]
+    fn pause_drops_the_repeat_backlog() {
+        let cfg = KeyRepeatConfig::repeat(Duration::from_millis(300), Duration::from_millis(100));
+        let mut button = Button::single(Key::A, cfg);
+        let mut frames = self::press(Key::A, DT);
+
+        button.update(frames.next(), DT);
+        for _ in 0..20 {
+            button.update(frames.next(), DT);
+        }
+        assert_eq!(button.state, StrictButtonState::Down);
+
+        // five seconds of frames while held, and a stall of five seconds
+        button.set_paused(true);
+        for _ in 0..500 {
+            button.update(frames.next(), DT);
+            assert_eq!(button.state, StrictButtonState::Down);
+        }
+        button.update(frames.next(), Duration::from_secs(5));
+        assert_eq!(button.state, StrictButtonState::Down);
+        button.set_paused(false);
+
+        // 100ms were left before the first repeat, then it's every 100ms
+        let mut repeats = Vec::new();
+        for i in 0..40 {
+            button.update(frames.next(), DT);
+            if button.state == StrictButtonState::Repeating {
+                assert_eq!(button.repeat_count_this_update(), 1);
+                repeats.push(i);
+            }
+        }
+        assert_eq!(repeats, vec![10, 20, 30]);
+    }
+
+    fn keys(keys: &[Key]) -> InputBundle {
+        InputBundle {
+            keys: keys.iter().map(|k| KeyEntry::from(*k)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transitions_of_press_hold_release() {
+        use StrictButtonState::*;
+
+        let cfg = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+        let mut button = Button::single(Key::A, cfg);
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..7);
+        let mut frames = Frames::new(script);
+
+        let mut log = Vec::new();
+        for i in 0..9 {
+            button.update(frames.next(), DT);
+            assert_eq!(button.transitions(), &button.clone().take_transitions()[..]);
+            log.extend(button.take_transitions().into_iter().map(|t| (i, t)));
+            assert!(button.transitions().is_empty());
+        }
+
+        assert_eq!(
+            log,
+            vec![
+                (0, (Up, Pressed)),
+                (1, (Pressed, Down)),
+                // 30ms after the press frame
+                (4, (Down, Repeating)),
+                (5, (Repeating, Down)),
+                (6, (Down, Repeating)),
+                (7, (Repeating, Released)),
+                (8, (Released, Up)),
+            ]
+        );
+    }
+
+    #[test]
+    fn transitions_survive_snapshot_restore() {
+        let mut button = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        let mut frames = self::press(Key::A, DT);
+        button.update(frames.next(), DT);
+        let snapshot = button.snapshot();
+
+        let mut restored = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        restored.restore(&snapshot);
+        assert_eq!(
+            restored.transitions(),
+            &[(StrictButtonState::Up, StrictButtonState::Pressed)]
+        );
+        assert_eq!(restored.transitions(), button.transitions());
+    }
+
+    /// Raw states of the bundle over the frames of the script
+    fn bundle_states(bundle: &InputBundle, script: ScriptBackend, n: usize) -> Vec<RawButtonState> {
+        let mut frames = Frames::new(script);
+        let mut grabs = 0;
+        (0..n)
+            .map(|_| bundle.state(frames.next(), &mut grabs))
+            .collect()
+    }
+
+    #[test]
+    fn bundle_states_of_a_key() {
+        use RawButtonState::*;
+
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..2);
+        let states = self::bundle_states(&keys(&[Key::A]), script, 4);
+        assert_eq!(states, vec![Pressed, Down, Released, Up]);
+    }
+
+    #[test]
+    fn bundle_states_of_a_chord() {
+        use RawButtonState::*;
+
+        let bundle = InputBundle {
+            chords: entries![KeyChord(vec![Key::A, Key::B])],
+            ..Default::default()
+        };
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..3).hold(Key::B, 1..4);
+        let states = self::bundle_states(&bundle, script, 5);
+        assert_eq!(states, vec![Up, Pressed, Down, Released, Up]);
+    }
+
+    #[test]
+    fn chord_fires_once_per_press_of_the_last_key() {
+        use RawButtonState::*;
+
+        let bundle = InputBundle {
+            chords: entries![KeyChord(vec![Key::Q, Key::E])],
+            ..self::keys(&[Key::A])
+        };
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::Q, 0..10)
+            .hold(Key::E, 2..4)
+            .hold(Key::E, 6..8)
+            .hold(Key::A, 8..9);
+        let states = self::bundle_states(&bundle, script, 11);
+        assert_eq!(
+            states,
+            vec![Up, Up, Pressed, Down, Released, Up, Pressed, Down, Pressed, Released, Up]
+        );
+
+        // the single-key entry alone
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..2).hold(Key::E, 0..3);
+        let states = self::bundle_states(&bundle, script, 4);
+        assert_eq!(states, vec![Pressed, Down, Released, Up]);
+    }
+
+    #[test]
+    fn bundle_released_with_another_entry_down() {
+        use RawButtonState::*;
+
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::A, 0..2).hold(Key::B, 1..3);
+        let states = self::bundle_states(&keys(&[Key::A, Key::B]), script, 4);
+        // still down by B on frame 2
+        assert_eq!(states, vec![Pressed, Pressed, Down, Released]);
+    }
+
+    #[test]
+    fn key_entry_with_held_modifiers() {
+        use RawButtonState::*;
+
+        let ctrl_s: KeyEntry = "Ctrl+S".parse().unwrap();
+        let bundle = InputBundle {
+            keys: entries![ctrl_s.clone()],
+            ..Default::default()
+        };
+        let states_and_presses = |script: ScriptBackend, n: usize| {
+            let mut button = Button::single(ctrl_s.clone(), KeyRepeatConfig::NoRepeat);
+            let mut frames = Frames::new(script.clone());
+            let presses = (0..n)
+                .map(|_| {
+                    button.update(frames.next(), DT);
+                    button.is_pressed()
+                })
+                .collect::<Vec<_>>();
+            (self::bundle_states(&bundle, script, n), presses)
+        };
+
+        // Ctrl of either side held before S
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::LCtrl, 0..4)
+            .hold(Key::S, 2..3)
+            .hold(Key::RCtrl, 5..9)
+            .hold(Key::S, 6..8);
+        let (states, presses) = states_and_presses(script, 10);
+        assert_eq!(
+            states,
+            vec![Up, Up, Pressed, Released, Up, Up, Pressed, Down, Released, Up]
+        );
+        let pressed = presses.iter().enumerate().filter(|(_, p)| **p);
+        assert_eq!(pressed.map(|(i, _)| i).collect::<Vec<_>>(), vec![2, 6]);
+
+        // Ctrl after S is not a press, and releasing it releases the entry
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::S, 0..4).hold(Key::LCtrl, 1..3);
+        let (states, presses) = states_and_presses(script, 5);
+        assert_eq!(states, vec![Up, Down, Down, Released, Up]);
+        assert!(presses.iter().all(|p| !p));
+
+        // Alt+Enter with both sides down
+        let bundle = InputBundle {
+            keys: entries!["alt+enter".parse().unwrap()],
+            ..Default::default()
+        };
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::LAlt, 0..3)
+            .hold(Key::RAlt, 0..3)
+            .hold(Key::Enter, 1..2);
+        let states = self::bundle_states(&bundle, script, 3);
+        assert_eq!(states, vec![Up, Pressed, Released]);
+    }
+
+    #[test]
+    fn key_entry_strings() {
+        let entry: KeyEntry = "ctrl + Shift + ALT + Meta + S".parse().unwrap();
+        assert!(entry.ctrl && entry.shift && entry.alt && entry.meta);
+        assert_eq!(entry.to_string(), "Ctrl+Shift+Alt+Meta+S");
+        assert_eq!(
+            "Option+Tab".parse(),
+            Ok(KeyEntry {
+                alt: true,
+                ..Key::Tab.into()
+            })
+        );
+        assert_eq!(
+            "Hyper+S".parse::<KeyEntry>().unwrap_err().to_string(),
+            "unknown key or modifier: `Hyper`"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn key_entry_serde_forms() {
+        let alt_enter = KeyEntry {
+            alt: true,
+            ..Key::Enter.into()
+        };
+        assert_eq!(ron::to_string(&alt_enter).unwrap(), "(key:Enter,alt:true)");
+        assert_eq!(
+            ron::from_str::<KeyEntry>("\"Alt+Enter\"").unwrap(),
+            alt_enter
+        );
+        assert_eq!(
+            ron::from_str::<KeyEntry>("(key:Enter,alt:true)").unwrap(),
+            alt_enter
+        );
+
+        // strings in formats that are not human-readable
+        let entries: Vec<KeyEntry> = vec!["Ctrl+S".parse().unwrap(), Key::Enter.into()];
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&entries, &mut bytes).unwrap();
+        let value: ciborium::Value = ciborium::from_reader(&bytes[..]).unwrap();
+        let strings = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_text().unwrap());
+        assert_eq!(strings.collect::<Vec<_>>(), vec!["Ctrl+S", "Enter"]);
+        let de: Vec<KeyEntry> = ciborium::from_reader(&bytes[..]).unwrap();
+        assert_eq!(de, entries);
+    }
+
+    /// Few keys so that random streams hit the entries often
+    const POOL: [Key; 8] = [
+        Key::A,
+        Key::S,
+        Key::D,
+        Key::LCtrl,
+        Key::RCtrl,
+        Key::LShift,
+        Key::RShift,
+        Key::LMeta,
+    ];
+
+    fn random_key(seed: &mut u64) -> Key {
+        POOL[xorshift64(seed) as u32 as usize % POOL.len()]
+    }
+
+    fn random_bundle(seed: &mut u64) -> InputBundle {
+        let keys = (0..1 + xorshift64(seed) as u32 % 3)
+            .map(|_| {
+                let r = xorshift64(seed) as u32;
+                KeyEntry {
+                    key: self::random_key(seed),
+                    ctrl: r & 0b111 == 0,
+                    shift: r & 0b111_000 == 0,
+                    alt: r & 0b111_000_000 == 0,
+                    meta: r & 0b111_000_000_000 == 0,
+                }
+            })
+            .collect();
+        let chords = (0..xorshift64(seed) as u32 % 3)
+            .map(|_| {
+                KeyChord(
+                    (0..1 + xorshift64(seed) as u32 % 3)
+                        .map(|_| random_key(seed))
+                        .collect(),
+                )
+            })
+            .collect();
+        InputBundle {
+            keys,
+            chords,
+            ..Default::default()
+        }
+    }
+
+    /// Toggles a few random keys of the pool
+    fn random_frame(seed: &mut u64, backend: &mut ManualBackend, input: &mut Input) {
+        backend.end_frame(input);
+        for _ in 0..xorshift64(seed) as u32 % 3 {
+            let key = self::random_key(seed);
+            if input.kbd.is_key_down(key) {
+                backend.key_up(key);
+            } else {
+                backend.key_down(key);
+            }
+        }
+        backend.update(input);
+    }
+
+    #[test]
+    fn dirty_tracking_matches_the_naive_path() {
+        let mut seed = 0x2545_f491_4f6c_dd1d;
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+
+        for _ in 0..200 {
+            let bundle = self::random_bundle(&mut seed);
+            let mut tracked = Button::new(bundle.clone(), repeat);
+            let mut naive = Button::new(bundle, repeat);
+            let mut backend = ManualBackend::new();
+            let mut input = Input::new();
+            backend.update(&mut input);
+
+            for frame in 0..100 {
+                self::random_frame(&mut seed, &mut backend, &mut input);
+                tracked.update(&input, DT);
+                let state = naive.input.state(&input, &mut naive.mouse_grabs);
+                let repeats = naive.repeat.update(state, DT);
+                naive.apply(state, repeats);
+
+                assert!(tracked.memo.is_some());
+                assert_eq!(
+                    (tracked.state, tracked.repeat_count),
+                    (naive.state, naive.repeat_count),
+                    "frame {} of {:?}",
+                    frame,
+                    tracked.input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compiled_masks_match_the_entries() {
+        let mut seed = 0x9e37_79b9_7f4a_7c15;
+
+        for _ in 0..200 {
+            let bundle = self::random_bundle(&mut seed);
+            let compiled = bundle.compile().unwrap();
+            let mut backend = ManualBackend::new();
+            let mut input = Input::new();
+            backend.update(&mut input);
+
+            for _ in 0..100 {
+                self::random_frame(&mut seed, &mut backend, &mut input);
+                let [cur, prev] = input.key_bits().unwrap();
+                assert_eq!(
+                    compiled.state(&cur, &prev),
+                    bundle.state(&input, &mut 0),
+                    "{:?}",
+                    bundle
+                );
+            }
+        }
+
+        // not compiled
+        let raw = InputBundle {
+            raw: entries![RawKey(1)],
+            ..self::keys(&[Key::A])
+        };
+        assert!(raw.compile().is_none());
+        assert_eq!(raw.key_deps(), None);
+    }
+
+    #[test]
+    fn mutated_bundle_is_recompiled() {
+        let mut seed = 0xdead_beef_cafe_f00d;
+        let mut button = Button::single(Key::A, KeyRepeatConfig::NoRepeat);
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        backend.update(&mut input);
+
+        for i in 0..1000 {
+            self::random_frame(&mut seed, &mut backend, &mut input);
+            if i % 10 == 0 {
+                *button.input_mut() = self::random_bundle(&mut seed);
+            }
+            button.update(&input, DT);
+            let memo = button.memo.as_ref().unwrap();
+            assert_eq!(button.input.key_deps(), Some(memo.compiled.deps));
+
+            let state = button.input.state(&input, &mut 0);
+            assert_eq!(
+                button.state,
+                StrictButtonState::from_raw(state, false),
+                "{:?}",
+                button.input
+            );
+        }
+    }
+
+    fn chords(chords: &[&[Key]]) -> InputBundle {
+        InputBundle {
+            chords: chords.iter().map(|c| KeyChord(c.to_vec())).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_empty() {
+        assert_eq!(InputBundle::default().validate(), vec![BundleIssue::Empty]);
+        assert_eq!(self::keys(&[Key::A]).validate(), vec![]);
+    }
+
+    #[test]
+    fn validate_duplicate_entry() {
+        assert_eq!(
+            self::keys(&[Key::A, Key::B, Key::A]).validate(),
+            vec![BundleIssue::DuplicateEntry { index: 2, first: 0 }]
+        );
+
+        // modifiers make another entry
+        let mut bundle = self::keys(&[Key::A, Key::A]);
+        bundle.keys[1].ctrl = true;
+        assert_eq!(bundle.validate(), vec![]);
+    }
+
+    #[test]
+    fn validate_none_key() {
+        assert_eq!(
+            self::keys(&[Key::A, Key::None]).validate(),
+            vec![BundleIssue::NoneKey { index: 1 }]
+        );
+    }
+
+    #[test]
+    fn validate_duplicate_chord() {
+        assert_eq!(
+            self::chords(&[&[Key::Q, Key::E], &[Key::Q, Key::E]]).validate(),
+            vec![BundleIssue::DuplicateChord { index: 1, first: 0 }]
+        );
+    }
+
+    #[test]
+    fn validate_chord_none_key() {
+        assert_eq!(
+            self::chords(&[&[Key::Q, Key::None]]).validate(),
+            vec![BundleIssue::ChordNoneKey { chord: 0 }]
+        );
+    }
+
+    #[test]
+    fn validate_chord_duplicate_key() {
+        assert_eq!(
+            self::chords(&[&[Key::Q, Key::E, Key::Q]]).validate(),
+            vec![BundleIssue::ChordDuplicateKey {
+                chord: 0,
+                key: Key::Q,
+            }]
+        );
+    }
+
+    #[test]
+    fn validate_chord_modifier_key() {
+        assert_eq!(
+            self::chords(&[&[Key::Q], &[Key::LCtrl, Key::E]]).validate(),
+            vec![BundleIssue::ChordModifierKey {
+                chord: 1,
+                key: Key::LCtrl,
+            }]
+        );
+    }
+
+    #[test]
+    fn normalize_fixes_the_issues() {
+        let mut bundle = InputBundle {
+            chords: entries![
+                KeyChord(vec![Key::Q, Key::None, Key::Q, Key::E]),
+                KeyChord(vec![Key::Q, Key::E]),
+                KeyChord(vec![Key::None]),
+            ],
+            ..self::keys(&[Key::A, Key::None, Key::B, Key::A])
+        };
+        bundle.normalize();
+        assert_eq!(bundle.validate(), vec![]);
+        assert_eq!(
+            bundle,
+            InputBundle {
+                chords: entries![KeyChord(vec![Key::Q, Key::E])],
+                ..self::keys(&[Key::A, Key::B])
+            }
+        );
+    }
+
+    fn axis() -> AxisButton {
+        AxisButton {
+            pos: Button::single(Key::Right, KeyRepeatConfig::NoRepeat),
+            neg: Button::single(Key::Left, KeyRepeatConfig::NoRepeat),
+        }
+    }
+
+    #[test]
+    fn sign_released() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..3).hold(Key::Left, 2..4);
+        let mut frames = Frames::new(script);
+        let mut axis = self::axis();
+
+        axis.update(frames.next(), DT);
+        axis.update(frames.next(), DT);
+        axis.update(frames.next(), DT);
+        assert_eq!(axis.sign_down(), Sign::Neg);
+
+        // one of the two opposing keys
+        axis.update(frames.next(), DT);
+        assert_eq!(axis.sign_released(), Sign::Pos);
+        assert_eq!(axis.sign_down(), Sign::Neg);
+
+        // the last held key
+        axis.update(frames.next(), DT);
+        assert_eq!(axis.sign_released(), Sign::Neg);
+        axis.update(frames.next(), DT);
+        assert_eq!(axis.sign_released(), Sign::Neutral);
+    }
+
+    #[test]
+    fn sign_released_at_once_is_latest_wins() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Left, 0..4).hold(Key::Right, 2..4);
+        let mut frames = Frames::new(script);
+        let mut axis = self::axis();
+
+        for _ in 0..4 {
+            axis.update(frames.next(), DT);
+        }
+        assert_eq!(axis.sign_down(), Sign::Pos);
+
+        axis.update(frames.next(), DT);
+        assert_eq!(axis.sign_released(), Sign::Pos);
+    }
+
+    fn arrows(repeat: KeyRepeatConfig) -> AxisDirButton {
+        AxisDirButton::new(
+            repeat,
+            [keys(&[Key::Right]), keys(&[Key::Left])],
+            [keys(&[Key::Down]), keys(&[Key::Up])],
+        )
+    }
+
+    #[test]
+    fn dir_released() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..2).hold(Key::Up, 0..1);
+        let mut frames = Frames::new(script);
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+
+        dir.update(frames.next(), DT);
+        dir.update(frames.next(), DT);
+        assert_eq!(dir.dir_released(), None);
+        assert_eq!(dir.dir8_down(), Some(Dir8::E));
+
+        dir.update(frames.next(), DT);
+        assert_eq!(dir.dir_released(), Some(Dir8::E));
+        dir.update(frames.next(), DT);
+        assert_eq!(dir.dir_released(), None);
+    }
+
+    #[test]
+    fn analog_value_normalizes_diagonals() {
+        let mut script = ScriptBackend::new(DT);
+        script.at_frame(0).press(Key::Right).press(Key::Up);
+        let mut frames = Frames::new(script);
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+
+        dir.update(frames.next(), DT);
+        assert_eq!(dir.value(), Axis2d::new(1.0, -1.0));
+
+        dir.set_analog_config(AnalogConfig {
+            normalize_diagonal: true,
+            ..Default::default()
+        });
+        let v = dir.value();
+        assert!((v.length() - 1.0).abs() < 1e-6, "{:?}", v);
+        assert!((v.x - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((v.y + std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn analog_value_ramps_over_attack_and_release() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..15).hold(Key::Down, 0..15);
+        let mut frames = Frames::new(script);
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_analog_config(AnalogConfig {
+            attack: Duration::from_millis(100),
+            release: Duration::from_millis(50),
+            normalize_diagonal: true,
+        });
+
+        // 0.1 per frame up to the normalized diagonal
+        let target = std::f32::consts::FRAC_1_SQRT_2;
+        for i in 1..=15 {
+            dir.update(frames.next(), DT);
+            let v = dir.value_smoothed();
+            let expected = (0.1 * i as f32).min(target);
+            assert!((v.x - expected).abs() < 1e-5, "frame {}: {:?}", i, v);
+            assert!((v.y - expected).abs() < 1e-5, "frame {}: {:?}", i, v);
+            assert!(v.length() <= 1.0 + 1e-6);
+        }
+
+        // 0.2 per frame down to zero
+        for i in 1..=5 {
+            dir.update(frames.next(), DT);
+            let v = dir.value_smoothed();
+            let expected = (target - 0.2 * i as f32).max(0.0);
+            assert!((v.x - expected).abs() < 1e-5, "frame {}: {:?}", i, v);
+        }
+        assert_eq!(dir.value_smoothed(), Axis2d::ZERO);
+    }
+
+    /// `(frame, dir4_pressed, dir8_pressed)` of the frames with a press
+    fn presses(
+        dir: &mut AxisDirButton,
+        script: ScriptBackend,
+        n: usize,
+    ) -> Vec<(usize, Option<Dir4>, Option<Dir8>)> {
+        let mut frames = Frames::new(script);
+        let mut presses = Vec::new();
+        for i in 0..n {
+            dir.update(frames.next(), DT);
+            if dir.dir4_pressed().is_some() || dir.dir8_pressed().is_some() {
+                presses.push((i, dir.dir4_pressed(), dir.dir8_pressed()));
+            }
+        }
+        presses
+    }
+
+    #[test]
+    fn diagonal_grace_merges_staggered_presses() {
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_diagonal_grace(Duration::from_millis(50));
+
+        // up arrives 20ms after right
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..20).hold(Key::Up, 2..20);
+        let presses = self::presses(&mut dir, script, 20);
+        assert_eq!(presses, vec![(2, Some(Dir4::N), Some(Dir8::NE))]);
+    }
+
+    #[test]
+    fn diagonal_grace_emits_cardinal_after_the_window() {
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_diagonal_grace(Duration::from_millis(50));
+
+        // the window ends on frame 5, before down arrives
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..20).hold(Key::Down, 8..20);
+        let presses = self::presses(&mut dir, script, 20);
+        assert_eq!(presses[0], (5, Some(Dir4::E), Some(Dir8::E)));
+
+        // released in the window
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_diagonal_grace(Duration::from_millis(50));
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Left, 0..2);
+        let presses = self::presses(&mut dir, script, 10);
+        assert_eq!(presses, vec![(2, Some(Dir4::W), Some(Dir8::W))]);
+    }
+
+    #[test]
+    fn dir_change_emits_only_if_enabled() {
+        let script = || {
+            let mut script = ScriptBackend::new(DT);
+            script.hold(Key::Right, 0..10).hold(Key::Up, 3..6);
+            script
+        };
+
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_emit_on_dir_change(true);
+        assert_eq!(
+            self::presses(&mut dir, script(), 10),
+            vec![
+                (0, Some(Dir4::E), Some(Dir8::E)),
+                (3, Some(Dir4::N), Some(Dir8::NE)),
+                (6, Some(Dir4::E), Some(Dir8::E)),
+            ]
+        );
+
+        // adding an axis to the held direction is not a press of the down direction
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        assert_eq!(
+            self::presses(&mut dir, script(), 10)
+                .iter()
+                .filter(|p| p.2.is_some())
+                .map(|p| (p.0, p.2))
+                .collect::<Vec<_>>(),
+            vec![(0, Some(Dir8::E))]
+        );
+    }
+
+    #[test]
+    fn diagonal_grace_wins_over_dir_change() {
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_emit_on_dir_change(true);
+        dir.set_diagonal_grace(Duration::from_millis(50));
+
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..20).hold(Key::Up, 2..10);
+        assert_eq!(
+            self::presses(&mut dir, script, 20),
+            vec![
+                (2, Some(Dir4::N), Some(Dir8::NE)),
+                (10, Some(Dir4::E), Some(Dir8::E)),
+            ]
+        );
+    }
+
+    #[test]
+    fn dir_timers_pause_only_if_every_button_is_paused() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Right, 0..20);
+        let mut frames = Frames::new(script);
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_analog_config(AnalogConfig {
+            attack: Duration::from_millis(100),
+            release: Duration::from_millis(100),
+            normalize_diagonal: false,
+        });
+
+        dir.update(frames.next(), DT);
+        assert!((dir.value_smoothed().x - 0.1).abs() < 1e-5);
+
+        // pausing the x axis alone keeps smoothing
+        dir.x_mut().set_paused(true);
+        assert!(!dir.is_paused());
+        dir.update(frames.next(), DT);
+        assert!((dir.value_smoothed().x - 0.2).abs() < 1e-5);
+
+        dir.y_mut().set_paused(true);
+        assert!(dir.is_paused());
+        dir.update(frames.next(), DT);
+        assert!((dir.value_smoothed().x - 0.2).abs() < 1e-5);
+
+        // resuming any button resumes the timers
+        dir.x_mut().set_paused(false);
+        dir.update(frames.next(), DT);
+        assert!((dir.value_smoothed().x - 0.3).abs() < 1e-5);
+    }
+
+    /// Number of `dir8_pressed` frames while holding the key for 60 frames
+    #[cfg(feature = "serde")]
+    fn count_presses(dir: &mut AxisDirButton, key: Key) -> usize {
+        let mut script = ScriptBackend::new(DT);
+        script.at_frame(0).press(key);
+        let mut frames = Frames::new(script);
+        (0..60)
+            .filter(|_| {
+                dir.update(frames.next(), DT);
+                dir.dir8_pressed().is_some()
+            })
+            .count()
+    }
+
+    /// RON arrow keys with the `repeat` field if any
+    #[cfg(feature = "serde")]
+    fn dir_pad(repeat: Option<&str>) -> String {
+        let axes = r#"
+            x: (pos: (keys: ["Right"]), neg: (keys: ["Left"])),
+            y: (pos: (keys: ["Down"]), neg: (keys: ["Up"])),
+        "#;
+        match repeat {
+            Some(repeat) => format!("({} repeat: {})", axes, repeat),
+            None => format!("({})", axes),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn axis_dir_serde_repeat() {
+        let frames = KeyRepeatConfig::repeat_frames(30, 5);
+        let load = |ron: &str| {
+            let mut de = ron::Deserializer::from_str(ron).unwrap();
+            AxisDirButton::deserialize_with_repeat(&mut de, frames).unwrap()
+        };
+
+        // missing config: the supplied one
+        let mut dir = load(&dir_pad(None));
+        assert_eq!(count_presses(&mut dir, Key::Right), 1 + 1 + 5);
+
+        // the same config for all of the buttons
+        let mut dir = load(&dir_pad(Some("(first: 10, multi: 10)")));
+        assert_eq!(count_presses(&mut dir, Key::Up), 1 + 1 + 4);
+
+        // explicit `NoRepeat` is not replaced with the supplied config
+        let mut dir = load(&dir_pad(Some("()")));
+        assert_eq!(count_presses(&mut dir, Key::Right), 1);
+        assert_eq!(dir.y().neg.repeat_config(), KeyRepeatConfig::NoRepeat);
+
+        // split x/y
+        let mut dir = load(&dir_pad(Some(
+            "(x: (pos: (first: 10, multi: 10), neg: (first: 10, multi: 10)), y: (pos: (), neg: ()))",
+        )));
+        assert_eq!(count_presses(&mut dir, Key::Left), 1 + 1 + 4);
+        assert_eq!(count_presses(&mut dir, Key::Down), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn axis_dir_serde_round_trip() {
+        let fast = KeyRepeatConfig::repeat_frames(10, 10);
+        let slow = KeyRepeatConfig::repeat(Duration::from_millis(500), Duration::from_millis(200));
+
+        let mut dir = self::arrows(KeyRepeatConfig::NoRepeat);
+        dir.set_repeat_config_xy(fast, KeyRepeatConfig::NoRepeat);
+        dir.x_mut().neg.set_repeat_config(slow);
+
+        let cfgs = |dir: &mut AxisDirButton| {
+            let bs = dir.buttons_mut();
+            [0, 1, 2, 3].map(|i| bs[i].repeat_config())
+        };
+        let expected = [
+            fast,
+            slow,
+            KeyRepeatConfig::NoRepeat,
+            KeyRepeatConfig::NoRepeat,
+        ];
+
+        let ron = ron::to_string(&dir).unwrap();
+        let mut de = ron::Deserializer::from_str(&ron).unwrap();
+        let mut loaded = AxisDirButton::deserialize_with_repeat(&mut de, fast).unwrap();
+        assert_eq!(cfgs(&mut loaded), expected);
+
+        // `NoRepeat` is `null` in JSON, which is still not a missing config
+        let json = serde_json::to_string(&dir).unwrap();
+        let mut de = serde_json::Deserializer::from_str(&json);
+        let mut loaded = AxisDirButton::deserialize_with_repeat(&mut de, fast).unwrap();
+        assert_eq!(cfgs(&mut loaded), expected);
+
+        // shared configs are supplied on load
+        let shared = SharedRepeatConfig::new(fast);
+        let dir = AxisDirButton::new_shared(
+            shared.clone(),
+            [keys(&[Key::Right]), keys(&[Key::Left])],
+            [keys(&[Key::Down]), keys(&[Key::Up])],
+        );
+        let ron = ron::to_string(&dir).unwrap();
+        assert!(!ron.contains("repeat"), "{}", ron);
+
+        let mut de = ron::Deserializer::from_str(&ron).unwrap();
+        let mut loaded = AxisDirButton::deserialize_with_shared(&mut de, shared.clone()).unwrap();
+        shared.set(slow);
+        assert_eq!(cfgs(&mut loaded), [slow; 4]);
+    }
+
+    #[test]
+    fn raw_key_binding() {
+        use crate::input::any::{AnyInput, AnyInputFilter};
+
+        // an SDL keycode without `Key` translation
+        let raw = RawKey(0x4000_0000);
+        let bundle = InputBundle {
+            raw: entries![raw],
+            ..Default::default()
+        };
+        let mut button = Button::new(bundle.clone(), KeyRepeatConfig::NoRepeat);
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+
+        backend.raw_key_down(raw);
+        backend.update(&mut input);
+        button.update(&input, DT);
+        assert!(input.kbd.is_raw_key_pressed(raw));
+        assert!(button.is_pressed());
+        // captured by the rebinding flow
+        let filter = AnyInputFilter::default();
+        assert_eq!(input.first_pressed(&filter), Some(AnyInput::Raw(raw)));
+        backend.end_frame(&mut input);
+
+        backend.update(&mut input);
+        button.update(&input, DT);
+        assert!(input.kbd.is_raw_key_down(raw) && !input.kbd.is_raw_key_pressed(raw));
+        assert!(button.is_down() && !button.is_pressed());
+        backend.end_frame(&mut input);
+
+        backend.raw_key_up(raw);
+        backend.update(&mut input);
+        button.update(&input, DT);
+        assert!(input.kbd.is_raw_key_released(raw));
+        assert!(button.is_released());
+
+        #[cfg(feature = "serde")]
+        {
+            let ron = ron::to_string(&bundle).unwrap();
+            assert!(ron.contains("\"raw:1073741824\""), "{}", ron);
+            assert_eq!(ron::from_str::<InputBundle>(&ron).unwrap(), bundle);
+        }
     }
 }
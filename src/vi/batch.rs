@@ -0,0 +1,435 @@
+//! Re-exported to super module
+
+use std::{collections::HashMap, hash::Hash, time::Duration};
+
+use crate::{
+    vi::{
+        BundleMemo, Button, InputBundle, KeyRepeatConfig, KeyRepeatState, RawButtonState,
+        StrictButtonState,
+    },
+    KeyState,
+};
+
+/// Many [`Button`]s stored as struct of arrays, for large action maps
+///
+/// Buttons are registered once with an action key and queried by the returned index or by the
+/// key. [`ButtonBatch::update_all`] updates every button in one loop, with the key states read
+/// once. Each button behaves the same as a standalone [`Button`].
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{
+///     backend::ManualBackend,
+///     vi::{presets, Button, ButtonBatch, KeyRepeatConfig},
+///     Input, Key,
+/// };
+///
+/// let button = |bundle| Button::new(bundle, KeyRepeatConfig::NoRepeat);
+/// let mut batch = ButtonBatch::new();
+/// let confirm = batch.register("confirm", button(presets::confirm()));
+/// batch.register("cancel", button(presets::cancel()));
+///
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+/// backend.key_down(Key::Enter);
+/// backend.update(&mut input);
+/// batch.update_all(&input, Duration::from_millis(16));
+///
+/// assert!(batch.is_pressed(confirm));
+/// assert!(!batch.action(&"cancel").unwrap().is_down());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ButtonBatch<A> {
+    inputs: Vec<InputBundle>,
+    memos: Vec<Option<Box<BundleMemo>>>,
+    repeats: Vec<KeyRepeatState>,
+    states: Vec<StrictButtonState>,
+    paused: Vec<bool>,
+    repeat_counts: Vec<u32>,
+    mouse_grabs: Vec<u32>,
+    /// At most one transition per update
+    transitions: Vec<Option<(StrictButtonState, StrictButtonState)>>,
+    /// Action key to index
+    actions: HashMap<A, usize>,
+}
+
+impl<A: Eq + Hash> Default for ButtonBatch<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Eq + Hash> ButtonBatch<A> {
+    pub fn new() -> Self {
+        Self {
+            inputs: Vec::new(),
+            memos: Vec::new(),
+            repeats: Vec::new(),
+            states: Vec::new(),
+            paused: Vec::new(),
+            repeat_counts: Vec::new(),
+            mouse_grabs: Vec::new(),
+            transitions: Vec::new(),
+            actions: HashMap::new(),
+        }
+    }
+
+    /// Adds the button with its current state and returns its stable index
+    ///
+    /// Panics if the action is already registered or the button has turbo enabled, which is not
+    /// supported by batches.
+    pub fn register(&mut self, action: A, button: Button) -> usize {
+        assert!(
+            button.turbo.is_none(),
+            "turbo is not supported by `ButtonBatch`"
+        );
+        let index = self.inputs.len();
+        assert!(
+            self.actions.insert(action, index).is_none(),
+            "action registered twice"
+        );
+
+        self.inputs.push(button.input);
+        self.memos.push(button.memo);
+        self.repeats.push(button.repeat);
+        self.states.push(button.state);
+        self.paused.push(button.paused);
+        self.repeat_counts.push(button.repeat_count);
+        self.mouse_grabs.push(button.mouse_grabs);
+        self.transitions.push(button.transition);
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Index of the action
+    pub fn index(&self, action: &A) -> Option<usize> {
+        self.actions.get(action).cloned()
+    }
+
+    pub fn action(&self, action: &A) -> Option<BatchButton<'_>> {
+        self.index(action).map(|i| self.get(i))
+    }
+
+    /// Panics if the index is out of range
+    pub fn get(&self, index: usize) -> BatchButton<'_> {
+        BatchButton {
+            input: &self.inputs[index],
+            repeat: &self.repeats[index],
+            state: self.states[index],
+            paused: self.paused[index],
+            repeat_count: self.repeat_counts[index],
+            transition: &self.transitions[index],
+        }
+    }
+
+    /// Bindings of the button. Changes are picked up on the next update
+    pub fn input_mut(&mut self, index: usize) -> &mut InputBundle {
+        self.memos[index] = None;
+        &mut self.inputs[index]
+    }
+
+    pub fn set_repeat_config(&mut self, index: usize, cfg: KeyRepeatConfig) {
+        self.repeats[index].reconfigure(KeyRepeatState::new(cfg));
+    }
+
+    /// See [`Button::set_max_repeats_per_update`]
+    pub fn set_max_repeats_per_update(&mut self, index: usize, max: u32) {
+        self.repeats[index].max_repeats = max;
+    }
+
+    /// See [`Button::set_paused`]
+    pub fn set_paused(&mut self, index: usize, paused: bool) {
+        self.paused[index] = paused;
+    }
+
+    pub fn state(&self, index: usize) -> StrictButtonState {
+        self.states[index]
+    }
+
+    pub fn is_down(&self, index: usize) -> bool {
+        self.states[index].is_down()
+    }
+
+    pub fn is_pressed(&self, index: usize) -> bool {
+        self.states[index].is_pressed()
+    }
+
+    pub fn is_released(&self, index: usize) -> bool {
+        self.states[index] == StrictButtonState::Released
+    }
+}
+
+/// Lifecycle
+impl<A> ButtonBatch<A> {
+    /// Same as [`Button::update`] on every button
+    pub fn update_all(&mut self, input: &impl KeyState, dt: Duration) {
+        self.update_with(input, |repeat, state| repeat.update(state, dt));
+    }
+
+    /// Same as [`Button::update_ticks`] on every button
+    pub fn update_all_ticks(&mut self, input: &impl KeyState, ticks: u32) {
+        self.update_with(input, |repeat, state| repeat.update_ticks(state, ticks));
+    }
+
+    fn update_with(
+        &mut self,
+        input: &impl KeyState,
+        mut update_repeat: impl FnMut(&mut KeyRepeatState, RawButtonState) -> u32,
+    ) {
+        let bits = input.key_bits();
+
+        let buttons = self
+            .inputs
+            .iter()
+            .zip(self.memos.iter_mut())
+            .zip(self.repeats.iter_mut())
+            .zip(self.states.iter_mut())
+            .zip(self.paused.iter())
+            .zip(self.repeat_counts.iter_mut())
+            .zip(self.mouse_grabs.iter_mut())
+            .zip(self.transitions.iter_mut());
+
+        for (((((((bundle, memo), repeat), strict), paused), count), grabs), transition) in buttons
+        {
+            let state = super::eval_bundle(bundle, memo, input, bits, grabs);
+            *count = if *paused && state == RawButtonState::Down {
+                0
+            } else {
+                update_repeat(repeat, state)
+            };
+
+            let prev = *strict;
+            *strict = StrictButtonState::from_raw(state, *count >= 1);
+            *transition = if *strict != prev {
+                Some((prev, *strict))
+            } else {
+                None
+            };
+        }
+    }
+}
+
+/// Read-only view of a button in a [`ButtonBatch`], with the queries of [`Button`]
+#[derive(Debug, Clone, Copy)]
+pub struct BatchButton<'a> {
+    input: &'a InputBundle,
+    repeat: &'a KeyRepeatState,
+    state: StrictButtonState,
+    paused: bool,
+    repeat_count: u32,
+    transition: &'a Option<(StrictButtonState, StrictButtonState)>,
+}
+
+impl<'a> BatchButton<'a> {
+    pub fn input(&self) -> &'a InputBundle {
+        self.input
+    }
+
+    pub fn state(&self) -> StrictButtonState {
+        self.state
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.state.is_down()
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.state.is_pressed()
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.state == StrictButtonState::Released
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// See [`Button::accum_down`]
+    pub fn accum_down(&self) -> Duration {
+        Duration::from_nanos(self.repeat.accum_down)
+    }
+
+    pub fn down_ticks(&self) -> u32 {
+        self.repeat.down_ticks
+    }
+
+    /// See [`Button::repeat_count_this_update`]
+    pub fn repeat_count_this_update(&self) -> u32 {
+        self.repeat_count
+    }
+
+    pub fn max_repeats_per_update(&self) -> u32 {
+        self.repeat.max_repeats
+    }
+
+    pub fn repeat_config(&self) -> KeyRepeatConfig {
+        match &self.repeat.shared {
+            Some(shared) => shared.get(),
+            None => self.repeat.config,
+        }
+    }
+
+    /// `(from, to)` state transitions that happened on last update
+    pub fn transitions(&self) -> &'a [(StrictButtonState, StrictButtonState)] {
+        self.transition.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::ScriptBackend,
+        entries,
+        vi::{KeyChord, KeyEntry},
+        Input, Key,
+    };
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn buttons() -> Vec<Button> {
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+        let save = InputBundle {
+            keys: entries![KeyEntry {
+                key: Key::S,
+                ctrl: true,
+                shift: false,
+                alt: false,
+                meta: false,
+            }],
+            ..Default::default()
+        };
+        let chord = InputBundle {
+            chords: entries![KeyChord(vec![Key::Q, Key::E])],
+            ..Default::default()
+        };
+
+        let mut capped = Button::single(Key::A, repeat);
+        capped.set_max_repeats_per_update(1);
+        vec![
+            Button::single(Key::A, repeat),
+            Button::single(Key::A, KeyRepeatConfig::NoRepeat),
+            Button::single(Key::B, KeyRepeatConfig::repeat_frames(3, 2)),
+            Button::new(save, repeat),
+            Button::new(chord, KeyRepeatConfig::NoRepeat),
+            capped,
+        ]
+    }
+
+    fn script() -> ScriptBackend {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::A, 1..20)
+            .hold(Key::B, 3..12)
+            .hold(Key::LCtrl, 5..30)
+            .hold(Key::S, 8..25)
+            .hold(Key::Q, 10..40)
+            .hold(Key::E, 15..18)
+            .hold(Key::E, 20..22)
+            .hold(Key::A, 26..50);
+        script
+    }
+
+    #[test]
+    fn batch_agrees_with_standalone_buttons() {
+        let mut buttons = self::buttons();
+        let mut batch = ButtonBatch::new();
+        for (i, button) in self::buttons().into_iter().enumerate() {
+            assert_eq!(batch.register(i, button), i);
+        }
+
+        let mut script = self::script();
+        let mut input = Input::new();
+        for frame in 0..60 {
+            // every 7th update is a long stall on the same frame
+            let dt = if frame % 7 == 0 {
+                DT * 9
+            } else {
+                script.step(&mut input)
+            };
+            if frame == 30 {
+                buttons[0].set_paused(true);
+                batch.set_paused(0, true);
+            }
+
+            for button in &mut buttons {
+                button.update(&input, dt);
+            }
+            batch.update_all(&input, dt);
+
+            for (i, button) in buttons.iter().enumerate() {
+                let b = batch.action(&i).unwrap();
+                assert_eq!(
+                    (
+                        b.state(),
+                        b.repeat_count_this_update(),
+                        b.accum_down(),
+                        b.down_ticks(),
+                        b.transitions(),
+                    ),
+                    (
+                        button.state,
+                        button.repeat_count_this_update(),
+                        button.accum_down(),
+                        button.down_ticks(),
+                        button.transitions(),
+                    ),
+                    "button {} on frame {}",
+                    i,
+                    frame
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn batch_agrees_on_ticks_and_rebinding() {
+        let mut buttons = self::buttons();
+        let mut batch = ButtonBatch::new();
+        for (i, button) in self::buttons().into_iter().enumerate() {
+            batch.register(i, button);
+        }
+
+        let mut script = self::script();
+        let mut input = Input::new();
+        for frame in 0..60 {
+            script.step(&mut input);
+            if frame == 20 {
+                buttons[1].input_mut().keys[0].key = Key::Q;
+                batch.input_mut(1).keys[0].key = Key::Q;
+            }
+
+            let ticks = 1 + frame % 3;
+            for button in &mut buttons {
+                button.update_ticks(&input, ticks);
+            }
+            batch.update_all_ticks(&input, ticks);
+
+            for (i, button) in buttons.iter().enumerate() {
+                assert_eq!(
+                    (batch.state(i), batch.get(i).down_ticks()),
+                    (button.state, button.down_ticks()),
+                    "button {} on frame {}",
+                    i,
+                    frame
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "action registered twice")]
+    fn duplicate_action() {
+        let mut batch = ButtonBatch::new();
+        batch.register("a", Button::single(Key::A, KeyRepeatConfig::NoRepeat));
+        batch.register("a", Button::single(Key::B, KeyRepeatConfig::NoRepeat));
+    }
+}
@@ -0,0 +1,332 @@
+//! Re-exported to super module
+
+use std::fmt::Write;
+
+use crate::vi::{ActionMap, InputBundle, KeyNameTable};
+
+/// Display name and category of an action, set with [`ActionRegistration`]
+///
+/// [`ActionRegistration`]: crate::vi::ActionRegistration
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActionLabel {
+    pub name: Option<String>,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheatSheetOptions {
+    /// Category of actions registered without one
+    pub uncategorized: String,
+    /// Lists actions added without [`ActionMap::register_button`] or
+    /// [`ActionMap::register_axis`], named after their index
+    pub include_unnamed: bool,
+    /// Names of the keys, modifiers and mouse buttons
+    pub key_names: KeyNameTable,
+}
+
+impl Default for CheatSheetOptions {
+    fn default() -> Self {
+        Self {
+            uncategorized: "General".to_string(),
+            include_unnamed: false,
+            key_names: KeyNameTable::english(),
+        }
+    }
+}
+
+/// Bindings of an [`ActionMap`] grouped by category, made with [`ActionMap::cheat_sheet`]
+///
+/// Lay out [`CheatSheet::categories`] in the game UI or dump [`CheatSheet::to_markdown`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheatSheet {
+    /// Sorted by name
+    pub categories: Vec<CheatSheetCategory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheatSheetCategory {
+    pub name: String,
+    /// Sorted by name
+    pub rows: Vec<CheatSheetRow>,
+}
+
+/// Action and its inputs. An axis makes two rows, `Name (+)` and `Name (-)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheatSheetRow {
+    pub name: String,
+    pub entries: Vec<CheatSheetEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheatSheetEntry {
+    /// Formatted input such as `Ctrl+S`
+    pub text: String,
+    /// The same input is bound to another row
+    pub duplicate: bool,
+}
+
+/// Cheat sheet
+impl ActionMap {
+    pub fn cheat_sheet(&self, opts: &CheatSheetOptions) -> CheatSheet {
+        let mut rows: Vec<(String, CheatSheetRow)> = Vec::new();
+
+        let mut push = |label: Option<&ActionLabel>, fallback: String, bundle: &InputBundle| {
+            let label = label.filter(|l| l.name.is_some());
+            if label.is_none() && !opts.include_unnamed {
+                return;
+            }
+
+            let category = label
+                .and_then(|l| l.category.clone())
+                .unwrap_or_else(|| opts.uncategorized.clone());
+            let name = label.and_then(|l| l.name.clone()).unwrap_or(fallback);
+            rows.push((
+                category,
+                CheatSheetRow {
+                    name,
+                    entries: self::entries(bundle, &opts.key_names),
+                },
+            ));
+        };
+
+        for (i, button) in self.buttons.iter().enumerate() {
+            push(
+                self.button_labels.get(i),
+                format!("Button {}", i),
+                button.input(),
+            );
+        }
+        for (i, axis) in self.axes.iter().enumerate() {
+            let label = self.axis_labels.get(i);
+            let sign = |sign: &str| {
+                label.map(|l| ActionLabel {
+                    name: l.name.as_ref().map(|name| format!("{} ({})", name, sign)),
+                    category: l.category.clone(),
+                })
+            };
+            push(
+                sign("+").as_ref(),
+                format!("Axis {} (+)", i),
+                axis.pos.input(),
+            );
+            push(
+                sign("-").as_ref(),
+                format!("Axis {} (-)", i),
+                axis.neg.input(),
+            );
+        }
+
+        // flag inputs bound to more than one row
+        let texts: Vec<Vec<String>> = rows
+            .iter()
+            .map(|(_, row)| row.entries.iter().map(|e| e.text.clone()).collect())
+            .collect();
+        for (i, (_, row)) in rows.iter_mut().enumerate() {
+            for entry in &mut row.entries {
+                entry.duplicate = texts
+                    .iter()
+                    .enumerate()
+                    .any(|(j, other)| j != i && other.contains(&entry.text));
+            }
+        }
+
+        rows.sort_by(|(c1, r1), (c2, r2)| (c1, &r1.name).cmp(&(c2, &r2.name)));
+
+        let mut sheet = CheatSheet::default();
+        for (category, row) in rows {
+            match sheet.categories.last_mut() {
+                Some(last) if last.name == category => last.rows.push(row),
+                _ => sheet.categories.push(CheatSheetCategory {
+                    name: category,
+                    rows: vec![row],
+                }),
+            }
+        }
+        sheet
+    }
+}
+
+fn entries(bundle: &InputBundle, names: &KeyNameTable) -> Vec<CheatSheetEntry> {
+    let texts = bundle
+        .keys
+        .iter()
+        .map(|e| e.display_with(names).to_string())
+        .chain(
+            bundle
+                .chords
+                .iter()
+                .map(|c| c.display_with(names).to_string()),
+        )
+        .chain(bundle.raw.iter().map(|r| r.to_string()))
+        .chain(bundle.custom.iter().map(|c| c.to_string()))
+        .chain(
+            bundle
+                .mouse
+                .iter()
+                .map(|m| names.mouse_button_name(m.button).to_string()),
+        );
+
+    texts
+        .map(|text| CheatSheetEntry {
+            text,
+            duplicate: false,
+        })
+        .collect()
+}
+
+impl CheatSheet {
+    pub fn has_duplicates(&self) -> bool {
+        self.categories
+            .iter()
+            .flat_map(|c| &c.rows)
+            .flat_map(|r| &r.entries)
+            .any(|e| e.duplicate)
+    }
+
+    /// One table per category. Duplicate inputs are marked with `⚠`
+    pub fn to_markdown(&self) -> String {
+        let mut s = String::new();
+
+        for (i, category) in self.categories.iter().enumerate() {
+            if i != 0 {
+                s.push('\n');
+            }
+            writeln!(s, "## {}\n", category.name).unwrap();
+            s.push_str("| Action | Input |\n");
+            s.push_str("| --- | --- |\n");
+
+            for row in &category.rows {
+                write!(s, "| {} |", row.name).unwrap();
+                for (i, entry) in row.entries.iter().enumerate() {
+                    s.push_str(if i == 0 { " " } else { ", " });
+                    write!(s, "`{}`", entry.text).unwrap();
+                    if entry.duplicate {
+                        s.push_str(" ⚠");
+                    }
+                }
+                s.push_str(" |\n");
+            }
+        }
+
+        if self.has_duplicates() {
+            s.push_str("\n⚠ Bound to more than one action\n");
+        }
+
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        vi::{AxisButton, Button, KeyChord, KeyEntry, KeyRepeatConfig},
+        Key, MouseButton,
+    };
+
+    fn button(keys: &[Key]) -> Button {
+        let bundle = InputBundle {
+            keys: keys.iter().map(|&key| key.into()).collect(),
+            ..Default::default()
+        };
+        Button::new(bundle, KeyRepeatConfig::NoRepeat)
+    }
+
+    fn fixture() -> ActionMap {
+        let mut map = ActionMap::new();
+        map.register_button(button(&[Key::Space, Key::Z]))
+            .name("Jump")
+            .category("Movement");
+        map.register_axis(AxisButton {
+            pos: button(&[Key::D, Key::Right]),
+            neg: button(&[Key::A, Key::Left]),
+        })
+        .name("Walk")
+        .category("Movement");
+
+        let mut save = button(&[]);
+        save.input_mut().keys.push(KeyEntry {
+            key: Key::S,
+            ctrl: true,
+            shift: false,
+            alt: false,
+            meta: false,
+        });
+        save.input_mut()
+            .chords
+            .push(KeyChord(vec![Key::LShift, Key::F5]));
+        map.register_button(save).name("Save");
+        map.register_button(button(&[Key::Z])).name("Attack");
+
+        // not registered
+        map.buttons.push(button(&[Key::Escape]));
+        map
+    }
+
+    #[test]
+    fn markdown_snapshot() {
+        let sheet = fixture().cheat_sheet(&CheatSheetOptions::default());
+        assert_eq!(
+            sheet.to_markdown(),
+            "\
+## General
+
+| Action | Input |
+| --- | --- |
+| Attack | `Z` ⚠ |
+| Save | `Ctrl+S`, `LShift+F5` |
+
+## Movement
+
+| Action | Input |
+| --- | --- |
+| Jump | `Space`, `Z` ⚠ |
+| Walk (+) | `D`, `Right` |
+| Walk (-) | `A`, `Left` |
+
+⚠ Bound to more than one action
+"
+        );
+    }
+
+    #[test]
+    fn unnamed_actions_and_localized_names() {
+        let mut key_names = KeyNameTable::new();
+        key_names.set_key(Key::Escape, "Échap");
+        key_names.set_mouse_button(MouseButton::Left, "Clic gauche");
+        let opts = CheatSheetOptions {
+            uncategorized: "Misc".to_string(),
+            include_unnamed: true,
+            key_names,
+        };
+
+        let mut map = fixture();
+        map.buttons[2] = button(&[Key::X]);
+        map.buttons[2]
+            .input_mut()
+            .mouse
+            .push(MouseButton::Left.into());
+        let sheet = map.cheat_sheet(&opts);
+        assert!(!sheet.has_duplicates());
+        assert_eq!(
+            sheet.to_markdown(),
+            "\
+## Misc
+
+| Action | Input |
+| --- | --- |
+| Attack | `X`, `Clic gauche` |
+| Button 3 | `Échap` |
+| Save | `Ctrl+S`, `LShift+F5` |
+
+## Movement
+
+| Action | Input |
+| --- | --- |
+| Jump | `Space`, `Z` |
+| Walk (+) | `D`, `Right` |
+| Walk (-) | `A`, `Left` |
+"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+use crate::{vi::Button, KeyState};
+
+/// [`Button`] gated by a cooldown, such as an ability button
+///
+/// [`CooldownButton::try_activate`] consumes a press only if a charge is ready, so mashing during
+/// the cooldown doesn't queue actions. With more than one charge, each spent charge recharges
+/// independently of the others.
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{
+///     backend::ManualBackend,
+///     vi::{presets, Button, CooldownButton, KeyRepeatConfig},
+///     Input, Key,
+/// };
+///
+/// let button = Button::new(presets::confirm(), KeyRepeatConfig::NoRepeat);
+/// let mut dash = CooldownButton::new(button, Duration::from_millis(500));
+///
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+/// backend.key_down(Key::Enter);
+/// backend.update(&mut input);
+/// dash.update(&input, Duration::from_millis(16));
+///
+/// assert!(dash.try_activate());
+/// assert!(!dash.ready());
+/// assert_eq!(dash.remaining(), Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CooldownButton {
+    pub button: Button,
+    cooldown: Duration,
+    /// Time until each charge is ready. Zero if ready. Nanoseconds
+    charges: Vec<u64>,
+    /// If the press on last update was consumed
+    consumed: bool,
+}
+
+impl CooldownButton {
+    /// Creates a button with one charge, ready
+    pub fn new(button: Button, cooldown: Duration) -> Self {
+        Self {
+            button,
+            cooldown,
+            charges: vec![0],
+            consumed: false,
+        }
+    }
+
+    /// Sets the number of charges, all ready
+    ///
+    /// Panics if `n` is zero.
+    pub fn with_charges(mut self, n: usize) -> Self {
+        assert!(n > 0, "`CooldownButton` needs at least one charge");
+        self.charges = vec![0; n];
+        self
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Applies to charges spent from now on
+    pub fn set_cooldown(&mut self, cooldown: Duration) {
+        self.cooldown = cooldown;
+    }
+
+    pub fn max_charges(&self) -> usize {
+        self.charges.len()
+    }
+
+    /// Number of ready charges
+    pub fn charges(&self) -> usize {
+        self.charges.iter().filter(|c| **c == 0).count()
+    }
+
+    /// If a charge is ready
+    pub fn ready(&self) -> bool {
+        self.charges.contains(&0)
+    }
+
+    /// Time until the next charge is ready. Zero if a charge is ready
+    pub fn remaining(&self) -> Duration {
+        Duration::from_nanos(self.charges.iter().cloned().min().unwrap_or(0))
+    }
+
+    /// Makes every charge ready
+    pub fn reset(&mut self) {
+        for charge in &mut self.charges {
+            *charge = 0;
+        }
+    }
+
+    /// Consumes the press on last update if a charge is ready, and starts its cooldown
+    ///
+    /// Returns `false` if the button was not pressed, the press was already consumed, or no charge
+    /// is ready.
+    pub fn try_activate(&mut self) -> bool {
+        if self.consumed || !self.button.is_pressed() {
+            return false;
+        }
+
+        let cooldown = super::nanos(self.cooldown);
+        match self.charges.iter_mut().find(|c| **c == 0) {
+            Some(charge) => {
+                *charge = cooldown;
+                self.consumed = true;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Lifecycle
+impl CooldownButton {
+    /// Updates the button and recharges
+    pub fn update(&mut self, input: &impl KeyState, dt: Duration) {
+        self.button.update(input, dt);
+
+        let dt = super::nanos(dt);
+        for charge in &mut self.charges {
+            *charge = charge.saturating_sub(dt);
+        }
+        self.consumed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ScriptBackend, vi::KeyRepeatConfig, Input, Key};
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn button(cooldown_ms: u64) -> CooldownButton {
+        let button = Button::single(Key::Z, KeyRepeatConfig::NoRepeat);
+        CooldownButton::new(button, Duration::from_millis(cooldown_ms))
+    }
+
+    /// Frames where `try_activate` succeeded, with `Z` tapped on the given frames
+    fn activations(button: &mut CooldownButton, taps: &[u32], n: u32) -> Vec<u32> {
+        let mut script = ScriptBackend::new(DT);
+        for &frame in taps {
+            script.hold(Key::Z, frame..frame + 1);
+        }
+        let mut input = Input::new();
+        let mut frames = Vec::new();
+        for frame in 0..n {
+            script.step(&mut input);
+            button.update(&input, DT);
+            if button.try_activate() {
+                frames.push(frame);
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn mashing_during_cooldown() {
+        let mut dash = self::button(50);
+        // every other frame
+        let taps = (0..20).step_by(2).collect::<Vec<_>>();
+        assert_eq!(self::activations(&mut dash, &taps, 20), vec![0, 6, 12, 18]);
+    }
+
+    #[test]
+    fn exact_boundary_activation() {
+        // ready again exactly 4 updates later
+        let mut dash = self::button(40);
+        assert_eq!(self::activations(&mut dash, &[0, 2, 4], 5), vec![0, 4]);
+        assert_eq!(dash.remaining(), Duration::from_millis(40));
+
+        // 1 frame short
+        let mut dash = self::button(50);
+        assert_eq!(self::activations(&mut dash, &[0, 2, 4], 5), vec![0]);
+        assert_eq!(dash.remaining(), Duration::from_millis(10));
+
+        let mut dash = self::button(30);
+        let mut input = Input::new();
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Z, 0..1);
+        script.step(&mut input);
+        dash.update(&input, DT);
+        assert!(dash.try_activate());
+        // one press is consumed once
+        assert!(!dash.try_activate());
+        assert_eq!(dash.remaining(), Duration::from_millis(30));
+        for remaining in [20, 10, 0] {
+            script.step(&mut input);
+            dash.update(&input, DT);
+            assert_eq!(dash.remaining(), Duration::from_millis(remaining));
+        }
+        assert!(dash.ready());
+    }
+
+    #[test]
+    fn charges_recharge_independently() {
+        let mut dash = self::button(50).with_charges(2);
+        assert_eq!((dash.charges(), dash.max_charges()), (2, 2));
+
+        // two charges spent in a row, then each comes back 5 frames after its use
+        let taps = (0..13).step_by(2).collect::<Vec<_>>();
+        assert_eq!(
+            self::activations(&mut dash, &taps, 13),
+            vec![0, 2, 6, 8, 12]
+        );
+        assert_eq!(dash.charges(), 0);
+        assert_eq!(dash.remaining(), Duration::from_millis(10));
+
+        dash.reset();
+        assert_eq!(dash.charges(), 2);
+        assert_eq!(dash.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one charge")]
+    fn zero_charges() {
+        let _ = self::button(50).with_charges(0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{vi::Button, Input};
+
+/// How [`ExclusiveGroup`] keeps the active member
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExclusiveMode {
+    /// Radio buttons. The last pressed member stays active until another one is pressed.
+    #[default]
+    Latch,
+    /// The pressed member is active while it's held. Another press overrides it. When the
+    /// active member is released, the first held member (in registration order) takes over.
+    Hold,
+}
+
+/// Mutually exclusive buttons
+///
+/// At most one member is activated per update. If multiple members are pressed on the same
+/// update, the one registered first wins.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExclusiveGroup<L> {
+    members: Vec<(L, Button)>,
+    mode: ExclusiveMode,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    active: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    just_changed: bool,
+}
+
+impl<L> Default for ExclusiveGroup<L> {
+    fn default() -> Self {
+        Self::new(ExclusiveMode::default())
+    }
+}
+
+impl<L> ExclusiveGroup<L> {
+    pub fn new(mode: ExclusiveMode) -> Self {
+        Self {
+            members: Vec::new(),
+            mode,
+            active: None,
+            just_changed: false,
+        }
+    }
+
+    /// Adds a member. Earlier members have higher priority.
+    pub fn add(&mut self, label: L, button: Button) {
+        self.members.push((label, button));
+    }
+
+    pub fn members(&self) -> &[(L, Button)] {
+        &self.members
+    }
+
+    pub fn mode(&self) -> ExclusiveMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ExclusiveMode) {
+        self.mode = mode;
+    }
+
+    pub fn active(&self) -> Option<&L> {
+        self.active.map(|ix| &self.members[ix].0)
+    }
+
+    /// If the active member changed on last update
+    pub fn just_changed(&self) -> bool {
+        self.just_changed
+    }
+
+    /// Sets the active member without input
+    pub fn set_active(&mut self, label: Option<&L>)
+    where
+        L: PartialEq,
+    {
+        let prev = self.active;
+        self.active = label.and_then(|l| self.members.iter().position(|(x, _)| x == l));
+        self.just_changed = prev != self.active;
+    }
+}
+
+/// Lifecycle
+impl<L> ExclusiveGroup<L> {
+    pub fn update(&mut self, input: &Input, dt: Duration) {
+        for (_, button) in self.members.iter_mut() {
+            button.update(input, dt);
+        }
+
+        let prev = self.active;
+
+        // registration order wins
+        let pressed = self
+            .members
+            .iter()
+            .position(|(_, b)| b.state == crate::vi::StrictButtonState::Pressed);
+
+        if let Some(ix) = pressed {
+            self.active = Some(ix);
+        } else if self.mode == ExclusiveMode::Hold {
+            let is_active_held = self.active.is_some_and(|ix| self.members[ix].1.is_down());
+            if !is_active_held {
+                self.active = self.members.iter().position(|(_, b)| b.is_down());
+            }
+        }
+
+        self.just_changed = prev != self.active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ScriptBackend, vi::KeyRepeatConfig, Key};
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn group(mode: ExclusiveMode) -> ExclusiveGroup<u8> {
+        let mut group = ExclusiveGroup::new(mode);
+        group.add(1, Button::single(Key::D1, KeyRepeatConfig::NoRepeat));
+        group.add(2, Button::single(Key::D2, KeyRepeatConfig::NoRepeat));
+        group.add(3, Button::single(Key::D3, KeyRepeatConfig::NoRepeat));
+        group
+    }
+
+    /// `(active, just_changed)` over the frames of the script
+    fn run(
+        group: &mut ExclusiveGroup<u8>,
+        mut script: ScriptBackend,
+        n: usize,
+    ) -> Vec<(Option<u8>, bool)> {
+        let mut input = Input::new();
+        (0..n)
+            .map(|_| {
+                let dt = script.step(&mut input);
+                group.update(&input, dt);
+                (group.active().cloned(), group.just_changed())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn simultaneous_presses_resolve_to_the_first_member() {
+        let mut script = ScriptBackend::new(DT);
+        script.at_frame(1).press(Key::D3).press(Key::D2);
+        let mut group = self::group(ExclusiveMode::Latch);
+        assert_eq!(
+            self::run(&mut group, script, 3),
+            vec![(None, false), (Some(2), true), (Some(2), false)]
+        );
+    }
+
+    #[test]
+    fn latch_hands_off_on_press() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::D1, 0..2)
+            .hold(Key::D3, 3..4)
+            .hold(Key::D1, 5..6);
+        let mut group = self::group(ExclusiveMode::Latch);
+        assert_eq!(
+            self::run(&mut group, script, 7),
+            vec![
+                (Some(1), true),
+                (Some(1), false),
+                // stays active after release
+                (Some(1), false),
+                (Some(3), true),
+                (Some(3), false),
+                (Some(1), true),
+                (Some(1), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn hold_hands_off_to_held_members() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::D3, 0..4)
+            .hold(Key::D2, 1..6)
+            .hold(Key::D1, 2..3);
+        let mut group = self::group(ExclusiveMode::Hold);
+        assert_eq!(
+            self::run(&mut group, script, 7),
+            vec![
+                (Some(3), true),
+                // the later press overrides the held member
+                (Some(2), true),
+                (Some(1), true),
+                // the first held member takes over
+                (Some(2), true),
+                (Some(2), false),
+                (Some(2), false),
+                (None, true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Re-exported to super module
+
+use std::{collections::HashMap, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    vi::{KeyChord, KeyEntry, Modifier},
+    Key, MouseButton,
+};
+
+/// Display names of keys, modifiers and mouse buttons for a locale
+///
+/// Missing entries fall back to the built-in English names, so [`KeyNameTable::english`] is
+/// empty. On `serde`, it's loaded from a document such as:
+///
+/// ```text
+/// {
+///     "keys": { "Space": "Leertaste" },
+///     "modifiers": { "Ctrl": "Strg" },
+///     "mouse": { "Left": "Linksklick" }
+/// }
+/// ```
+///
+/// Format inputs with [`KeyEntry::display_with`] and [`KeyChord::display_with`]:
+///
+/// ```
+/// use xdl::{vi::{KeyEntry, KeyNameTable, Modifier}, Key};
+///
+/// let mut german = KeyNameTable::new();
+/// german.set_key(Key::Space, "Leertaste");
+/// german.set_modifier(Modifier::Ctrl, "Strg");
+///
+/// let entry: KeyEntry = "Ctrl+S".parse().unwrap();
+/// assert_eq!(entry.display_with(&german).to_string(), "Strg+S");
+/// assert_eq!(entry.to_string(), "Ctrl+S");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct KeyNameTable {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    keys: HashMap<Key, String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    modifiers: HashMap<Modifier, String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    mouse: HashMap<MouseButton, String>,
+}
+
+impl KeyNameTable {
+    /// Empty table, which is the same as [`KeyNameTable::english`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in names, as used by `Display`
+    pub fn english() -> Self {
+        Self::default()
+    }
+
+    pub fn set_key(&mut self, key: Key, name: impl Into<String>) {
+        self.keys.insert(key, name.into());
+    }
+
+    /// Name of the modifier in [`KeyEntry`]s, such as `Strg` for `Ctrl`
+    pub fn set_modifier(&mut self, modifier: Modifier, name: impl Into<String>) {
+        self.modifiers.insert(modifier, name.into());
+    }
+
+    pub fn set_mouse_button(&mut self, button: MouseButton, name: impl Into<String>) {
+        self.mouse.insert(button, name.into());
+    }
+
+    pub fn key_name(&self, key: Key) -> &str {
+        self.keys.get(&key).map_or(key.name(), String::as_str)
+    }
+
+    pub fn modifier_name(&self, modifier: Modifier) -> &str {
+        match self.modifiers.get(&modifier) {
+            Some(name) => name,
+            None => match modifier {
+                Modifier::Ctrl => "Ctrl",
+                Modifier::Shift => "Shift",
+                Modifier::Alt => "Alt",
+                Modifier::Meta => "Meta",
+            },
+        }
+    }
+
+    /// Falls back to `Mouse<Button>`, such as `MouseLeft`, so that it's not confused with keys
+    pub fn mouse_button_name(&self, button: MouseButton) -> &str {
+        match self.mouse.get(&button) {
+            Some(name) => name,
+            None => match button {
+                MouseButton::Left => "MouseLeft",
+                MouseButton::Right => "MouseRight",
+                MouseButton::Middle => "MouseMiddle",
+                MouseButton::X1 => "MouseX1",
+                MouseButton::X2 => "MouseX2",
+            },
+        }
+    }
+}
+
+/// Localized `Display` of a [`KeyEntry`] or [`KeyChord`], made with `display_with`
+#[derive(Debug, Clone, Copy)]
+pub struct LocalizedDisplay<'a, T> {
+    item: &'a T,
+    table: &'a KeyNameTable,
+}
+
+impl KeyEntry {
+    /// `Ctrl+Shift+Meta+Key` with the names of the table
+    pub fn display_with<'a>(&'a self, table: &'a KeyNameTable) -> LocalizedDisplay<'a, Self> {
+        LocalizedDisplay { item: self, table }
+    }
+}
+
+impl KeyChord {
+    /// `Key+Key` with the names of the table
+    pub fn display_with<'a>(&'a self, table: &'a KeyNameTable) -> LocalizedDisplay<'a, Self> {
+        LocalizedDisplay { item: self, table }
+    }
+}
+
+impl<'a> fmt::Display for LocalizedDisplay<'a, KeyEntry> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let entry = self.item;
+        let mods = [
+            (entry.ctrl, Modifier::Ctrl),
+            (entry.shift, Modifier::Shift),
+            (entry.alt, Modifier::Alt),
+            (entry.meta, Modifier::Meta),
+        ];
+        for (_, modifier) in mods.iter().filter(|(on, _)| *on) {
+            write!(f, "{}+", self.table.modifier_name(*modifier))?;
+        }
+        f.write_str(self.table.key_name(entry.key))
+    }
+}
+
+impl<'a> fmt::Display for LocalizedDisplay<'a, KeyChord> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, key) in self.item.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str("+")?;
+            }
+            f.write_str(self.table.key_name(*key))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(s: &str) -> KeyEntry {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn english_is_display() {
+        let english = KeyNameTable::english();
+        for &key in Key::ALL {
+            assert_eq!(english.key_name(key), key.to_string());
+        }
+        for s in ["S", "Ctrl+S", "Ctrl+Shift+Meta+Space"] {
+            let entry = self::entry(s);
+            assert_eq!(entry.display_with(&english).to_string(), entry.to_string());
+        }
+        let chord = KeyChord(vec![Key::LShift, Key::Tab]);
+        assert_eq!(chord.display_with(&english).to_string(), chord.to_string());
+    }
+
+    #[test]
+    fn japanese_falls_back_to_english() {
+        let mut japanese = KeyNameTable::new();
+        japanese.set_key(Key::Space, "スペース");
+        japanese.set_key(Key::Enter, "エンター");
+        japanese.set_modifier(Modifier::Shift, "シフト");
+        japanese.set_mouse_button(MouseButton::Left, "左クリック");
+
+        assert_eq!(japanese.key_name(Key::Space), "スペース");
+        assert_eq!(japanese.key_name(Key::Escape), "Escape");
+        assert_eq!(japanese.modifier_name(Modifier::Ctrl), "Ctrl");
+        assert_eq!(japanese.mouse_button_name(MouseButton::Left), "左クリック");
+        assert_eq!(japanese.mouse_button_name(MouseButton::X1), "MouseX1");
+        assert_eq!(
+            self::entry("Ctrl+Shift+Space")
+                .display_with(&japanese)
+                .to_string(),
+            "Ctrl+シフト+スペース"
+        );
+        let chord = KeyChord(vec![Key::A, Key::Enter]);
+        assert_eq!(chord.display_with(&japanese).to_string(), "A+エンター");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn german_document() {
+        let doc = r#"{ "keys": { "Space": "Leertaste", "Escape": "Esc" }, "modifiers": { "Ctrl": "Strg" }, "mouse": { "Right": "Rechtsklick" } }"#;
+        let german: KeyNameTable = serde_json::from_str(doc).unwrap();
+        assert_eq!(german.mouse_button_name(MouseButton::Right), "Rechtsklick");
+        assert_eq!(
+            self::entry("Ctrl+S").display_with(&german).to_string(),
+            "Strg+S"
+        );
+        assert_eq!(
+            self::entry("Ctrl+Shift+Space")
+                .display_with(&german)
+                .to_string(),
+            "Strg+Shift+Leertaste"
+        );
+
+        let partial: KeyNameTable = serde_json::from_str(r#"{ "keys": {} }"#).unwrap();
+        assert_eq!(partial, KeyNameTable::english());
+        let json = serde_json::to_string(&german).unwrap();
+        assert_eq!(serde_json::from_str::<KeyNameTable>(&json).unwrap(), german);
+        assert_eq!(
+            serde_json::to_string(&KeyNameTable::english()).unwrap(),
+            "{}"
+        );
+    }
+}
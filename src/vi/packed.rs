@@ -0,0 +1,416 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    axis::Sign,
+    vi::{cheat_sheet::ActionLabel, AxisButton, Button, ButtonSnapshot},
+    KeyState,
+};
+
+/// Ordered buttons and axes of a player
+///
+/// The order defines the bit layout of [`PackedInput`], so peers have to build the same map.
+#[derive(Debug, Clone, Default)]
+pub struct ActionMap {
+    pub buttons: Vec<Button>,
+    pub axes: Vec<AxisButton>,
+    /// Indexed like `buttons`. May be shorter if buttons are pushed directly
+    pub(crate) button_labels: Vec<ActionLabel>,
+    /// Indexed like `axes`
+    pub(crate) axis_labels: Vec<ActionLabel>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts [`Input`] or a [`KeyboardView`] for split keyboards
+    ///
+    /// [`Input`]: crate::Input
+    /// [`KeyboardView`]: crate::KeyboardView
+    pub fn update(&mut self, input: &impl KeyState, dt: Duration) {
+        for button in &mut self.buttons {
+            button.update(input, dt);
+        }
+        for axis in &mut self.axes {
+            axis.update(input, dt);
+        }
+    }
+
+    /// Size of the [`PackedInput`] in bytes
+    pub fn packed_len(&self) -> usize {
+        PackedInput::len_for(self.buttons.len(), self.axes.len())
+    }
+
+    /// Encodes the down states of the actions
+    pub fn pack(&self) -> PackedInput {
+        let mut packed = PackedInput::new(self.buttons.len(), self.axes.len());
+        for (i, button) in self.buttons.iter().enumerate() {
+            packed.set_bit(i, button.is_down());
+        }
+        for (i, axis) in self.axes.iter().enumerate() {
+            packed.set_sign(i, axis.sign_down());
+        }
+        packed
+    }
+}
+
+/// Registration
+impl ActionMap {
+    /// Adds a button to be annotated for [`ActionMap::cheat_sheet`]
+    ///
+    /// ```
+    /// use xdl::{vi::{presets, ActionMap, Button, KeyRepeatConfig}};
+    ///
+    /// let mut map = ActionMap::new();
+    /// let jump = map
+    ///     .register_button(Button::new(presets::confirm(), KeyRepeatConfig::NoRepeat))
+    ///     .name("Jump")
+    ///     .category("Movement")
+    ///     .index();
+    /// assert_eq!(jump, 0);
+    /// ```
+    pub fn register_button(&mut self, button: Button) -> ActionRegistration<'_> {
+        self.buttons.push(button);
+        let index = self.buttons.len() - 1;
+        ActionRegistration {
+            label: self::label_at(&mut self.button_labels, index),
+            index,
+        }
+    }
+
+    /// Adds an axis to be annotated for [`ActionMap::cheat_sheet`]
+    pub fn register_axis(&mut self, axis: AxisButton) -> ActionRegistration<'_> {
+        self.axes.push(axis);
+        let index = self.axes.len() - 1;
+        ActionRegistration {
+            label: self::label_at(&mut self.axis_labels, index),
+            index,
+        }
+    }
+}
+
+fn label_at(labels: &mut Vec<ActionLabel>, index: usize) -> &mut ActionLabel {
+    if labels.len() <= index {
+        labels.resize_with(index + 1, Default::default);
+    }
+    &mut labels[index]
+}
+
+/// Annotates an action added with [`ActionMap::register_button`] or [`ActionMap::register_axis`]
+#[derive(Debug)]
+pub struct ActionRegistration<'a> {
+    label: &'a mut ActionLabel,
+    index: usize,
+}
+
+impl<'a> ActionRegistration<'a> {
+    /// Display name of the action
+    pub fn name(self, name: impl Into<String>) -> Self {
+        self.label.name = Some(name.into());
+        self
+    }
+
+    pub fn category(self, category: impl Into<String>) -> Self {
+        self.label.category = Some(category.into());
+        self
+    }
+
+    /// Index into [`ActionMap::buttons`] or [`ActionMap::axes`]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// Button states and repeat timers of an [`ActionMap`], saved with [`ActionMap::state_snapshot`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActionMapState {
+    buttons: Vec<ButtonSnapshot>,
+    /// `[pos, neg]`
+    axes: Vec<[ButtonSnapshot; 2]>,
+}
+
+/// Save states
+impl ActionMap {
+    pub fn state_snapshot(&self) -> ActionMapState {
+        ActionMapState {
+            buttons: self.buttons.iter().map(Button::snapshot).collect(),
+            axes: self
+                .axes
+                .iter()
+                .map(|axis| [axis.pos.snapshot(), axis.neg.snapshot()])
+                .collect(),
+        }
+    }
+
+    /// Overwrites the button states and repeat timers. Bindings and repeat configs are kept
+    ///
+    /// Panics if the snapshot was taken from a map with a different number of actions.
+    pub fn restore_state(&mut self, state: &ActionMapState) {
+        assert_eq!(
+            (state.buttons.len(), state.axes.len()),
+            (self.buttons.len(), self.axes.len()),
+            "action map layout mismatch"
+        );
+        for (button, snapshot) in self.buttons.iter_mut().zip(&state.buttons) {
+            button.restore(snapshot);
+        }
+        for (axis, [pos, neg]) in self.axes.iter_mut().zip(&state.axes) {
+            axis.pos.restore(pos);
+            axis.neg.restore(neg);
+        }
+    }
+}
+
+/// Down states of an [`ActionMap`] in a fixed number of bytes
+///
+/// Bits are laid out from the least significant bit of the first byte:
+///
+/// * one bit per button (`1` = down), in the order of [`ActionMap::buttons`]
+/// * then two bits per axis (`00` = neutral, `01` = positive, `10` = negative), in the order of
+///   [`ActionMap::axes`]
+///
+/// Unused bits of the last byte are zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PackedInput {
+    n_buttons: usize,
+    n_axes: usize,
+    bytes: Vec<u8>,
+}
+
+impl PackedInput {
+    fn len_for(n_buttons: usize, n_axes: usize) -> usize {
+        (n_buttons + 2 * n_axes).div_ceil(8)
+    }
+
+    /// Everything up or neutral
+    pub fn new(n_buttons: usize, n_axes: usize) -> Self {
+        Self {
+            n_buttons,
+            n_axes,
+            bytes: vec![0; Self::len_for(n_buttons, n_axes)],
+        }
+    }
+
+    /// Returns `None` if the length doesn't match the layout
+    pub fn from_bytes(n_buttons: usize, n_axes: usize, bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::len_for(n_buttons, n_axes) {
+            return None;
+        }
+        Some(Self {
+            n_buttons,
+            n_axes,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn n_buttons(&self) -> usize {
+        self.n_buttons
+    }
+
+    pub fn n_axes(&self) -> usize {
+        self.n_axes
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.bytes[i / 8] & (1 << (i % 8)) != 0
+    }
+
+    fn set_bit(&mut self, i: usize, b: bool) {
+        if b {
+            self.bytes[i / 8] |= 1 << (i % 8);
+        } else {
+            self.bytes[i / 8] &= !(1 << (i % 8));
+        }
+    }
+
+    fn set_sign(&mut self, axis: usize, sign: Sign) {
+        let i = self.n_buttons + 2 * axis;
+        self.set_bit(i, sign == Sign::Pos);
+        self.set_bit(i + 1, sign == Sign::Neg);
+    }
+
+    pub fn is_down(&self, button: usize) -> bool {
+        assert!(button < self.n_buttons, "button index out of range");
+        self.bit(button)
+    }
+
+    pub fn sign_down(&self, axis: usize) -> Sign {
+        assert!(axis < self.n_axes, "axis index out of range");
+        let i = self.n_buttons + 2 * axis;
+        match [self.bit(i), self.bit(i + 1)] {
+            [true, false] => Sign::Pos,
+            [false, true] => Sign::Neg,
+            _ => Sign::Neutral,
+        }
+    }
+}
+
+/// Actions of a remote player, with pressed/released states derived from consecutive packets
+#[derive(Debug, Clone)]
+pub struct RemoteActions {
+    prev: PackedInput,
+    cur: PackedInput,
+}
+
+impl RemoteActions {
+    pub fn new(n_buttons: usize, n_axes: usize) -> Self {
+        Self {
+            prev: PackedInput::new(n_buttons, n_axes),
+            cur: PackedInput::new(n_buttons, n_axes),
+        }
+    }
+
+    /// Applies the packet of the next tick
+    pub fn push(&mut self, packet: PackedInput) {
+        assert_eq!(
+            (packet.n_buttons, packet.n_axes),
+            (self.cur.n_buttons, self.cur.n_axes),
+            "packet layout mismatch"
+        );
+        self.prev = std::mem::replace(&mut self.cur, packet);
+    }
+
+    pub fn current(&self) -> &PackedInput {
+        &self.cur
+    }
+
+    pub fn is_down(&self, button: usize) -> bool {
+        self.cur.is_down(button)
+    }
+
+    pub fn is_pressed(&self, button: usize) -> bool {
+        self.cur.is_down(button) && !self.prev.is_down(button)
+    }
+
+    pub fn is_released(&self, button: usize) -> bool {
+        !self.cur.is_down(button) && self.prev.is_down(button)
+    }
+
+    pub fn sign_down(&self, axis: usize) -> Sign {
+        self.cur.sign_down(axis)
+    }
+
+    /// Sign that became down on this tick
+    pub fn sign_pressed(&self, axis: usize) -> Sign {
+        let sign = self.cur.sign_down(axis);
+        if sign != self.prev.sign_down(axis) {
+            sign
+        } else {
+            Sign::Neutral
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ScriptBackend, vi::KeyRepeatConfig, Input, Key};
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn map() -> ActionMap {
+        let button = |key| Button::single(key, KeyRepeatConfig::NoRepeat);
+        let mut map = ActionMap::new();
+        map.buttons = vec![button(Key::Z), button(Key::X), button(Key::C)];
+        map.axes = vec![
+            AxisButton {
+                pos: button(Key::Right),
+                neg: button(Key::Left),
+            },
+            AxisButton {
+                pos: button(Key::Down),
+                neg: button(Key::Up),
+            },
+        ];
+        map
+    }
+
+    #[test]
+    fn bit_layout() {
+        let mut packed = PackedInput::new(3, 2);
+        assert_eq!(packed.as_bytes(), &[0]);
+        packed.set_bit(1, true);
+        packed.set_sign(0, Sign::Neg);
+        packed.set_sign(1, Sign::Pos);
+        assert_eq!(packed.as_bytes(), &[0b0011_0010]);
+
+        // 9 buttons take 2 bytes
+        assert_eq!(PackedInput::new(9, 0).as_bytes().len(), 2);
+        assert_eq!(PackedInput::new(8, 1).as_bytes().len(), 2);
+        assert!(PackedInput::from_bytes(3, 2, &[0, 0]).is_none());
+    }
+
+    #[test]
+    fn remote_actions_match_local_ones() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::Z, 1..4)
+            .hold(Key::X, 2..3)
+            .hold(Key::Right, 1..6)
+            .hold(Key::Left, 3..5)
+            .hold(Key::Up, 4..7)
+            .hold(Key::C, 6..7);
+
+        let mut local = self::map();
+        let mut input = Input::new();
+        let mut remote = RemoteActions::new(3, 2);
+        assert_eq!(local.packed_len(), 1);
+
+        for _ in 0..9 {
+            let dt = script.step(&mut input);
+            local.update(&input, dt);
+
+            // over the wire
+            let bytes = local.pack().as_bytes().to_vec();
+            remote.push(PackedInput::from_bytes(3, 2, &bytes).unwrap());
+
+            for (i, button) in local.buttons.iter().enumerate() {
+                assert_eq!(remote.is_down(i), button.is_down());
+                assert_eq!(remote.is_pressed(i), button.is_pressed());
+                assert_eq!(remote.is_released(i), button.is_released());
+            }
+            for (i, axis) in local.axes.iter().enumerate() {
+                assert_eq!(remote.sign_down(i), axis.sign_down());
+            }
+        }
+    }
+
+    #[test]
+    fn split_keyboard_views() {
+        const P1: &[Key] = &[Key::W, Key::A, Key::S, Key::D, Key::Z];
+        const P2: &[Key] = &[Key::Up, Key::Left, Key::Down, Key::Right, Key::Slash];
+
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Z, 1..3).hold(Key::Right, 1..3);
+        let mut input = Input::new();
+
+        let mut p1 = ActionMap::new();
+        p1.buttons = vec![
+            Button::single(Key::Z, KeyRepeatConfig::NoRepeat),
+            // outside of the view
+            Button::single(Key::Right, KeyRepeatConfig::NoRepeat),
+        ];
+
+        for _ in 0..3 {
+            let dt = script.step(&mut input);
+            p1.update(&input.keyboard_view(P1), dt);
+        }
+        let (view1, view2) = (input.keyboard_view(P1), input.keyboard_view(P2));
+        assert!(input.kbd.is_key_down(Key::Z) && input.kbd.is_key_down(Key::Right));
+        assert!(view1.is_key_down(Key::Z) && !view1.is_key_down(Key::Right));
+        assert!(view2.is_key_down(Key::Right) && !view2.is_key_down(Key::Z));
+        assert!(p1.buttons[0].is_down());
+        assert!(!p1.buttons[1].is_down());
+    }
+}
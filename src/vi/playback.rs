@@ -0,0 +1,377 @@
+//! Re-exported to super module
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{
+        event::XdlEvent,
+        keyboard::{Key, RawKey},
+    },
+    utils::BitSet256,
+    vi::{Button, StrictButtonState},
+    Input,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum MacroMode {
+    #[default]
+    Idle,
+    Recording,
+    /// Index of the next frame to play
+    Playing(usize),
+}
+
+/// Recorded key events replayed frame by frame, such as a combo bound to a trigger button
+///
+/// Call [`Macro::update`] every frame after the backend applied the real events and before the
+/// virtual inputs read [`Input`]. While recording, the events of each frame are appended. While
+/// playing, the events of one frame are injected into the [`Input`] per update.
+///
+/// # Real input during playback
+///
+/// Real input wins per key, so an action bound to a key the player is holding stays under the
+/// player's control:
+///
+/// * A key that is already down is not pressed by the macro, and the macro doesn't release it
+///   later.
+/// * Keys pressed by the macro are released when the playback ends or stops. If the player
+///   releases one of them earlier, the release goes through and the key is the player's again.
+///
+/// On `serde`, the recorded frames are saved.
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{
+///     backend::ManualBackend,
+///     vi::{presets, Button, KeyRepeatConfig, Macro},
+///     Input, Key,
+/// };
+///
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+/// let trigger = Button::single(Key::F5, KeyRepeatConfig::NoRepeat);
+/// let mut jump = Button::new(presets::confirm(), KeyRepeatConfig::NoRepeat);
+///
+/// let mut m = Macro::new();
+/// m.start_recording();
+/// backend.key_down(Key::Enter);
+/// backend.update(&mut input);
+/// m.update(&mut input, &trigger);
+/// backend.end_frame(&mut input);
+/// m.stop_recording();
+/// backend.key_up(Key::Enter);
+/// backend.update(&mut input);
+/// backend.end_frame(&mut input);
+///
+/// // replayed on the next update
+/// m.play();
+/// m.update(&mut input, &trigger);
+/// jump.update(&input, Duration::from_millis(16));
+/// assert!(jump.is_pressed());
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Macro {
+    /// Events of each recorded frame
+    frames: Vec<Vec<XdlEvent>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mode: MacroMode,
+    /// Keys pressed by the playback and not released yet
+    #[cfg_attr(feature = "serde", serde(skip))]
+    held: BitSet256,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    held_raw: Vec<RawKey>,
+}
+
+impl Macro {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Macro of recorded frames
+    pub fn from_frames(frames: Vec<Vec<XdlEvent>>) -> Self {
+        Self {
+            frames,
+            ..Default::default()
+        }
+    }
+
+    /// Events of each recorded frame
+    pub fn frames(&self) -> &[Vec<XdlEvent>] {
+        &self.frames
+    }
+
+    /// Number of recorded frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.mode == MacroMode::Recording
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.mode, MacroMode::Playing(_))
+    }
+
+    /// Clears the frames and records from the next update. Stops the playback if any
+    ///
+    /// The keys pressed by the playback are released on the next update.
+    pub fn start_recording(&mut self) {
+        self.frames.clear();
+        self.mode = MacroMode::Recording;
+    }
+
+    pub fn stop_recording(&mut self) {
+        if self.is_recording() {
+            self.mode = MacroMode::Idle;
+        }
+    }
+
+    /// Plays from the first frame on the next update. Restarts if it's already playing
+    pub fn play(&mut self) {
+        self.mode = MacroMode::Playing(0);
+    }
+
+    /// Stops the playback. The keys pressed by the playback are released on the next update
+    pub fn stop(&mut self) {
+        if self.is_playing() {
+            self.mode = MacroMode::Idle;
+        }
+    }
+}
+
+/// Lifecycle
+impl Macro {
+    /// Records or plays one frame
+    ///
+    /// When the `trigger` is pressed while idle, the playback starts on this update. Presses
+    /// while playing or recording are ignored. The update after the last frame releases the keys
+    /// still held by the playback.
+    pub fn update(&mut self, input: &mut Input, trigger: &Button) {
+        if self.mode == MacroMode::Idle && trigger.state == StrictButtonState::Pressed {
+            self.mode = MacroMode::Playing(0);
+        }
+
+        // keys released by the player are not ours anymore
+        for &key in Key::ALL {
+            if self.held.get(key as usize) && !input.kbd.cur().is_down(key) {
+                self.held.clear(key as usize);
+            }
+        }
+        let raw_down = input.kbd.raw_keys_down();
+        self.held_raw
+            .retain(|key| raw_down.binary_search(key).is_ok());
+
+        match self.mode {
+            MacroMode::Idle => self.release(input),
+            MacroMode::Recording => {
+                self.release(input);
+                // mouse buttons depend on the cursor, which is not recorded
+                let events = input
+                    .events()
+                    .iter()
+                    .filter(|ev| !matches!(ev, XdlEvent::MouseDown(..) | XdlEvent::MouseUp(..)))
+                    .cloned()
+                    .collect();
+                self.frames.push(events);
+            }
+            MacroMode::Playing(frame) => match self.frames.get(frame) {
+                Some(events) => {
+                    for ev in events {
+                        self::inject(input, *ev, &mut self.held, &mut self.held_raw);
+                    }
+                    self.mode = MacroMode::Playing(frame + 1);
+                }
+                None => {
+                    self.mode = MacroMode::Idle;
+                    self.release(input);
+                }
+            },
+        }
+    }
+
+    /// Releases the keys pressed by the playback
+    fn release(&mut self, input: &mut Input) {
+        for &key in Key::ALL {
+            if self.held.get(key as usize) {
+                input.kbd.release(key);
+            }
+        }
+        self.held.clear_all();
+        for key in self.held_raw.drain(..) {
+            input.kbd.on_raw_key_up(key);
+        }
+    }
+}
+
+fn inject(input: &mut Input, ev: XdlEvent, held: &mut BitSet256, held_raw: &mut Vec<RawKey>) {
+    let kbd = &mut input.kbd;
+    match ev {
+        XdlEvent::KeyDown(key, _) => {
+            // real input wins
+            if !kbd.cur().is_down(key) {
+                kbd.press(key);
+                held.set(key as usize);
+            }
+        }
+        XdlEvent::KeyUp(key, _) => {
+            if held.get(key as usize) {
+                kbd.release(key);
+                held.clear(key as usize);
+            }
+        }
+        XdlEvent::RawKeyDown(key, _) => {
+            if !kbd.is_raw_key_down(key) {
+                kbd.on_raw_key_down(key);
+                held_raw.push(key);
+            }
+        }
+        XdlEvent::RawKeyUp(key, _) => {
+            if let Some(ix) = held_raw.iter().position(|k| *k == key) {
+                kbd.on_raw_key_up(key);
+                held_raw.swap_remove(ix);
+            }
+        }
+        XdlEvent::MouseDown(..) | XdlEvent::MouseUp(..) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{ManualBackend, ScriptBackend},
+        vi::{presets, KeyRepeatConfig},
+    };
+    use std::time::Duration;
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn trigger() -> Button {
+        Button::single(Key::F5, KeyRepeatConfig::NoRepeat)
+    }
+
+    /// `(pressed, down, released)` of the jump button on each frame
+    fn run(script: &mut ScriptBackend, m: &mut Macro, n: u32) -> Vec<(bool, bool, bool)> {
+        let mut input = Input::new();
+        let mut trigger = self::trigger();
+        let mut jump = Button::new(presets::confirm(), KeyRepeatConfig::NoRepeat);
+        (0..n)
+            .map(|_| {
+                let dt = script.step(&mut input);
+                trigger.update(&input, dt);
+                m.update(&mut input, &trigger);
+                jump.update(&input, dt);
+                (jump.is_pressed(), jump.is_down(), jump.is_released())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recorded_jump_replays_identically() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Enter, 1..4).hold(Key::Enter, 6..7);
+        let mut m = Macro::new();
+        m.start_recording();
+        let recorded = self::run(&mut script, &mut m, 10);
+        m.stop_recording();
+        assert_eq!(m.len(), 10);
+        assert!(recorded.iter().any(|s| s.0));
+
+        // F5 on frame 2 replays from that frame on
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::F5, 2..3);
+        let replayed = self::run(&mut script, &mut m, 14);
+        assert_eq!(&replayed[2..12], &recorded[..]);
+        assert!(replayed[..2].iter().all(|s| *s == (false, false, false)));
+        assert!(replayed[12..].iter().all(|s| *s == (false, false, false)));
+        assert!(!m.is_playing());
+    }
+
+    #[test]
+    fn playback_releases_its_keys_on_end() {
+        let mut m = Macro::from_frames(vec![vec![XdlEvent::KeyDown(Key::Enter, DT)], vec![]]);
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::F5, 0..1);
+        let states = self::run(&mut script, &mut m, 4);
+        assert_eq!(
+            states,
+            vec![
+                (true, true, false),
+                (false, true, false),
+                (false, false, true),
+                (false, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn real_input_wins() {
+        let trigger = self::trigger();
+        let press_release = vec![
+            vec![XdlEvent::KeyDown(Key::Enter, DT)],
+            vec![],
+            vec![XdlEvent::KeyUp(Key::Enter, DT)],
+            vec![],
+        ];
+
+        // the player's key is not released by the macro
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut m = Macro::from_frames(press_release.clone());
+        backend.key_down(Key::Enter);
+        m.play();
+        for _ in 0..5 {
+            backend.update(&mut input);
+            m.update(&mut input, &trigger);
+            assert!(input.kbd.is_key_down(Key::Enter));
+            backend.end_frame(&mut input);
+        }
+        assert!(!m.is_playing());
+
+        // the player's release goes through and the key is theirs from then on
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut m = Macro::from_frames(press_release);
+        m.play();
+        backend.update(&mut input);
+        m.update(&mut input, &trigger);
+        assert!(input.kbd.is_key_pressed(Key::Enter));
+        backend.end_frame(&mut input);
+
+        backend.key_up(Key::Enter);
+        backend.update(&mut input);
+        m.update(&mut input, &trigger);
+        assert!(input.kbd.is_key_released(Key::Enter));
+        backend.end_frame(&mut input);
+
+        backend.key_down(Key::Enter);
+        backend.update(&mut input);
+        m.update(&mut input, &trigger);
+        // the macro's `KeyUp` is ignored
+        assert!(input.kbd.is_key_down(Key::Enter));
+        backend.end_frame(&mut input);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn saved_macro_round_trip() {
+        let mut m = Macro::from_frames(vec![
+            vec![XdlEvent::KeyDown(Key::Enter, DT)],
+            vec![],
+            vec![XdlEvent::KeyUp(Key::Enter, DT * 2)],
+        ]);
+        m.play();
+        let ron = ron::to_string(&m).unwrap();
+        let loaded: Macro = ron::from_str(&ron).unwrap();
+        assert_eq!(loaded.frames(), m.frames());
+        // the playback state is not saved
+        assert!(!loaded.is_playing());
+    }
+}
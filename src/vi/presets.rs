@@ -0,0 +1,280 @@
+/*!
+Ready-made bundles for common key layouts
+
+The returned values are plain data; tweak them after construction as needed.
+
+```
+use std::time::Duration;
+use xdl::{backend::ManualBackend, vi::{presets, KeyRepeatConfig}, Dir8, Input, Key};
+
+let mut dir = presets::vim_hjkl(KeyRepeatConfig::NoRepeat);
+let mut backend = ManualBackend::new();
+let mut input = Input::new();
+
+// diagonal key
+backend.key_down(Key::U);
+backend.update(&mut input);
+dir.update(&input, Duration::from_millis(16));
+assert_eq!(dir.dir8_down(), Some(Dir8::NE));
+```
+*/
+
+use crate::{
+    vi::{AxisDirButton, InputBundle, KeyRepeatConfig},
+    Key,
+};
+
+/// Keys of the four directions. Diagonal keys are listed in two directions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirKeys {
+    pub right: Vec<Key>,
+    pub left: Vec<Key>,
+    pub down: Vec<Key>,
+    pub up: Vec<Key>,
+}
+
+impl DirKeys {
+    /// Diagonal keys in the order of up-left, up-right, down-left and down-right
+    fn with_diagonals(mut self, [ul, ur, dl, dr]: [Key; 4]) -> Self {
+        self.up.extend([ul, ur].iter());
+        self.down.extend([dl, dr].iter());
+        self.left.extend([ul, dl].iter());
+        self.right.extend([ur, dr].iter());
+        self
+    }
+
+    /// Combines the keys of the two layouts
+    pub fn and(mut self, other: DirKeys) -> Self {
+        self.right.extend(other.right);
+        self.left.extend(other.left);
+        self.down.extend(other.down);
+        self.up.extend(other.up);
+        self
+    }
+
+    pub fn into_button(self, repeat: KeyRepeatConfig) -> AxisDirButton {
+        AxisDirButton::new(
+            repeat,
+            [self::bundle(&self.right), self::bundle(&self.left)],
+            [self::bundle(&self.down), self::bundle(&self.up)],
+        )
+    }
+}
+
+pub(crate) fn bundle(keys: &[Key]) -> InputBundle {
+    InputBundle {
+        keys: keys.iter().map(|&key| key.into()).collect(),
+        ..Default::default()
+    }
+}
+
+pub fn wasd_keys() -> DirKeys {
+    DirKeys {
+        right: vec![Key::D],
+        left: vec![Key::A],
+        down: vec![Key::S],
+        up: vec![Key::W],
+    }
+}
+
+pub fn arrow_keys() -> DirKeys {
+    DirKeys {
+        right: vec![Key::Right],
+        left: vec![Key::Left],
+        down: vec![Key::Down],
+        up: vec![Key::Up],
+    }
+}
+
+/// `hjkl` and the diagonal `yubn` keys
+pub fn vim_keys() -> DirKeys {
+    DirKeys {
+        right: vec![Key::L],
+        left: vec![Key::H],
+        down: vec![Key::J],
+        up: vec![Key::K],
+    }
+    .with_diagonals([Key::Y, Key::U, Key::B, Key::N])
+}
+
+/// `8462` and the diagonal `7913` keys
+pub fn numpad_keys() -> DirKeys {
+    DirKeys {
+        right: vec![Key::NumPad6],
+        left: vec![Key::NumPad4],
+        down: vec![Key::NumPad2],
+        up: vec![Key::NumPad8],
+    }
+    .with_diagonals([Key::NumPad7, Key::NumPad9, Key::NumPad1, Key::NumPad3])
+}
+
+pub fn wasd(repeat: KeyRepeatConfig) -> AxisDirButton {
+    self::wasd_keys().into_button(repeat)
+}
+
+pub fn arrows(repeat: KeyRepeatConfig) -> AxisDirButton {
+    self::arrow_keys().into_button(repeat)
+}
+
+/// 8-way movement with `hjkl` and `yubn`
+pub fn vim_hjkl(repeat: KeyRepeatConfig) -> AxisDirButton {
+    self::vim_keys().into_button(repeat)
+}
+
+/// 8-way movement with the number pad
+pub fn numpad(repeat: KeyRepeatConfig) -> AxisDirButton {
+    self::numpad_keys().into_button(repeat)
+}
+
+pub fn wasd_and_arrows(repeat: KeyRepeatConfig) -> AxisDirButton {
+    self::wasd_keys()
+        .and(self::arrow_keys())
+        .into_button(repeat)
+}
+
+pub fn arrows_and_numpad(repeat: KeyRepeatConfig) -> AxisDirButton {
+    self::arrow_keys()
+        .and(self::numpad_keys())
+        .into_button(repeat)
+}
+
+/// `Enter` or `Space`
+pub fn confirm() -> InputBundle {
+    self::bundle(&[Key::Enter, Key::Space])
+}
+
+/// `Escape` or `Backspace`
+pub fn cancel() -> InputBundle {
+    self::bundle(&[Key::Escape, Key::Back])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{backend::ManualBackend, vi::Button, Dir8, Input};
+
+    /// Direction the keys drive on the first frame
+    fn dir8(mut button: AxisDirButton, keys: &[Key]) -> Option<Dir8> {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        for &key in keys {
+            backend.key_down(key);
+        }
+        backend.update(&mut input);
+        button.update(&input, Duration::from_millis(16));
+        button.dir8_down()
+    }
+
+    fn check(preset: impl Fn(KeyRepeatConfig) -> AxisDirButton, cases: &[(&[Key], Option<Dir8>)]) {
+        for (keys, dir) in cases {
+            let button = preset(KeyRepeatConfig::NoRepeat);
+            assert_eq!(self::dir8(button, keys), *dir, "{:?}", keys);
+        }
+    }
+
+    #[test]
+    fn wasd_and_arrows_drive_four_directions() {
+        let wasd_cases: &[(&[Key], Option<Dir8>)] = &[
+            (&[], None),
+            (&[Key::W], Some(Dir8::N)),
+            (&[Key::A], Some(Dir8::W)),
+            (&[Key::S], Some(Dir8::S)),
+            (&[Key::D], Some(Dir8::E)),
+            (&[Key::W, Key::D], Some(Dir8::NE)),
+            (&[Key::Up], None),
+        ];
+        let arrow_cases: &[(&[Key], Option<Dir8>)] = &[
+            (&[Key::Up], Some(Dir8::N)),
+            (&[Key::Left], Some(Dir8::W)),
+            (&[Key::Down], Some(Dir8::S)),
+            (&[Key::Right], Some(Dir8::E)),
+            (&[Key::Down, Key::Left], Some(Dir8::SW)),
+            (&[Key::W], None),
+        ];
+        self::check(wasd, wasd_cases);
+        self::check(arrows, arrow_cases);
+
+        // either layout drives the combined preset
+        let both = wasd_cases
+            .iter()
+            .chain(arrow_cases)
+            .filter(|(keys, dir)| keys.is_empty() || dir.is_some())
+            .copied()
+            .collect::<Vec<_>>();
+        self::check(wasd_and_arrows, &both);
+    }
+
+    #[test]
+    fn vim_and_numpad_drive_eight_directions() {
+        self::check(
+            vim_hjkl,
+            &[
+                (&[Key::K], Some(Dir8::N)),
+                (&[Key::H], Some(Dir8::W)),
+                (&[Key::J], Some(Dir8::S)),
+                (&[Key::L], Some(Dir8::E)),
+                (&[Key::Y], Some(Dir8::NW)),
+                (&[Key::U], Some(Dir8::NE)),
+                (&[Key::B], Some(Dir8::SW)),
+                (&[Key::N], Some(Dir8::SE)),
+            ],
+        );
+
+        let numpad_cases: &[(&[Key], Option<Dir8>)] = &[
+            (&[Key::NumPad8], Some(Dir8::N)),
+            (&[Key::NumPad4], Some(Dir8::W)),
+            (&[Key::NumPad2], Some(Dir8::S)),
+            (&[Key::NumPad6], Some(Dir8::E)),
+            (&[Key::NumPad7], Some(Dir8::NW)),
+            (&[Key::NumPad9], Some(Dir8::NE)),
+            (&[Key::NumPad1], Some(Dir8::SW)),
+            (&[Key::NumPad3], Some(Dir8::SE)),
+            (&[Key::NumPad5], None),
+        ];
+        self::check(numpad, numpad_cases);
+        self::check(arrows_and_numpad, numpad_cases);
+        self::check(arrows_and_numpad, &[(&[Key::Up], Some(Dir8::N))]);
+    }
+
+    #[test]
+    fn confirm_and_cancel() {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut confirm = Button::new(confirm(), KeyRepeatConfig::NoRepeat);
+        let mut cancel = Button::new(cancel(), KeyRepeatConfig::NoRepeat);
+
+        for (key, is_confirm) in [
+            (Key::Enter, true),
+            (Key::Space, true),
+            (Key::Escape, false),
+            (Key::Back, false),
+        ] {
+            backend.key_down(key);
+            backend.update(&mut input);
+            confirm.update(&input, Duration::from_millis(16));
+            cancel.update(&input, Duration::from_millis(16));
+            assert_eq!(
+                (confirm.is_pressed(), cancel.is_pressed()),
+                (is_confirm, !is_confirm),
+                "{:?}",
+                key
+            );
+            backend.key_up(key);
+            backend.end_frame(&mut input);
+            backend.update(&mut input);
+            backend.end_frame(&mut input);
+        }
+    }
+
+    #[test]
+    fn presets_are_plain_data() {
+        let mut keys = wasd_keys();
+        keys.up.push(Key::Space);
+        self::check(
+            move |repeat| keys.clone().into_button(repeat),
+            &[(&[Key::Space], Some(Dir8::N))],
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! Re-exported to super module
+
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    vi::{InputBundle, KeyEntry},
+    CustomInput, Key, MouseButton,
+};
+
+/// Identifier of an on-screen prompt glyph, such as a sprite name in the UI atlas
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct PromptId(pub Cow<'static, str>);
+
+impl PromptId {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&'static str> for PromptId {
+    fn from(id: &'static str) -> Self {
+        Self(Cow::Borrowed(id))
+    }
+}
+
+impl From<String> for PromptId {
+    fn from(id: String) -> Self {
+        Self(Cow::Owned(id))
+    }
+}
+
+impl fmt::Display for PromptId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Device the player used last, to pick the prompts of an [`InputBundle`]
+///
+/// Backends report the device of each event in [`EventOutcome::device`].
+///
+/// [`EventOutcome::device`]: crate::backend::EventOutcome::device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptHint<'a> {
+    Keyboard,
+    Mouse,
+    /// Device registered with [`Input::register_device`], such as a gamepad
+    ///
+    /// `style` selects the glyph set of the device, such as `"xbox"` or `"ps"`.
+    ///
+    /// [`Input::register_device`]: crate::Input::register_device
+    Device {
+        name: &'a str,
+        style: Option<&'a str>,
+    },
+}
+
+/// Mapping from inputs to [`PromptId`]s
+///
+/// Keys map to `key_<name>` by default, such as `key_space` for [`Key::Space`], and mouse
+/// buttons to `mouse_<name>`, such as `mouse_left` for [`MouseButton::Left`]. Channels of
+/// custom devices map to `<device>_<channel>` by default, and per-style tables override them so
+/// that the same channel can show Xbox or PlayStation glyphs. On `serde`, only the overrides are
+/// saved, so games can ship their own tables.
+///
+/// ```
+/// use xdl::{
+///     entries,
+///     vi::{InputBundle, PromptHint, PromptId, PromptMap},
+///     CustomInput, Key,
+/// };
+///
+/// let mut prompts = PromptMap::new();
+/// prompts.set_channel("ps", "south", "ps_cross");
+///
+/// let bundle = InputBundle {
+///     keys: entries![Key::Enter.into()],
+///     custom: entries![CustomInput {
+///         device: "pad".to_string(),
+///         channel: "south".to_string(),
+///     }],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     prompts.prompt_for_bundle(&bundle, PromptHint::Keyboard),
+///     vec![PromptId::from("key_enter")],
+/// );
+/// let hint = PromptHint::Device {
+///     name: "pad",
+///     style: Some("ps"),
+/// };
+/// assert_eq!(
+///     prompts.prompt_for_bundle(&bundle, hint),
+///     vec![PromptId::from("ps_cross")],
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct PromptMap {
+    /// Overrides of the default key prompts
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    keys: HashMap<Key, PromptId>,
+    /// Overrides of the default mouse button prompts
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    mouse: HashMap<MouseButton, PromptId>,
+    /// Style to channel to prompt
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "HashMap::is_empty"))]
+    styles: HashMap<String, HashMap<String, PromptId>>,
+}
+
+impl PromptMap {
+    /// Default mapping
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the prompt of the key
+    pub fn set_key(&mut self, key: Key, id: impl Into<PromptId>) {
+        self.keys.insert(key, id.into());
+    }
+
+    /// Overrides the prompt of the mouse button
+    pub fn set_mouse_button(&mut self, button: MouseButton, id: impl Into<PromptId>) {
+        self.mouse.insert(button, id.into());
+    }
+
+    /// Overrides the prompt of the channel in the style
+    pub fn set_channel(
+        &mut self,
+        style: impl Into<String>,
+        channel: impl Into<String>,
+        id: impl Into<PromptId>,
+    ) {
+        self.styles
+            .entry(style.into())
+            .or_default()
+            .insert(channel.into(), id.into());
+    }
+
+    /// Adds the entries of the other map, overriding the ones of this map
+    pub fn extend(&mut self, other: PromptMap) {
+        self.keys.extend(other.keys);
+        self.mouse.extend(other.mouse);
+        for (style, channels) in other.styles {
+            self.styles.entry(style).or_default().extend(channels);
+        }
+    }
+}
+
+/// Lookup
+impl PromptMap {
+    pub fn prompt_for_key(&self, key: Key) -> PromptId {
+        match self.keys.get(&key) {
+            Some(id) => id.clone(),
+            None => PromptId::from(format!("key_{}", key.name().to_ascii_lowercase())),
+        }
+    }
+
+    pub fn prompt_for_mouse_button(&self, button: MouseButton) -> PromptId {
+        match self.mouse.get(&button) {
+            Some(id) => id.clone(),
+            None => PromptId::from(format!("mouse_{}", button.name().to_ascii_lowercase())),
+        }
+    }
+
+    /// Prompts of the modifiers (left keys) and then the key
+    pub fn prompt_for_entry(&self, entry: &KeyEntry) -> Vec<PromptId> {
+        let mods = [
+            (entry.ctrl, Key::LCtrl),
+            (entry.shift, Key::LShift),
+            (entry.alt, Key::LAlt),
+            (entry.meta, Key::LMeta),
+        ];
+        mods.iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, key)| *key)
+            .chain(Some(entry.key))
+            .map(|key| self.prompt_for_key(key))
+            .collect()
+    }
+
+    /// Prompt of the channel in the style, or `<device>_<channel>`
+    pub fn prompt_for_custom(&self, input: &CustomInput, style: Option<&str>) -> PromptId {
+        let id = style
+            .and_then(|style| self.styles.get(style))
+            .and_then(|channels| channels.get(&input.channel));
+        match id {
+            Some(id) => id.clone(),
+            None => PromptId::from(format!("{}_{}", input.device, input.channel)),
+        }
+    }
+
+    /// Prompts of the entry that matches the device, or of the first entry if none matches
+    ///
+    /// For the keyboard, key entries are preferred over chords. Empty if the bundle has no keys,
+    /// custom inputs or mouse entries.
+    pub fn prompt_for_bundle(&self, bundle: &InputBundle, hint: PromptHint) -> Vec<PromptId> {
+        match hint {
+            PromptHint::Keyboard => {}
+            PromptHint::Mouse => {
+                if let Some(entry) = bundle.mouse.first() {
+                    return vec![self.prompt_for_mouse_button(entry.button)];
+                }
+            }
+            PromptHint::Device { name, style } => {
+                if let Some(input) = bundle.custom.iter().find(|c| c.device == name) {
+                    return vec![self.prompt_for_custom(input, style)];
+                }
+            }
+        }
+
+        if let Some(entry) = bundle.keys.first() {
+            return self.prompt_for_entry(entry);
+        }
+        if let Some(chord) = bundle.chords.first() {
+            return chord
+                .0
+                .iter()
+                .map(|key| self.prompt_for_key(*key))
+                .collect();
+        }
+        if let Some(input) = bundle.custom.first() {
+            return vec![self.prompt_for_custom(input, None)];
+        }
+        match bundle.mouse.first() {
+            Some(entry) => vec![self.prompt_for_mouse_button(entry.button)],
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entries, vi::KeyChord};
+
+    fn custom(device: &str, channel: &str) -> CustomInput {
+        CustomInput {
+            device: device.to_string(),
+            channel: channel.to_string(),
+        }
+    }
+
+    fn ids(ids: &[&'static str]) -> Vec<PromptId> {
+        ids.iter().map(|id| PromptId::from(*id)).collect()
+    }
+
+    fn pad(style: Option<&str>) -> PromptHint<'_> {
+        PromptHint::Device { name: "pad", style }
+    }
+
+    #[test]
+    fn device_hint_selection() {
+        let mut prompts = PromptMap::new();
+        prompts.set_channel("xbox", "south", "xbox_a");
+        prompts.set_channel("ps", "south", "ps_cross");
+
+        let bundle = InputBundle {
+            keys: entries!["Ctrl+S".parse().unwrap(), Key::Enter.into()],
+            chords: entries![KeyChord(vec![Key::A, Key::B])],
+            custom: entries![custom("stick", "south"), custom("pad", "south")],
+            ..Default::default()
+        };
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, PromptHint::Keyboard),
+            ids(&["key_lctrl", "key_s"])
+        );
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, pad(Some("xbox"))),
+            ids(&["xbox_a"])
+        );
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, pad(Some("ps"))),
+            ids(&["ps_cross"])
+        );
+        // unknown styles and no style fall back to the default name
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, pad(Some("switch"))),
+            ids(&["pad_south"])
+        );
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, pad(None)),
+            ids(&["pad_south"])
+        );
+        // a device without an entry shows the keyboard prompts
+        let tablet = PromptHint::Device {
+            name: "tablet",
+            style: None,
+        };
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, tablet),
+            ids(&["key_lctrl", "key_s"])
+        );
+        assert_eq!(
+            prompts.prompt_for_bundle(&bundle, PromptHint::Mouse),
+            ids(&["key_lctrl", "key_s"])
+        );
+
+        let clickable = InputBundle {
+            mouse: entries![MouseButton::Right.into(), MouseButton::Left.into()],
+            ..bundle.clone()
+        };
+        assert_eq!(
+            prompts.prompt_for_bundle(&clickable, PromptHint::Mouse),
+            ids(&["mouse_right"])
+        );
+        assert_eq!(
+            prompts.prompt_for_bundle(&clickable, PromptHint::Keyboard),
+            ids(&["key_lctrl", "key_s"])
+        );
+        let mouse_only = InputBundle {
+            mouse: clickable.mouse.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            prompts.prompt_for_bundle(&mouse_only, pad(Some("ps"))),
+            ids(&["mouse_right"])
+        );
+
+        let chords = InputBundle {
+            chords: bundle.chords.clone(),
+            custom: bundle.custom.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            prompts.prompt_for_bundle(&chords, PromptHint::Keyboard),
+            ids(&["key_a", "key_b"])
+        );
+
+        let pad_only = InputBundle {
+            custom: bundle.custom.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            prompts.prompt_for_bundle(&pad_only, PromptHint::Keyboard),
+            ids(&["stick_south"])
+        );
+        assert!(prompts
+            .prompt_for_bundle(&InputBundle::default(), pad(Some("ps")))
+            .is_empty());
+    }
+
+    #[test]
+    fn overrides() {
+        let mut prompts = PromptMap::new();
+        assert_eq!(
+            prompts.prompt_for_key(Key::Space),
+            PromptId::from("key_space")
+        );
+        prompts.set_key(Key::Space, "spacebar_wide");
+        prompts.set_key(Key::LCtrl, String::from("ctrl"));
+        prompts.set_mouse_button(MouseButton::Middle, "wheel_click");
+        assert_eq!(
+            prompts.prompt_for_key(Key::Space),
+            PromptId::from("spacebar_wide")
+        );
+        assert_eq!(
+            prompts.prompt_for_entry(&"Ctrl+Shift+Space".parse().unwrap()),
+            ids(&["ctrl", "key_lshift", "spacebar_wide"])
+        );
+
+        let mut shipped = PromptMap::new();
+        shipped.set_key(Key::Space, "space_v2");
+        shipped.set_channel("ps", "south", "ps_cross_v2");
+        prompts.set_channel("ps", "south", "ps_cross");
+        prompts.set_channel("ps", "east", "ps_circle");
+        prompts.extend(shipped);
+
+        assert_eq!(
+            prompts.prompt_for_key(Key::Space),
+            PromptId::from("space_v2")
+        );
+        assert_eq!(prompts.prompt_for_key(Key::LCtrl), PromptId::from("ctrl"));
+        assert_eq!(
+            prompts.prompt_for_mouse_button(MouseButton::Middle),
+            PromptId::from("wheel_click")
+        );
+        assert_eq!(
+            prompts.prompt_for_mouse_button(MouseButton::X1),
+            PromptId::from("mouse_x1")
+        );
+        let ps = Some("ps");
+        assert_eq!(
+            prompts.prompt_for_custom(&custom("pad", "south"), ps),
+            PromptId::from("ps_cross_v2")
+        );
+        assert_eq!(
+            prompts.prompt_for_custom(&custom("pad", "east"), ps),
+            PromptId::from("ps_circle")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn only_overrides_are_saved() {
+        assert_eq!(ron::to_string(&PromptMap::new()).unwrap(), "()");
+
+        let mut prompts = PromptMap::new();
+        prompts.set_key(Key::Space, "spacebar_wide");
+        prompts.set_channel("ps", "south", "ps_cross");
+        prompts.set_mouse_button(MouseButton::Left, "click");
+        let ron = ron::to_string(&prompts).unwrap();
+        let loaded: PromptMap = ron::from_str(&ron).unwrap();
+        assert_eq!(loaded, prompts);
+
+        let shipped: PromptMap = ron::from_str(r#"(keys: {Enter: "enter_long"})"#).unwrap();
+        assert_eq!(
+            shipped.prompt_for_key(Key::Enter),
+            PromptId::from("enter_long")
+        );
+        assert_eq!(
+            shipped.prompt_for_key(Key::Escape),
+            PromptId::from("key_escape")
+        );
+    }
+}
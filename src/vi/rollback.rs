@@ -0,0 +1,361 @@
+//! Re-exported to super module
+
+use std::{error, fmt};
+
+use crate::vi::{ActionMap, PackedInput, RemoteActions};
+
+/// Number of buttons and axes of an [`ActionMap`], which every peer has to agree on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionMapLayout {
+    pub n_buttons: usize,
+    pub n_axes: usize,
+}
+
+impl ActionMapLayout {
+    /// Size of the payload in bytes
+    pub fn payload_len(&self) -> usize {
+        PackedInput::new(self.n_buttons, self.n_axes)
+            .as_bytes()
+            .len()
+    }
+}
+
+impl ActionMap {
+    pub fn layout(&self) -> ActionMapLayout {
+        ActionMapLayout {
+            n_buttons: self.buttons.len(),
+            n_axes: self.axes.len(),
+        }
+    }
+}
+
+/// Payload of a wrong size for the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadError {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "payload of {} bytes for a layout of {} bytes",
+            self.found, self.expected
+        )
+    }
+}
+
+impl error::Error for PayloadError {}
+
+/// Bridge between [`ActionMap`]s and the per-tick byte payloads of a rollback session (GGRS)
+///
+/// The payload is the [`PackedInput`] encoding, so it has a fixed size and all zeroes means no
+/// input, which is what rollback libraries send for disconnected players. Every player's
+/// actions are reconstructed from the payloads as [`RemoteActions`], the local player's
+/// included, so that all the peers simulate the same states.
+///
+/// On rollback, save the adapter with [`RollbackAdapter::save_state`] along with the game
+/// state and load it back with [`RollbackAdapter::load_state`].
+///
+/// ```
+/// use xdl::vi::{ActionMapLayout, RollbackAdapter};
+///
+/// let layout = ActionMapLayout { n_buttons: 3, n_axes: 1 };
+/// let mut adapter = RollbackAdapter::new(layout, 2);
+///
+/// // tick 0: player 0 presses button 1
+/// adapter.advance(&[&[0b010], &[0]]).unwrap();
+/// assert!(adapter.player(0).is_pressed(1));
+///
+/// let saved = adapter.save_state();
+/// adapter.advance(&[&[0b010], &[0]]).unwrap();
+/// assert!(!adapter.player(0).is_pressed(1));
+///
+/// // roll back and resimulate with the corrected input
+/// adapter.load_state(&saved);
+/// adapter.advance(&[&[0], &[0]]).unwrap();
+/// assert!(adapter.player(0).is_released(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RollbackAdapter {
+    layout: ActionMapLayout,
+    players: Vec<RemoteActions>,
+}
+
+/// Saved state of a [`RollbackAdapter`]
+#[derive(Debug, Clone)]
+pub struct RollbackState {
+    players: Vec<RemoteActions>,
+}
+
+impl RollbackAdapter {
+    pub fn new(layout: ActionMapLayout, n_players: usize) -> Self {
+        Self {
+            layout,
+            players: (0..n_players)
+                .map(|_| RemoteActions::new(layout.n_buttons, layout.n_axes))
+                .collect(),
+        }
+    }
+
+    pub fn layout(&self) -> ActionMapLayout {
+        self.layout
+    }
+
+    /// Payload of the local player on this tick
+    ///
+    /// Panics if the map has a different layout.
+    pub fn local_input(&self, map: &ActionMap) -> Vec<u8> {
+        assert_eq!(map.layout(), self.layout, "action map layout mismatch");
+        map.pack().as_bytes().to_vec()
+    }
+
+    /// Applies the payloads of the next tick, one per player in order
+    ///
+    /// Nothing is applied if any payload has a wrong size. Panics if the number of payloads is
+    /// not the number of players.
+    pub fn advance(&mut self, payloads: &[&[u8]]) -> Result<(), PayloadError> {
+        assert_eq!(
+            payloads.len(),
+            self.players.len(),
+            "wrong number of payloads"
+        );
+        let packets = payloads
+            .iter()
+            .map(|payload| self::packet(self.layout, payload))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (player, packet) in self.players.iter_mut().zip(packets) {
+            player.push(packet);
+        }
+        Ok(())
+    }
+
+    /// Panics if the index is out of range
+    pub fn player(&self, index: usize) -> &RemoteActions {
+        &self.players[index]
+    }
+
+    pub fn n_players(&self) -> usize {
+        self.players.len()
+    }
+}
+
+/// Save states
+impl RollbackAdapter {
+    pub fn save_state(&self) -> RollbackState {
+        RollbackState {
+            players: self.players.clone(),
+        }
+    }
+
+    /// Panics if the state was saved from an adapter with a different number of players
+    pub fn load_state(&mut self, state: &RollbackState) {
+        assert_eq!(
+            state.players.len(),
+            self.players.len(),
+            "wrong number of players"
+        );
+        self.players.clone_from(&state.players);
+    }
+}
+
+impl RemoteActions {
+    /// Applies the payload of the next tick, made by [`RollbackAdapter::local_input`]
+    pub fn apply(&mut self, payload: &[u8]) -> Result<(), PayloadError> {
+        let layout = ActionMapLayout {
+            n_buttons: self.current().n_buttons(),
+            n_axes: self.current().n_axes(),
+        };
+        self.push(self::packet(layout, payload)?);
+        Ok(())
+    }
+}
+
+fn packet(layout: ActionMapLayout, payload: &[u8]) -> Result<PackedInput, PayloadError> {
+    PackedInput::from_bytes(layout.n_buttons, layout.n_axes, payload).ok_or(PayloadError {
+        expected: layout.payload_len(),
+        found: payload.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        backend::ScriptBackend,
+        vi::{AxisButton, Button, KeyRepeatConfig},
+        Input, Key,
+    };
+
+    const DT: Duration = Duration::from_millis(10);
+
+    /// Player of a session with its own scripted keyboard
+    struct Peer {
+        script: ScriptBackend,
+        input: Input,
+        map: ActionMap,
+        adapter: RollbackAdapter,
+    }
+
+    impl Peer {
+        fn new(script: ScriptBackend) -> Self {
+            let button = |key| Button::single(key, KeyRepeatConfig::NoRepeat);
+            let mut map = ActionMap::new();
+            map.buttons = vec![button(Key::Z), button(Key::X), button(Key::C)];
+            map.axes = vec![AxisButton {
+                pos: button(Key::Right),
+                neg: button(Key::Left),
+            }];
+            let adapter = RollbackAdapter::new(map.layout(), 2);
+            Self {
+                script,
+                input: Input::new(),
+                map,
+                adapter,
+            }
+        }
+
+        fn local_input(&mut self) -> Vec<u8> {
+            self.script.step(&mut self.input);
+            self.map.update(&self.input, DT);
+            self.adapter.local_input(&self.map)
+        }
+    }
+
+    fn peers() -> [Peer; 2] {
+        let mut a = ScriptBackend::new(DT);
+        a.hold(Key::Z, 1..4)
+            .hold(Key::Right, 2..9)
+            .hold(Key::X, 5..6)
+            .hold(Key::Z, 7..8);
+        let mut b = ScriptBackend::new(DT);
+        b.hold(Key::C, 0..10)
+            .hold(Key::Left, 3..5)
+            .hold(Key::Right, 4..7)
+            .hold(Key::Z, 6..7);
+        [Peer::new(a), Peer::new(b)]
+    }
+
+    /// Queries of every player as seen by the adapter
+    fn states(adapter: &RollbackAdapter) -> Vec<(Vec<[bool; 3]>, crate::Sign)> {
+        (0..adapter.n_players())
+            .map(|p| {
+                let actions = adapter.player(p);
+                let buttons = (0..3)
+                    .map(|i| {
+                        [
+                            actions.is_down(i),
+                            actions.is_pressed(i),
+                            actions.is_released(i),
+                        ]
+                    })
+                    .collect();
+                (buttons, actions.sign_down(0))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_peers_see_symmetric_results() {
+        let [mut a, mut b] = self::peers();
+
+        for tick in 0..12 {
+            let payloads = [a.local_input(), b.local_input()];
+            let payloads = [&payloads[0][..], &payloads[1][..]];
+            a.adapter.advance(&payloads).unwrap();
+            b.adapter.advance(&payloads).unwrap();
+            assert_eq!(
+                self::states(&a.adapter),
+                self::states(&b.adapter),
+                "tick {}",
+                tick
+            );
+
+            // each player's actions match the player's own map
+            for (p, peer) in [&a, &b].iter().enumerate() {
+                let remote = a.adapter.player(p);
+                for (i, button) in peer.map.buttons.iter().enumerate() {
+                    assert_eq!(
+                        (remote.is_down(i), remote.is_pressed(i)),
+                        (button.is_down(), button.is_pressed()),
+                        "tick {}, player {}, button {}",
+                        tick,
+                        p,
+                        i
+                    );
+                }
+                assert_eq!(remote.sign_down(0), peer.map.axes[0].sign_down());
+            }
+        }
+    }
+
+    #[test]
+    fn rollback_resimulates_mispredicted_ticks() {
+        let [mut a, mut b] = self::peers();
+
+        // `a`'s input of ticks 3..6 arrives late at `b`, which predicts it by repeating the
+        // last confirmed payload
+        let mut confirmed = Vec::new();
+        let mut saved = None;
+        let mut late = Vec::new();
+        for tick in 0..10 {
+            let (pa, pb) = (a.local_input(), b.local_input());
+            a.adapter.advance(&[&pa, &pb]).unwrap();
+
+            if (3..6).contains(&tick) {
+                if tick == 3 {
+                    saved = Some(b.adapter.save_state());
+                }
+                b.adapter.advance(&[&confirmed, &pb]).unwrap();
+                late.push((pa, pb));
+                continue;
+            }
+            if tick == 6 {
+                b.adapter.load_state(saved.as_ref().unwrap());
+                for (pa, pb) in late.drain(..) {
+                    b.adapter.advance(&[&pa, &pb]).unwrap();
+                }
+            }
+            b.adapter.advance(&[&pa, &pb]).unwrap();
+            confirmed = pa;
+
+            assert_eq!(
+                self::states(&a.adapter),
+                self::states(&b.adapter),
+                "tick {}",
+                tick
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_payload_size() {
+        let [a, _] = self::peers();
+        let mut adapter = a.adapter;
+        assert_eq!(adapter.layout().payload_len(), 1);
+
+        let before = self::states(&adapter);
+        let err = adapter.advance(&[&[0b001], &[0, 0]]).unwrap_err();
+        assert_eq!(
+            err,
+            PayloadError {
+                expected: 1,
+                found: 2
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "payload of 2 bytes for a layout of 1 bytes"
+        );
+        // nothing is applied
+        assert_eq!(self::states(&adapter), before);
+
+        let mut remote = RemoteActions::new(3, 1);
+        assert!(remote.apply(&[]).is_err());
+        remote.apply(&[0b100]).unwrap();
+        assert!(remote.is_pressed(2));
+    }
+}
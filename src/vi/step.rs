@@ -0,0 +1,284 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+use crate::{
+    input::device::Device, utils::BitSet256, vi::ActionMap, Input, Key, KeyState, Pointer, RawKey,
+};
+
+/// Updates an [`ActionMap`] once per fixed simulation step
+///
+/// Pressed and released edges are seen on exactly one step: the first step simulated after the
+/// change. If no step elapses on a render frame, the edges carry over to the next step, so a tap
+/// between two steps is down for one step. A key released and pressed again between two steps
+/// is up for one step. Raw keys carry their state but not taps between steps, and edges of
+/// custom devices and the mouse are per frame. Keys already down on the first step are seen as
+/// pressed.
+#[derive(Debug, Clone)]
+pub struct FixedStepDriver {
+    step: Duration,
+    accum: Duration,
+    /// Keys down on the last simulated step, as seen by the step
+    last: BitSet256,
+    /// Keys pressed since the last simulated step
+    pressed: BitSet256,
+    /// Raw keys down on the last simulated step
+    last_raw: Vec<RawKey>,
+}
+
+impl FixedStepDriver {
+    pub fn new(step: Duration) -> Self {
+        assert!(step > Duration::ZERO, "zero step");
+        Self {
+            step,
+            accum: Duration::ZERO,
+            last: BitSet256::new(),
+            pressed: BitSet256::new(),
+            last_raw: Vec::new(),
+        }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Time left in the accumulator, e.g. for interpolating rendering
+    pub fn remainder(&self) -> Duration {
+        self.accum
+    }
+
+    /// Updates the map once per whole step in `frame_dt` (plus the remainder of previous frames)
+    /// and returns the number of simulated steps
+    ///
+    /// Call it every frame, including the frames that simulate no step.
+    pub fn advance(&mut self, map: &mut ActionMap, input: &Input, frame_dt: Duration) -> u32 {
+        self.advance_with(map, input, frame_dt, |_| {})
+    }
+
+    /// [`FixedStepDriver::advance`] calling `on_step` after each update, where the simulation step
+    /// reads the map
+    pub fn advance_with(
+        &mut self,
+        map: &mut ActionMap,
+        input: &Input,
+        frame_dt: Duration,
+        mut on_step: impl FnMut(&ActionMap),
+    ) -> u32 {
+        self.accum += frame_dt;
+
+        let down = input.kbd.keys_down_bits();
+        let pressed = down.diff(&input.kbd.keys_down_prev_bits());
+        self.pressed = self.pressed.union(&pressed);
+
+        let mut n = 0;
+        while self.accum >= self.step {
+            self.accum -= self.step;
+
+            let view = if n == 0 {
+                // the first step sees the edges since the last simulated step
+                let repressed = self.pressed.intersection(&self.last);
+                let tapped = self.pressed.diff(&self.last);
+                StepView {
+                    input,
+                    down: down.diff(&repressed).union(&tapped),
+                    prev: self.last,
+                    prev_raw: &self.last_raw,
+                }
+            } else {
+                StepView {
+                    input,
+                    down,
+                    prev: self.last,
+                    prev_raw: input.kbd.raw_keys_down(),
+                }
+            };
+            map.update(&view, self.step);
+            self.last = view.down;
+            self.pressed.clear_all();
+            on_step(map);
+            n += 1;
+        }
+
+        if n > 0 {
+            self.last_raw.clear();
+            self.last_raw.extend_from_slice(input.kbd.raw_keys_down());
+        }
+        n
+    }
+}
+
+/// [`Input`] as seen from a simulation step
+struct StepView<'a> {
+    input: &'a Input,
+    down: BitSet256,
+    /// Keys down on the previous step
+    prev: BitSet256,
+    /// Raw keys down on the previous step
+    prev_raw: &'a [RawKey],
+}
+
+impl<'a> KeyState for StepView<'a> {
+    fn is_key_down(&self, key: Key) -> bool {
+        self.down.get(key as usize)
+    }
+
+    fn was_key_down(&self, key: Key) -> bool {
+        self.prev.get(key as usize)
+    }
+
+    fn is_raw_key_down(&self, key: RawKey) -> bool {
+        self.input.kbd.is_raw_key_down(key)
+    }
+
+    fn was_raw_key_down(&self, key: RawKey) -> bool {
+        self.prev_raw.contains(&key)
+    }
+
+    fn device(&self, name: &str) -> Option<&dyn Device> {
+        self.input.device(name)
+    }
+
+    // mouse edges are per frame, not per step
+    fn pointer(&self) -> Option<&Pointer> {
+        self.input.pointer()
+    }
+
+    fn key_bits(&self) -> Option<[BitSet256; 2]> {
+        Some([self.down, self.prev])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::{ManualBackend, ScriptBackend},
+        vi::{Button, KeyRepeatConfig},
+    };
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    fn map() -> ActionMap {
+        let repeat = KeyRepeatConfig::repeat(Duration::from_millis(30), Duration::from_millis(20));
+        let mut map = ActionMap::new();
+        map.buttons = vec![Button::single(Key::Space, repeat)];
+        map
+    }
+
+    /// `is_pressed` on each simulated step while Space is held from the first frame
+    fn run(frame_dts: &[u64]) -> Vec<bool> {
+        let mut backend = ManualBackend::new();
+        let mut input = Input::new();
+        let mut driver = FixedStepDriver::new(STEP);
+        let mut map = self::map();
+
+        backend.key_down(Key::Space);
+        let mut pressed = Vec::new();
+        for &dt in frame_dts {
+            backend.update(&mut input);
+            driver.advance_with(&mut map, &input, Duration::from_millis(dt), |map| {
+                pressed.push(map.buttons[0].is_pressed());
+            });
+            backend.end_frame(&mut input);
+        }
+        pressed
+    }
+
+    #[test]
+    fn uneven_frames_repeat_on_steps() {
+        // the press frame simulates no step
+        let pressed = self::run(&[4, 13, 25, 5, 0, 1, 31, 2, 19]);
+        assert_eq!(pressed.len(), 10);
+
+        // updated once per step
+        let mut script = ScriptBackend::new(STEP);
+        script.hold(Key::Space, 0..100);
+        let mut input = Input::new();
+        let mut map = self::map();
+        let expected = (0..10)
+            .map(|_| {
+                let dt = script.step(&mut input);
+                map.update(&input, dt);
+                map.buttons[0].is_pressed()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(pressed, expected);
+        assert_eq!(pressed.iter().filter(|b| **b).count(), 4);
+    }
+
+    #[test]
+    fn remainder_carries_over() {
+        let mut driver = FixedStepDriver::new(STEP);
+        let mut map = self::map();
+        let input = Input::new();
+        let ms = Duration::from_millis;
+        assert_eq!(driver.advance(&mut map, &input, ms(7)), 0);
+        assert_eq!(driver.advance(&mut map, &input, ms(7)), 1);
+        assert_eq!(driver.remainder(), ms(4));
+        assert_eq!(driver.advance(&mut map, &input, ms(36)), 4);
+        assert_eq!(driver.remainder(), ms(0));
+    }
+
+    /// `(pressed, down, released)` of a Space button on each step, rendering at 4 frames per step
+    fn steps_of(script: &mut ScriptBackend, frames: u32) -> Vec<(bool, bool, bool)> {
+        let mut driver = FixedStepDriver::new(STEP * 4);
+        let mut map = ActionMap::new();
+        map.buttons = vec![Button::single(Key::Space, KeyRepeatConfig::NoRepeat)];
+        let mut input = Input::new();
+        let mut states = Vec::new();
+        for _ in 0..frames {
+            let dt = script.step(&mut input);
+            driver.advance_with(&mut map, &input, dt, |map| {
+                let b = &map.buttons[0];
+                states.push((b.is_pressed(), b.is_down(), b.is_released()));
+            });
+        }
+        states
+    }
+
+    #[test]
+    fn tap_between_steps_is_latched() {
+        // steps run on frames 3, 7 and 11
+        let mut script = ScriptBackend::new(STEP);
+        script.hold(Key::Space, 1..2);
+        let states = self::steps_of(&mut script, 12);
+        assert_eq!(
+            states,
+            vec![
+                (true, true, false),
+                (false, false, true),
+                (false, false, false)
+            ]
+        );
+
+        // a tap within the frames of a step
+        let mut script = ScriptBackend::new(STEP);
+        script.hold(Key::Space, 4..5);
+        let states = self::steps_of(&mut script, 12);
+        assert_eq!(
+            states,
+            vec![
+                (false, false, false),
+                (true, true, false),
+                (false, false, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn repress_between_steps_is_latched() {
+        let mut script = ScriptBackend::new(STEP);
+        script.hold(Key::Space, 0..5).hold(Key::Space, 6..12);
+        let states = self::steps_of(&mut script, 16);
+        assert_eq!(
+            states,
+            vec![
+                (true, true, false),
+                (false, false, true),
+                (true, true, false),
+                (false, false, true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{input::device::Device, Key, KeyState, Pointer, RawKey};
+
+/// Modifier group of [`StickyModifiers`]. Left and right keys are the same modifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Meta,
+}
+
+impl Modifier {
+    pub const ALL: [Modifier; 4] = [Self::Ctrl, Self::Shift, Self::Alt, Self::Meta];
+
+    /// `[left, right]`
+    pub fn keys(self) -> [Key; 2] {
+        match self {
+            Self::Ctrl => [Key::LCtrl, Key::RCtrl],
+            Self::Shift => [Key::LShift, Key::RShift],
+            Self::Alt => [Key::LAlt, Key::RAlt],
+            Self::Meta => [Key::LMeta, Key::RMeta],
+        }
+    }
+
+    pub fn of_key(key: Key) -> Option<Self> {
+        Self::ALL.iter().find(|m| m.keys().contains(&key)).cloned()
+    }
+}
+
+/// State of a sticky modifier, for on-screen indicators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StickyState {
+    #[default]
+    Off,
+    /// Tapped. Applies to the next non-modifier press
+    Latched,
+    /// Double-tapped. Applies until tapped again
+    Locked,
+}
+
+#[derive(Debug, Clone, Default)]
+struct StickyModifier {
+    state: StickyState,
+    /// Time since latched
+    age: Duration,
+    /// Down with no other key pressed since
+    tapping: bool,
+    /// Latch applied to a press on last update, released on this update
+    consumed: bool,
+    /// If it was latched or locked before this update
+    was_active: bool,
+}
+
+/// Sticky keys, an accessibility mode for one-handed play
+///
+/// Tapping a modifier latches it for the next non-modifier key press, or until the latch window
+/// runs out. Tapping it again while latched locks it until the next tap. Holding a modifier
+/// while pressing another key works as usual.
+///
+/// Call [`StickyModifiers::update`] every frame, then read keys through
+/// [`StickyModifiers::view`], which sees the latched and locked modifiers as down, so
+/// [`KeyEntry`] modifiers match:
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{
+///     backend::ManualBackend,
+///     vi::{Button, KeyEntry, KeyRepeatConfig, StickyModifiers},
+///     Input, Key,
+/// };
+///
+/// let mut sticky = StickyModifiers::new(Some(Duration::from_secs(1)));
+/// let ctrl_s: KeyEntry = "Ctrl+S".parse().unwrap();
+/// let mut save = Button::single(ctrl_s, KeyRepeatConfig::NoRepeat);
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+/// let dt = Duration::from_millis(16);
+///
+/// for key in [Some(Key::LCtrl), None, Some(Key::S)] {
+///     match key {
+///         Some(key) => backend.key_down(key),
+///         None => backend.key_up(Key::LCtrl),
+///     }
+///     backend.update(&mut input);
+///     sticky.update(&input, dt);
+///     save.update(&sticky.view(&input), dt);
+///     backend.end_frame(&mut input);
+/// }
+///
+/// assert!(save.is_pressed());
+/// ```
+///
+/// [`KeyEntry`]: crate::vi::KeyEntry
+#[derive(Debug, Clone, Default)]
+pub struct StickyModifiers {
+    /// How long a latch lasts. `None` lasts until the next non-modifier press
+    window: Option<Duration>,
+    /// Indexed by [`Modifier`]
+    mods: [StickyModifier; 4],
+}
+
+impl StickyModifiers {
+    pub fn new(window: Option<Duration>) -> Self {
+        Self {
+            window,
+            ..Default::default()
+        }
+    }
+
+    pub fn window(&self) -> Option<Duration> {
+        self.window
+    }
+
+    pub fn set_window(&mut self, window: Option<Duration>) {
+        self.window = window;
+    }
+
+    pub fn state(&self, modifier: Modifier) -> StickyState {
+        self.mods[modifier as usize].state
+    }
+
+    /// If the modifier is latched or locked
+    pub fn is_active(&self, modifier: Modifier) -> bool {
+        self.state(modifier) != StickyState::Off
+    }
+
+    /// Unlatches and unlocks every modifier
+    pub fn clear(&mut self) {
+        self.mods = Default::default();
+    }
+
+    /// Key states with the latched and locked modifiers down
+    pub fn view<'a, K: KeyState>(&'a self, keys: &'a K) -> StickyView<'a, K> {
+        StickyView { keys, sticky: self }
+    }
+}
+
+/// Lifecycle
+impl StickyModifiers {
+    /// Reads the physical key states
+    pub fn update(&mut self, keys: &impl KeyState, dt: Duration) {
+        let other_pressed = Key::ALL
+            .iter()
+            .any(|k| *k != Key::None && !k.is_modifier() && keys.is_key_pressed(*k));
+
+        for modifier in Modifier::ALL {
+            let m = &mut self.mods[modifier as usize];
+            m.was_active = m.state != StickyState::Off;
+            if m.consumed {
+                m.consumed = false;
+                m.state = StickyState::Off;
+            }
+
+            let [l, r] = modifier.keys();
+            let is_down = keys.is_key_down(l) || keys.is_key_down(r);
+            let was_down = keys.was_key_down(l) || keys.was_key_down(r);
+
+            if is_down && !was_down {
+                m.tapping = true;
+            }
+            if other_pressed {
+                // used as a held modifier, not a tap
+                m.tapping = false;
+            }
+            if !is_down && was_down && m.tapping {
+                m.tapping = false;
+                m.age = Duration::ZERO;
+                m.state = match m.state {
+                    StickyState::Off => StickyState::Latched,
+                    StickyState::Latched => StickyState::Locked,
+                    StickyState::Locked => StickyState::Off,
+                };
+                continue;
+            }
+
+            if m.state == StickyState::Latched {
+                m.age += dt;
+                if self.window.is_some_and(|window| m.age > window) {
+                    m.state = StickyState::Off;
+                } else if other_pressed {
+                    // applies on this frame
+                    m.consumed = true;
+                }
+            }
+        }
+    }
+}
+
+/// [`KeyState`] with the sticky modifiers applied, made with [`StickyModifiers::view`]
+///
+/// The left key of an active modifier is seen as down.
+#[derive(Debug, Clone, Copy)]
+pub struct StickyView<'a, K> {
+    keys: &'a K,
+    sticky: &'a StickyModifiers,
+}
+
+impl<'a, K: KeyState> StickyView<'a, K> {
+    /// The sticky modifier of the left key
+    fn sticky(&self, key: Key) -> Option<&StickyModifier> {
+        let modifier = Modifier::of_key(key).filter(|m| m.keys()[0] == key)?;
+        Some(&self.sticky.mods[modifier as usize])
+    }
+}
+
+impl<'a, K: KeyState> KeyState for StickyView<'a, K> {
+    fn is_key_down(&self, key: Key) -> bool {
+        self.keys.is_key_down(key)
+            || self
+                .sticky(key)
+                .is_some_and(|m| m.state != StickyState::Off)
+    }
+
+    fn was_key_down(&self, key: Key) -> bool {
+        self.keys.was_key_down(key) || self.sticky(key).is_some_and(|m| m.was_active)
+    }
+
+    fn is_raw_key_down(&self, key: RawKey) -> bool {
+        self.keys.is_raw_key_down(key)
+    }
+
+    fn was_raw_key_down(&self, key: RawKey) -> bool {
+        self.keys.was_raw_key_down(key)
+    }
+
+    fn device(&self, name: &str) -> Option<&dyn Device> {
+        self.keys.device(name)
+    }
+
+    fn pointer(&self) -> Option<&Pointer> {
+        self.keys.pointer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::ScriptBackend,
+        vi::{Button, KeyEntry, KeyRepeatConfig},
+        Input,
+    };
+
+    const DT: Duration = Duration::from_millis(10);
+
+    /// Frames where Ctrl+S fired, frames where plain S fired, and the Ctrl state on each frame
+    fn run(
+        window: Option<Duration>,
+        script: &mut ScriptBackend,
+        n: u32,
+    ) -> (Vec<u32>, Vec<u32>, Vec<StickyState>) {
+        let mut sticky = StickyModifiers::new(window);
+        let ctrl_s: KeyEntry = "Ctrl+S".parse().unwrap();
+        let mut save = Button::single(ctrl_s, KeyRepeatConfig::NoRepeat);
+        let mut plain = Button::single(Key::S, KeyRepeatConfig::NoRepeat);
+        let mut input = Input::new();
+        let mut fired = Vec::new();
+        let mut plain_fired = Vec::new();
+        let mut states = Vec::new();
+        for frame in 0..n {
+            let dt = script.step(&mut input);
+            sticky.update(&input, dt);
+            save.update(&sticky.view(&input), dt);
+            plain.update(&sticky.view(&input), dt);
+            if save.is_pressed() {
+                fired.push(frame);
+            }
+            if plain.is_pressed() {
+                plain_fired.push(frame);
+            }
+            states.push(sticky.state(Modifier::Ctrl));
+        }
+        (fired, plain_fired, states)
+    }
+
+    #[test]
+    fn tap_then_press_within_window() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::LCtrl, 0..1)
+            .hold(Key::S, 4..5)
+            .hold(Key::S, 6..7);
+        let (fired, _, states) = self::run(Some(Duration::from_millis(50)), &mut script, 8);
+        // the latch applies to one press only
+        assert_eq!(fired, vec![4]);
+        assert_eq!(states[1..4], [StickyState::Latched; 3]);
+        assert_eq!(states[5..], [StickyState::Off; 3]);
+    }
+
+    #[test]
+    fn press_after_window_is_plain() {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::RCtrl, 0..1).hold(Key::S, 8..9);
+        let (fired, plain, states) = self::run(Some(Duration::from_millis(50)), &mut script, 10);
+        assert!(fired.is_empty());
+        assert_eq!(plain, vec![8]);
+        // still latched when the window has just run out
+        assert_eq!(states[1..7], [StickyState::Latched; 6]);
+        assert_eq!(states[7..], [StickyState::Off; 3]);
+
+        // without a window, the latch waits for the press
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::RCtrl, 0..1).hold(Key::S, 80..81);
+        let (fired, _, _) = self::run(None, &mut script, 82);
+        assert_eq!(fired, vec![80]);
+    }
+
+    #[test]
+    fn double_tap_locks_until_tapped_again() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::LCtrl, 0..1)
+            .hold(Key::LCtrl, 2..3)
+            .hold(Key::S, 10..11)
+            .hold(Key::S, 12..13)
+            .hold(Key::LCtrl, 14..15)
+            .hold(Key::S, 16..17);
+        let (fired, _, states) = self::run(Some(Duration::from_millis(50)), &mut script, 18);
+        assert_eq!(fired, vec![10, 12]);
+        assert_eq!(states[1], StickyState::Latched);
+        assert_eq!(states[3..15], [StickyState::Locked; 12]);
+        assert_eq!(states[15], StickyState::Off);
+    }
+
+    #[test]
+    fn held_modifier_works_as_usual() {
+        let mut script = ScriptBackend::new(DT);
+        script
+            .hold(Key::LCtrl, 0..4)
+            .hold(Key::S, 2..3)
+            .hold(Key::S, 6..7);
+        let (fired, _, states) = self::run(Some(Duration::from_millis(50)), &mut script, 8);
+        // held Ctrl matches, and releasing it after use doesn't latch
+        assert_eq!(fired, vec![2]);
+        assert!(states.iter().all(|s| *s == StickyState::Off));
+    }
+}
@@ -0,0 +1,409 @@
+//! Re-exported to super module
+
+use std::{marker::PhantomData, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    vi::{AxisButton, AxisDirButton, Button},
+    Input,
+};
+
+/// Any virtual input object stored in [`InputSystem`]
+///
+/// On `serde`, the key repeat configuration of each button is stored along with the object.
+/// Buttons following a [`SharedRepeatConfig`](crate::vi::SharedRepeatConfig) are saved with its
+/// current value.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum ViItem {
+    Button(Button),
+    AxisButton(AxisButton),
+    AxisDirButton(AxisDirButton),
+}
+
+/// Lifecycle
+impl ViItem {
+    pub fn update(&mut self, input: &Input, dt: Duration) {
+        match self {
+            Self::Button(x) => x.update(input, dt),
+            Self::AxisButton(x) => x.update(input, dt),
+            Self::AxisDirButton(x) => x.update(input, dt),
+        }
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        match self {
+            Self::Button(x) => x.set_paused(paused),
+            Self::AxisButton(x) => x.set_paused(paused),
+            Self::AxisDirButton(x) => x.set_paused(paused),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod item_serde {
+    //! `serde` [`ViItem`] with key repeat configuration
+
+    use super::*;
+    use crate::vi::KeyRepeatConfig;
+    use serde::{Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "ViItem")]
+    enum Repr<B, A, D> {
+        Button {
+            button: B,
+            #[serde(default)]
+            repeat: KeyRepeatConfig,
+        },
+        AxisButton {
+            axis: A,
+            /// Configs of the positive and the negative buttons
+            #[serde(default)]
+            repeat: [KeyRepeatConfig; 2],
+        },
+        AxisDirButton(D),
+    }
+
+    impl Serialize for ViItem {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let repr: Repr<&Button, &AxisButton, &AxisDirButton> = match self {
+                Self::Button(x) => Repr::Button {
+                    button: x,
+                    repeat: x.repeat_config(),
+                },
+                Self::AxisButton(x) => Repr::AxisButton {
+                    axis: x,
+                    repeat: [x.pos.repeat_config(), x.neg.repeat_config()],
+                },
+                // stores its own configs
+                Self::AxisDirButton(x) => Repr::AxisDirButton(x),
+            };
+            repr.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ViItem {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let repr = Repr::<Button, AxisButton, AxisDirButton>::deserialize(deserializer)?;
+            Ok(match repr {
+                Repr::Button { mut button, repeat } => {
+                    button.set_repeat_config(repeat);
+                    Self::Button(button)
+                }
+                Repr::AxisButton { mut axis, repeat } => {
+                    axis.pos.set_repeat_config(repeat[0]);
+                    axis.neg.set_repeat_config(repeat[1]);
+                    Self::AxisButton(axis)
+                }
+                Repr::AxisDirButton(x) => Self::AxisDirButton(x),
+            })
+        }
+    }
+}
+
+/// Virtual input object that can be stored in [`InputSystem`]
+pub trait ViObject: Into<ViItem> + Sized {
+    fn from_item(item: &ViItem) -> Option<&Self>;
+    fn from_item_mut(item: &mut ViItem) -> Option<&mut Self>;
+    fn from_item_owned(item: ViItem) -> Option<Self>;
+}
+
+macro_rules! impl_vi_object {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for ViItem {
+                fn from(x: $ty) -> Self {
+                    ViItem::$ty(x)
+                }
+            }
+
+            impl ViObject for $ty {
+                fn from_item(item: &ViItem) -> Option<&Self> {
+                    match item {
+                        ViItem::$ty(x) => Some(x),
+                        _ => None,
+                    }
+                }
+
+                fn from_item_mut(item: &mut ViItem) -> Option<&mut Self> {
+                    match item {
+                        ViItem::$ty(x) => Some(x),
+                        _ => None,
+                    }
+                }
+
+                fn from_item_owned(item: ViItem) -> Option<Self> {
+                    match item {
+                        ViItem::$ty(x) => Some(x),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_vi_object!(Button, AxisButton, AxisDirButton);
+
+/// Typed index of an object in [`InputSystem`]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Handle<T> {
+    ix: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ix == other.ix
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Handle<T> {
+    pub fn index(&self) -> usize {
+        self.ix
+    }
+}
+
+/// Registry of virtual input objects updated in one call
+///
+/// On `serde`, the whole control scheme (bindings and key repeat configs) is saved as one value,
+/// and the handles stay valid for the loaded registry.
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{vi::*, Input, Key};
+///
+/// let bundle = |key: Key| InputBundle::from_keys([key]);
+///
+/// let mut system = InputSystem::default();
+/// let select = system.add(Button::new(bundle(Key::Enter), KeyRepeatConfig::NoRepeat));
+/// let dir = system.add(AxisDirButton::new(
+///     KeyRepeatConfig::NoRepeat,
+///     [bundle(Key::Right), bundle(Key::Left)],
+///     [bundle(Key::Down), bundle(Key::Up)],
+/// ));
+///
+/// // instead of updating every object one by one:
+/// let input = Input::new();
+/// system.update(&input, Duration::from_millis(16));
+///
+/// assert!(!system[select].is_pressed());
+/// assert_eq!(system[dir].dir8_down(), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputSystem {
+    /// Removed slots are kept as `None` so that handles are stable
+    items: Vec<Option<ViItem>>,
+}
+
+/// Lifecycle
+impl InputSystem {
+    pub fn update(&mut self, input: &Input, dt: Duration) {
+        for item in self.items.iter_mut().flatten() {
+            item.update(input, dt);
+        }
+    }
+
+    /// Updates all the objects with the `dt` of the last [`Input::tick`]
+    pub fn tick(&mut self, input: &Input) {
+        self.update(input, input.dt());
+    }
+
+    /// Freezes timers of all the objects. See [`Button::set_paused`].
+    pub fn set_paused(&mut self, paused: bool) {
+        for item in self.items.iter_mut().flatten() {
+            item.set_paused(paused);
+        }
+    }
+}
+
+impl InputSystem {
+    pub fn add<T: ViObject>(&mut self, x: T) -> Handle<T> {
+        let ix = self.items.len();
+        self.items.push(Some(x.into()));
+        Handle {
+            ix,
+            _ty: PhantomData,
+        }
+    }
+
+    pub fn remove<T: ViObject>(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.items.get_mut(handle.ix)?;
+        match slot.take().map(T::from_item_owned) {
+            Some(Some(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn get<T: ViObject>(&self, handle: Handle<T>) -> Option<&T> {
+        self.items.get(handle.ix)?.as_ref().and_then(T::from_item)
+    }
+
+    pub fn get_mut<T: ViObject>(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.items
+            .get_mut(handle.ix)?
+            .as_mut()
+            .and_then(T::from_item_mut)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ViItem> {
+        self.items.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ViItem> {
+        self.items.iter_mut().flatten()
+    }
+}
+
+impl<T: ViObject> std::ops::Index<Handle<T>> for InputSystem {
+    type Output = T;
+    fn index(&self, handle: Handle<T>) -> &T {
+        self.get(handle).expect("invalid handle")
+    }
+}
+
+impl<T: ViObject> std::ops::IndexMut<Handle<T>> for InputSystem {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut T {
+        self.get_mut(handle).expect("invalid handle")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ScriptBackend, vi::KeyRepeatConfig, Key};
+
+    const DT: Duration = Duration::from_millis(10);
+
+    fn system() -> (InputSystem, Handle<Button>, Handle<AxisButton>) {
+        let repeat = KeyRepeatConfig::repeat_frames(2, 1);
+        let mut system = InputSystem::default();
+        let select = system.add(Button::single(Key::Enter, repeat));
+        let axis = system.add(AxisButton {
+            pos: Button::single(Key::Right, repeat),
+            neg: Button::single(Key::Left, KeyRepeatConfig::NoRepeat),
+        });
+        (system, select, axis)
+    }
+
+    /// Frames where `select` and the positive side of `axis` were pressed
+    fn run(
+        system: &mut InputSystem,
+        select: Handle<Button>,
+        axis: Handle<AxisButton>,
+    ) -> [Vec<u32>; 2] {
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Enter, 1..7).hold(Key::Right, 1..7);
+        let mut input = Input::new();
+        let mut pressed = [Vec::new(), Vec::new()];
+        for frame in 0..8 {
+            let dt = script.step(&mut input);
+            system.update(&input, dt);
+            if system[select].is_pressed() {
+                pressed[0].push(frame);
+            }
+            if system[axis].pos.is_pressed() {
+                pressed[1].push(frame);
+            }
+        }
+        pressed
+    }
+
+    #[test]
+    fn update_advances_every_object() {
+        let (mut system, select, axis) = self::system();
+        let pressed = self::run(&mut system, select, axis);
+        assert_eq!(pressed[0], pressed[1]);
+        // pressed and then repeated
+        assert!(pressed[0].len() > 1);
+        assert_eq!(pressed[0][0], 1);
+    }
+
+    #[test]
+    fn removal_and_iteration() {
+        let (mut system, select, axis) = self::system();
+        let other = system.add(Button::single(Key::Escape, KeyRepeatConfig::NoRepeat));
+        assert_eq!(system.len(), 3);
+
+        let removed = system.remove(select).unwrap();
+        assert_eq!(
+            removed.repeat_config(),
+            KeyRepeatConfig::repeat_frames(2, 1)
+        );
+        assert!(system.remove(select).is_none());
+        assert_eq!(system.len(), 2);
+
+        // the other handles still point to their objects
+        assert!(system.get(select).is_none());
+        assert_eq!(system[axis].neg.repeat_config(), KeyRepeatConfig::NoRepeat);
+        assert!(system.get(other).is_some());
+
+        // new objects don't reuse the removed slot
+        let added = system.add(Button::single(Key::Space, KeyRepeatConfig::NoRepeat));
+        assert_ne!(added.index(), select.index());
+        assert!(system.get(select).is_none());
+
+        let kinds = system
+            .iter()
+            .map(|item| match item {
+                ViItem::Button(_) => "button",
+                ViItem::AxisButton(_) => "axis",
+                ViItem::AxisDirButton(_) => "dir",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["axis", "button", "button"]);
+
+        for item in system.iter_mut() {
+            item.set_paused(true);
+        }
+        assert!(system[axis].is_paused());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn ron_round_trip_keeps_repeat() {
+        let (mut system, select, axis) = self::system();
+        let ron = ron::to_string(&system).unwrap();
+        let mut loaded: InputSystem = ron::from_str(&ron).unwrap();
+
+        assert_eq!(
+            loaded[select].repeat_config(),
+            KeyRepeatConfig::repeat_frames(2, 1)
+        );
+        assert_eq!(loaded[axis].neg.repeat_config(), KeyRepeatConfig::NoRepeat);
+        assert_eq!(
+            self::run(&mut loaded, select, axis),
+            self::run(&mut system, select, axis)
+        );
+    }
+}
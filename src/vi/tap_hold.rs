@@ -0,0 +1,219 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+use crate::{
+    vi::{Button, StrictButtonState},
+    KeyState,
+};
+
+/// [`Button`] with a tap action and a hold action, such as tap to dodge and hold to sprint
+///
+/// [`TapHoldButton::just_held`] fires once when the button has been down for the threshold.
+/// [`TapHoldButton::just_tapped`] fires on release if it didn't, so only one of them fires per
+/// press.
+///
+/// In eager mode, the tap fires on press instead, and turns into a hold when the threshold is
+/// crossed: [`TapHoldButton::just_cancelled_tap`] fires together with `just_held` so the tap
+/// action can be undone.
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{
+///     backend::ManualBackend,
+///     vi::{presets, Button, KeyRepeatConfig, TapHoldButton},
+///     Input, Key,
+/// };
+///
+/// let button = Button::new(presets::confirm(), KeyRepeatConfig::NoRepeat);
+/// let mut dodge = TapHoldButton::new(button, Duration::from_millis(200));
+///
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+/// let dt = Duration::from_millis(100);
+///
+/// backend.key_down(Key::Enter);
+/// backend.update(&mut input);
+/// dodge.update(&input, dt);
+/// backend.end_frame(&mut input);
+///
+/// backend.key_up(Key::Enter);
+/// backend.update(&mut input);
+/// dodge.update(&input, dt);
+///
+/// assert!(dodge.just_tapped());
+/// assert!(!dodge.just_held());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TapHoldButton {
+    pub button: Button,
+    threshold: Duration,
+    eager: bool,
+    /// If the hold fired on this press
+    holding: bool,
+    tapped: bool,
+    held: bool,
+    cancelled_tap: bool,
+}
+
+impl TapHoldButton {
+    /// Creates a button that taps on release before `threshold` and holds at `threshold`
+    pub fn new(button: Button, threshold: Duration) -> Self {
+        Self {
+            button,
+            threshold,
+            eager: false,
+            holding: false,
+            tapped: false,
+            held: false,
+            cancelled_tap: false,
+        }
+    }
+
+    /// Fires the tap on press, cancelling it on hold
+    pub fn with_eager_tap(mut self) -> Self {
+        self.eager = true;
+        self
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Applies from the next update, including to the current press
+    pub fn set_threshold(&mut self, threshold: Duration) {
+        self.threshold = threshold;
+    }
+
+    pub fn is_eager(&self) -> bool {
+        self.eager
+    }
+
+    /// If the threshold was crossed on this press and the button is still down
+    pub fn is_holding(&self) -> bool {
+        self.holding && self.button.is_down()
+    }
+
+    /// If a tap fired on last update: on release, or on press in eager mode
+    pub fn just_tapped(&self) -> bool {
+        self.tapped
+    }
+
+    /// If the threshold was crossed on last update while the button was down
+    pub fn just_held(&self) -> bool {
+        self.held
+    }
+
+    /// If an eager tap turned into a hold on last update
+    pub fn just_cancelled_tap(&self) -> bool {
+        self.cancelled_tap
+    }
+}
+
+/// Lifecycle
+impl TapHoldButton {
+    pub fn update(&mut self, input: &impl KeyState, dt: Duration) {
+        self.button.update(input, dt);
+
+        self.tapped = false;
+        self.held = false;
+        self.cancelled_tap = false;
+
+        if self.button.is_down() {
+            if self.button.state == StrictButtonState::Pressed {
+                self.holding = false;
+                self.tapped = self.eager;
+            }
+            if !self.holding && self.button.accum_down() >= self.threshold {
+                self.holding = true;
+                self.held = true;
+                self.cancelled_tap = self.eager;
+            }
+        } else if self.button.is_released() {
+            if !self.holding && !self.eager {
+                self.tapped = true;
+            }
+            self.holding = false;
+        } else {
+            self.holding = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::ScriptBackend, vi::KeyRepeatConfig, Input, Key};
+
+    const DT: Duration = Duration::from_millis(10);
+
+    /// Frames where the tap, the hold and the tap cancel fired, with `Z` held over `frames`
+    fn run(eager: bool, frames: std::ops::Range<u32>, n: u32) -> [Vec<u32>; 3] {
+        let button = Button::single(Key::Z, KeyRepeatConfig::NoRepeat);
+        let mut button = TapHoldButton::new(button, Duration::from_millis(50));
+        if eager {
+            button = button.with_eager_tap();
+        }
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Z, frames);
+        let mut input = Input::new();
+        let mut fired = [vec![], vec![], vec![]];
+        for frame in 0..n {
+            let dt = script.step(&mut input);
+            button.update(&input, dt);
+            for (i, b) in [
+                button.just_tapped(),
+                button.just_held(),
+                button.just_cancelled_tap(),
+            ]
+            .iter()
+            .enumerate()
+            {
+                if *b {
+                    fired[i].push(frame);
+                }
+            }
+        }
+        fired
+    }
+
+    // `Z` is down for 50ms on frame 6
+
+    #[test]
+    fn release_just_before_the_threshold_taps() {
+        assert_eq!(self::run(false, 1..6, 12), [vec![6], vec![], vec![]]);
+        assert_eq!(self::run(false, 1..2, 12), [vec![2], vec![], vec![]]);
+    }
+
+    #[test]
+    fn release_just_after_the_threshold_only_holds() {
+        assert_eq!(self::run(false, 1..7, 12), [vec![], vec![6], vec![]]);
+        // the hold fires once per press
+        assert_eq!(self::run(false, 1..11, 12), [vec![], vec![6], vec![]]);
+    }
+
+    #[test]
+    fn eager_tap_cancels_into_hold() {
+        assert_eq!(self::run(true, 1..6, 12), [vec![1], vec![], vec![]]);
+        assert_eq!(self::run(true, 1..7, 12), [vec![1], vec![6], vec![6]]);
+    }
+
+    #[test]
+    fn is_holding_until_release() {
+        let button = Button::single(Key::Z, KeyRepeatConfig::NoRepeat);
+        let mut button = TapHoldButton::new(button, Duration::from_millis(20));
+        let mut script = ScriptBackend::new(DT);
+        script.hold(Key::Z, 0..4).hold(Key::Z, 5..6);
+        let mut input = Input::new();
+        let holding = (0..7)
+            .map(|_| {
+                let dt = script.step(&mut input);
+                button.update(&input, dt);
+                button.is_holding()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(holding, vec![false, false, true, true, false, false, false]);
+        // the short press after the hold is a tap
+        assert!(button.just_tapped());
+    }
+}
@@ -0,0 +1,351 @@
+//! Re-exported to super module
+
+use std::time::Duration;
+
+use crate::{
+    vi::{Button, KeyRepeatConfig, StrictButtonState},
+    Input, Key,
+};
+
+/// Single-line text field driven by [`Input`], such as a name entry box
+///
+/// [`TextEdit::update`] inserts the characters typed on the frame at the cursor and handles
+/// Backspace, Delete, Left, Right, Home and End. Enter submits and Escape cancels. The cursor
+/// moves by `char`, so a codepoint is never split (grapheme clusters are not considered).
+///
+/// Enable text input on the [`Input`] so that the typed characters are delivered.
+///
+/// ```
+/// use std::time::Duration;
+/// use xdl::{backend::ManualBackend, vi::{KeyRepeatConfig, TextEdit}, Input, Key};
+///
+/// let mut edit = TextEdit::new(KeyRepeatConfig::NoRepeat).with_max_len(8);
+/// edit.set_text("xdl");
+///
+/// let mut backend = ManualBackend::new();
+/// let mut input = Input::new();
+/// backend.key_down(Key::Back);
+/// backend.update(&mut input);
+/// edit.update(&input, Duration::from_millis(16));
+///
+/// assert_eq!(edit.text(), "xd");
+/// assert_eq!(edit.cursor(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    text: String,
+    /// Byte index on a `char` boundary
+    cursor: usize,
+    /// Max number of `char`s
+    max_len: Option<usize>,
+    filter: Option<fn(char) -> bool>,
+    /// Editing keys with key repeat
+    back: Button,
+    delete: Button,
+    left: Button,
+    right: Button,
+    home: Button,
+    end: Button,
+    submitted: bool,
+    cancelled: bool,
+}
+
+impl Default for TextEdit {
+    fn default() -> Self {
+        Self::new(KeyRepeatConfig::default())
+    }
+}
+
+impl TextEdit {
+    /// Creates an empty field. The key repeat config applies to the editing keys
+    pub fn new(repeat: KeyRepeatConfig) -> Self {
+        Self {
+            text: String::new(),
+            cursor: 0,
+            max_len: None,
+            filter: None,
+            back: Button::single(Key::Back, repeat),
+            delete: Button::single(Key::Delete, repeat),
+            left: Button::single(Key::Left, repeat),
+            right: Button::single(Key::Right, repeat),
+            home: Button::single(Key::Home, KeyRepeatConfig::NoRepeat),
+            end: Button::single(Key::End, KeyRepeatConfig::NoRepeat),
+            submitted: false,
+            cancelled: false,
+        }
+    }
+
+    /// Limits the number of `char`s. Typed characters beyond it are dropped
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Accepts only the typed characters that pass the filter, e.g. `char::is_alphanumeric`
+    pub fn with_filter(mut self, filter: fn(char) -> bool) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte index of the cursor in [`TextEdit::text`], always on a `char` boundary
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the text and moves the cursor to the end. The max length and filter don't apply
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.len();
+    }
+
+    /// Clamps the cursor to the text and moves it back to a `char` boundary
+    pub fn set_cursor(&mut self, cursor: usize) {
+        let mut cursor = cursor.min(self.text.len());
+        while !self.text.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        self.cursor = cursor;
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// If Enter was pressed on last update
+    pub fn just_submitted(&self) -> bool {
+        self.submitted
+    }
+
+    /// If Escape was pressed on last update
+    pub fn just_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+/// Lifecycle
+impl TextEdit {
+    pub fn update(&mut self, input: &Input, dt: Duration) {
+        for button in [
+            &mut self.back,
+            &mut self.delete,
+            &mut self.left,
+            &mut self.right,
+            &mut self.home,
+            &mut self.end,
+        ] {
+            button.update(input, dt);
+        }
+
+        for c in input.text.text().chars() {
+            self.insert(c);
+        }
+
+        for _ in 0..self::count(&self.back) {
+            if let Some(c) = self.text[..self.cursor].chars().next_back() {
+                self.cursor -= c.len_utf8();
+                self.text.remove(self.cursor);
+            }
+        }
+        for _ in 0..self::count(&self.delete) {
+            if self.cursor < self.text.len() {
+                self.text.remove(self.cursor);
+            }
+        }
+        for _ in 0..self::count(&self.left) {
+            if let Some(c) = self.text[..self.cursor].chars().next_back() {
+                self.cursor -= c.len_utf8();
+            }
+        }
+        for _ in 0..self::count(&self.right) {
+            if let Some(c) = self.text[self.cursor..].chars().next() {
+                self.cursor += c.len_utf8();
+            }
+        }
+        if self.home.is_pressed() {
+            self.cursor = 0;
+        }
+        if self.end.is_pressed() {
+            self.cursor = self.text.len();
+        }
+
+        self.submitted = input.kbd.is_key_pressed(Key::Enter);
+        self.cancelled = input.kbd.is_key_pressed(Key::Escape);
+    }
+
+    fn insert(&mut self, c: char) {
+        // some backends send control characters for the editing keys
+        if c.is_control() || self.filter.is_some_and(|f| !f(c)) {
+            return;
+        }
+        if self
+            .max_len
+            .is_some_and(|max| self.text.chars().count() >= max)
+        {
+            return;
+        }
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+}
+
+/// Number of edits on last update: one on press and one per key repeat
+fn count(button: &Button) -> u32 {
+    match button.state {
+        StrictButtonState::Pressed => 1,
+        StrictButtonState::Repeating => button.repeat_count_this_update(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ManualBackend;
+
+    const DT: Duration = Duration::from_millis(10);
+
+    struct Field {
+        edit: TextEdit,
+        backend: ManualBackend,
+        input: Input,
+    }
+
+    impl Field {
+        fn new(edit: TextEdit) -> Self {
+            let mut input = Input::new();
+            input.start_text_input();
+            Self {
+                edit,
+                backend: ManualBackend::new(),
+                input,
+            }
+        }
+
+        fn frame(&mut self, f: impl FnOnce(&mut ManualBackend)) {
+            f(&mut self.backend);
+            self.backend.update(&mut self.input);
+            self.edit.update(&self.input, DT);
+            self.backend.end_frame(&mut self.input);
+        }
+
+        /// Taps the key over two frames
+        fn tap(&mut self, key: Key) {
+            self.frame(|b| b.key_down(key));
+            self.frame(|b| b.key_up(key));
+        }
+
+        fn state(&self) -> (&str, usize) {
+            (self.edit.text(), self.edit.cursor())
+        }
+    }
+
+    #[test]
+    fn typing_and_editing_keys() {
+        let mut field = Field::new(TextEdit::new(KeyRepeatConfig::NoRepeat));
+        field.frame(|b| b.text("helo"));
+        assert_eq!(field.state(), ("helo", 4));
+
+        field.tap(Key::Left);
+        field.frame(|b| b.text("l"));
+        assert_eq!(field.state(), ("hello", 4));
+
+        field.tap(Key::Home);
+        field.tap(Key::Delete);
+        assert_eq!(field.state(), ("ello", 0));
+        field.frame(|b| b.text("H"));
+        field.tap(Key::End);
+        field.tap(Key::Back);
+        assert_eq!(field.state(), ("Hell", 4));
+
+        // control characters sent along with the editing keys are not typed
+        field.frame(|b| b.text("\u{8}\r"));
+        assert_eq!(field.state(), ("Hell", 4));
+
+        field.frame(|b| b.key_down(Key::Enter));
+        assert!(field.edit.just_submitted() && !field.edit.just_cancelled());
+        field.frame(|b| b.key_up(Key::Enter));
+        assert!(!field.edit.just_submitted());
+        field.frame(|b| b.key_down(Key::Escape));
+        assert!(field.edit.just_cancelled() && !field.edit.just_submitted());
+    }
+
+    #[test]
+    fn repeat_backspace() {
+        let mut field = Field::new(TextEdit::new(KeyRepeatConfig::repeat_frames(3, 2)));
+        field.edit.set_text("abcdefghij");
+        let mut lens = Vec::new();
+        field.frame(|b| b.key_down(Key::Back));
+        lens.push(field.edit.text().len());
+        for _ in 0..7 {
+            field.frame(|_| {});
+            lens.push(field.edit.text().len());
+        }
+        field.frame(|b| b.key_up(Key::Back));
+        lens.push(field.edit.text().len());
+        // one on press, then repeats after the first delay and every other frame
+        assert_eq!(lens, vec![9, 9, 9, 8, 8, 7, 7, 6, 6]);
+        assert_eq!(field.state(), ("abcdef", 6));
+
+        // holding Back on an empty field is harmless
+        field.edit.clear();
+        for _ in 0..8 {
+            field.frame(|b| b.key_down(Key::Back));
+        }
+        assert_eq!(field.state(), ("", 0));
+    }
+
+    #[test]
+    fn unicode_boundaries() {
+        let mut field = Field::new(TextEdit::new(KeyRepeatConfig::NoRepeat));
+        field.edit.set_text("aé🦀");
+        assert_eq!(field.state(), ("aé🦀", 7));
+
+        field.tap(Key::Back);
+        assert_eq!(field.state(), ("aé", 3));
+        field.tap(Key::Left);
+        assert_eq!(field.state(), ("aé", 1));
+        field.frame(|b| b.text("日"));
+        assert_eq!(field.state(), ("a日é", 4));
+        field.tap(Key::Delete);
+        assert_eq!(field.state(), ("a日", 4));
+        field.tap(Key::Delete);
+        assert_eq!(field.state(), ("a日", 4));
+        field.tap(Key::Right);
+        assert_eq!(field.state(), ("a日", 4));
+
+        field.tap(Key::Home);
+        field.tap(Key::Back);
+        field.tap(Key::Left);
+        assert_eq!(field.state(), ("a日", 0));
+
+        // the cursor never lands inside a codepoint
+        field.edit.set_cursor(2);
+        assert_eq!(field.edit.cursor(), 1);
+        field.edit.set_cursor(3);
+        assert_eq!(field.edit.cursor(), 1);
+        field.edit.set_cursor(100);
+        assert_eq!(field.edit.cursor(), 4);
+    }
+
+    #[test]
+    fn max_len_and_filter() {
+        let edit = TextEdit::new(KeyRepeatConfig::NoRepeat).with_max_len(3);
+        let mut field = Field::new(edit);
+        field.frame(|b| b.text("日本語!"));
+        assert_eq!(field.state(), ("日本語", 9));
+        field.tap(Key::Back);
+        field.tap(Key::Home);
+        field.frame(|b| b.text("xy"));
+        assert_eq!(field.state(), ("x日本", 1));
+
+        let edit = TextEdit::new(KeyRepeatConfig::NoRepeat).with_filter(char::is_alphanumeric);
+        let mut field = Field::new(edit);
+        field.frame(|b| b.text("a-b c1"));
+        assert_eq!(field.state(), ("abc1", 4));
+    }
+}
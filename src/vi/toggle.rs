@@ -0,0 +1,260 @@
+//! Re-exported to super module
+
+use crate::{
+    vi::{InputBundle, RawButtonState},
+    KeyState,
+};
+
+/// Boolean switched by presses, such as a walk/run toggle
+///
+/// Each press flips [`ToggleButton::is_on`]; holding the bundle down doesn't flip it again. While
+/// the optional [`ToggleButton::invert`] bundle is held, the toggle reads inverted (hold shift to
+/// walk while the toggle says run).
+///
+/// On `serde`, the bundles are saved. The on/off state is saved only if
+/// [`ToggleButton::set_persist_state`] is enabled.
+#[derive(Debug, Clone, Default)]
+pub struct ToggleButton {
+    pub input: InputBundle,
+    /// Inverts [`ToggleButton::is_on`] while held
+    pub invert: Option<InputBundle>,
+    persist_state: bool,
+    /// State switched by presses, without inversion
+    latched: bool,
+    inverted: bool,
+    just_changed: bool,
+    /// Mouse region grabs of `input` and `invert`
+    mouse_grabs: [u32; 2],
+}
+
+impl ToggleButton {
+    /// Creates a toggle that is off
+    pub fn new(input: InputBundle) -> Self {
+        Self {
+            input,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the bundle that inverts the toggle while held
+    pub fn with_invert(mut self, invert: InputBundle) -> Self {
+        self.invert = Some(invert);
+        self
+    }
+
+    /// The toggle state, inverted while [`ToggleButton::invert`] is held
+    pub fn is_on(&self) -> bool {
+        self.latched != self.inverted
+    }
+
+    /// The toggle state switched by presses and [`ToggleButton::set`], ignoring the inversion
+    pub fn is_latched(&self) -> bool {
+        self.latched
+    }
+
+    /// If the inversion bundle is held
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Sets the toggle state without input
+    pub fn set(&mut self, on: bool) {
+        let prev = self.is_on();
+        self.latched = on;
+        self.just_changed |= prev != self.is_on();
+    }
+
+    /// If [`ToggleButton::is_on`] changed on last update or by [`ToggleButton::set`] since then
+    pub fn just_changed(&self) -> bool {
+        self.just_changed
+    }
+
+    pub fn persists_state(&self) -> bool {
+        self.persist_state
+    }
+
+    /// Saves the on/off state on `serde`, for settings such as always-run
+    pub fn set_persist_state(&mut self, persist: bool) {
+        self.persist_state = persist;
+    }
+}
+
+/// Lifecycle
+impl ToggleButton {
+    pub fn update(&mut self, input: &impl KeyState) {
+        let prev = self.is_on();
+
+        if self.input.state(input, &mut self.mouse_grabs[0]) == RawButtonState::Pressed {
+            self.latched = !self.latched;
+        }
+        self.inverted = match &self.invert {
+            Some(invert) => matches!(
+                invert.state(input, &mut self.mouse_grabs[1]),
+                RawButtonState::Pressed | RawButtonState::Down
+            ),
+            None => false,
+        };
+
+        self.just_changed = prev != self.is_on();
+    }
+}
+
+#[cfg(feature = "serde")]
+mod toggle_serde {
+    //! `serde` [`ToggleButton`] with optional on/off state
+
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct ToggleButtonSer<'a> {
+        input: &'a InputBundle,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        invert: Option<&'a InputBundle>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        on: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    struct ToggleButtonDe {
+        input: InputBundle,
+        #[serde(default)]
+        invert: Option<InputBundle>,
+        #[serde(default)]
+        on: Option<bool>,
+    }
+
+    impl Serialize for ToggleButton {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            ToggleButtonSer {
+                input: &self.input,
+                invert: self.invert.as_ref(),
+                on: if self.persist_state {
+                    Some(self.latched)
+                } else {
+                    None
+                },
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ToggleButton {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let de = ToggleButtonDe::deserialize(deserializer)?;
+            Ok(ToggleButton {
+                input: de.input,
+                invert: de.invert,
+                // a saved state is kept on the next save
+                persist_state: de.on.is_some(),
+                latched: de.on.unwrap_or(false),
+                ..Default::default()
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{backend::ScriptBackend, vi::presets::bundle, Input, Key};
+
+    /// `(is_on, just_changed)` over the frames of the script
+    fn run(toggle: &mut ToggleButton, mut script: ScriptBackend, n: usize) -> Vec<(bool, bool)> {
+        let mut input = Input::new();
+        (0..n)
+            .map(|_| {
+                script.step(&mut input);
+                toggle.update(&input);
+                (toggle.is_on(), toggle.just_changed())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn presses_toggle_and_holding_does_not_oscillate() {
+        let mut toggle = ToggleButton::new(bundle(&[Key::R]));
+        let mut script = ScriptBackend::new(Duration::from_millis(10));
+        // long hold, then a tap
+        script.hold(Key::R, 1..6).hold(Key::R, 7..8);
+        assert_eq!(
+            self::run(&mut toggle, script, 9),
+            vec![
+                (false, false),
+                (true, true),
+                (true, false),
+                (true, false),
+                (true, false),
+                (true, false),
+                (true, false),
+                (false, true),
+                (false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn hold_to_invert() {
+        let mut toggle = ToggleButton::new(bundle(&[Key::R])).with_invert(bundle(&[Key::LShift]));
+        toggle.set(true);
+        assert!(toggle.just_changed());
+
+        let mut script = ScriptBackend::new(Duration::from_millis(10));
+        // walk while shift is held, and a press while inverted
+        script
+            .hold(Key::LShift, 1..3)
+            .hold(Key::LShift, 4..7)
+            .hold(Key::R, 5..6);
+        assert_eq!(
+            self::run(&mut toggle, script, 8),
+            vec![
+                (true, false),
+                (false, true),
+                (false, false),
+                (true, true),
+                (false, true),
+                (true, true),
+                (true, false),
+                (false, true),
+            ]
+        );
+        assert!(!toggle.is_latched() && !toggle.is_inverted());
+    }
+
+    #[test]
+    fn set_reports_changes() {
+        let mut toggle = ToggleButton::new(bundle(&[Key::R]));
+        toggle.set(false);
+        assert!(!toggle.just_changed());
+        toggle.set(true);
+        toggle.set(true);
+        assert!(toggle.is_on() && toggle.just_changed());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_keeps_the_state_only_if_persisted() {
+        let mut toggle = ToggleButton::new(bundle(&[Key::R])).with_invert(bundle(&[Key::LShift]));
+        toggle.set(true);
+
+        let ron = ron::to_string(&toggle).unwrap();
+        assert!(!ron.contains("on:"), "{}", ron);
+        let de: ToggleButton = ron::from_str(&ron).unwrap();
+        assert_eq!((&de.input, &de.invert), (&toggle.input, &toggle.invert));
+        assert!(!de.is_on() && !de.persists_state());
+
+        toggle.set_persist_state(true);
+        let ron = ron::to_string(&toggle).unwrap();
+        assert!(ron.contains("on:Some(true)"), "{}", ron);
+        let de: ToggleButton = ron::from_str(&ron).unwrap();
+        assert!(de.is_on() && de.persists_state());
+    }
+}